@@ -56,7 +56,10 @@ fn main() {
                 Ok(false)
             });
 
-        let is_alive = interact.spawn().expect("Failed to start interact");
+        let is_alive = interact
+            .spawn()
+            .expect("Failed to start interact")
+            .is_alive();
 
         (is_alive, interact.get_status())
     };
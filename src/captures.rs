@@ -1,4 +1,8 @@
-use std::ops::Index;
+use std::{
+    borrow::Cow,
+    ops::{Index, Range},
+    time::Instant,
+};
 
 use crate::needle::Match;
 
@@ -9,12 +13,54 @@ use crate::needle::Match;
 pub struct Captures {
     buf: Vec<u8>,
     matches: Vec<Match>,
+    offset: usize,
+    timestamp: Option<Instant>,
 }
 
 impl Captures {
     /// New returns an instance of Found.
     pub(crate) fn new(buf: Vec<u8>, matches: Vec<Match>) -> Self {
-        Self { buf, matches }
+        Self {
+            buf,
+            matches,
+            offset: 0,
+            timestamp: None,
+        }
+    }
+
+    /// Sets a position of the consumed chunk in a stream.
+    pub(crate) fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets a time when the match was read.
+    pub(crate) fn with_timestamp(mut self, timestamp: Option<Instant>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Creates empty captures with a preallocated storage for `capacity` bytes.
+    ///
+    /// It's meant to be reused by `expect_into` calls.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::new(Vec::with_capacity(capacity), Vec::new())
+    }
+
+    /// Replaces the captures by a new match, reusing an allocated storage.
+    pub(crate) fn replace(
+        &mut self,
+        buf: &[u8],
+        matches: &[Match],
+        offset: usize,
+        timestamp: Option<Instant>,
+    ) {
+        self.buf.clear();
+        self.buf.extend_from_slice(buf);
+        self.matches.clear();
+        self.matches.extend_from_slice(matches);
+        self.offset = offset;
+        self.timestamp = timestamp;
     }
 
     /// is_empty verifies if any matches were actually found.
@@ -31,7 +77,7 @@ impl Captures {
 
     /// Matches returns a list of matches.
     pub fn matches(&self) -> MatchIter<'_> {
-        MatchIter::new(self)
+        MatchIter::new(&self.buf, &self.matches)
     }
 
     /// before returns a bytes before match.
@@ -49,6 +95,52 @@ impl Captures {
         &self.buf
     }
 
+    /// as_str_lossy returns all bytes involved in a match as a string.
+    ///
+    /// Invalid UTF-8 sequences are replaced with [`char::REPLACEMENT_CHARACTER`].
+    pub fn as_str_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.buf)
+    }
+
+    /// before_str_lossy returns a bytes before match as a string.
+    ///
+    /// Invalid UTF-8 sequences are replaced with [`char::REPLACEMENT_CHARACTER`].
+    pub fn before_str_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.before())
+    }
+
+    /// get_str_lossy returns a match by index as a string.
+    ///
+    /// Invalid UTF-8 sequences are replaced with [`char::REPLACEMENT_CHARACTER`].
+    pub fn get_str_lossy(&self, index: usize) -> Option<Cow<'_, str>> {
+        self.get(index).map(String::from_utf8_lossy)
+    }
+
+    /// offset returns a position in a stream where the consumed chunk ([Self::as_bytes]) starts.
+    ///
+    /// The position is a number of bytes which were consumed from a session before the match,
+    /// either by `expect` calls or by reading from it directly.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// timestamp returns a time when the end of the match was read from a process.
+    ///
+    /// It can be used to measure a latency of an output, e.g. from a moment a command was sent.
+    /// It's `None` for captures which were not produced by a session.
+    pub fn timestamp(&self) -> Option<Instant> {
+        self.timestamp
+    }
+
+    /// match_range returns a position of a match by index in a stream.
+    ///
+    /// The range is absolute, use [Self::offset] to get a position relative to [Self::as_bytes].
+    pub fn match_range(&self, index: usize) -> Option<Range<usize>> {
+        self.matches
+            .get(index)
+            .map(|m| self.offset + m.start()..self.offset + m.end())
+    }
+
     fn left_most_index(&self) -> usize {
         self.matches
             .iter()
@@ -62,6 +154,12 @@ impl Captures {
     }
 }
 
+impl Default for Captures {
+    fn default() -> Self {
+        Self::new(Vec::new(), Vec::new())
+    }
+}
+
 impl Index<usize> for Captures {
     type Output = [u8];
 
@@ -76,7 +174,153 @@ impl<'a> IntoIterator for &'a Captures {
     type IntoIter = MatchIter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
-        MatchIter::new(self)
+        MatchIter::new(&self.buf, &self.matches)
+    }
+}
+
+/// CapturesRef is a represention of matched pattern which borrows a session buffer.
+///
+/// No bytes are copied, so a session can't be used while the captures are alive.
+/// Use [CapturesRef::to_owned] to keep them for longer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturesRef<'a> {
+    buf: &'a [u8],
+    matches: Vec<Match>,
+    offset: usize,
+    timestamp: Option<Instant>,
+}
+
+impl<'a> CapturesRef<'a> {
+    /// New returns an instance of CapturesRef.
+    pub(crate) fn new(buf: &'a [u8], matches: Vec<Match>) -> Self {
+        Self {
+            buf,
+            matches,
+            offset: 0,
+            timestamp: None,
+        }
+    }
+
+    /// Sets a position of the consumed chunk in a stream.
+    pub(crate) fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets a time when the match was read.
+    pub(crate) fn with_timestamp(mut self, timestamp: Option<Instant>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// is_empty verifies if any matches were actually found.
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    /// get returns a match by index.
+    pub fn get(&self, index: usize) -> Option<&'a [u8]> {
+        let buf = self.buf;
+        self.matches.get(index).map(|m| &buf[m.start()..m.end()])
+    }
+
+    /// Matches returns a list of matches.
+    pub fn matches(&self) -> MatchIter<'_> {
+        MatchIter::new(self.buf, &self.matches)
+    }
+
+    /// before returns a bytes before match.
+    pub fn before(&self) -> &'a [u8] {
+        let index = self
+            .matches
+            .iter()
+            .map(|m| m.start())
+            .min()
+            .unwrap_or_default();
+        &self.buf[..index]
+    }
+
+    /// as_bytes returns all bytes involved in a match, e.g. before the match and
+    /// in a match itself.
+    ///
+    /// See [Captures::as_bytes].
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// as_str_lossy returns all bytes involved in a match as a string.
+    ///
+    /// Invalid UTF-8 sequences are replaced with [`char::REPLACEMENT_CHARACTER`].
+    pub fn as_str_lossy(&self) -> Cow<'a, str> {
+        String::from_utf8_lossy(self.buf)
+    }
+
+    /// before_str_lossy returns a bytes before match as a string.
+    ///
+    /// Invalid UTF-8 sequences are replaced with [`char::REPLACEMENT_CHARACTER`].
+    pub fn before_str_lossy(&self) -> Cow<'a, str> {
+        String::from_utf8_lossy(self.before())
+    }
+
+    /// get_str_lossy returns a match by index as a string.
+    ///
+    /// Invalid UTF-8 sequences are replaced with [`char::REPLACEMENT_CHARACTER`].
+    pub fn get_str_lossy(&self, index: usize) -> Option<Cow<'a, str>> {
+        self.get(index).map(String::from_utf8_lossy)
+    }
+
+    /// offset returns a position in a stream where the consumed chunk ([Self::as_bytes]) starts.
+    ///
+    /// See [Captures::offset].
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// timestamp returns a time when the end of the match was read from a process.
+    pub fn timestamp(&self) -> Option<Instant> {
+        self.timestamp
+    }
+
+    /// match_range returns a position of a match by index in a stream.
+    ///
+    /// The range is absolute, use [Self::offset] to get a position relative to [Self::as_bytes].
+    pub fn match_range(&self, index: usize) -> Option<Range<usize>> {
+        self.matches
+            .get(index)
+            .map(|m| self.offset + m.start()..self.offset + m.end())
+    }
+
+    /// to_owned copies the captures, so they no longer borrow a session.
+    pub fn to_owned(&self) -> Captures {
+        Captures::new(self.buf.to_vec(), self.matches.clone())
+            .with_offset(self.offset)
+            .with_timestamp(self.timestamp)
+    }
+}
+
+impl Index<usize> for CapturesRef<'_> {
+    type Output = [u8];
+
+    fn index(&self, index: usize) -> &Self::Output {
+        let m = &self.matches[index];
+        &self.buf[m.start()..m.end()]
+    }
+}
+
+impl<'a> IntoIterator for &'a CapturesRef<'_> {
+    type Item = &'a [u8];
+    type IntoIter = MatchIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MatchIter::new(self.buf, &self.matches)
+    }
+}
+
+impl<'a> From<CapturesRef<'a>> for Captures {
+    fn from(captures: CapturesRef<'a>) -> Self {
+        Captures::new(captures.buf.to_vec(), captures.matches)
+            .with_offset(captures.offset)
+            .with_timestamp(captures.timestamp)
     }
 }
 
@@ -87,10 +331,10 @@ pub struct MatchIter<'a> {
 }
 
 impl<'a> MatchIter<'a> {
-    fn new(captures: &'a Captures) -> Self {
+    fn new(buf: &'a [u8], matches: &'a [Match]) -> Self {
         Self {
-            buf: &captures.buf,
-            matches: captures.matches.iter(),
+            buf,
+            matches: matches.iter(),
         }
     }
 }
@@ -271,6 +515,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_captures_offset() {
+        let m = Captures::new(b"You can use iterator".to_vec(), vec![Match::new(4, 7)]);
+        assert_eq!(m.offset(), 0);
+        assert_eq!(m.match_range(0), Some(4..7));
+        assert_eq!(m.match_range(1), None);
+
+        let m = m.with_offset(10);
+        assert_eq!(m.offset(), 10);
+        assert_eq!(m.match_range(0), Some(14..17));
+    }
+
+    #[test]
+    fn test_captures_str_lossy() {
+        let m = Captures::new(b"You can\xFF use".to_vec(), vec![Match::new(4, 8)]);
+        assert_eq!(m.as_str_lossy(), "You can\u{FFFD} use");
+        assert_eq!(m.before_str_lossy(), "You ");
+        assert_eq!(m.get_str_lossy(0).unwrap(), "can\u{FFFD}");
+        assert_eq!(m.get_str_lossy(1), None);
+    }
+
     #[test]
     #[should_panic]
     fn test_captures_into_iter_panics_on_invalid_match() {
@@ -278,4 +543,38 @@ mod tests {
             .into_iter()
             .for_each(|_| {});
     }
+
+    #[test]
+    fn test_captures_ref() {
+        let buf = b"You can use iterator".to_vec();
+        let m =
+            CapturesRef::new(&buf[..11], vec![Match::new(4, 7), Match::new(8, 11)]).with_offset(10);
+
+        assert_eq!(m.before(), b"You ");
+        assert_eq!(m.get(1), Some(b"use".as_ref()));
+        assert_eq!(&m[0], b"can");
+        assert_eq!(m.matches().collect::<Vec<_>>(), vec![b"can", b"use"]);
+        assert_eq!(m.match_range(0), Some(14..17));
+
+        let owned = m.to_owned();
+        assert_eq!(owned.as_bytes(), b"You can use");
+        assert_eq!(owned.offset(), 10);
+        assert_eq!(owned, Captures::from(m));
+    }
+
+    #[test]
+    fn test_captures_replace() {
+        let mut m = Captures::with_capacity(64);
+        assert!(m.is_empty());
+
+        m.replace(b"Hello World", &[Match::new(6, 11)], 5, None);
+        assert_eq!(m.before(), b"Hello ");
+        assert_eq!(m.get(0), Some(b"World".as_ref()));
+        assert_eq!(m.match_range(0), Some(11..16));
+
+        m.replace(b"Hi", &[Match::new(0, 2)], 16, None);
+        assert_eq!(m.as_bytes(), b"Hi");
+        assert_eq!(m.get(1), None);
+        assert_eq!(m.offset(), 16);
+    }
 }
@@ -1,4 +1,8 @@
-//! This module contains a `check!` macros.
+//! This module contains a `check!` and `expect!` macros.
+
+use std::cell::Cell;
+
+use crate::{needle::Match, Error, Needle};
 
 /// Check macros provides a convient way to check if things are available in a stream of a process.
 ///
@@ -257,8 +261,299 @@ macro_rules! check {
     };
 }
 
+/// Expect macros waits until any of the given patterns is matched,
+/// and falls into a corresponding branch.
+///
+/// It reads like a `match` statement.
+/// A pattern is on the left and a body which receives [`Captures`] is on the right.
+/// Patterns are checked from top to bottom on each iteration.
+///
+/// The macros levareges a single [`Expect::expect`] call,
+/// so expect timeout and lazy/gready mode of a session are respected.
+///
+/// There are 2 special branches.
+///
+/// - `timeout` is called when an expect timeout is reached (otherwise [`Error::ExpectTimeout`] is returned).
+/// - `eof` is called when an EOF is reached (otherwise [`Error::Eof`] is returned).
+///
+/// # Example
+/// ```no_run
+/// use expectrl::{spawn, Expect, Regex};
+///
+/// let mut session = spawn("ftp speedtest.tele2.net").unwrap();
+///
+/// expectrl::expect! {
+///     &mut session,
+///     _ = Regex("Name \\(.*\\):") => {
+///         session.send_line("anonymous").unwrap();
+///     },
+///     found = "Connection refused" => {
+///         panic!("{:?}", found.before());
+///     },
+///     timeout => {
+///         println!("ftp is not responding");
+///     },
+/// }
+/// .unwrap();
+/// ```
+///
+/// [`Expect::expect`]: crate::Expect::expect
+/// [`Captures`]: crate::Captures
+/// [`Error::ExpectTimeout`]: crate::Error::ExpectTimeout
+/// [`Error::Eof`]: crate::Error::Eof
+#[cfg(not(feature = "async"))]
+#[macro_export]
+macro_rules! expect {
+    (@parse $session:expr, (timeout => $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)+), ($($eof:tt)*)) => {
+        compile_error!("Only 1 timeout case is allowed")
+    };
+    (@parse $session:expr, (eof => $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ($($eof:tt)+)) => {
+        compile_error!("Only 1 eof case is allowed")
+    };
+    (@parse $session:expr, (timeout => $body:tt, $($tail:tt)*), ($($arms:tt)*), (), ($($eof:tt)*)) => {
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)*), ($body), ($($eof)*))
+    };
+    (@parse $session:expr, (timeout => $body:tt $($tail:tt)*), ($($arms:tt)*), (), ($($eof:tt)*)) => {
+        // allow missing comma
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)*), ($body), ($($eof)*))
+    };
+    (@parse $session:expr, (eof => $body:tt, $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ()) => {
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)*), ($($timeout)*), ($body))
+    };
+    (@parse $session:expr, (eof => $body:tt $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ()) => {
+        // allow missing comma
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)*), ($($timeout)*), ($body))
+    };
+    (@parse $session:expr, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ($($eof:tt)*)) => {
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)* ($var = $exp => $body)), ($($timeout)*), ($($eof)*))
+    };
+    (@parse $session:expr, ($var:tt = $exp:expr => $body:tt $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ($($eof:tt)*)) => {
+        // allow missing comma
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)* ($var = $exp => $body)), ($($timeout)*), ($($eof)*))
+    };
+    (@parse $session:expr, (), (), ($($timeout:tt)*), ($($eof:tt)*)) => {
+        compile_error!("There's no reason in running expect with no patterns. Please supply a pattern branches")
+    };
+    (@parse $session:expr, (), ($(($var:tt = $exp:expr => $body:tt))+), ($($timeout:tt)*), ($($eof:tt)*)) => {
+        {
+            let needles: &[&dyn $crate::Needle] = &[$(&$exp),+];
+            let arms = $crate::__ExpectArms::new(needles);
+            match $crate::Expect::expect($session, &arms) {
+                Ok(found) => {
+                    let index = arms.matched();
+                    $crate::expect!(@branch index, found, 1, $(($var => $body))+)
+                }
+                // an error of a named session is wrapped so the original one is matched
+                Err(err) => match err.root_error() {
+                    $crate::Error::ExpectTimeout { .. } => $crate::expect!(@special err, ($($timeout)*)),
+                    $crate::Error::Eof => $crate::expect!(@special err, ($($eof)*)),
+                    _ => Err(err),
+                },
+            }
+        }
+    };
+    (@parse $session:expr, ($($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ($($eof:tt)*)) => {
+        compile_error!(concat!("No supported syntax tail=(", stringify!($($tail)*), ")"))
+    };
+    (@branch $index:ident, $found:ident, $n:expr, ($var:tt => $body:tt) $($tail:tt)*) => {
+        if $index + 1 == $n {
+            let $var = $found;
+            $body;
+            #[allow(unreachable_code)]
+            Ok(())
+        } else {
+            $crate::expect!(@branch $index, $found, $n + 1, $($tail)*)
+        }
+    };
+    (@branch $index:ident, $found:ident, $n:expr,) => {
+        unreachable!("a matched branch index is out of range")
+    };
+    (@special $err:expr, ()) => {
+        Err($err)
+    };
+    (@special $err:expr, ($body:tt)) => {
+        {
+            $body;
+            #[allow(unreachable_code)]
+            Ok(())
+        }
+    };
+    // Entry point
+    ($session:expr, $($tokens:tt)*) => {
+        {
+            let result: Result::<(), $crate::Error> = $crate::expect!(@parse $session, ($($tokens)*), (), (), ());
+            result
+        }
+    };
+    ($($tokens:tt)*) => {
+        compile_error!("Please provide a session as a first argument")
+    };
+}
+
+/// See sync version.
+///
+/// Async version returns a future which must be awaited.
+#[cfg(feature = "async")]
+#[macro_export]
+macro_rules! expect {
+    (@parse $session:expr, (timeout => $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)+), ($($eof:tt)*)) => {
+        compile_error!("Only 1 timeout case is allowed")
+    };
+    (@parse $session:expr, (eof => $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ($($eof:tt)+)) => {
+        compile_error!("Only 1 eof case is allowed")
+    };
+    (@parse $session:expr, (timeout => $body:tt, $($tail:tt)*), ($($arms:tt)*), (), ($($eof:tt)*)) => {
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)*), ($body), ($($eof)*))
+    };
+    (@parse $session:expr, (timeout => $body:tt $($tail:tt)*), ($($arms:tt)*), (), ($($eof:tt)*)) => {
+        // allow missing comma
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)*), ($body), ($($eof)*))
+    };
+    (@parse $session:expr, (eof => $body:tt, $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ()) => {
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)*), ($($timeout)*), ($body))
+    };
+    (@parse $session:expr, (eof => $body:tt $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ()) => {
+        // allow missing comma
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)*), ($($timeout)*), ($body))
+    };
+    (@parse $session:expr, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ($($eof:tt)*)) => {
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)* ($var = $exp => $body)), ($($timeout)*), ($($eof)*))
+    };
+    (@parse $session:expr, ($var:tt = $exp:expr => $body:tt $($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ($($eof:tt)*)) => {
+        // allow missing comma
+        $crate::expect!(@parse $session, ($($tail)*), ($($arms)* ($var = $exp => $body)), ($($timeout)*), ($($eof)*))
+    };
+    (@parse $session:expr, (), (), ($($timeout:tt)*), ($($eof:tt)*)) => {
+        compile_error!("There's no reason in running expect with no patterns. Please supply a pattern branches")
+    };
+    (@parse $session:expr, (), ($(($var:tt = $exp:expr => $body:tt))+), ($($timeout:tt)*), ($($eof:tt)*)) => {
+        {
+            let needles: &[&dyn $crate::Needle] = &[$(&$exp),+];
+            let arms = $crate::__ExpectArms::new(needles);
+            match $crate::AsyncExpect::expect($session, &arms).await {
+                Ok(found) => {
+                    let index = arms.matched();
+                    $crate::expect!(@branch index, found, 1, $(($var => $body))+)
+                }
+                // an error of a named session is wrapped so the original one is matched
+                Err(err) => match err.root_error() {
+                    $crate::Error::ExpectTimeout { .. } => $crate::expect!(@special err, ($($timeout)*)),
+                    $crate::Error::Eof => $crate::expect!(@special err, ($($eof)*)),
+                    _ => Err(err),
+                },
+            }
+        }
+    };
+    (@parse $session:expr, ($($tail:tt)*), ($($arms:tt)*), ($($timeout:tt)*), ($($eof:tt)*)) => {
+        compile_error!(concat!("No supported syntax tail=(", stringify!($($tail)*), ")"))
+    };
+    (@branch $index:ident, $found:ident, $n:expr, ($var:tt => $body:tt) $($tail:tt)*) => {
+        if $index + 1 == $n {
+            let $var = $found;
+            $body;
+            #[allow(unreachable_code)]
+            Ok(())
+        } else {
+            $crate::expect!(@branch $index, $found, $n + 1, $($tail)*)
+        }
+    };
+    (@branch $index:ident, $found:ident, $n:expr,) => {
+        unreachable!("a matched branch index is out of range")
+    };
+    (@special $err:expr, ()) => {
+        Err($err)
+    };
+    (@special $err:expr, ($body:tt)) => {
+        {
+            $body;
+            #[allow(unreachable_code)]
+            Ok(())
+        }
+    };
+    // Entry point
+    ($session:expr, $($tokens:tt)*) => {
+        async {
+            let result: Result::<(), $crate::Error> = $crate::expect!(@parse $session, ($($tokens)*), (), (), ());
+            result
+        }
+    };
+    ($($tokens:tt)*) => {
+        compile_error!("Please provide a session as a first argument")
+    };
+}
+
+/// A needle which is used by [`expect!`] macros.
+///
+/// It's not a part of public API.
+#[doc(hidden)]
+pub struct ExpectArms<'a> {
+    needles: &'a [&'a dyn Needle],
+    matched: Cell<usize>,
+}
+
+impl<'a> ExpectArms<'a> {
+    #[doc(hidden)]
+    pub fn new(needles: &'a [&'a dyn Needle]) -> Self {
+        Self {
+            needles,
+            matched: Cell::new(0),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn matched(&self) -> usize {
+        self.matched.get()
+    }
+}
+
+impl std::fmt::Debug for ExpectArms<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExpectArms")
+            .field("needles", &self.needles.len())
+            .field("matched", &self.matched)
+            .finish()
+    }
+}
+
+impl Needle for ExpectArms<'_> {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        for (i, needle) in self.needles.iter().enumerate() {
+            let found = needle.check(buf, eof)?;
+            if !found.is_empty() {
+                self.matched.set(i);
+                return Ok(found);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    fn recheck_in(&self) -> Option<std::time::Duration> {
+        self.needles
+            .iter()
+            .filter_map(|needle| needle.recheck_in())
+            .min()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expect_arms() {
+        let needles: &[&dyn Needle] = &[&"123", &"rty", &"qwe"];
+        let arms = ExpectArms::new(needles);
+
+        assert_eq!(
+            arms.check(b"qwerty", false).unwrap(),
+            vec![Match::new(3, 6)]
+        );
+        assert_eq!(arms.matched(), 1);
+
+        assert_eq!(arms.check(b"asd", false).unwrap(), vec![]);
+    }
+
     #[allow(unused_variables)]
     #[allow(unused_must_use)]
     #[test]
@@ -322,6 +617,38 @@ mod tests {
             .unwrap();
         }
 
+        #[cfg(not(feature = "async"))]
+        {
+            crate::expect! {
+                &mut session,
+                as11d = "zxc" => {},
+                _ = crate::Regex("zxc") => {}
+                timeout => {},
+                eof => {}
+            }
+            .unwrap();
+            crate::expect! {
+                &mut session,
+                as11d = "zxc" => {
+                    println!("{:?}", as11d)
+                },
+            }
+            .unwrap();
+        }
+
+        #[cfg(feature = "async")]
+        async {
+            crate::expect! {
+                &mut session,
+                as11d = "zxc" => {},
+                _ = crate::Regex("zxc") => {}
+                timeout => {},
+                eof => {}
+            }
+            .await
+            .unwrap();
+        };
+
         #[cfg(feature = "async")]
         async {
             crate::check! {
@@ -0,0 +1,223 @@
+//! This module contains a clock abstraction which measures timeouts of a session.
+//!
+//! A session uses a [`Clock`] for expect timeouts and deadlines,
+//! a watchdog and a keepalive timers and delays between retries.
+//! By default it's [`SystemClock`], while [`ManualClock`] can be set
+//! to test a timeout handling code without real sleeps.
+//!
+//! Note that pacing of IO (e.g. polling a process for an exit, typing delays) uses a real time anyway.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+/// A future returned by [`Clock::delay`].
+#[cfg(feature = "async")]
+pub type Delay = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Clock is a source of time of a session.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Returns a current time.
+    fn now(&self) -> Instant;
+
+    /// Blocks for the `duration`.
+    fn sleep(&self, duration: Duration);
+
+    /// Returns whether the clock goes along with a real time.
+    ///
+    /// A session can't block on a process output till a deadline measured by a clock which doesn't,
+    /// so it wakes up each poll interval to check the deadline instead.
+    fn is_real_time(&self) -> bool {
+        true
+    }
+
+    /// Returns a future which is resolved after the `duration`.
+    #[cfg(feature = "async")]
+    fn delay(&self, duration: Duration) -> Delay;
+}
+
+/// SystemClock is a real time clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+
+    #[cfg(feature = "async")]
+    fn delay(&self, duration: Duration) -> Delay {
+        Box::pin(futures_timer::Delay::new(duration))
+    }
+}
+
+/// ManualClock is a virtual clock which is advanced only by hand.
+///
+/// Its clones share the time, so a clock can be given to a session
+/// and be advanced from a test.
+///
+/// [`Clock::sleep`] advances the clock right away instead of blocking,
+/// while a [`Clock::delay`] future waits until the clock is advanced far enough.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use expectrl::{clock::ManualClock, spawn, Error, Expect};
+///
+/// let clock = ManualClock::new();
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.set_clock(clock.clone());
+/// p.set_expect_timeout(Some(Duration::from_secs(60)));
+///
+/// let handle = std::thread::spawn(move || {
+///     std::thread::sleep(Duration::from_millis(100));
+///     clock.advance(Duration::from_secs(61));
+/// });
+///
+/// assert!(matches!(p.expect("never printed"), Err(Error::ExpectTimeout { .. })));
+/// handle.join().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    start: Instant,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    elapsed: Duration,
+    #[cfg(feature = "async")]
+    wakers: Vec<Waker>,
+}
+
+impl ManualClock {
+    /// Creates a clock which starts at a current time and stands still.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            state: Arc::default(),
+        }
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.elapsed += duration;
+
+        #[cfg(feature = "async")]
+        for waker in state.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Returns a time the clock was advanced by.
+    pub fn elapsed(&self) -> Duration {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .elapsed
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+
+    fn is_real_time(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "async")]
+    fn delay(&self, duration: Duration) -> Delay {
+        Box::pin(ManualDelay {
+            deadline: self.elapsed() + duration,
+            state: self.state.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+struct ManualDelay {
+    deadline: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+#[cfg(feature = "async")]
+impl Future for ManualDelay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.elapsed >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        state.wakers.push(cx.waker().clone());
+
+        Poll::Pending
+    }
+}
+
+/// Returns a clock which is used by default.
+pub(crate) fn default_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+
+        clock.clone().advance(Duration::from_secs(5));
+        assert_eq!(clock.now() - start, Duration::from_secs(5));
+
+        clock.sleep(Duration::from_secs(1));
+        assert_eq!(clock.elapsed(), Duration::from_secs(6));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_manual_clock_delay() {
+        let clock = ManualClock::new();
+        let mut delay = clock.delay(Duration::from_secs(5));
+
+        futures_lite::future::block_on(async {
+            assert!(futures_lite::future::poll_once(&mut delay).await.is_none());
+            clock.advance(Duration::from_secs(4));
+            assert!(futures_lite::future::poll_once(&mut delay).await.is_none());
+            clock.advance(Duration::from_secs(1));
+            assert!(futures_lite::future::poll_once(&mut delay).await.is_some());
+        });
+    }
+}
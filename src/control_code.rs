@@ -1,6 +1,6 @@
 //! A module which contains [ControlCode] type.
 
-use std::convert::TryFrom;
+use std::{convert::TryFrom, str::FromStr};
 
 /// ControlCode represents the standard ASCII control codes [wiki]
 ///
@@ -247,48 +247,88 @@ impl TryFrom<char> for ControlCode {
 impl TryFrom<&str> for ControlCode {
     type Error = ();
 
+    /// Parses a control code from a human readable form.
+    ///
+    /// The following notations are supported:
+    ///
+    /// - caret notation, e.g. `^C` or `^[`
+    /// - emacs like notation, e.g. `C-d`, `Ctrl-D` or `Ctrl+D`
+    /// - abbreviations, e.g. `ESC`, `NUL` or `DEL`
+    ///
+    /// Letters are case insensitive.
     fn try_from(c: &str) -> Result<ControlCode, ()> {
-        use ControlCode::*;
-        match c {
-            "^@" => Ok(Null),
-            "^A" => Ok(StartOfHeading),
-            "^B" => Ok(StartOfText),
-            "^C" => Ok(EndOfText),
-            "^D" => Ok(EndOfTransmission),
-            "^E" => Ok(Enquiry),
-            "^F" => Ok(Acknowledge),
-            "^G" => Ok(Bell),
-            "^H" => Ok(Backspace),
-            "^I" => Ok(HorizontalTabulation),
-            "^J" => Ok(LineFeed),
-            "^K" => Ok(VerticalTabulation),
-            "^L" => Ok(FormFeed),
-            "^M" => Ok(CarriageReturn),
-            "^N" => Ok(ShiftOut),
-            "^O" => Ok(ShiftIn),
-            "^P" => Ok(DataLinkEscape),
-            "^Q" => Ok(DeviceControl1),
-            "^R" => Ok(DeviceControl2),
-            "^S" => Ok(DeviceControl3),
-            "^T" => Ok(DeviceControl4),
-            "^U" => Ok(NegativeAcknowledge),
-            "^V" => Ok(SynchronousIdle),
-            "^W" => Ok(EndOfTransmissionBlock),
-            "^X" => Ok(Cancel),
-            "^Y" => Ok(EndOfMedium),
-            "^Z" => Ok(Substitute),
-            "^[" => Ok(Escape),
-            "^\\" => Ok(FileSeparator),
-            "^]" => Ok(GroupSeparator),
-            "^^" => Ok(RecordSeparator),
-            "^_" => Ok(UnitSeparator),
-            "^ " => Ok(Space),
-            "^?" => Ok(Delete),
-            _ => Err(()),
+        let key = c.strip_prefix('^').or_else(|| strip_ctrl_prefix(c));
+        if let Some(key) = key {
+            let mut chars = key.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(key), None) => ControlCode::try_from(key),
+                _ => Err(()),
+            };
         }
+
+        ABBREVIATIONS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(c))
+            .map(|(_, code)| *code)
+            .ok_or(())
+    }
+}
+
+impl FromStr for ControlCode {
+    type Err = ();
+
+    /// See [`ControlCode::try_from`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ControlCode::try_from(s)
     }
 }
 
+const ABBREVIATIONS: [(&str, ControlCode); 34] = [
+    ("NUL", ControlCode::NUL),
+    ("SOH", ControlCode::SOH),
+    ("STX", ControlCode::STX),
+    ("ETX", ControlCode::ETX),
+    ("EOT", ControlCode::EOT),
+    ("ENQ", ControlCode::ENQ),
+    ("ACK", ControlCode::ACK),
+    ("BEL", ControlCode::BEL),
+    ("BS", ControlCode::BS),
+    ("HT", ControlCode::HT),
+    ("LF", ControlCode::LF),
+    ("VT", ControlCode::VT),
+    ("FF", ControlCode::FF),
+    ("CR", ControlCode::CR),
+    ("SO", ControlCode::SO),
+    ("SI", ControlCode::SI),
+    ("DLE", ControlCode::DLE),
+    ("DC1", ControlCode::DC1),
+    ("DC2", ControlCode::DC2),
+    ("DC3", ControlCode::DC3),
+    ("DC4", ControlCode::DC4),
+    ("NAK", ControlCode::NAK),
+    ("SYN", ControlCode::SYN),
+    ("ETB", ControlCode::ETB),
+    ("CAN", ControlCode::CAN),
+    ("EM", ControlCode::EM),
+    ("SUB", ControlCode::SUB),
+    ("ESC", ControlCode::ESC),
+    ("FS", ControlCode::FS),
+    ("GS", ControlCode::GS),
+    ("RS", ControlCode::RS),
+    ("US", ControlCode::US),
+    ("SP", ControlCode::SP),
+    ("DEL", ControlCode::DEL),
+];
+
+fn strip_ctrl_prefix(s: &str) -> Option<&str> {
+    ["C-", "Ctrl-", "Ctrl+"]
+        .iter()
+        .find_map(|prefix| match s.get(..prefix.len()) {
+            Some(start) if start.eq_ignore_ascii_case(prefix) => Some(&s[prefix.len()..]),
+            _ => None,
+        })
+}
+
 impl AsRef<str> for ControlCode {
     fn as_ref(&self) -> &str {
         use ControlCode::*;
@@ -372,3 +412,31 @@ impl AsRef<[u8]> for ControlCode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        for s in ["^C", "^c", "C-c", "C-C", "Ctrl-C", "ctrl+c", "ETX", "etx"] {
+            assert_eq!(
+                ControlCode::try_from(s),
+                Ok(ControlCode::EndOfText),
+                "{:?}",
+                s
+            );
+        }
+
+        assert_eq!("C-d".parse(), Ok(ControlCode::EndOfTransmission));
+        assert_eq!("ESC".parse(), Ok(ControlCode::Escape));
+        assert_eq!("^[".parse(), Ok(ControlCode::Escape));
+        assert_eq!("^?".parse(), Ok(ControlCode::Delete));
+        assert_eq!("DEL".parse(), Ok(ControlCode::Delete));
+        assert_eq!("^ ".parse(), Ok(ControlCode::Space));
+
+        for s in ["", "^", "^CC", "C-", "Ctrl-Cc", "ESCAPE", "^1"] {
+            assert_eq!(ControlCode::try_from(s), Err(()), "{:?}", s);
+        }
+    }
+}
@@ -0,0 +1,617 @@
+//! This module contains helpers which go through common dialogs.
+//!
+//! [`Login`] answers username, password and host key prompts
+//! until a shell prompt is reached or the dialog fails.
+//! [`Sudo`] runs a command with elevated privileges and supplies a password.
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use expectrl::{dialog::{Login, LoginOutcome}, spawn};
+//!
+//! let mut session = spawn("ssh user@localhost").unwrap();
+//!
+//! let outcome = Login::new("user", "secret")
+//!     .accept_host_key(true)
+//!     .run(&mut session)
+//!     .unwrap();
+//!
+//! assert_eq!(outcome, LoginOutcome::Shell);
+//! ```
+
+use std::fmt;
+
+use crate::{
+    check_macros::ExpectArms, stream::log::SecretGuard, ControlCode, Error, Needle, Regex,
+};
+
+#[cfg(not(feature = "async"))]
+use crate::Expect;
+
+#[cfg(feature = "async")]
+use crate::AsyncExpect;
+
+/// A state which ended a login dialog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginOutcome {
+    /// A shell prompt was reached.
+    Shell,
+    /// Credentials were rejected more times than it's allowed by [`Login::retries`].
+    AuthFailure,
+    /// A host key was not accepted, see [`Login::accept_host_key`].
+    HostKeyRejected,
+    /// None of the prompts was seen in time.
+    Timeout,
+    /// A process has exited.
+    Eof,
+}
+
+/// Login is a description of a login dialog.
+///
+/// Prompts are matched in the following order:
+/// an auth failure, a host key confirmation, a password, a username and a shell prompt.
+/// Each of them can be replaced if a program uses a different wording.
+///
+/// A timeout of each step is a session's one.
+/// A password is hidden from logs while it's sent.
+pub struct Login {
+    username: String,
+    password: String,
+    accept_host_key: bool,
+    retries: usize,
+    failure: Box<dyn Needle>,
+    host_key_prompt: Box<dyn Needle>,
+    password_prompt: Box<dyn Needle>,
+    username_prompt: Box<dyn Needle>,
+    shell_prompt: Box<dyn Needle>,
+}
+
+impl Login {
+    /// Creates a dialog with default prompts.
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+            accept_host_key: false,
+            retries: 0,
+            failure: Box::new(Regex(
+                "(?i)(permission denied|login incorrect|authentication failed)",
+            )),
+            host_key_prompt: Box::new(Regex("(?i)continue connecting.*\\?")),
+            password_prompt: Box::new(Regex("(?i)password[^:\\n]*:\\s*$")),
+            username_prompt: Box::new(Regex("(?i)(username|login)\\s*:\\s*$")),
+            shell_prompt: Box::new(Regex(SHELL_PROMPT)),
+        }
+    }
+
+    /// Answer `yes` to an unknown host key confirmation.
+    ///
+    /// It's off by default and the dialog ends with [`LoginOutcome::HostKeyRejected`].
+    pub fn accept_host_key(mut self, on: bool) -> Self {
+        self.accept_host_key = on;
+        self
+    }
+
+    /// Set how many times credentials are resent after an auth failure.
+    ///
+    /// By default the dialog ends on a first failure.
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set a pattern of an auth failure message.
+    pub fn failure<N>(mut self, needle: N) -> Self
+    where
+        N: Needle + 'static,
+    {
+        self.failure = Box::new(needle);
+        self
+    }
+
+    /// Set a pattern of a host key confirmation.
+    pub fn host_key_prompt<N>(mut self, needle: N) -> Self
+    where
+        N: Needle + 'static,
+    {
+        self.host_key_prompt = Box::new(needle);
+        self
+    }
+
+    /// Set a pattern of a password prompt.
+    pub fn password_prompt<N>(mut self, needle: N) -> Self
+    where
+        N: Needle + 'static,
+    {
+        self.password_prompt = Box::new(needle);
+        self
+    }
+
+    /// Set a pattern of a username prompt.
+    pub fn username_prompt<N>(mut self, needle: N) -> Self
+    where
+        N: Needle + 'static,
+    {
+        self.username_prompt = Box::new(needle);
+        self
+    }
+
+    /// Set a pattern of a shell prompt.
+    pub fn shell_prompt<N>(mut self, needle: N) -> Self
+    where
+        N: Needle + 'static,
+    {
+        self.shell_prompt = Box::new(needle);
+        self
+    }
+
+    /// Runs the dialog.
+    ///
+    /// A timeout and EOF are reported as a [`LoginOutcome`], other errors are returned as they are.
+    #[cfg(not(feature = "async"))]
+    pub fn run<E>(&self, session: &mut E) -> Result<LoginOutcome, Error>
+    where
+        E: Expect,
+    {
+        let mut state = LoginState::new(self);
+        run_dialog(session, &self.needles(), |index| state.on_match(index))
+    }
+
+    /// Runs the dialog.
+    ///
+    /// A timeout and EOF are reported as a [`LoginOutcome`], other errors are returned as they are.
+    #[cfg(feature = "async")]
+    pub async fn run<E>(&self, session: &mut E) -> Result<LoginOutcome, Error>
+    where
+        E: AsyncExpect,
+    {
+        let mut state = LoginState::new(self);
+        run_dialog(session, &self.needles(), |index| state.on_match(index)).await
+    }
+
+    fn needles(&self) -> [&dyn Needle; 5] {
+        [
+            self.failure.as_ref(),
+            self.host_key_prompt.as_ref(),
+            self.password_prompt.as_ref(),
+            self.username_prompt.as_ref(),
+            self.shell_prompt.as_ref(),
+        ]
+    }
+}
+
+impl fmt::Debug for Login {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Login")
+            .field("username", &self.username)
+            .field("password", &"******")
+            .field("accept_host_key", &self.accept_host_key)
+            .field("retries", &self.retries)
+            .finish_non_exhaustive()
+    }
+}
+
+/// LoginState keeps a state of a running [`Login`].
+#[derive(Debug)]
+struct LoginState<'a> {
+    login: &'a Login,
+    failures: usize,
+    passwords: usize,
+}
+
+impl<'a> LoginState<'a> {
+    fn new(login: &'a Login) -> Self {
+        Self {
+            login,
+            failures: 0,
+            passwords: 0,
+        }
+    }
+
+    /// Decides what to do when a needle with a given index from [`Login::needles`] is matched.
+    fn on_match(&mut self, index: usize) -> Answer<'a, LoginOutcome> {
+        match index {
+            0 => {
+                self.failures += 1;
+                if self.failures > self.login.retries {
+                    Answer::Done(LoginOutcome::AuthFailure)
+                } else {
+                    // A failure message is usually followed by a new prompt.
+                    Answer::Wait
+                }
+            }
+            1 if self.login.accept_host_key => Answer::Send("yes"),
+            1 => Answer::Done(LoginOutcome::HostKeyRejected),
+            2 => {
+                // A password prompt may be repeated without a failure message.
+                self.passwords += 1;
+                if self.passwords > self.login.retries + 1 {
+                    Answer::Done(LoginOutcome::AuthFailure)
+                } else {
+                    Answer::SendSecret(&self.login.password)
+                }
+            }
+            3 => Answer::Send(&self.login.username),
+            _ => Answer::Done(LoginOutcome::Shell),
+        }
+    }
+}
+
+/// A state which ended a [`Sudo`] dialog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SudoOutcome {
+    /// A command prompt was reached.
+    Prompt,
+    /// A password was rejected.
+    ///
+    /// A pending password prompt is interrupted by `Ctrl-C`.
+    AuthFailure,
+    /// Neither a password prompt nor a command prompt was seen in time.
+    Timeout,
+    /// A process has exited.
+    Eof,
+}
+
+/// Sudo runs a command with elevated privileges and supplies a password when it's asked.
+///
+/// A password prompt is recognized in a few common locales,
+/// and the password is hidden from logs while it's sent.
+///
+/// A session is expected to be at a command prompt with everything before it consumed,
+/// as the same prompt is waited for once the command is done.
+///
+/// # Example
+///
+#[cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#[cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+/// use expectrl::{dialog::{Sudo, SudoOutcome}, spawn};
+///
+/// let mut session = spawn("bash").unwrap();
+///
+/// let outcome = Sudo::new("apt-get update", "secret").run(&mut session).unwrap();
+/// assert_eq!(outcome, SudoOutcome::Prompt);
+/// ```
+pub struct Sudo {
+    command: String,
+    password: String,
+    failure: Box<dyn Needle>,
+    password_prompt: Box<dyn Needle>,
+    prompt: Box<dyn Needle>,
+}
+
+impl Sudo {
+    /// Creates a dialog which runs a command by `sudo`.
+    pub fn new(command: impl AsRef<str>, password: impl Into<String>) -> Self {
+        Self::with_command(format!("sudo {}", command.as_ref()), password.into())
+    }
+
+    /// Creates a dialog which runs a command by `su -c`.
+    pub fn su(command: impl AsRef<str>, password: impl Into<String>) -> Self {
+        let command = command.as_ref().replace('\'', r"'\''");
+        Self::with_command(format!("su -c '{}'", command), password.into())
+    }
+
+    fn with_command(command: String, password: String) -> Self {
+        Self {
+            command,
+            password,
+            failure: Box::new(Regex(SUDO_FAILURE)),
+            password_prompt: Box::new(Regex(SUDO_PASSWORD_PROMPT)),
+            prompt: Box::new(Regex(SHELL_PROMPT)),
+        }
+    }
+
+    /// Set a pattern of a rejected password message.
+    pub fn failure<N>(mut self, needle: N) -> Self
+    where
+        N: Needle + 'static,
+    {
+        self.failure = Box::new(needle);
+        self
+    }
+
+    /// Set a pattern of a password prompt.
+    pub fn password_prompt<N>(mut self, needle: N) -> Self
+    where
+        N: Needle + 'static,
+    {
+        self.password_prompt = Box::new(needle);
+        self
+    }
+
+    /// Set a pattern of a command prompt.
+    pub fn prompt<N>(mut self, needle: N) -> Self
+    where
+        N: Needle + 'static,
+    {
+        self.prompt = Box::new(needle);
+        self
+    }
+
+    /// Get a command line which is sent to a session.
+    pub fn get_command(&self) -> &str {
+        &self.command
+    }
+
+    /// Runs the command and waits for a prompt.
+    ///
+    /// A timeout and EOF are reported as a [`SudoOutcome`], other errors are returned as they are.
+    #[cfg(not(feature = "async"))]
+    pub fn run<E>(&self, session: &mut E) -> Result<SudoOutcome, Error>
+    where
+        E: Expect,
+    {
+        session.send_line(&self.command)?;
+
+        let mut state = SudoState::new(self);
+        let outcome = run_dialog(session, &self.needles(), |index| state.on_match(index))?;
+        if outcome == SudoOutcome::AuthFailure {
+            session.send(ControlCode::EndOfText)?;
+        }
+
+        Ok(outcome)
+    }
+
+    /// Runs the command and waits for a prompt.
+    ///
+    /// A timeout and EOF are reported as a [`SudoOutcome`], other errors are returned as they are.
+    #[cfg(feature = "async")]
+    pub async fn run<E>(&self, session: &mut E) -> Result<SudoOutcome, Error>
+    where
+        E: AsyncExpect,
+    {
+        session.send_line(&self.command).await?;
+
+        let mut state = SudoState::new(self);
+        let outcome = run_dialog(session, &self.needles(), |index| state.on_match(index)).await?;
+        if outcome == SudoOutcome::AuthFailure {
+            session.send(ControlCode::EndOfText).await?;
+        }
+
+        Ok(outcome)
+    }
+
+    fn needles(&self) -> [&dyn Needle; 3] {
+        [
+            self.failure.as_ref(),
+            self.password_prompt.as_ref(),
+            self.prompt.as_ref(),
+        ]
+    }
+}
+
+impl fmt::Debug for Sudo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sudo")
+            .field("command", &self.command)
+            .field("password", &"******")
+            .finish_non_exhaustive()
+    }
+}
+
+/// SudoState keeps a state of a running [`Sudo`].
+#[derive(Debug)]
+struct SudoState<'a> {
+    sudo: &'a Sudo,
+    passwords: usize,
+}
+
+impl<'a> SudoState<'a> {
+    fn new(sudo: &'a Sudo) -> Self {
+        Self { sudo, passwords: 0 }
+    }
+
+    /// Decides what to do when a needle with a given index from [`Sudo::needles`] is matched.
+    fn on_match(&mut self, index: usize) -> Answer<'a, SudoOutcome> {
+        match index {
+            0 => Answer::Done(SudoOutcome::AuthFailure),
+            1 => {
+                // A repeated prompt means the password was rejected.
+                self.passwords += 1;
+                if self.passwords > 1 {
+                    Answer::Done(SudoOutcome::AuthFailure)
+                } else {
+                    Answer::SendSecret(&self.sudo.password)
+                }
+            }
+            _ => Answer::Done(SudoOutcome::Prompt),
+        }
+    }
+}
+
+const SHELL_PROMPT: &str = r"[$#>%]\s*$";
+
+/// A password prompt of `sudo` and `su` in a few common locales.
+const SUDO_PASSWORD_PROMPT: &str = r"(?i)(\[sudo\]|password|passwort|mot de passe|contraseña|senha|пароль|密码|パスワード)[^\n]*[:：]\s*$";
+
+/// A rejected password message of `sudo` and `su` in a few common locales.
+const SUDO_FAILURE: &str = r"(?i)(sorry, try again|incorrect password|authentication failure|versuchen sie es noch einmal|réessayer|pruebe otra vez|попробуйте ещё раз)";
+
+/// An action taken after a needle of a dialog is matched.
+#[derive(Debug, PartialEq, Eq)]
+enum Answer<'a, O> {
+    Send(&'a str),
+    /// Send a line which is hidden from logs.
+    SendSecret(&'a str),
+    Wait,
+    Done(O),
+}
+
+/// Outcome is a result of a dialog.
+trait Outcome: Sized {
+    /// Turns a timeout and EOF into an outcome.
+    fn from_error(err: Error) -> Result<Self, Error>;
+}
+
+impl Outcome for LoginOutcome {
+    fn from_error(err: Error) -> Result<Self, Error> {
+        match err.root_error() {
+            Error::ExpectTimeout { .. } => Ok(LoginOutcome::Timeout),
+            Error::Eof => Ok(LoginOutcome::Eof),
+            _ => Err(err),
+        }
+    }
+}
+
+impl Outcome for SudoOutcome {
+    fn from_error(err: Error) -> Result<Self, Error> {
+        match err.root_error() {
+            Error::ExpectTimeout { .. } => Ok(SudoOutcome::Timeout),
+            Error::Eof => Ok(SudoOutcome::Eof),
+            _ => Err(err),
+        }
+    }
+}
+
+/// Expects any of the needles and answers them until a dialog is done.
+///
+/// It's shared by all dialogs so the logic lives in their `on_match` functions.
+#[cfg(not(feature = "async"))]
+fn run_dialog<'a, E, O>(
+    session: &mut E,
+    needles: &[&dyn Needle],
+    mut on_match: impl FnMut(usize) -> Answer<'a, O>,
+) -> Result<O, Error>
+where
+    E: Expect,
+    O: Outcome,
+{
+    loop {
+        let arms = ExpectArms::new(needles);
+        if let Err(err) = session.expect(&arms) {
+            return O::from_error(err);
+        }
+
+        match on_match(arms.matched()) {
+            Answer::Send(line) => session.send_line(line)?,
+            Answer::SendSecret(secret) => {
+                let _guard = SecretGuard::new(secret);
+                session.send_line(secret)?;
+            }
+            Answer::Wait => (),
+            Answer::Done(outcome) => return Ok(outcome),
+        }
+    }
+}
+
+/// Expects any of the needles and answers them until a dialog is done.
+///
+/// It's shared by all dialogs so the logic lives in their `on_match` functions.
+#[cfg(feature = "async")]
+async fn run_dialog<'a, E, O>(
+    session: &mut E,
+    needles: &[&dyn Needle],
+    mut on_match: impl FnMut(usize) -> Answer<'a, O>,
+) -> Result<O, Error>
+where
+    E: AsyncExpect,
+    O: Outcome,
+{
+    loop {
+        let arms = ExpectArms::new(needles);
+        if let Err(err) = session.expect(&arms).await {
+            return O::from_error(err);
+        }
+
+        match on_match(arms.matched()) {
+            Answer::Send(line) => session.send_line(line).await?,
+            Answer::SendSecret(secret) => {
+                let _guard = SecretGuard::new(secret);
+                session.send_line(secret).await?;
+            }
+            Answer::Wait => (),
+            Answer::Done(outcome) => return Ok(outcome),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dialog() {
+        let login = Login::new("user", "secret").retries(1);
+        let mut dialog = LoginState::new(&login);
+
+        assert_eq!(dialog.on_match(3), Answer::Send("user"));
+        assert_eq!(dialog.on_match(2), Answer::SendSecret("secret"));
+        assert_eq!(dialog.on_match(0), Answer::Wait);
+        assert_eq!(dialog.on_match(2), Answer::SendSecret("secret"));
+        assert_eq!(dialog.on_match(0), Answer::Done(LoginOutcome::AuthFailure));
+    }
+
+    #[test]
+    fn test_dialog_host_key() {
+        let login = Login::new("user", "secret");
+        assert_eq!(
+            LoginState::new(&login).on_match(1),
+            Answer::Done(LoginOutcome::HostKeyRejected)
+        );
+
+        let login = login.accept_host_key(true);
+        assert_eq!(LoginState::new(&login).on_match(1), Answer::Send("yes"));
+        assert_eq!(
+            LoginState::new(&login).on_match(4),
+            Answer::Done(LoginOutcome::Shell)
+        );
+    }
+
+    #[test]
+    fn test_default_prompts() {
+        let login = Login::new("user", "secret");
+        let needles = login.needles();
+        let index_of = |buf: &str| {
+            let arms = ExpectArms::new(&needles);
+            let found = arms.check(buf.as_bytes(), false).unwrap();
+            (!found.is_empty()).then(|| arms.matched())
+        };
+
+        assert_eq!(
+            index_of("Permission denied, please try again.\r\n"),
+            Some(0)
+        );
+        assert_eq!(
+            index_of("Are you sure you want to continue connecting (yes/no)? "),
+            Some(1)
+        );
+        assert_eq!(index_of("user@localhost's password: "), Some(2));
+        assert_eq!(index_of("Password:"), Some(2));
+        assert_eq!(index_of("ubuntu login: "), Some(3));
+        assert_eq!(index_of("user@localhost:~$ "), Some(4));
+        assert_eq!(index_of("Welcome to Ubuntu"), None);
+    }
+
+    #[test]
+    fn test_sudo() {
+        let sudo = Sudo::new("ls -la", "secret");
+        assert_eq!(sudo.get_command(), "sudo ls -la");
+
+        let mut state = SudoState::new(&sudo);
+        assert_eq!(state.on_match(1), Answer::SendSecret("secret"));
+        assert_eq!(state.on_match(1), Answer::Done(SudoOutcome::AuthFailure));
+
+        let sudo = Sudo::su("echo 'hi'", "secret");
+        assert_eq!(sudo.get_command(), r"su -c 'echo '\''hi'\'''");
+    }
+
+    #[test]
+    fn test_sudo_prompts() {
+        let sudo = Sudo::new("ls", "secret");
+        let needles = sudo.needles();
+        let index_of = |buf: &str| {
+            let arms = ExpectArms::new(&needles);
+            let found = arms.check(buf.as_bytes(), false).unwrap();
+            (!found.is_empty()).then(|| arms.matched())
+        };
+
+        assert_eq!(index_of("[sudo] password for user: "), Some(1));
+        assert_eq!(index_of("[sudo] Passwort für user: "), Some(1));
+        assert_eq!(index_of("[sudo] Mot de passe de user : "), Some(1));
+        assert_eq!(index_of("[sudo] пароль для user: "), Some(1));
+        assert_eq!(index_of("Password:"), Some(1));
+        assert_eq!(index_of("Sorry, try again.\r\n"), Some(0));
+        assert_eq!(index_of("su: Authentication failure\r\n"), Some(0));
+        assert_eq!(index_of("total 0\r\nuser@host:~$ "), Some(2));
+        assert_eq!(index_of("sudo ls\r\n"), None);
+    }
+}
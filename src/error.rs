@@ -2,21 +2,112 @@ use std::error;
 use std::fmt;
 use std::fmt::Display;
 use std::io;
+use std::time::Duration;
+
+use crate::{process::ExitStatus, Captures};
 
 #[allow(variant_size_differences)]
 /// An main error type used in [crate].
 #[derive(Debug)]
 pub enum Error {
     /// An Error in IO operation.
-    IO(io::Error),
+    Io {
+        /// An operation which has failed, e.g. `read` or `write`.
+        op: &'static str,
+        /// An underlying error.
+        source: io::Error,
+    },
+    /// A process could not be spawned.
+    SpawnFailed {
+        /// A command which was spawned.
+        cmd: String,
+        /// An underlying error.
+        source: io::Error,
+    },
     /// An Error in command line parsing.
     CommandParsing,
     /// An Error in regex parsing.
     RegexParsing,
     /// An timeout was reached while waiting in expect call.
-    ExpectTimeout,
+    ExpectTimeout {
+        /// A description of a needle which was expected, see [`Needle::describe`].
+        ///
+        /// [`Needle::describe`]: crate::Needle::describe
+        needle: String,
+        /// A tail of an output which was read but not consumed.
+        ///
+        /// It's at most [`Error::BUFFER_TAIL_LEN`] bytes long.
+        buffer: Vec<u8>,
+    },
     /// Unhandled EOF error.
     Eof,
+    /// An expect call was cancelled.
+    Cancelled,
+    /// An abort pattern registered on a session was found in an output.
+    Aborted {
+        /// A description of the matched pattern.
+        pattern: String,
+        /// An output which was consumed, including the match.
+        captures: Captures,
+    },
+    /// A step of a [`Script`] has failed.
+    ///
+    /// [`Script`]: crate::script::Script
+    Script {
+        /// A position of the step.
+        ///
+        /// It contains an index on each level of nesting.
+        step: Vec<usize>,
+        /// An error of the step.
+        err: Box<Error>,
+    },
+    /// An error of a named session.
+    ///
+    /// It's returned by an expect call of a session with a name,
+    /// see `Session::set_name`.
+    Session {
+        /// A name of the session.
+        name: String,
+        /// An original error.
+        err: Box<Error>,
+    },
+    /// An operation on a session of a [`SessionPool`] has failed.
+    ///
+    /// [`SessionPool`]: crate::SessionPool
+    Pool {
+        /// An index of the session in the pool.
+        index: usize,
+        /// An error of the session.
+        err: Box<Error>,
+    },
+    /// A time limit of a block was reached, see `Session::within`.
+    Within {
+        /// An index of a step which was in flight.
+        ///
+        /// Expect and send calls of the block are counted from 0.
+        step: usize,
+        /// A kind of the step, `expect` or `send`.
+        action: String,
+        /// A time limit of the block.
+        limit: Duration,
+    },
+    /// A process has exited while it was expected to be running.
+    ProcessExited {
+        /// A status of the process.
+        status: ExitStatus,
+    },
+    /// A process is still running after a timeout of waiting for its exit was reached.
+    ProcessAlive {
+        /// An output which was read but not consumed.
+        buffer: Vec<u8>,
+    },
+    /// A process exited with an unexpected status.
+    UnexpectedExit {
+        /// A status of the process.
+        status: ExitStatus,
+        /// An output which was read but not consumed.
+        buffer: Vec<u8>,
+    },
     /// It maybe OS specific error or a general erorr.
     Other {
         /// The reason of the erorr.
@@ -27,6 +118,57 @@ pub enum Error {
 }
 
 impl Error {
+    /// A maximum length of an output kept in [`Error::ExpectTimeout`].
+    pub const BUFFER_TAIL_LEN: usize = 256;
+
+    /// Returns a function which wraps an IO error of an operation.
+    pub(crate) fn io(op: &'static str) -> impl FnOnce(io::Error) -> Error {
+        move |source| Error::Io { op, source }
+    }
+
+    /// Creates [`Error::ExpectTimeout`] keeping a tail of a not consumed output.
+    pub(crate) fn expect_timeout(needle: String, buffer: &[u8]) -> Error {
+        let start = buffer.len().saturating_sub(Self::BUFFER_TAIL_LEN);
+        Error::ExpectTimeout {
+            needle,
+            buffer: buffer[start..].to_vec(),
+        }
+    }
+
+    /// Returns an original error of a named session,
+    /// so it can be matched regardless of whether the session has a name.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, Error, Expect};
+    ///
+    /// let mut p = spawn("cat").unwrap();
+    /// p.set_name("cat");
+    ///
+    /// match p.expect("Hello") {
+    ///     Err(err) if matches!(err.root_error(), Error::ExpectTimeout { .. }) => {}
+    ///     result => drop(result.unwrap()),
+    /// }
+    /// ```
+    pub fn root_error(&self) -> &Error {
+        match self {
+            Error::Session { err, .. } => err.root_error(),
+            err => err,
+        }
+    }
+
+    /// Wraps the error into [`Error::Session`] if a session has a name.
+    pub(crate) fn with_session_name(self, name: Option<&str>) -> Error {
+        match name {
+            Some(name) => Error::Session {
+                name: name.to_owned(),
+                err: Box::new(self),
+            },
+            None => self,
+        }
+    }
+
     #[cfg(unix)]
     pub(crate) fn unknown(message: impl Into<String>, err: impl Into<String>) -> Error {
         Self::Other {
@@ -39,21 +181,81 @@ impl Error {
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Error::IO(err) => write!(f, "IO error {}", err),
+            Error::Io { op, source } => write!(f, "IO error ({}) {}", op, source),
+            Error::SpawnFailed { cmd, source } => {
+                write!(f, "Failed to spawn a command {}; {}", cmd, source)
+            }
             Error::CommandParsing => write!(f, "Can't parse a command string, please check it out"),
             Error::RegexParsing => write!(f, "Can't parse a regex expression"),
-            Error::ExpectTimeout => write!(f, "Reached a timeout for expect type of command"),
+            Error::ExpectTimeout { needle, buffer } => write!(
+                f,
+                "Reached a timeout for expect type of command, waiting for {}; {:?}",
+                needle,
+                String::from_utf8_lossy(buffer)
+            ),
             Error::Eof => write!(f, "EOF was reached; the read may successed later"),
+            Error::Cancelled => write!(f, "An expect call was cancelled"),
+            Error::Aborted { pattern, captures } => write!(
+                f,
+                "An abort pattern {} was found in the output; {:?}",
+                pattern,
+                captures.as_str_lossy()
+            ),
+            Error::Script { step, err } => {
+                let step = step
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                write!(f, "A script failed at step {}; {}", step, err)
+            }
+            Error::Session { name, err } => write!(f, "{} on session {:?}", err, name),
+            Error::Pool { index, err } => {
+                write!(f, "A session {} in a pool failed; {}", index, err)
+            }
+            Error::Within {
+                step,
+                action,
+                limit,
+            } => write!(
+                f,
+                "A time limit {:?} was reached at step {} ({})",
+                limit, step, action
+            ),
+            Error::ProcessExited { status } => {
+                write!(f, "A process has unexpectedly exited ({})", status)
+            }
+            Error::ProcessAlive { buffer } => write!(
+                f,
+                "A process is still running; {:?}",
+                String::from_utf8_lossy(buffer)
+            ),
+            Error::UnexpectedExit { status, buffer } => write!(
+                f,
+                "A process exited with an unexpected status ({}); {:?}",
+                status,
+                String::from_utf8_lossy(buffer)
+            ),
             Error::Other { message, err } => write!(f, "Unexpected error; {}; {}", message, err),
         }
     }
 }
 
-impl error::Error for Error {}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } | Error::SpawnFailed { source, .. } => Some(source),
+            Error::Script { err, .. } | Error::Session { err, .. } | Error::Pool { err, .. } => {
+                Some(err.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
 
 impl From<io::Error> for Error {
-    fn from(err: io::Error) -> Self {
-        Self::IO(err)
+    fn from(source: io::Error) -> Self {
+        Self::Io { op: "io", source }
     }
 }
 
@@ -1,6 +1,9 @@
-use crate::{Captures, Error, Needle};
+use crate::{Captures, ControlCode, Error, Key, Needle};
 
 /// Expect trait provides common expect functions.
+///
+/// The methods are generic over [`Needle`] therefore the trait itself is not object safe,
+/// but any needle can be passed as `Box<dyn Needle>` or `&dyn Needle`.
 pub trait Expect {
     /// Expect waits until a pattern is matched.
     ///
@@ -155,6 +158,46 @@ pub trait Expect {
     fn send_line<B>(&mut self, buf: B) -> Result<(), Error>
     where
         B: AsRef<[u8]>;
+
+    /// Send a control code given in a human readable form, e.g. `^C`, `C-d` or `ESC`.
+    ///
+    /// See [`ControlCode::try_from`] for a list of supported notations.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(any(windows, feature = "async"), doc = "```ignore")]
+    #[cfg_attr(not(any(windows, feature = "async")), doc = "```")]
+    /// use expectrl::{spawn, Expect};
+    ///
+    /// let mut proc = spawn("cat").unwrap();
+    ///
+    /// proc.send_control_str("C-c").unwrap();
+    /// ```
+    fn send_control_str(&mut self, code: &str) -> Result<(), Error> {
+        let code = parse_control_code(code)?;
+        self.send(code)
+    }
+
+    /// Send a special key like an arrow or a function key.
+    ///
+    /// An escape sequence of [`TerminalType::Xterm`] is used,
+    /// use [`Key::sequence`] with [`Expect::send`] for other terminals.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(any(windows, feature = "async"), doc = "```ignore")]
+    #[cfg_attr(not(any(windows, feature = "async")), doc = "```")]
+    /// use expectrl::{spawn, Expect, Key};
+    ///
+    /// let mut proc = spawn("cat").unwrap();
+    ///
+    /// proc.send_key(Key::Up).unwrap();
+    /// ```
+    ///
+    /// [`TerminalType::Xterm`]: crate::TerminalType::Xterm
+    fn send_key(&mut self, key: Key) -> Result<(), Error> {
+        self.send(key)
+    }
 }
 
 impl<T> Expect for &mut T
@@ -197,6 +240,46 @@ where
     }
 }
 
+impl<T> Expect for Box<T>
+where
+    T: Expect,
+{
+    fn expect<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        T::expect(self, needle)
+    }
+
+    fn check<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        T::check(self, needle)
+    }
+
+    fn is_matched<N>(&mut self, needle: N) -> Result<bool, Error>
+    where
+        N: Needle,
+    {
+        T::is_matched(self, needle)
+    }
+
+    fn send<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        T::send(self, buf)
+    }
+
+    fn send_line<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        T::send_line(self, buf)
+    }
+}
+
 #[cfg(feature = "async")]
 /// Expect trait provides common expect functions.
 pub trait AsyncExpect {
@@ -319,6 +402,24 @@ pub trait AsyncExpect {
     async fn send_line<B>(&mut self, buf: B) -> Result<(), Error>
     where
         B: AsRef<[u8]>;
+
+    /// Send a control code given in a human readable form, e.g. `^C`, `C-d` or `ESC`.
+    ///
+    /// See [`ControlCode::try_from`] for a list of supported notations.
+    async fn send_control_str(&mut self, code: &str) -> Result<(), Error> {
+        let code = parse_control_code(code)?;
+        self.send(code).await
+    }
+
+    /// Send a special key like an arrow or a function key.
+    ///
+    /// An escape sequence of [`TerminalType::Xterm`] is used,
+    /// use [`Key::sequence`] with [`AsyncExpect::send`] for other terminals.
+    ///
+    /// [`TerminalType::Xterm`]: crate::TerminalType::Xterm
+    async fn send_key(&mut self, key: Key) -> Result<(), Error> {
+        self.send(key).await
+    }
 }
 
 #[cfg(feature = "async")]
@@ -361,3 +462,10 @@ where
         T::send_line(self, buf).await
     }
 }
+
+fn parse_control_code(code: &str) -> Result<ControlCode, Error> {
+    ControlCode::try_from(code).map_err(|_| Error::Other {
+        message: "unknown control code".to_string(),
+        err: code.to_string(),
+    })
+}
@@ -0,0 +1,96 @@
+//! This module contains a [`InteractHandle`] which stops an interact session from another task or thread.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll, Waker},
+};
+
+/// InteractHandle stops a running [`InteractSession`].
+///
+/// It can be cloned and sent to another task or thread,
+/// e.g. to a ctrl-c handler or to a UI event loop.
+/// When cancelled the session returns [`StopReason::Cancelled`]
+/// and restores a terminal state the same way it does when a process exits.
+///
+/// A cancel requested before the session is started stops the next run.
+///
+/// [`InteractSession`]: crate::interact::InteractSession
+/// [`StopReason::Cancelled`]: crate::interact::StopReason::Cancelled
+#[derive(Debug, Clone, Default)]
+pub struct InteractHandle {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    #[cfg(feature = "async")]
+    waker: Mutex<Option<Waker>>,
+}
+
+impl InteractHandle {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests a session to stop.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+
+        #[cfg(feature = "async")]
+        if let Some(waker) = self.inner.waker.lock().ok().and_then(|mut w| w.take()) {
+            waker.wake();
+        }
+    }
+
+    /// Checks whether a cancel was requested but not yet handled by a session.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Handles a cancel request.
+    pub(crate) fn take(&self) -> bool {
+        self.inner.cancelled.swap(false, Ordering::SeqCst)
+    }
+
+    /// Returns a future which is resolved when a cancel is requested.
+    #[cfg(feature = "async")]
+    pub(crate) fn cancelled(&self) -> Cancelled<'_> {
+        Cancelled(self)
+    }
+}
+
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub(crate) struct Cancelled<'a>(&'a InteractHandle);
+
+#[cfg(feature = "async")]
+impl Future for Cancelled<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = &self.0.inner;
+        if inner.cancelled.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+
+        if let Ok(mut waker) = inner.waker.lock() {
+            *waker = Some(cx.waker().clone());
+        }
+
+        // A cancel could be requested before the waker was set.
+        if inner.cancelled.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    }
+}
@@ -49,7 +49,11 @@
 
 pub mod actions;
 mod context;
+mod handle;
 mod session;
+mod summary;
 
 pub use context::Context;
+pub use handle::InteractHandle;
 pub use session::InteractSession;
+pub use summary::{InteractSummary, StopReason};
@@ -5,13 +5,14 @@
 use std::{
     borrow::Cow,
     io::{ErrorKind, Write},
+    time::{Duration, Instant},
 };
 
 #[cfg(not(feature = "async"))]
 use std::io::Read;
 
 #[cfg(feature = "async")]
-use std::{io, time::Duration};
+use std::io;
 
 #[cfg(feature = "async")]
 use futures_timer::Delay;
@@ -23,7 +24,11 @@ use futures_lite::{
     AsyncReadExt, AsyncWriteExt,
 };
 
-use crate::{process::Healthcheck, Error};
+use crate::{
+    process::{Healthcheck, WindowSize},
+    terminal::ResizeWatcher,
+    Error,
+};
 
 #[cfg(unix)]
 use crate::process::Termios;
@@ -34,7 +39,7 @@ use crate::Expect;
 #[cfg(feature = "async")]
 use crate::AsyncExpect;
 
-use crate::interact::Context;
+use crate::interact::{Context, InteractHandle, InteractSummary, StopReason};
 #[cfg(all(not(feature = "async"), not(feature = "polling")))]
 use crate::process::NonBlocking;
 
@@ -51,6 +56,12 @@ pub struct InteractSession<Session, Input, Output, State> {
     escape_character: u8,
     #[cfg(unix)]
     status: Option<WaitStatus>,
+    #[cfg(unix)]
+    input_fd: Option<std::os::unix::io::RawFd>,
+    bytes_in: usize,
+    bytes_out: usize,
+    last_activity: Instant,
+    handle: InteractHandle,
     opts: InteractOptions<Session, Input, Output, State>,
 }
 
@@ -62,12 +73,17 @@ struct InteractOptions<S, I, O, C> {
     input_action: Option<OptAction<S, I, O, C>>,
     output_action: Option<OptAction<S, I, O, C>>,
     idle_action: Option<OptAction<S, I, O, C>>,
+    idle_timeout: Option<Duration>,
+    idle_timeout_action: Option<OptAction<S, I, O, C>>,
+    window_size: Option<OptWindowSize<S>>,
 }
 
 type OptAction<S, I, O, C> = Box<dyn FnMut(Context<'_, S, I, O, C>) -> ExpectResult<bool>>;
 
 type OptFilter = Box<dyn FnMut(&[u8]) -> ExpectResult<Cow<'_, [u8]>>>;
 
+type OptWindowSize<S> = fn(&mut S, u16, u16) -> std::io::Result<()>;
+
 impl<S, I, O, C> InteractSession<S, I, O, C> {
     /// Default escape character. <Ctrl-\]>
     pub const ESCAPE: u8 = 29;
@@ -86,9 +102,18 @@ impl<S, I, O, C> InteractSession<S, I, O, C> {
                 input_action: None,
                 output_action: None,
                 idle_action: None,
+                idle_timeout: None,
+                idle_timeout_action: None,
+                window_size: None,
             },
             #[cfg(unix)]
             status: None,
+            #[cfg(unix)]
+            input_fd: None,
+            bytes_in: 0,
+            bytes_out: 0,
+            last_activity: Instant::now(),
+            handle: InteractHandle::new(),
         }
     }
 
@@ -101,7 +126,7 @@ impl<S, I, O, C> InteractSession<S, I, O, C> {
 
     /// Returns a status of spawned session if it was exited.
     ///
-    /// If [`Self::spawn`] returns false but this method returns None it means that a child process was shutdown by various reasons.
+    /// If [`Self::spawn`] reports [`StopReason::ProcessExit`] but this method returns None it means that a child process was shutdown by various reasons.
     /// Which sometimes happens and it's not considered to be a valid [`WaitStatus`], so None is returned.
     ///
     /// [`Self::spawn`]: crate::interact::InteractSession::spawn
@@ -109,6 +134,56 @@ impl<S, I, O, C> InteractSession<S, I, O, C> {
     pub fn get_status(&self) -> Option<WaitStatus> {
         self.status
     }
+
+    /// Returns a handle which can stop the session from another task or thread.
+    ///
+    /// The session returns [`StopReason::Cancelled`] after [`InteractHandle::cancel`] is called.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(all(unix, feature = "async"), doc = "```no_run")]
+    #[cfg_attr(not(all(unix, feature = "async")), doc = "```ignore")]
+    /// use expectrl::{interact::StopReason, spawn, stream::stdin::Stdin};
+    ///
+    /// let mut session = spawn("cat").unwrap();
+    /// let mut stdin = Stdin::open().unwrap();
+    /// let stdout = futures_lite::io::sink();
+    ///
+    /// let mut interact = session.interact(&mut stdin, stdout);
+    /// let handle = interact.handle();
+    /// std::thread::spawn(move || {
+    ///     std::thread::sleep(std::time::Duration::from_secs(5));
+    ///     handle.cancel();
+    /// });
+    ///
+    /// let summary = futures_lite::future::block_on(interact.spawn()).unwrap();
+    /// assert_eq!(summary.reason(), StopReason::Cancelled);
+    ///
+    /// stdin.close().unwrap();
+    /// ```
+    pub fn handle(&self) -> InteractHandle {
+        self.handle.clone()
+    }
+
+    fn start(&mut self) -> Instant {
+        self.bytes_in = 0;
+        self.bytes_out = 0;
+        let now = Instant::now();
+        self.last_activity = now;
+        now
+    }
+
+    fn summary(&self, reason: StopReason, start: Instant) -> InteractSummary {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            reason = ?reason,
+            bytes_in = self.bytes_in,
+            bytes_out = self.bytes_out,
+            "interact finished"
+        );
+
+        InteractSummary::new(reason, self.bytes_in, self.bytes_out, start.elapsed())
+    }
 }
 
 impl<S, I, O, C> InteractSession<S, I, O, C> {
@@ -116,6 +191,9 @@ impl<S, I, O, C> InteractSession<S, I, O, C> {
     pub fn with_state<State>(self, state: State) -> InteractSession<S, I, O, State> {
         let mut s = InteractSession::new(self.session, self.input, self.output, state);
         s.escape_character = self.escape_character;
+        s.handle = self.handle;
+        s.opts.idle_timeout = self.opts.idle_timeout;
+        s.opts.window_size = self.opts.window_size;
         #[cfg(unix)]
         {
             s.status = self.status;
@@ -198,6 +276,68 @@ impl<S, I, O, C> InteractSession<S, I, O, C> {
         self.opts.idle_action = Some(Box::new(action));
         self
     }
+
+    /// Set a timeout after which the session is stopped
+    /// if neither an input nor a process produced any data.
+    ///
+    /// The session is stopped with [`StopReason::IdleTimeout`],
+    /// unless an action is set by [`InteractSession::set_idle_timeout_action`].
+    pub fn set_idle_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.opts.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set an action called when an idle timeout is reached.
+    ///
+    /// If the action returns `true` the session is stopped with [`StopReason::IdleTimeout`],
+    /// otherwise the timeout is restarted.
+    ///
+    /// See [`InteractSession::set_idle_timeout`].
+    pub fn set_idle_timeout_action<F>(&mut self, action: F) -> &mut Self
+    where
+        F: FnMut(Context<'_, S, I, O, C>) -> ExpectResult<bool> + 'static,
+    {
+        self.opts.idle_timeout_action = Some(Box::new(action));
+        self
+    }
+}
+
+impl<S, I, O, C> InteractSession<S, I, O, C>
+where
+    S: WindowSize,
+{
+    /// Propagate a size of a host terminal to the process.
+    ///
+    /// The size is set when the session starts and each time the terminal is resized,
+    /// so full screen applications (e.g. `vim`, `htop`) are redrawn correctly.
+    ///
+    /// On unix a resize is caught by a `SIGWINCH` handler, which is installed only while the session runs.
+    /// On windows a console size is checked on each iteration and a ConPTY is resized accordingly.
+    pub fn propagate_window_size(&mut self) -> &mut Self {
+        self.opts.window_size = Some(S::set_window_size);
+        self
+    }
+}
+
+#[cfg(unix)]
+impl<S, I, O, C> InteractSession<S, I, O, C>
+where
+    I: std::os::unix::io::AsRawFd,
+{
+    /// Wait on a descriptor of the input while the session is idle.
+    ///
+    /// An arbitrary [`Read`]er can't be waited on, so by default the input is checked
+    /// each few milliseconds while nothing happens.
+    /// With the descriptor the session sleeps until either a process or the input has something to read,
+    /// which gives a lower input latency and no CPU usage in idle.
+    ///
+    /// It's used by the default sync backend, `polling` and `async` ones wait on the input anyway.
+    ///
+    /// [`Read`]: std::io::Read
+    pub fn wait_on_input_fd(&mut self) -> &mut Self {
+        self.input_fd = Some(self.input.as_raw_fd());
+        self
+    }
 }
 
 #[cfg(all(unix, not(any(feature = "async", feature = "polling"))))]
@@ -212,20 +352,25 @@ where
     /// See [`Session::interact`].
     ///
     /// [`Session::interact`]: crate::session::Session::interact
-    pub fn spawn(&mut self) -> ExpectResult<bool> {
+    pub fn spawn(&mut self) -> ExpectResult<InteractSummary> {
         let is_echo = self.session.is_echo()?;
         if !is_echo {
             let _ = self.session.set_echo(true);
         }
 
         self.status = None;
-        let is_alive = interact_buzy_loop(self)?;
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("interact").entered();
+
+        let start = self.start();
+        let reason = interact_buzy_loop(self)?;
 
         if !is_echo {
             let _ = self.session.set_echo(false);
         }
 
-        Ok(is_alive)
+        Ok(self.summary(reason, start))
     }
 }
 
@@ -241,8 +386,13 @@ where
     /// See [`Session::interact`].
     ///
     /// [`Session::interact`]: crate::session::Session::interact
-    pub fn spawn(&mut self) -> ExpectResult<bool> {
-        interact_buzy_loop(self)
+    pub fn spawn(&mut self) -> ExpectResult<InteractSummary> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("interact").entered();
+
+        let start = self.start();
+        let reason = interact_buzy_loop(self)?;
+        Ok(self.summary(reason, start))
     }
 }
 
@@ -263,7 +413,7 @@ where
     /// See [`Session::interact`].
     ///
     /// [`Session::interact`]: crate::session::Session::interact
-    pub fn spawn(&mut self) -> ExpectResult<bool> {
+    pub fn spawn(&mut self) -> ExpectResult<InteractSummary> {
         #[cfg(unix)]
         {
             let is_echo = self.session.is_echo()?;
@@ -272,18 +422,28 @@ where
             }
 
             self.status = None;
-            let is_alive = interact_polling(self)?;
+
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("interact").entered();
+
+            let start = self.start();
+            let reason = interact_polling(self)?;
 
             if !is_echo {
                 let _ = self.session.set_echo(false);
             }
 
-            Ok(is_alive)
+            Ok(self.summary(reason, start))
         }
 
         #[cfg(windows)]
         {
-            interact_buzy_loop(self)
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("interact").entered();
+
+            let start = self.start();
+            let reason = interact_buzy_loop(self)?;
+            Ok(self.summary(reason, start))
         }
     }
 }
@@ -300,19 +460,23 @@ where
     /// See [`Session::interact`].
     ///
     /// [`Session::interact`]: crate::session::Session::interact
-    pub async fn spawn(&mut self) -> Result<bool, Error> {
-        let is_echo = self.session.is_echo().map_err(Error::IO)?;
+    pub async fn spawn(&mut self) -> Result<InteractSummary, Error> {
+        let is_echo = self.session.is_echo().map_err(Error::io("get echo"))?;
         if !is_echo {
             let _ = self.session.set_echo(true);
         }
 
-        let is_alive = interact_async(self).await?;
+        let start = self.start();
+        let interact = interact_async(self);
+        #[cfg(feature = "tracing")]
+        let interact = tracing::Instrument::instrument(interact, tracing::debug_span!("interact"));
+        let reason = interact.await?;
 
         if !is_echo {
             let _ = self.session.set_echo(false);
         }
 
-        Ok(is_alive)
+        Ok(self.summary(reason, start))
     }
 }
 
@@ -328,8 +492,13 @@ where
     /// See [`Session::interact`].
     ///
     /// [`Session::interact`]: crate::session::Session::interact
-    pub async fn spawn(&mut self) -> Result<bool, Error> {
-        interact_async(self).await
+    pub async fn spawn(&mut self) -> Result<InteractSummary, Error> {
+        let start = self.start();
+        let interact = interact_async(self);
+        #[cfg(feature = "tracing")]
+        let interact = tracing::Instrument::instrument(interact, tracing::debug_span!("interact"));
+        let reason = interact.await?;
+        Ok(self.summary(reason, start))
     }
 }
 
@@ -344,8 +513,13 @@ where
     /// See [`Session::interact`].
     ///
     /// [`Session::interact`]: crate::session::Session::interact
-    pub fn spawn(&mut self) -> Result<bool, Error> {
-        interact_polling_on_thread(self)
+    pub fn spawn(&mut self) -> Result<InteractSummary, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("interact").entered();
+
+        let start = self.start();
+        let reason = interact_polling_on_thread(self)?;
+        Ok(self.summary(reason, start))
     }
 }
 
@@ -362,7 +536,9 @@ where
         let _ = s.field("session", &self.session)
             .field("input", &self.input)
             .field("output", &self.output)
-            .field("escape_character", &self.escape_character);
+            .field("escape_character", &self.escape_character)
+            .field("bytes_in", &self.bytes_in)
+            .field("bytes_out", &self.bytes_out);
 
         #[cfg(unix)]
         {
@@ -375,14 +551,17 @@ where
             .field("opts:on_input", &get_pointer(&self.opts.input_action))
             .field("opts:on_output", &get_pointer(&self.opts.output_action))
             .field("opts:input_filter", &get_pointer(&self.opts.input_filter))
-            .field("opts:output_filter", &get_pointer(&self.opts.output_filter));
+            .field("opts:output_filter", &get_pointer(&self.opts.output_filter))
+            .field("opts:idle_timeout", &self.opts.idle_timeout)
+            .field("opts:on_idle_timeout", &get_pointer(&self.opts.idle_timeout_action))
+            .field("opts:window_size", &self.opts.window_size.is_some());
 
         s.finish()
     }
 }
 
 #[cfg(all(unix, not(feature = "async"), not(feature = "polling")))]
-fn interact_buzy_loop<S, O, I, C>(s: &mut InteractSession<S, I, O, C>) -> ExpectResult<bool>
+fn interact_buzy_loop<S, O, I, C>(s: &mut InteractSession<S, I, O, C>) -> ExpectResult<StopReason>
 where
     S: Healthcheck<Status = WaitStatus> + NonBlocking + Write + Read,
     O: Write,
@@ -390,21 +569,38 @@ where
 {
     let mut buf = [0; 512];
 
+    let mut resize = watch_window_size(s)?;
+
     loop {
+        update_window_size(s, resize.as_mut())?;
+
+        if s.handle.take() {
+            return Ok(StopReason::Cancelled);
+        }
+
         let status = get_status(&s.session)?;
         if !matches!(status, Some(WaitStatus::StillAlive)) {
             s.status = status;
-            return Ok(false);
+            return Ok(StopReason::ProcessExit);
         }
 
+        let mut active = false;
+
         if let Some(n) = try_read(&mut s.session, &mut buf)? {
+            active = true;
             let eof = n == 0;
+            s.bytes_out += n;
+            s.last_activity = Instant::now();
             let buf = &buf[..n];
             let buf = call_filter(s.opts.output_filter.as_mut(), buf)?;
 
             let exit = run_action_output(s, &buf, eof)?;
-            if eof || exit {
-                return Ok(true);
+            if exit {
+                return Ok(StopReason::Action);
+            }
+
+            if eof {
+                return Ok(StopReason::ProcessEof);
             }
 
             spin_write(&mut s.output, &buf)?;
@@ -417,21 +613,28 @@ where
         // The terminal must have been prepared before.
         match s.input.read(&mut buf) {
             Ok(n) => {
+                active = true;
                 let eof = n == 0;
+                s.bytes_in += n;
+                s.last_activity = Instant::now();
                 let buf = &buf[..n];
                 let buf = call_filter(s.opts.input_filter.as_mut(), buf)?;
 
                 #[rustfmt::skip]
                 let exit = run_action_input(s, &buf, eof)?;
-                if eof | exit {
-                    return Ok(true);
+                if exit {
+                    return Ok(StopReason::Action);
+                }
+
+                if eof {
+                    return Ok(StopReason::InputEof);
                 }
 
                 let escape_char_position = buf.iter().position(|c| *c == s.escape_character);
                 match escape_char_position {
                     Some(pos) => {
                         s.session.write_all(&buf[..pos])?;
-                        return Ok(true);
+                        return Ok(StopReason::Escape);
                     }
                     None => {
                         s.session.write_all(&buf[..])?;
@@ -444,13 +647,52 @@ where
 
         let exit = run_action_idle(s, &[], false)?;
         if exit {
-            return Ok(true);
+            return Ok(StopReason::Action);
+        }
+
+        if check_idle_timeout(s)? {
+            return Ok(StopReason::IdleTimeout);
+        }
+
+        if !active {
+            wait_activity(s)?;
         }
     }
 }
 
+/// Blocks until a process or an input has something to read.
+///
+/// The wait is limited so a cancel request and an idle timeout are not missed,
+/// and it's short if the input has no descriptor to wait on.
+#[cfg(all(unix, not(feature = "async"), not(feature = "polling")))]
+fn wait_activity<S, I, O, C>(s: &InteractSession<S, I, O, C>) -> ExpectResult<()>
+where
+    S: NonBlocking,
+{
+    // A cancel request can't interrupt the wait.
+    const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+    const INPUT_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+    let fd = match s.session.readiness_fd() {
+        Some(fd) => fd,
+        None => {
+            std::thread::sleep(idle_wait_timeout(s, INPUT_CHECK_INTERVAL));
+            return Ok(());
+        }
+    };
+
+    let (fds, timeout) = match s.input_fd {
+        Some(input) => (vec![fd, input], CANCEL_CHECK_INTERVAL),
+        None => (vec![fd], INPUT_CHECK_INTERVAL),
+    };
+
+    crate::process::unix::poll_readable(&fds, Some(idle_wait_timeout(s, timeout)))?;
+
+    Ok(())
+}
+
 #[cfg(all(windows, not(feature = "async"), not(feature = "polling")))]
-fn interact_buzy_loop<S, O, I, C>(s: &mut InteractSession<S, I, O, C>) -> ExpectResult<bool>
+fn interact_buzy_loop<S, O, I, C>(s: &mut InteractSession<S, I, O, C>) -> ExpectResult<StopReason>
 where
     S: Healthcheck + NonBlocking + Write + Read,
     O: Write,
@@ -458,19 +700,33 @@ where
 {
     let mut buf = [0; 512];
 
+    let mut resize = watch_window_size(s)?;
+
     loop {
+        update_window_size(s, resize.as_mut())?;
+
+        if s.handle.take() {
+            return Ok(StopReason::Cancelled);
+        }
+
         if !s.session.is_alive()? {
-            return Ok(false);
+            return Ok(StopReason::ProcessExit);
         }
 
         if let Some(n) = try_read(&mut s.session, &mut buf)? {
             let eof = n == 0;
+            s.bytes_out += n;
+            s.last_activity = Instant::now();
             let buf = &buf[..n];
             let buf = call_filter(s.opts.output_filter.as_mut(), buf)?;
 
             let exit = run_action_output(s, &buf, eof)?;
-            if eof || exit {
-                return Ok(true);
+            if exit {
+                return Ok(StopReason::Action);
+            }
+
+            if eof {
+                return Ok(StopReason::ProcessEof);
             }
 
             spin_write(&mut s.output, &buf)?;
@@ -484,19 +740,25 @@ where
         match s.input.read(&mut buf) {
             Ok(n) => {
                 let eof = n == 0;
+                s.bytes_in += n;
+                s.last_activity = Instant::now();
                 let buf = &buf[..n];
                 let buf = call_filter(s.opts.input_filter.as_mut(), buf)?;
 
                 let exit = run_action_input(s, &buf, eof)?;
-                if eof | exit {
-                    return Ok(true);
+                if exit {
+                    return Ok(StopReason::Action);
+                }
+
+                if eof {
+                    return Ok(StopReason::InputEof);
                 }
 
                 let escape_char_position = buf.iter().position(|c| *c == s.escape_character);
                 match escape_char_position {
                     Some(pos) => {
                         s.session.write_all(&buf[..pos])?;
-                        return Ok(true);
+                        return Ok(StopReason::Escape);
                     }
                     None => {
                         s.session.write_all(&buf[..])?;
@@ -509,13 +771,17 @@ where
 
         let exit = run_action_idle(s, &[], false)?;
         if exit {
-            return Ok(true);
+            return Ok(StopReason::Action);
+        }
+
+        if check_idle_timeout(s)? {
+            return Ok(StopReason::IdleTimeout);
         }
     }
 }
 
 #[cfg(all(unix, not(feature = "async"), feature = "polling"))]
-fn interact_polling<S, O, I, C>(s: &mut InteractSession<S, I, O, C>) -> Result<bool, Error>
+fn interact_polling<S, O, I, C>(s: &mut InteractSession<S, I, O, C>) -> Result<StopReason, Error>
 where
     S: Healthcheck<Status = WaitStatus> + Write + Read + std::os::unix::io::AsRawFd,
     I: Read + std::os::unix::io::AsRawFd,
@@ -532,16 +798,30 @@ where
 
     // The event loop.
     let mut events = Vec::new();
+    let mut resize = watch_window_size(s)?;
+
     loop {
+        update_window_size(s, resize.as_mut())?;
+
+        if s.handle.take() {
+            return Ok(StopReason::Cancelled);
+        }
+
         let status = get_status(&s.session)?;
         if !matches!(status, Some(WaitStatus::StillAlive)) {
             s.status = status;
-            return Ok(false);
+            return Ok(StopReason::ProcessExit);
         }
 
         // Wait for at least one I/O event.
         events.clear();
-        let _ = poller.wait(&mut events, Some(std::time::Duration::from_secs(5)))?;
+        let timeout = idle_wait_timeout(s, Duration::from_secs(5));
+        match poller.wait(&mut events, Some(timeout)) {
+            Ok(_) => {}
+            // SIGWINCH interrupts the wait when a window size is propagated.
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
 
         for ev in &events {
             if ev.key == 0 {
@@ -552,19 +832,27 @@ where
                 match s.input.read(&mut buf) {
                     Ok(n) => {
                         let eof = n == 0;
+                        s.bytes_in += n;
+                        s.last_activity = Instant::now();
                         let buf = &buf[..n];
                         let buf = call_filter(s.opts.input_filter.as_mut(), buf)?;
 
                         let exit = run_action_input(s, &buf, eof)?;
-                        if eof || exit {
-                            return Ok(true);
+                        if exit {
+                            return Ok(StopReason::Action);
+                        }
+
+                        if eof {
+                            return Ok(StopReason::InputEof);
                         }
 
                         let escape_char_pos = buf.iter().position(|c| *c == s.escape_character);
                         match escape_char_pos {
                             Some(pos) => {
-                                s.session.write_all(&buf[..pos]).map_err(Error::IO)?;
-                                return Ok(true);
+                                s.session
+                                    .write_all(&buf[..pos])
+                                    .map_err(Error::io("write"))?;
+                                return Ok(StopReason::Escape);
                             }
                             None => s.session.write_all(&buf[..])?,
                         }
@@ -581,13 +869,19 @@ where
                 match s.session.read(&mut buf) {
                     Ok(n) => {
                         let eof = n == 0;
+                        s.bytes_out += n;
+                        s.last_activity = Instant::now();
                         let buf = &buf[..n];
                         let buf = call_filter(s.opts.output_filter.as_mut(), buf)?;
 
                         let exit = run_action_output(s, &buf, eof)?;
 
-                        if eof || exit {
-                            return Ok(true);
+                        if exit {
+                            return Ok(StopReason::Action);
+                        }
+
+                        if eof {
+                            return Ok(StopReason::ProcessEof);
                         }
 
                         spin_write(&mut s.output, &buf)?;
@@ -604,7 +898,11 @@ where
 
         let exit = run_action_idle(s, &[], false)?;
         if exit {
-            return Ok(true);
+            return Ok(StopReason::Action);
+        }
+
+        if check_idle_timeout(s)? {
+            return Ok(StopReason::IdleTimeout);
         }
     }
 }
@@ -612,7 +910,7 @@ where
 #[cfg(all(windows, not(feature = "async"), feature = "polling"))]
 fn interact_polling_on_thread<I, O, C>(
     s: &mut InteractSession<crate::session::OsSession, I, O, C>,
-) -> Result<bool, Error>
+) -> Result<StopReason, Error>
 where
     I: Read + Clone + Send + 'static,
     O: Write,
@@ -630,16 +928,25 @@ where
         .map_err(to_io_error(""))?;
     let mut poller = Wait2::new(s.input.clone(), stream);
 
+    let mut resize = watch_window_size(s)?;
+
     loop {
+        update_window_size(s, resize.as_mut())?;
+
+        if s.handle.take() {
+            return Ok(StopReason::Cancelled);
+        }
+
         // In case where proceses exits we are trying to
         // fill buffer to run callbacks if there was something in.
         //
         // We ignore errors because there might be errors like EOCHILD etc.
         if s.session.is_alive()? {
-            return Ok(false);
+            return Ok(StopReason::ProcessExit);
         }
 
         // Wait for at least one I/O event.
+        poller.set_timeout(idle_wait_timeout(s, Duration::from_secs(5)));
         let event = poller.recv().map_err(to_io_error(""))?;
         match event {
             Recv::R1(b) => match b {
@@ -647,13 +954,19 @@ where
                     let buf = b.map_or([0], |b| [b]);
                     let eof = b.is_none();
                     let n = if eof { 0 } else { 1 };
+                    s.bytes_in += n;
+                    s.last_activity = Instant::now();
                     let buf = &buf[..n];
 
                     let buf = call_filter(s.opts.input_filter.as_mut(), buf)?;
 
                     let exit = run_action_input(s, &buf, eof)?;
-                    if eof || exit {
-                        return Ok(true);
+                    if exit {
+                        return Ok(StopReason::Action);
+                    }
+
+                    if eof {
+                        return Ok(StopReason::InputEof);
                     }
 
                     // todo: replace all of these by 1 by 1 write
@@ -661,7 +974,7 @@ where
                     match escape_char_pos {
                         Some(pos) => {
                             s.session.write_all(&buf[..pos])?;
-                            return Ok(true);
+                            return Ok(StopReason::Escape);
                         }
                         None => s.session.write_all(&buf[..])?,
                     }
@@ -674,13 +987,19 @@ where
                     let buf = b.map_or([0], |b| [b]);
                     let eof = b.is_none();
                     let n = if eof { 0 } else { 1 };
+                    s.bytes_out += n;
+                    s.last_activity = Instant::now();
                     let buf = &buf[..n];
 
                     let buf = call_filter(s.opts.output_filter.as_mut(), buf)?;
 
                     let exit = run_action_output(s, &buf, eof)?;
-                    if eof || exit {
-                        return Ok(true);
+                    if exit {
+                        return Ok(StopReason::Action);
+                    }
+
+                    if eof {
+                        return Ok(StopReason::ProcessEof);
                     }
 
                     s.output.write_all(&buf)?;
@@ -692,7 +1011,11 @@ where
             Recv::Timeout => {
                 let exit = run_action_idle(s, &[], false)?;
                 if exit {
-                    return Ok(true);
+                    return Ok(StopReason::Action);
+                }
+
+                if check_idle_timeout(s)? {
+                    return Ok(StopReason::IdleTimeout);
                 }
             }
         }
@@ -700,7 +1023,9 @@ where
 }
 
 #[cfg(all(unix, feature = "async"))]
-async fn interact_async<S, O, I, C>(s: &mut InteractSession<S, I, O, C>) -> Result<bool, Error>
+async fn interact_async<S, O, I, C>(
+    s: &mut InteractSession<S, I, O, C>,
+) -> Result<StopReason, Error>
 where
     S: Healthcheck<Status = WaitStatus> + AsyncRead + AsyncWrite + Unpin,
     I: AsyncRead + Unpin,
@@ -710,6 +1035,8 @@ where
     enum ReadFrom {
         Input,
         Proc,
+        Resize,
+        Cancel,
         Timeout,
     }
 
@@ -717,18 +1044,32 @@ where
     let mut input_buf = [0; 512];
     let mut proc_buf = [0; 512];
 
+    let mut resize = watch_window_size(s)?;
+    let handle = s.handle.clone();
+
     loop {
+        update_window_size(s, resize.as_mut())?;
+
+        if s.handle.take() {
+            return Ok(StopReason::Cancelled);
+        }
+
         let status = get_status(&s.session)?;
         if !matches!(status, Some(WaitStatus::StillAlive)) {
             s.status = status;
-            return Ok(false);
+            return Ok(StopReason::ProcessExit);
         }
 
+        let wait = idle_wait_timeout(s, TIMEOUT);
         let read_process = async { (ReadFrom::Proc, s.session.read(&mut proc_buf).await) };
         let read_input = async { (ReadFrom::Input, s.input.read(&mut input_buf).await) };
-        let timeout = async { (ReadFrom::Timeout, async_timeout(TIMEOUT).await) };
+        let timeout = async { (ReadFrom::Timeout, async_timeout(wait).await) };
+        let resized = async { (ReadFrom::Resize, wait_window_size(resize.as_ref()).await) };
+        let cancelled = async { (ReadFrom::Cancel, wait_cancel(&handle).await) };
 
         let read_any = future::or(read_process, read_input);
+        let read_any = future::or(read_any, resized);
+        let read_any = future::or(cancelled, read_any);
         let read_output = future::or(read_any, timeout).await;
         let read_target = read_output.0;
         let read_result = read_output.1;
@@ -737,13 +1078,19 @@ where
             ReadFrom::Proc => {
                 let n = read_result?;
                 let eof = n == 0;
+                s.bytes_out += n;
+                s.last_activity = Instant::now();
                 let buf = &proc_buf[..n];
                 let buf = call_filter(s.opts.output_filter.as_mut(), buf)?;
 
                 let exit = run_action_output(s, &buf, eof)?;
 
-                if eof || exit {
-                    return Ok(true);
+                if exit {
+                    return Ok(StopReason::Action);
+                }
+
+                if eof {
+                    return Ok(StopReason::ProcessEof);
                 }
 
                 s.output.write(&buf).await?;
@@ -757,20 +1104,26 @@ where
                 match read_result {
                     Ok(n) => {
                         let eof = n == 0;
+                        s.bytes_in += n;
+                        s.last_activity = Instant::now();
                         let buf = &input_buf[..n];
                         let buf = call_filter(s.opts.output_filter.as_mut(), buf)?;
 
                         let exit = run_action_input(s, &buf, eof)?;
 
-                        if eof || exit {
-                            return Ok(true);
+                        if exit {
+                            return Ok(StopReason::Action);
+                        }
+
+                        if eof {
+                            return Ok(StopReason::InputEof);
                         }
 
                         let escape_char_pos = buf.iter().position(|c| *c == s.escape_character);
                         match escape_char_pos {
                             Some(pos) => {
                                 s.session.write_all(&buf[..pos]).await?;
-                                return Ok(true);
+                                return Ok(StopReason::Escape);
                             }
                             None => s.session.write_all(&buf[..]).await?,
                         }
@@ -779,10 +1132,20 @@ where
                     Err(err) => return Err(err.into()),
                 }
             }
+            ReadFrom::Resize => {
+                // A size is propagated at the beginning of the loop.
+            }
+            ReadFrom::Cancel => {
+                // A cancel is handled at the beginning of the loop.
+            }
             ReadFrom::Timeout => {
                 let exit = run_action_idle(s, &[], false)?;
                 if exit {
-                    return Ok(true);
+                    return Ok(StopReason::Action);
+                }
+
+                if check_idle_timeout(s)? {
+                    return Ok(StopReason::IdleTimeout);
                 }
             }
         }
@@ -790,7 +1153,9 @@ where
 }
 
 #[cfg(all(windows, feature = "async"))]
-async fn interact_async<S, O, I, C>(s: &mut InteractSession<S, I, O, C>) -> Result<bool, Error>
+async fn interact_async<S, O, I, C>(
+    s: &mut InteractSession<S, I, O, C>,
+) -> Result<StopReason, Error>
 where
     S: Healthcheck + AsyncRead + AsyncWrite + Unpin,
     I: AsyncRead + Unpin,
@@ -800,6 +1165,8 @@ where
     enum ReadFrom {
         Input,
         Proc,
+        Resize,
+        Cancel,
         Timeout,
     }
 
@@ -807,16 +1174,30 @@ where
     let mut input_buf = [0; 512];
     let mut proc_buf = [0; 512];
 
+    let mut resize = watch_window_size(s)?;
+    let handle = s.handle.clone();
+
     loop {
+        update_window_size(s, resize.as_mut())?;
+
+        if s.handle.take() {
+            return Ok(StopReason::Cancelled);
+        }
+
         if !s.session.is_alive()? {
-            return Ok(false);
+            return Ok(StopReason::ProcessExit);
         }
 
+        let wait = idle_wait_timeout(s, TIMEOUT);
         let read_process = async { (ReadFrom::Proc, s.session.read(&mut proc_buf).await) };
         let read_input = async { (ReadFrom::Input, s.input.read(&mut input_buf).await) };
-        let timeout = async { (ReadFrom::Timeout, async_timeout(TIMEOUT).await) };
+        let timeout = async { (ReadFrom::Timeout, async_timeout(wait).await) };
+        let resized = async { (ReadFrom::Resize, wait_window_size(resize.as_ref()).await) };
+        let cancelled = async { (ReadFrom::Cancel, wait_cancel(&handle).await) };
 
         let read_any = future::or(read_process, read_input);
+        let read_any = future::or(read_any, resized);
+        let read_any = future::or(cancelled, read_any);
         let read_output = future::or(read_any, timeout).await;
         let read_target = read_output.0;
         let read_result = read_output.1;
@@ -825,13 +1206,19 @@ where
             ReadFrom::Proc => {
                 let n = read_result?;
                 let eof = n == 0;
+                s.bytes_out += n;
+                s.last_activity = Instant::now();
                 let buf = &proc_buf[..n];
                 let buf = call_filter(s.opts.output_filter.as_mut(), buf)?;
 
                 let exit = run_action_output(s, &buf, eof)?;
 
-                if eof || exit {
-                    return Ok(true);
+                if exit {
+                    return Ok(StopReason::Action);
+                }
+
+                if eof {
+                    return Ok(StopReason::ProcessEof);
                 }
 
                 s.output.write(&buf).await?;
@@ -845,20 +1232,26 @@ where
                 match read_result {
                     Ok(n) => {
                         let eof = n == 0;
+                        s.bytes_in += n;
+                        s.last_activity = Instant::now();
                         let buf = &input_buf[..n];
                         let buf = call_filter(s.opts.output_filter.as_mut(), buf)?;
 
                         let exit = run_action_input(s, &buf, eof)?;
 
-                        if eof || exit {
-                            return Ok(true);
+                        if exit {
+                            return Ok(StopReason::Action);
+                        }
+
+                        if eof {
+                            return Ok(StopReason::InputEof);
                         }
 
                         let escape_char_pos = buf.iter().position(|c| *c == s.escape_character);
                         match escape_char_pos {
                             Some(pos) => {
                                 s.session.write_all(&buf[..pos]).await?;
-                                return Ok(true);
+                                return Ok(StopReason::Escape);
                             }
                             None => s.session.write_all(&buf[..]).await?,
                         }
@@ -867,10 +1260,20 @@ where
                     Err(err) => return Err(err.into()),
                 }
             }
+            ReadFrom::Resize => {
+                // A size is propagated at the beginning of the loop.
+            }
+            ReadFrom::Cancel => {
+                // A cancel is handled at the beginning of the loop.
+            }
             ReadFrom::Timeout => {
                 let exit = run_action_idle(s, &[], false)?;
                 if exit {
-                    return Ok(true);
+                    return Ok(StopReason::Action);
+                }
+
+                if check_idle_timeout(s)? {
+                    return Ok(StopReason::IdleTimeout);
                 }
             }
         }
@@ -927,6 +1330,39 @@ fn run_action_idle<S, I, O, C>(s: &mut InteractSession<S, I, O, C>, buf: &[u8],
     opt_action(ctx, &mut s.opts.idle_action)
 }
 
+/// Checks whether the session has been idle longer than the idle timeout.
+///
+/// Returns `true` if the session must be stopped.
+fn check_idle_timeout<S, I, O, C>(s: &mut InteractSession<S, I, O, C>) -> ExpectResult<bool> {
+    let timeout = match s.opts.idle_timeout {
+        Some(timeout) => timeout,
+        None => return Ok(false),
+    };
+
+    if s.last_activity.elapsed() < timeout {
+        return Ok(false);
+    }
+
+    if s.opts.idle_timeout_action.is_none() {
+        return Ok(true);
+    }
+
+    s.last_activity = Instant::now();
+
+    #[rustfmt::skip]
+    let ctx = Context::new(&mut s.session, &mut s.input, &mut s.output, &mut s.opts.state, &[], false);
+    opt_action(ctx, &mut s.opts.idle_timeout_action)
+}
+
+/// Shortens a wait so an idle timeout is not missed.
+#[cfg(any(unix, feature = "async", feature = "polling"))]
+fn idle_wait_timeout<S, I, O, C>(s: &InteractSession<S, I, O, C>, timeout: Duration) -> Duration {
+    match s.opts.idle_timeout {
+        Some(idle) => timeout.min(idle.saturating_sub(s.last_activity.elapsed())),
+        None => timeout,
+    }
+}
+
 fn opt_action<S, I, O, C>(
     ctx: Context<'_, S, I, O, C>,
     opt: &mut Option<OptAction<S, I, O, C>>,
@@ -947,6 +1383,51 @@ where
     }
 }
 
+fn watch_window_size<S, I, O, C>(
+    s: &InteractSession<S, I, O, C>,
+) -> ExpectResult<Option<ResizeWatcher>> {
+    match s.opts.window_size {
+        Some(_) => ResizeWatcher::new().map(Some),
+        None => Ok(None),
+    }
+}
+
+fn update_window_size<S, I, O, C>(
+    s: &mut InteractSession<S, I, O, C>,
+    watcher: Option<&mut ResizeWatcher>,
+) -> ExpectResult<()> {
+    if let (Some(set_window_size), Some(watcher)) = (s.opts.window_size, watcher) {
+        if let Some((cols, rows)) = watcher.check() {
+            set_window_size(&mut s.session, cols, rows)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+async fn wait_cancel(handle: &InteractHandle) -> io::Result<usize> {
+    handle.cancelled().await;
+    Ok(0)
+}
+
+#[cfg(feature = "async")]
+async fn wait_window_size(watcher: Option<&ResizeWatcher>) -> io::Result<usize> {
+    // A signal can't wake a future up so the watcher is checked periodically.
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    match watcher {
+        Some(watcher) => {
+            while !watcher.is_resized() {
+                Delay::new(INTERVAL).await;
+            }
+
+            Ok(0)
+        }
+        None => future::pending().await,
+    }
+}
+
 #[cfg(unix)]
 fn get_status<S>(session: &S) -> Result<Option<S::Status>, Error>
 where
@@ -955,7 +1436,7 @@ where
     match session.get_status() {
         Ok(status) => Ok(Some(status)),
         Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(None),
-        Err(err) => Err(Error::IO(err)),
+        Err(err) => Err(Error::io("get status")(err)),
     }
 }
 
@@ -973,7 +1454,7 @@ where
     match result {
         Ok(n) => Ok(Some(n)),
         Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(None),
-        Err(err) => Err(Error::IO(err)),
+        Err(err) => Err(Error::io("read")(err)),
     }
 }
 
@@ -0,0 +1,84 @@
+//! This module contains a [`InteractSummary`] which describes a finished interact session.
+
+use std::time::Duration;
+
+/// A reason why an interact session was stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// A user typed an escape character.
+    Escape,
+    /// An input reached EOF.
+    InputEof,
+    /// A process output reached EOF.
+    ProcessEof,
+    /// A process has exited.
+    ProcessExit,
+    /// One of the callbacks requested a stop by returning `true`.
+    Action,
+    /// Neither an input nor a process produced any data for a given time.
+    ///
+    /// See [`InteractSession::set_idle_timeout`].
+    ///
+    /// [`InteractSession::set_idle_timeout`]: crate::interact::InteractSession::set_idle_timeout
+    IdleTimeout,
+    /// The session was cancelled by an [`InteractHandle`].
+    ///
+    /// [`InteractHandle`]: crate::interact::InteractHandle
+    Cancelled,
+}
+
+/// InteractSummary describes a finished interact session.
+///
+/// A callback can pass a value out of the session through a state,
+/// see [`InteractSession::with_state`] and [`InteractSession::into_state`].
+///
+/// [`InteractSession::with_state`]: crate::interact::InteractSession::with_state
+/// [`InteractSession::into_state`]: crate::interact::InteractSession::into_state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InteractSummary {
+    reason: StopReason,
+    bytes_in: usize,
+    bytes_out: usize,
+    duration: Duration,
+}
+
+impl InteractSummary {
+    pub(crate) fn new(
+        reason: StopReason,
+        bytes_in: usize,
+        bytes_out: usize,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            reason,
+            bytes_in,
+            bytes_out,
+            duration,
+        }
+    }
+
+    /// Returns a reason why the session was stopped.
+    pub fn reason(&self) -> StopReason {
+        self.reason
+    }
+
+    /// Returns an amount of bytes read from an input.
+    pub fn bytes_in(&self) -> usize {
+        self.bytes_in
+    }
+
+    /// Returns an amount of bytes read from a process.
+    pub fn bytes_out(&self) -> usize {
+        self.bytes_out
+    }
+
+    /// Returns a time the session was running.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Checks whether a process was still running when the session was stopped.
+    pub fn is_alive(&self) -> bool {
+        self.reason != StopReason::ProcessExit
+    }
+}
@@ -0,0 +1,159 @@
+//! A module which contains [Key] type.
+
+/// Key represents a special key of a keyboard.
+///
+/// A terminal sends such keys as escape sequences,
+/// which are different for different terminal types, see [`Key::sequence`].
+/// [`AsRef<[u8]>`] implementation returns a sequence of [`TerminalType::Xterm`].
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Expect, Key, TerminalType};
+///
+/// let mut p = spawn("vim").unwrap();
+/// p.send_key(Key::Down).unwrap();
+/// p.send(Key::End.sequence(TerminalType::Linux)).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// Arrow up.
+    Up,
+    /// Arrow down.
+    Down,
+    /// Arrow right.
+    Right,
+    /// Arrow left.
+    Left,
+    /// Home key.
+    Home,
+    /// End key.
+    End,
+    /// Insert key.
+    Insert,
+    /// Delete key.
+    Delete,
+    /// Page Up key.
+    PageUp,
+    /// Page Down key.
+    PageDown,
+    /// F1 key.
+    F1,
+    /// F2 key.
+    F2,
+    /// F3 key.
+    F3,
+    /// F4 key.
+    F4,
+    /// F5 key.
+    F5,
+    /// F6 key.
+    F6,
+    /// F7 key.
+    F7,
+    /// F8 key.
+    F8,
+    /// F9 key.
+    F9,
+    /// F10 key.
+    F10,
+    /// F11 key.
+    F11,
+    /// F12 key.
+    F12,
+    /// Tab key.
+    Tab,
+    /// Enter key.
+    Enter,
+    /// Backspace key.
+    Backspace,
+    /// Escape key.
+    Escape,
+}
+
+/// TerminalType is a kind of a terminal which defines escape sequences of [Key]s.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminalType {
+    /// `xterm` and most of the modern terminal emulators.
+    #[default]
+    Xterm,
+    /// `xterm` when an application enabled a cursor keys mode (DECCKM),
+    /// which is usually done by full screen applications.
+    XtermApplication,
+    /// `vt220` terminal.
+    Vt220,
+    /// Linux console.
+    Linux,
+}
+
+impl Key {
+    /// Returns an escape sequence of the key for a given terminal type.
+    pub fn sequence(&self, terminal: TerminalType) -> &'static [u8] {
+        use TerminalType::*;
+
+        match (self, terminal) {
+            (Key::Up, XtermApplication) => b"\x1bOA",
+            (Key::Down, XtermApplication) => b"\x1bOB",
+            (Key::Right, XtermApplication) => b"\x1bOC",
+            (Key::Left, XtermApplication) => b"\x1bOD",
+            (Key::Up, _) => b"\x1b[A",
+            (Key::Down, _) => b"\x1b[B",
+            (Key::Right, _) => b"\x1b[C",
+            (Key::Left, _) => b"\x1b[D",
+            (Key::Home, Xterm) => b"\x1b[H",
+            (Key::End, Xterm) => b"\x1b[F",
+            (Key::Home, XtermApplication) => b"\x1bOH",
+            (Key::End, XtermApplication) => b"\x1bOF",
+            (Key::Home, Vt220 | Linux) => b"\x1b[1~",
+            (Key::End, Vt220 | Linux) => b"\x1b[4~",
+            (Key::Insert, _) => b"\x1b[2~",
+            (Key::Delete, _) => b"\x1b[3~",
+            (Key::PageUp, _) => b"\x1b[5~",
+            (Key::PageDown, _) => b"\x1b[6~",
+            (Key::F1, Linux) => b"\x1b[[A",
+            (Key::F2, Linux) => b"\x1b[[B",
+            (Key::F3, Linux) => b"\x1b[[C",
+            (Key::F4, Linux) => b"\x1b[[D",
+            (Key::F5, Linux) => b"\x1b[[E",
+            (Key::F1, _) => b"\x1bOP",
+            (Key::F2, _) => b"\x1bOQ",
+            (Key::F3, _) => b"\x1bOR",
+            (Key::F4, _) => b"\x1bOS",
+            (Key::F5, _) => b"\x1b[15~",
+            (Key::F6, _) => b"\x1b[17~",
+            (Key::F7, _) => b"\x1b[18~",
+            (Key::F8, _) => b"\x1b[19~",
+            (Key::F9, _) => b"\x1b[20~",
+            (Key::F10, _) => b"\x1b[21~",
+            (Key::F11, _) => b"\x1b[23~",
+            (Key::F12, _) => b"\x1b[24~",
+            (Key::Tab, _) => b"\t",
+            (Key::Enter, _) => b"\r",
+            (Key::Backspace, _) => b"\x7f",
+            (Key::Escape, _) => b"\x1b",
+        }
+    }
+}
+
+impl AsRef<[u8]> for Key {
+    fn as_ref(&self) -> &[u8] {
+        self.sequence(TerminalType::Xterm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence() {
+        assert_eq!(Key::Up.as_ref(), b"\x1b[A");
+        assert_eq!(Key::Up.sequence(TerminalType::XtermApplication), b"\x1bOA");
+        assert_eq!(Key::Home.sequence(TerminalType::Xterm), b"\x1b[H");
+        assert_eq!(Key::Home.sequence(TerminalType::Linux), b"\x1b[1~");
+        assert_eq!(Key::F1.sequence(TerminalType::Xterm), b"\x1bOP");
+        assert_eq!(Key::F1.sequence(TerminalType::Linux), b"\x1b[[A");
+        assert_eq!(Key::F12.sequence(TerminalType::Vt220), b"\x1b[24~");
+        assert_eq!(Key::Delete.sequence(TerminalType::Vt220), b"\x1b[3~");
+    }
+}
@@ -30,7 +30,20 @@
 //! ## Feature flags
 //!
 //! - `async`: Enables a async/await public API.
+//! - `runtime-async-std`, `runtime-smol`, `runtime-tokio`: Enables `async` and a process stream adapter of the runtime
+//!   (`Session::spawn_async_std`, `Session::spawn_smol`, `Session::spawn_tokio`) on unix.
+//!   The features are additive, a default stream is kept driven by `async-io` reactor.
 //! - `polling`: Enables polling backend in interact session. Be cautious to use it on windows.
+//! - `mio`: Implements `mio::event::Source` for sync sessions and streams on unix,
+//!   so they can be registered in a `mio` event loop.
+//! - `ssh`: Enables `session::ssh` module which drives a remote host via an SSH channel.
+//! - `json`: Enables `ReplSession::execute_json` which parses a command output as JSON.
+//! - `pexpect-compat`: Enables `pexpect` module which runs pexpect like scripts written in JSON or YAML.
+//! - `encoding`: Enables `session::encoding` which transcodes a session of a non UTF-8 charset (Latin-1, Shift-JIS, GBK etc.).
+//! - `snapshot`: Enables `session::Snapshot` and `Session::expect_snapshot` which compare an output against stored snapshot files.
+//! - `uring`: Enables `session::uring` which reads a process output through `io_uring` on Linux.
+//! - `tracing`: Emits `tracing` spans around spawn, expect and interact calls,
+//!   and events for each read and write with a byte count and a truncated preview.
 //!
 //! ## Examples
 //!
@@ -115,23 +128,50 @@ mod check_macros;
 mod control_code;
 mod error;
 mod expect;
+mod key;
+mod line_ending;
 mod needle;
+mod pool;
+mod select;
 
+#[cfg(feature = "tracing")]
+mod trace;
 #[cfg(all(windows, feature = "polling"))]
 mod waiter;
 
+pub mod clock;
+pub mod dialog;
 pub mod interact;
+pub mod netdev;
+#[cfg(feature = "pexpect-compat")]
+pub mod pexpect;
 pub mod process;
 pub mod repl;
+pub mod scenario;
+pub mod script;
 pub mod session;
 pub mod stream;
+pub mod terminal;
 
-pub use captures::Captures;
+pub use captures::{Captures, CapturesRef};
 pub use control_code::ControlCode;
 pub use error::Error;
-pub use needle::{Any, Eof, NBytes, Needle, Regex};
+pub use key::{Key, TerminalType};
+pub use line_ending::LineEnding;
+pub use needle::{
+    Any, Delimited, Endianness, Eof, GreedyRegex, LenPrefixed, LenWidth, Match, NBytes, NChars,
+    Needle, Pred, Regex, Utf8Str,
+};
+pub use pool::SessionPool;
+
+#[cfg(feature = "async")]
+pub use needle::AsyncNeedle;
+pub use select::{select, select_timeout};
 
 pub use expect::Expect;
+
+#[doc(hidden)]
+pub use check_macros::ExpectArms as __ExpectArms;
 pub use session::Session;
 
 #[cfg(feature = "async")]
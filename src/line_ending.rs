@@ -0,0 +1,34 @@
+//! A module which contains [LineEnding] type.
+
+/// LineEnding represents a sequence which is appended to a line by `send_line`.
+///
+/// The default one is `\r\n` on windows and `\n` on other platforms.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineEnding {
+    /// `\n`, commonly used on unix.
+    #[cfg_attr(not(windows), default)]
+    Lf,
+    /// `\r\n`, commonly used on windows and in network protocols.
+    #[cfg_attr(windows, default)]
+    CrLf,
+    /// `\r`, the one which is produced by ENTER key in raw terminal mode (often required by serial consoles).
+    Cr,
+}
+
+impl LineEnding {
+    /// Returns bytes of the line ending.
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+            LineEnding::Cr => b"\r",
+        }
+    }
+}
+
+impl AsRef<[u8]> for LineEnding {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
@@ -3,12 +3,63 @@
 //!
 //! The list of provided implementations can be found in the documentation.
 
+use std::{
+    cell::{Cell, RefCell},
+    time::{Duration, Instant},
+};
+
 use crate::error::Error;
 
 /// Needle an interface for search of a match in a buffer.
+///
+/// The trait is object safe, so a list of needles can be built in runtime
+/// as `Vec<Box<dyn Needle>>` and be passed around by `&dyn Needle`, `Box<dyn Needle>` or `Arc<dyn Needle>`.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Any, Expect, Needle, Regex};
+///
+/// let patterns: Vec<Box<dyn Needle>> = vec![Box::new("ERROR"), Box::new(Regex("\\d+"))];
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.expect(Any(patterns)).unwrap();
+/// ```
 pub trait Needle {
     /// Function returns all matches that were occured.
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error>;
+
+    /// Function returns all matches that were occured,
+    /// given that `buf[..checked]` was already checked by a previous call and nothing was found.
+    ///
+    /// An `expect` call uses it on each read so a needle can skip the checked part
+    /// instead of scanning the whole buffer again.
+    /// Literals (`str`, `[u8]`, `char` etc.) skip it;
+    /// a [`Regex`] with a bounded match length skips all but its longest match,
+    /// while an unbounded one (e.g. `a+`) scans the checked part again.
+    ///
+    /// The default implementation calls [Needle::check].
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        let _ = checked;
+        self.check(buf, eof)
+    }
+
+    /// Returns a human readable description of the needle.
+    ///
+    /// It's used in [`Error::ExpectTimeout`] to tell what was expected.
+    fn describe(&self) -> String {
+        String::from("a custom needle")
+    }
+
+    /// Returns a time after which the needle must be checked again even if no new data arrives,
+    /// e.g. when a match depends on a quiet period.
+    ///
+    /// A sync `expect` call doesn't block waiting for an output longer than that.
+    ///
+    /// The default implementation returns [`None`], so the needle is checked only when data arrives.
+    fn recheck_in(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// Match structure represent a range of bytes where match was found.
@@ -42,17 +93,256 @@ impl From<regex::bytes::Match<'_>> for Match {
 }
 
 /// Regex tries to look up a match by a regex.
+///
+/// A compiled regex is cached per thread, so a pattern is not parsed on each check.
 #[derive(Debug)]
 pub struct Regex<Re: AsRef<str>>(pub Re);
 
 impl<Re: AsRef<str>> Needle for Regex<Re> {
-    fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
-        let regex = regex::bytes::Regex::new(self.0.as_ref()).map_err(|_| Error::RegexParsing)?;
-        let matches = regex
-            .captures_iter(buf)
-            .flat_map(|c| c.iter().flatten().map(|m| m.into()).collect::<Vec<Match>>())
-            .collect();
-        Ok(matches)
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        self.check_from(buf, 0, eof)
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, _: bool) -> Result<Vec<Match>, Error> {
+        let regex = CompiledRegex::get(self.0.as_ref())?;
+        let start = search_start(checked, regex.max_len).min(buf.len());
+        Ok(captures_from(&regex.regex, buf, start))
+    }
+
+    fn describe(&self) -> String {
+        format!("regex {:?}", self.0.as_ref())
+    }
+}
+
+/// A precompiled regex can be used directly,
+/// e.g. one which is built with custom flags by [`regex::bytes::RegexBuilder`].
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Expect};
+///
+/// let prompt = regex::bytes::Regex::new(r"\$ $").unwrap();
+///
+/// let mut p = spawn("bash").unwrap();
+/// p.expect(&prompt).unwrap();
+/// ```
+impl Needle for regex::bytes::Regex {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        self.check_from(buf, 0, eof)
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, _: bool) -> Result<Vec<Match>, Error> {
+        let start = match checked {
+            0 => 0,
+            _ => search_start(checked, CompiledRegex::max_len(self.as_str())).min(buf.len()),
+        };
+        Ok(captures_from(self, buf, start))
+    }
+
+    fn describe(&self) -> String {
+        format!("regex {:?}", self.as_str())
+    }
+}
+
+/// A number of compiled regexes which are kept for reuse by [`Regex`] in each thread.
+const REGEX_CACHE_SIZE: usize = 16;
+
+thread_local! {
+    static REGEX_CACHE: RefCell<Vec<CompiledRegex>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone)]
+struct CompiledRegex {
+    regex: regex::bytes::Regex,
+    max_len: Option<usize>,
+}
+
+impl CompiledRegex {
+    /// Returns a compiled regex for a pattern,
+    /// so a [`Regex`] needle is not parsed on each check.
+    fn get(pattern: &str) -> Result<Self, Error> {
+        REGEX_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some(i) = cache.iter().position(|c| c.regex.as_str() == pattern) {
+                // the most recently used regex is kept first
+                cache[..=i].rotate_right(1);
+                return Ok(cache[0].clone());
+            }
+
+            let regex = regex::bytes::Regex::new(pattern).map_err(|_| Error::RegexParsing)?;
+            let compiled = Self {
+                regex,
+                max_len: max_match_len(pattern),
+            };
+
+            cache.insert(0, compiled.clone());
+            cache.truncate(REGEX_CACHE_SIZE);
+
+            Ok(compiled)
+        })
+    }
+
+    /// Returns the longest possible match of a pattern in bytes,
+    /// it's [`None`] if a match is not bounded.
+    fn max_len(pattern: &str) -> Option<usize> {
+        let cached = REGEX_CACHE.with(|cache| {
+            cache
+                .borrow()
+                .iter()
+                .find(|c| c.regex.as_str() == pattern)
+                .map(|c| c.max_len)
+        });
+
+        cached.unwrap_or_else(|| max_match_len(pattern))
+    }
+}
+
+/// Returns an offset from which a buffer must be searched
+/// given that `buf[..checked]` has no match.
+///
+/// A match may start in the checked part only if it ends in a new one,
+/// so it starts no further than its longest length before the end of the checked part.
+fn search_start(checked: usize, max_len: Option<usize>) -> usize {
+    max_len.map_or(0, |max_len| checked.saturating_sub(max_len))
+}
+
+/// Calculates the longest possible match of a pattern in bytes.
+///
+/// A pattern is parsed case insensitive, which never makes the length shorter,
+/// so the bound holds for a precompiled regex built with such a flag as well.
+fn max_match_len(pattern: &str) -> Option<usize> {
+    regex_syntax::ParserBuilder::new()
+        .utf8(false)
+        .case_insensitive(true)
+        .build()
+        .parse(pattern)
+        .ok()?
+        .properties()
+        .maximum_len()
+}
+
+/// Returns all captures of successive non overlapping matches starting from `start`,
+/// the same way as [`regex::bytes::Regex::captures_iter`] does from the beginning.
+///
+/// A buffer before `start` is still used as a context for assertions like `^` or `\b`.
+fn captures_from(regex: &regex::bytes::Regex, buf: &[u8], start: usize) -> Vec<Match> {
+    let mut matches = Vec::new();
+    let mut at = start;
+    let mut last_end = None;
+    while at <= buf.len() {
+        let captures = match regex.captures_at(buf, at) {
+            Some(captures) => captures,
+            None => break,
+        };
+
+        let m = captures.get(0).expect("a group 0 is always present");
+        if m.is_empty() && last_end == Some(m.end()) {
+            // an empty match right after a previous one is skipped
+            at = m.end() + 1;
+            continue;
+        }
+
+        matches.extend(captures.iter().flatten().map(Match::from));
+        at = m.end();
+        last_end = Some(m.end());
+    }
+
+    matches
+}
+
+impl<Re: AsRef<str>> Regex<Re> {
+    /// Turns the regex into a [`GreedyRegex`],
+    /// which waits until a match can't be extended by a following output.
+    pub fn greedy(self) -> GreedyRegex<Re> {
+        GreedyRegex::new(self.0)
+    }
+}
+
+/// GreedyRegex tries to look up a match by a regex,
+/// but it doesn't return a match which may be extended by a data which is still coming.
+///
+/// A match which ends before the end of a buffer is returned imideately
+/// (e.g. it's bounded by a delimiter which regex doesn't accept).
+/// A match which touches the end of a buffer is returned only on EOF
+/// or after the buffer was not changed for a quiet period.
+///
+/// Be aware that in `async` mode a buffer is rechecked only when new data arrives.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Expect, Regex};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.send_line("hello world").unwrap();
+/// let m = p.expect(Regex("lo.*").greedy()).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct GreedyRegex<Re: AsRef<str>> {
+    regex: Re,
+    quiet_period: Duration,
+    last_change: Cell<Option<(usize, Instant)>>,
+}
+
+impl<Re: AsRef<str>> GreedyRegex<Re> {
+    /// A default quiet period.
+    pub const QUIET_PERIOD: Duration = Duration::from_millis(100);
+
+    /// Creates a new greedy regex.
+    pub fn new(regex: Re) -> Self {
+        Self {
+            regex,
+            quiet_period: Self::QUIET_PERIOD,
+            last_change: Cell::new(None),
+        }
+    }
+
+    /// Sets a time during which a buffer must not be changed
+    /// for a match touching its end to be returned.
+    pub fn quiet_period(mut self, period: Duration) -> Self {
+        self.quiet_period = period;
+        self
+    }
+
+    fn is_quiet(&self, len: usize) -> bool {
+        match self.last_change.get() {
+            Some((last_len, time)) if last_len == len => time.elapsed() >= self.quiet_period,
+            _ => {
+                self.last_change.set(Some((len, Instant::now())));
+                self.quiet_period.is_zero()
+            }
+        }
+    }
+}
+
+impl<Re: AsRef<str>> Needle for GreedyRegex<Re> {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        self.check_from(buf, 0, eof)
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        let matches = Regex(self.regex.as_ref()).check_from(buf, checked, eof)?;
+        if matches.is_empty() || eof {
+            return Ok(matches);
+        }
+
+        let end = matches.iter().map(|m| m.end()).max().unwrap_or_default();
+        if end < buf.len() || self.is_quiet(buf.len()) {
+            self.last_change.set(None);
+            return Ok(matches);
+        }
+
+        Ok(Vec::new())
+    }
+
+    fn describe(&self) -> String {
+        format!("greedy regex {:?}", self.regex.as_ref())
+    }
+
+    fn recheck_in(&self) -> Option<Duration> {
+        let (_, time) = self.last_change.get()?;
+        Some(self.quiet_period.saturating_sub(time.elapsed()))
     }
 }
 
@@ -67,6 +357,10 @@ impl Needle for Eof {
             false => Ok(Vec::new()),
         }
     }
+
+    fn describe(&self) -> String {
+        String::from("EOF")
+    }
 }
 
 /// NBytes matches N bytes from the stream.
@@ -86,6 +380,337 @@ impl Needle for NBytes {
             false => Ok(Vec::new()),
         }
     }
+
+    fn describe(&self) -> String {
+        format!("{} bytes", self.count())
+    }
+}
+
+/// Delimited matches a frame which ends with a delimiter.
+///
+/// A frame starts at the beginning of a buffer and includes the delimiter,
+/// a first capture group is a frame payload without the delimiter.
+///
+/// An empty delimiter never matches.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Delimited, Expect};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.send("frame\0").unwrap();
+///
+/// let m = p.expect(Delimited(b"\0")).unwrap();
+/// assert_eq!(m.get(1).unwrap(), b"frame");
+/// ```
+#[derive(Debug)]
+pub struct Delimited<D: AsRef<[u8]>>(pub D);
+
+impl<D: AsRef<[u8]>> Needle for Delimited<D> {
+    fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+        let delimiter = self.0.as_ref();
+        if delimiter.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let end = buf
+            .windows(delimiter.len())
+            .position(|window| window == delimiter);
+        match end {
+            Some(end) => Ok(vec![
+                Match::new(0, end + delimiter.len()),
+                Match::new(0, end),
+            ]),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "a frame delimited by {:?}",
+            String::from_utf8_lossy(self.0.as_ref())
+        )
+    }
+}
+
+/// A width of a length prefix of [`LenPrefixed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LenWidth {
+    /// A 1 byte length.
+    U8,
+    /// A 2 bytes length.
+    U16,
+    /// A 4 bytes length.
+    U32,
+}
+
+impl LenWidth {
+    fn size(&self) -> usize {
+        match self {
+            LenWidth::U8 => 1,
+            LenWidth::U16 => 2,
+            LenWidth::U32 => 4,
+        }
+    }
+}
+
+/// A byte order of a length prefix of [`LenPrefixed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// The most significant byte goes first.
+    Big,
+    /// The least significant byte goes first.
+    Little,
+}
+
+/// LenPrefixed matches a message which starts with its length.
+///
+/// A message starts at the beginning of a buffer and it's matched once it's complete.
+/// The length doesn't include the prefix itself,
+/// a first capture group is a message payload without the prefix.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Endianness, Expect, LenPrefixed, LenWidth};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.send(b"\x00\x05hello").unwrap();
+///
+/// let m = p.expect(LenPrefixed(LenWidth::U16, Endianness::Big)).unwrap();
+/// assert_eq!(m.get(1).unwrap(), b"hello");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct LenPrefixed(pub LenWidth, pub Endianness);
+
+impl LenPrefixed {
+    fn payload_len(&self, prefix: &[u8]) -> usize {
+        let mut bytes = [0; 4];
+        let len = match self.1 {
+            Endianness::Big => {
+                bytes[4 - prefix.len()..].copy_from_slice(prefix);
+                u32::from_be_bytes(bytes)
+            }
+            Endianness::Little => {
+                bytes[..prefix.len()].copy_from_slice(prefix);
+                u32::from_le_bytes(bytes)
+            }
+        };
+
+        len as usize
+    }
+}
+
+impl Needle for LenPrefixed {
+    fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+        let width = self.0.size();
+        if buf.len() < width {
+            return Ok(Vec::new());
+        }
+
+        let end = width.saturating_add(self.payload_len(&buf[..width]));
+        if buf.len() < end {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![Match::new(0, end), Match::new(width, end)])
+    }
+
+    fn describe(&self) -> String {
+        format!("a message prefixed by {:?} {:?} length", self.1, self.0)
+    }
+}
+
+/// Pred is a needle which is checked by a closure.
+///
+/// The closure gets a buffer and an EOF flag, and returns a match if it's found.
+/// It lets one-off matching logic be passed inline without a new [`Needle`] type.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Expect, Match, Pred};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.send_line("abc123").unwrap();
+///
+/// // a first digit
+/// let digit = Pred(|buf: &[u8], _| {
+///     let i = buf.iter().position(u8::is_ascii_digit)?;
+///     Some(Match::new(i, i + 1))
+/// });
+///
+/// p.expect(digit).unwrap();
+/// ```
+pub struct Pred<F>(pub F);
+
+impl<F> Needle for Pred<F>
+where
+    F: Fn(&[u8], bool) -> Option<Match>,
+{
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        Ok((self.0)(buf, eof).into_iter().collect())
+    }
+
+    fn describe(&self) -> String {
+        String::from("a predicate")
+    }
+}
+
+impl<F> std::fmt::Debug for Pred<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Pred").finish()
+    }
+}
+
+/// AsyncNeedle an interface for search of a match which may do an async work,
+/// e.g. consult an external service or wait on a rate limiter.
+///
+/// It's used by `Session::expect_awaitable`.
+/// The work is awaited within the expect loop so it doesn't block a reactor,
+/// and it's a subject of the session's expect timeout.
+///
+/// Any [Needle] is an [AsyncNeedle].
+///
+/// # Example
+///
+/// ```no_run
+/// use expectrl::{spawn, AsyncNeedle, Error, Match};
+///
+/// struct Oracle;
+///
+/// impl AsyncNeedle for Oracle {
+///     async fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+///         // ask something which takes time
+///         futures_lite::future::yield_now().await;
+///
+///         let line = buf.iter().position(|&b| b == b'\n');
+///         Ok(line.map(|i| Match::new(0, i + 1)).into_iter().collect())
+///     }
+/// }
+///
+/// # futures_lite::future::block_on(async {
+/// let mut p = spawn("cat").unwrap();
+/// p.expect_awaitable(Oracle).await.unwrap();
+/// # });
+/// ```
+#[cfg(feature = "async")]
+pub trait AsyncNeedle {
+    /// Function returns all matches that were occured.
+    async fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error>;
+
+    /// Returns a human readable description of the needle, see [`Needle::describe`].
+    fn describe(&self) -> String {
+        String::from("a custom needle")
+    }
+}
+
+#[cfg(feature = "async")]
+impl<N> AsyncNeedle for N
+where
+    N: Needle + ?Sized,
+{
+    async fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        Needle::check(self, buf, eof)
+    }
+
+    fn describe(&self) -> String {
+        Needle::describe(self)
+    }
+}
+
+/// NChars matches N UTF-8 characters from the stream.
+///
+/// Unlike [NBytes] it never splits a multi-byte character,
+/// an incomplete character at the end of a buffer is awaited until more data arrives.
+/// An invalid UTF-8 sequence is counted as a character per byte,
+/// the same way [String::from_utf8_lossy] replaces it.
+#[derive(Debug)]
+pub struct NChars(pub usize);
+
+impl Needle for NChars {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        let mut end = 0;
+        for _ in 0..self.0 {
+            match next_char_len(&buf[end..], eof) {
+                Some(len) => end += len,
+                None => return Ok(Vec::new()),
+            }
+        }
+
+        Ok(vec![Match::new(0, end)])
+    }
+
+    fn describe(&self) -> String {
+        format!("{} characters", self.0)
+    }
+}
+
+/// Utf8Str matches a string, but only at character boundaries of a buffer.
+///
+/// A match never starts inside of a multi-byte character,
+/// and an incomplete character at the end of a buffer is not checked
+/// until the rest of it arrives (or an EOF is reached),
+/// so [`Captures::before`] is always a valid UTF-8 if the output is.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Expect, Utf8Str};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.send_line("こんにちは").unwrap();
+/// p.expect(Utf8Str("にち")).unwrap();
+/// ```
+///
+/// [`Captures::before`]: crate::Captures::before
+#[derive(Debug)]
+pub struct Utf8Str<S: AsRef<str>>(pub S);
+
+impl<S: AsRef<str>> Needle for Utf8Str<S> {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        let needle = self.0.as_ref().as_bytes();
+
+        let mut start = 0;
+        loop {
+            if buf[start..].starts_with(needle) {
+                return Ok(vec![Match::new(start, start + needle.len())]);
+            }
+
+            match next_char_len(&buf[start..], eof) {
+                Some(len) => start += len,
+                None => return Ok(Vec::new()),
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}", self.0.as_ref())
+    }
+}
+
+/// Returns a length of a first character in a buffer.
+///
+/// It returns [None] if a buffer is empty or the character is not complete yet.
+fn next_char_len(buf: &[u8], eof: bool) -> Option<usize> {
+    let first = *buf.first()?;
+    let width = match first {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => 1,
+    };
+    let end = width.min(buf.len());
+
+    match std::str::from_utf8(&buf[..end]) {
+        Ok(_) => Some(end),
+        Err(err) => match err.error_len() {
+            Some(_) => Some(1),
+            None if eof => Some(end),
+            None => None,
+        },
+    }
 }
 
 impl Needle for [u8] {
@@ -105,43 +730,161 @@ impl Needle for [u8] {
             }
         }
 
-        Ok(Vec::new())
+        Ok(Vec::new())
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        // A match may start in the checked part only if it ends in a new one.
+        let start = checked
+            .saturating_sub(self.len().saturating_sub(1))
+            .min(buf.len());
+        let found = self.check(&buf[start..], eof)?;
+        Ok(shift_matches(found, start))
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}", String::from_utf8_lossy(self))
+    }
+}
+
+impl Needle for str {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        self.as_bytes().check(buf, eof)
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        self.as_bytes().check_from(buf, checked, eof)
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl Needle for String {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        self.as_bytes().check(buf, eof)
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        self.as_bytes().check_from(buf, checked, eof)
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl Needle for u8 {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        ([*self][..]).check(buf, eof)
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        ([*self][..]).check_from(buf, checked, eof)
+    }
+
+    fn describe(&self) -> String {
+        format!("byte {:#04x}", self)
+    }
+}
+
+impl Needle for char {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        char::to_string(self).check(buf, eof)
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        let mut bytes = [0; 4];
+        let bytes = self.encode_utf8(&mut bytes).as_bytes();
+        bytes.check_from(buf, checked, eof)
     }
-}
 
-impl Needle for &[u8] {
-    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        (*self).check(buf, eof)
+    fn describe(&self) -> String {
+        format!("{:?}", self)
     }
 }
 
-impl Needle for str {
-    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        self.as_bytes().check(buf, eof)
-    }
+/// An array of needles matches any of them the same way as [`Any`].
+///
+/// It's implemented for a set of element types rather than for any needle,
+/// so a byte string literal like `b"abc"` still matches as a sequence of bytes.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Expect};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.expect(["we", "are", "here"]).unwrap();
+/// p.expect(['$', '#']).unwrap();
+/// ```
+macro_rules! impl_needle_for_array {
+    ($([$($generics:tt)*] $ty:ty),* $(,)?) => {
+        $(
+            impl<$($generics)* const N: usize> Needle for [$ty; N] {
+                fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+                    Any(&self[..]).check(buf, eof)
+                }
+
+                fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+                    Any(&self[..]).check_from(buf, checked, eof)
+                }
+
+                fn describe(&self) -> String {
+                    Any(&self[..]).describe()
+                }
+
+                fn recheck_in(&self) -> Option<Duration> {
+                    Any(&self[..]).recheck_in()
+                }
+            }
+        )*
+    };
 }
 
-impl Needle for &str {
+impl_needle_for_array!(
+    [] char,
+    [] &'_ str,
+    [] String,
+    [] &'_ String,
+    [] &'_ [u8],
+    [Re: AsRef<str>,] Regex<Re>,
+    [Re: AsRef<str>,] GreedyRegex<Re>,
+    [] NBytes,
+    [] Box<dyn Needle + '_>,
+    [] &'_ dyn Needle,
+);
+
+/// A list of boxed needles matches any of them the same way as [`Any::boxed`].
+///
+/// It lets a list which is built in runtime, e.g. from a config, be passed as it is.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Expect, Needle, Regex};
+///
+/// let patterns: Vec<Box<dyn Needle>> = vec![Box::new("ERROR"), Box::new(Regex("\\d+"))];
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.expect(&patterns).unwrap();
+/// ```
+impl Needle for Vec<Box<dyn Needle>> {
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        self.as_bytes().check(buf, eof)
+        Any(self.as_slice()).check(buf, eof)
     }
-}
 
-impl Needle for String {
-    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        self.as_bytes().check(buf, eof)
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        Any(self.as_slice()).check_from(buf, checked, eof)
     }
-}
 
-impl Needle for u8 {
-    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        ([*self][..]).check(buf, eof)
+    fn describe(&self) -> String {
+        Any(self.as_slice()).describe()
     }
-}
 
-impl Needle for char {
-    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        char::to_string(self).check(buf, eof)
+    fn recheck_in(&self) -> Option<Duration> {
+        Any(self.as_slice()).recheck_in()
     }
 }
 
@@ -191,6 +934,25 @@ where
 
         Ok(Vec::new())
     }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        for needle in self.0.iter() {
+            let found = needle.check_from(buf, checked, eof)?;
+            if !found.is_empty() {
+                return Ok(found);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    fn describe(&self) -> String {
+        describe_any(self.0.iter().map(|needle| needle.describe()))
+    }
+
+    fn recheck_in(&self) -> Option<Duration> {
+        self.0.iter().filter_map(|needle| needle.recheck_in()).min()
+    }
 }
 
 impl<T> Needle for Any<Vec<T>>
@@ -200,6 +962,18 @@ where
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         Any(self.0.as_slice()).check(buf, eof)
     }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        Any(self.0.as_slice()).check_from(buf, checked, eof)
+    }
+
+    fn describe(&self) -> String {
+        Any(self.0.as_slice()).describe()
+    }
+
+    fn recheck_in(&self) -> Option<Duration> {
+        Any(self.0.as_slice()).recheck_in()
+    }
 }
 
 impl<T, const N: usize> Needle for Any<[T; N]>
@@ -209,6 +983,18 @@ where
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         Any(&self.0[..]).check(buf, eof)
     }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        Any(&self.0[..]).check_from(buf, checked, eof)
+    }
+
+    fn describe(&self) -> String {
+        Any(&self.0[..]).describe()
+    }
+
+    fn recheck_in(&self) -> Option<Duration> {
+        Any(&self.0[..]).recheck_in()
+    }
 }
 
 impl<T, const N: usize> Needle for Any<&'_ [T; N]>
@@ -218,24 +1004,112 @@ where
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         Any(&self.0[..]).check(buf, eof)
     }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        Any(&self.0[..]).check_from(buf, checked, eof)
+    }
+
+    fn describe(&self) -> String {
+        Any(&self.0[..]).describe()
+    }
+
+    fn recheck_in(&self) -> Option<Duration> {
+        Any(&self.0[..]).recheck_in()
+    }
+}
+
+impl<T: Needle + ?Sized> Needle for &T {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        T::check(self, buf, eof)
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        T::check_from(self, buf, checked, eof)
+    }
+
+    fn describe(&self) -> String {
+        T::describe(self)
+    }
+
+    fn recheck_in(&self) -> Option<Duration> {
+        T::recheck_in(self)
+    }
+}
+
+impl<T: Needle + ?Sized> Needle for Box<T> {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        T::check(self, buf, eof)
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        T::check_from(self, buf, checked, eof)
+    }
+
+    fn describe(&self) -> String {
+        T::describe(self)
+    }
+
+    fn recheck_in(&self) -> Option<Duration> {
+        T::recheck_in(self)
+    }
 }
 
-impl<T: Needle> Needle for &T {
+impl<T: Needle + ?Sized> Needle for std::rc::Rc<T> {
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         T::check(self, buf, eof)
     }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        T::check_from(self, buf, checked, eof)
+    }
+
+    fn describe(&self) -> String {
+        T::describe(self)
+    }
+
+    fn recheck_in(&self) -> Option<Duration> {
+        T::recheck_in(self)
+    }
 }
 
-impl Needle for Box<dyn Needle + '_> {
+impl<T: Needle + ?Sized> Needle for std::sync::Arc<T> {
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        self.as_ref().check(buf, eof)
+        T::check(self, buf, eof)
+    }
+
+    fn check_from(&self, buf: &[u8], checked: usize, eof: bool) -> Result<Vec<Match>, Error> {
+        T::check_from(self, buf, checked, eof)
+    }
+
+    fn describe(&self) -> String {
+        T::describe(self)
+    }
+
+    fn recheck_in(&self) -> Option<Duration> {
+        T::recheck_in(self)
+    }
+}
+
+fn describe_any(needles: impl Iterator<Item = String>) -> String {
+    let needles = needles.collect::<Vec<_>>();
+    format!("any of [{}]", needles.join(", "))
+}
+
+fn shift_matches(mut matches: Vec<Match>, offset: usize) -> Vec<Match> {
+    for m in &mut matches {
+        m.start += offset;
+        m.end += offset;
     }
+
+    matches
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::sync::Arc;
+
     #[test]
     fn test_regex() {
         assert_eq!(
@@ -270,6 +1144,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_delimited() {
+        assert_eq!(
+            Delimited(b"\r\n").check(b"abc\r\ndef\r\n", false).unwrap(),
+            vec![Match::new(0, 5), Match::new(0, 3)]
+        );
+        assert_eq!(Delimited(b"\r\n").check(b"abc\r", false).unwrap(), vec![]);
+        assert_eq!(Delimited(b"").check(b"abc", false).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_len_prefixed() {
+        let needle = LenPrefixed(LenWidth::U16, Endianness::Big);
+        assert_eq!(needle.check(b"\x00", false).unwrap(), vec![]);
+        assert_eq!(needle.check(b"\x00\x03ab", false).unwrap(), vec![]);
+        assert_eq!(
+            needle.check(b"\x00\x03abcd", false).unwrap(),
+            vec![Match::new(0, 5), Match::new(2, 5)]
+        );
+
+        let needle = LenPrefixed(LenWidth::U32, Endianness::Little);
+        assert_eq!(
+            needle.check(b"\x02\x00\x00\x00ab", false).unwrap(),
+            vec![Match::new(0, 6), Match::new(4, 6)]
+        );
+
+        let needle = LenPrefixed(LenWidth::U8, Endianness::Big);
+        assert_eq!(
+            needle.check(b"\x00rest", false).unwrap(),
+            vec![Match::new(0, 1), Match::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_pred() {
+        let digit = Pred(|buf: &[u8], _| {
+            let i = buf.iter().position(u8::is_ascii_digit)?;
+            Some(Match::new(i, i + 1))
+        });
+
+        assert_eq!(
+            digit.check(b"abc123", false).unwrap(),
+            vec![Match::new(3, 4)]
+        );
+        assert_eq!(digit.check(b"abc", false).unwrap(), vec![]);
+
+        let eof = Pred(|buf: &[u8], eof: bool| eof.then(|| Match::new(0, buf.len())));
+        assert_eq!(eof.check(b"abc", true).unwrap(), vec![Match::new(0, 3)]);
+    }
+
+    #[test]
+    fn test_compiled_regex() {
+        let re = regex::bytes::Regex::new(r"(\w+)=(\w+)").unwrap();
+        assert_eq!(
+            re.check(b"asd=123", false).unwrap(),
+            vec![Match::new(0, 7), Match::new(0, 3), Match::new(4, 7)]
+        );
+        assert_eq!((&re).check(b"asd", false).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_greedy_regex() {
+        let re = Regex("lo.*").greedy();
+        assert_eq!(re.check(b"hello wo", false).unwrap(), vec![]);
+        assert_eq!(
+            re.check(b"hello world", true).unwrap(),
+            vec![Match::new(3, 11)]
+        );
+
+        let re = Regex(r"lo \w+").greedy();
+        assert_eq!(re.check(b"hello wo", false).unwrap(), vec![]);
+        assert_eq!(
+            re.check(b"hello world\r\n", false).unwrap(),
+            vec![Match::new(3, 11)]
+        );
+
+        let re = Regex("lo.*")
+            .greedy()
+            .quiet_period(Duration::from_millis(10));
+        assert_eq!(re.check(b"hello wo", false).unwrap(), vec![]);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(re.check(b"hello world", false).unwrap(), vec![]);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(
+            re.check(b"hello world", false).unwrap(),
+            vec![Match::new(3, 11)]
+        );
+    }
+
     #[test]
     fn test_eof() {
         assert_eq!(Eof.check(b"qwe", true).unwrap(), vec![Match::new(0, 3)]);
@@ -289,6 +1252,55 @@ mod tests {
         assert_eq!(NBytes(10).check(b"qwe", false).unwrap(), vec![]);
     }
 
+    #[test]
+    fn test_n_chars() {
+        let buf = "😁😄😅".as_bytes();
+        assert_eq!(NChars(1).check(buf, false).unwrap(), vec![Match::new(0, 4)]);
+        assert_eq!(
+            NChars(3).check(buf, false).unwrap(),
+            vec![Match::new(0, 12)]
+        );
+        assert_eq!(NChars(4).check(buf, false).unwrap(), vec![]);
+        assert_eq!(NChars(0).check(buf, false).unwrap(), vec![Match::new(0, 0)]);
+
+        // incomplete character
+        assert_eq!(NChars(2).check(&buf[..6], false).unwrap(), vec![]);
+        assert_eq!(
+            NChars(2).check(&buf[..6], true).unwrap(),
+            vec![Match::new(0, 6)]
+        );
+
+        // invalid sequence
+        assert_eq!(
+            NChars(2).check(b"\xFF\xFFa", false).unwrap(),
+            vec![Match::new(0, 2)]
+        );
+    }
+
+    #[test]
+    fn test_utf8_str() {
+        let buf = "こんにちは".as_bytes();
+        assert_eq!(
+            Utf8Str("にち").check(buf, false).unwrap(),
+            vec![Match::new(6, 12)]
+        );
+        assert_eq!(Utf8Str("は!").check(buf, false).unwrap(), vec![]);
+        assert_eq!(
+            Utf8Str("").check(buf, false).unwrap(),
+            vec![Match::new(0, 0)]
+        );
+
+        // incomplete character is not checked
+        assert_eq!(Utf8Str("は").check(&buf[..14], false).unwrap(), vec![]);
+        assert_eq!(
+            Utf8Str("は").check(buf, false).unwrap(),
+            vec![Match::new(12, 15)]
+        );
+
+        // an incomplete character is checked on EOF
+        assert_eq!(Utf8Str("ち").check(&buf[..11], true).unwrap(), vec![]);
+    }
+
     #[test]
     fn test_str() {
         assert_eq!(
@@ -299,6 +1311,77 @@ mod tests {
         assert_eq!("".check(b"qwerty", false).unwrap(), vec![Match::new(0, 0)]);
     }
 
+    #[test]
+    fn test_check_from() {
+        let buf = b"qwerty";
+
+        // nothing was found in buf[..checked] so a match must end after it.
+        for checked in 0..4 {
+            assert_eq!(
+                "wer".check_from(buf, checked, false).unwrap(),
+                vec![Match::new(1, 4)]
+            );
+        }
+
+        for checked in 0..=buf.len() {
+            assert_eq!("123".check_from(buf, checked, false).unwrap(), vec![]);
+        }
+
+        assert_eq!(
+            ('y').check_from(buf, 5, false).unwrap(),
+            vec![Match::new(5, 6)]
+        );
+        assert_eq!(
+            Any(["123", "ty"]).check_from(buf, 5, false).unwrap(),
+            vec![Match::new(4, 6)]
+        );
+        assert_eq!(
+            NBytes(2).check_from(buf, 5, false).unwrap(),
+            vec![Match::new(0, 2)]
+        );
+        assert_eq!(
+            Regex("w.r").check_from(buf, 3, false).unwrap(),
+            vec![Match::new(1, 4)]
+        );
+    }
+
+    #[test]
+    fn test_regex_check_from() {
+        let buf = b"ty qwerty ty";
+
+        // a match may start no further than its longest length before the checked part.
+        assert_eq!(
+            Regex("ty").check_from(buf, 11, false).unwrap(),
+            vec![Match::new(10, 12)]
+        );
+        assert_eq!(
+            Regex("ty").check(buf, false).unwrap(),
+            vec![Match::new(0, 2), Match::new(7, 9), Match::new(10, 12)]
+        );
+
+        // an unbounded match may start anywhere.
+        assert_eq!(
+            Regex("t+").check_from(buf, 11, false).unwrap(),
+            vec![Match::new(0, 1), Match::new(7, 8), Match::new(10, 11)]
+        );
+
+        // a checked part is still a context of assertions.
+        assert_eq!(
+            Regex(r"\bty").check_from(buf, 9, false).unwrap(),
+            vec![Match::new(10, 12)]
+        );
+
+        let re = regex::bytes::Regex::new(r"(t)y").unwrap();
+        assert_eq!(
+            re.check_from(buf, 11, false).unwrap(),
+            vec![Match::new(10, 12), Match::new(10, 11)]
+        );
+        assert!(matches!(
+            Regex("(").check_from(buf, 11, false),
+            Err(Error::RegexParsing)
+        ));
+    }
+
     #[test]
     fn test_bytes() {
         assert_eq!(
@@ -322,7 +1405,7 @@ mod tests {
             vec![]
         );
         assert_eq!(
-            (&[]).check(b"qwerty", false).unwrap(),
+            (&[] as &[u8]).check(b"qwerty", false).unwrap(),
             vec![Match::new(0, 0)]
         );
     }
@@ -346,6 +1429,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dyn() {
+        let needle: &dyn Needle = &"wer";
+        assert_eq!(
+            needle.check(b"qwerty", false).unwrap(),
+            vec![Match::new(1, 4)]
+        );
+
+        let needle: Box<dyn Needle> = Box::new(NBytes(2));
+        assert_eq!(
+            needle.check(b"qwerty", false).unwrap(),
+            vec![Match::new(0, 2)]
+        );
+
+        let needle: Arc<dyn Needle + Send + Sync> = Arc::new(Regex("t+"));
+        assert_eq!(
+            needle.check(b"qwerty", false).unwrap(),
+            vec![Match::new(4, 5)]
+        );
+    }
+
     #[test]
     fn test_any() {
         assert_eq!(
@@ -361,7 +1465,7 @@ mod tests {
             vec![],
         );
         assert_eq!(
-            Any(["123", "234", "rty"]).check(b"qwerty", false).unwrap(),
+            ["123", "234", "rty"].check(b"qwerty", false).unwrap(),
             vec![Match::new(3, 6)]
         );
         assert_eq!(
@@ -375,4 +1479,57 @@ mod tests {
             vec![Match::new(3, 6)]
         );
     }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(Needle::describe("Hello"), "\"Hello\"");
+        assert_eq!(Needle::describe(&Regex("\\d+")), "regex \"\\\\d+\"");
+        assert_eq!(Needle::describe(&NBytes(3)), "3 bytes");
+        assert_eq!(Needle::describe(&b'\n'), "byte 0x0a");
+        assert_eq!(
+            Needle::describe(&Any::boxed(vec![Box::new("we"), Box::new(Eof)])),
+            "any of [\"we\", EOF]"
+        );
+        assert_eq!(Needle::describe(&Pred(|_: &[u8], _| None)), "a predicate");
+    }
+
+    #[test]
+    fn test_literals() {
+        assert_eq!('r'.check(b"qwerty", false).unwrap(), vec![Match::new(3, 4)]);
+        assert_eq!(
+            '—'.check("a—b".as_bytes(), false).unwrap(),
+            vec![Match::new(1, 4)]
+        );
+        assert_eq!(
+            String::from("rty").check(b"qwerty", false).unwrap(),
+            vec![Match::new(3, 6)]
+        );
+
+        let needle = String::from("rty");
+        let needle: &String = &needle;
+        assert_eq!(
+            Needle::check(&needle, b"qwerty", false).unwrap(),
+            vec![Match::new(3, 6)]
+        );
+    }
+
+    #[test]
+    fn test_any_of_list() {
+        assert_eq!(
+            ["123", "234", "rty"].check(b"qwerty", false).unwrap(),
+            vec![Match::new(3, 6)]
+        );
+        assert_eq!(
+            ['y', 'w'].check_from(b"qwerty", 3, false).unwrap(),
+            vec![Match::new(5, 6)]
+        );
+        assert_eq!(["123", "234"].check(b"qwerty", false).unwrap(), vec![]);
+
+        let needles: Vec<Box<dyn Needle>> = vec![Box::new("we"), Box::new(NBytes(3))];
+        assert_eq!(
+            needles.check(b"qwerty", false).unwrap(),
+            vec![Match::new(1, 3)]
+        );
+        assert_eq!(Needle::describe(&needles), "any of [\"we\", 3 bytes]");
+    }
 }
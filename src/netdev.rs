@@ -0,0 +1,453 @@
+//! This module contains helpers which drive network devices, like Cisco and Juniper ones.
+//!
+//! [`NetDevice`] is built on [`ReplSession`] and knows prompts and commands of a [`Dialect`],
+//! so a login, an enable mode, a pagination and a config mode are handled in one call.
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use expectrl::{netdev::{Dialect, NetDevice}, spawn};
+//!
+//! let session = spawn("telnet 192.168.0.1").unwrap();
+//!
+//! let mut router = NetDevice::new(session, Dialect::Cisco);
+//! router.login("admin", "secret").unwrap();
+//! router.enable("enable-secret").unwrap();
+//! router.disable_paging().unwrap();
+//!
+//! router.configure(["interface Gi0/1", "description uplink"]).unwrap();
+//! let config = router.execute("show running-config").unwrap();
+//! ```
+
+use crate::{
+    check_macros::ExpectArms,
+    dialog::{Login, LoginOutcome},
+    repl::ReplSession,
+    stream::log::SecretGuard,
+    Captures, Error, Needle, Regex,
+};
+
+#[cfg(not(feature = "async"))]
+use crate::Expect;
+
+#[cfg(feature = "async")]
+use crate::AsyncExpect;
+
+/// A command line dialect of a network OS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// Cisco IOS, IOS-XE, NX-OS and alike CLIs (e.g. Arista EOS).
+    ///
+    /// Prompts look like `router>`, `router#` and `router(config-if)#`.
+    Cisco,
+    /// Juniper Junos.
+    ///
+    /// Prompts look like `user@router>` and `user@router#`.
+    Juniper,
+}
+
+impl Dialect {
+    /// Returns a regex which matches a prompt in any mode.
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            Dialect::Cisco => CISCO_PROMPT,
+            Dialect::Juniper => JUNIPER_PROMPT,
+        }
+    }
+
+    /// Returns a command which turns off a pagination of an output.
+    pub fn disable_paging_command(&self) -> &'static str {
+        match self {
+            Dialect::Cisco => "terminal length 0",
+            Dialect::Juniper => "set cli screen-length 0",
+        }
+    }
+
+    /// Returns a command which enters a config mode.
+    pub fn configure_command(&self) -> &'static str {
+        match self {
+            Dialect::Cisco => "configure terminal",
+            Dialect::Juniper => "configure",
+        }
+    }
+
+    /// Returns commands which apply a configuration and leave a config mode.
+    pub fn commit_commands(&self) -> &'static [&'static str] {
+        match self {
+            Dialect::Cisco => &["end"],
+            Dialect::Juniper => &["commit and-quit"],
+        }
+    }
+
+    /// Returns commands which leave a config mode after a failed command.
+    ///
+    /// Junos discards a candidate configuration, while Cisco applies commands right away,
+    /// so the ones before a failure stay applied.
+    pub fn abort_commands(&self) -> &'static [&'static str] {
+        match self {
+            Dialect::Cisco => &["end"],
+            Dialect::Juniper => &["rollback", "exit configuration-mode"],
+        }
+    }
+
+    /// Returns a regex which matches an error message of a rejected command.
+    pub fn error(&self) -> &'static str {
+        match self {
+            Dialect::Cisco => CISCO_ERROR,
+            Dialect::Juniper => JUNIPER_ERROR,
+        }
+    }
+}
+
+/// NetDevice is a session with a network device.
+///
+/// Commands are executed by the inner [`ReplSession`],
+/// and an output is checked for error messages of a [`Dialect`].
+/// A session is expected to be created right after connecting,
+/// so [`NetDevice::login`] or [`NetDevice::expect_prompt`] must be called first.
+#[derive(Debug)]
+pub struct NetDevice<S> {
+    repl: ReplSession<S>,
+    dialect: Dialect,
+}
+
+impl<S> NetDevice<S> {
+    /// Creates a network device session of a given dialect.
+    pub fn new(session: S, dialect: Dialect) -> Self {
+        let mut repl = ReplSession::new(session, dialect.prompt());
+        repl.set_prompt_regex(true);
+        repl.set_quit_command("exit");
+
+        Self { repl, dialect }
+    }
+
+    /// Get a dialect of the device.
+    pub fn get_dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Get an inner repl session.
+    pub fn get_repl(&self) -> &ReplSession<S> {
+        &self.repl
+    }
+
+    /// Get an inner repl session.
+    pub fn get_repl_mut(&mut self) -> &mut ReplSession<S> {
+        &mut self.repl
+    }
+
+    /// Get an inner repl session.
+    pub fn into_repl(self) -> ReplSession<S> {
+        self.repl
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<S> NetDevice<S>
+where
+    S: Expect,
+{
+    /// Answers username and password prompts until a device prompt is reached.
+    ///
+    /// See [`Login`] for details.
+    pub fn login<U, P>(&mut self, username: U, password: P) -> Result<LoginOutcome, Error>
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        Login::new(username, password)
+            .shell_prompt(Regex(self.dialect.prompt()))
+            .run(&mut self.repl)
+    }
+
+    /// Block until a device prompt is found,
+    /// e.g. when a connection doesn't need a login.
+    pub fn expect_prompt(&mut self) -> Result<String, Error> {
+        self.repl.expect_prompt()
+    }
+
+    /// Enters a privileged mode, sending a password if it's asked.
+    ///
+    /// Junos has no enable mode so nothing is done for [`Dialect::Juniper`].
+    pub fn enable(&mut self, password: &str) -> Result<(), Error> {
+        if self.dialect != Dialect::Cisco {
+            return Ok(());
+        }
+
+        self.repl.send_line("enable")?;
+
+        let (failure, password_prompt, prompt) = enable_needles(self.dialect);
+        let needles: [&dyn Needle; 3] = [&failure, &password_prompt, &prompt];
+        let mut passwords = 0;
+        loop {
+            let arms = ExpectArms::new(&needles);
+            let found = self.repl.expect(&arms)?;
+            match arms.matched() {
+                0 => return Err(enable_failed(&found)),
+                1 if passwords > 0 => return Err(enable_failed(&found)),
+                1 => {
+                    passwords += 1;
+                    let _guard = SecretGuard::new(password);
+                    self.repl.send_line(password)?;
+                }
+                _ => return check_privileged(&found),
+            }
+        }
+    }
+
+    /// Turns off a pagination, so a long output is printed at once.
+    pub fn disable_paging(&mut self) -> Result<(), Error> {
+        let _ = self.execute(self.dialect.disable_paging_command())?;
+        Ok(())
+    }
+
+    /// Execute a command and return its output.
+    ///
+    /// If the device rejects the command an error is returned.
+    pub fn execute<C>(&mut self, cmd: C) -> Result<Vec<u8>, Error>
+    where
+        C: AsRef<str>,
+    {
+        let out = self.repl.execute(cmd)?;
+        check_output(self.dialect, out)
+    }
+
+    /// Enters a config mode, executes the commands and applies them.
+    ///
+    /// If a command is rejected the config mode is left by [`Dialect::abort_commands`]
+    /// and an error is returned.
+    ///
+    /// Returns an output of all the commands.
+    pub fn configure<I, C>(&mut self, commands: I) -> Result<Vec<u8>, Error>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<str>,
+    {
+        let mut out = self.execute(self.dialect.configure_command())?;
+
+        for cmd in commands {
+            match self.execute(cmd) {
+                Ok(output) => out.extend(output),
+                Err(err) => {
+                    for cmd in self.dialect.abort_commands() {
+                        let _ = self.repl.execute(cmd)?;
+                    }
+
+                    return Err(err);
+                }
+            }
+        }
+
+        for cmd in self.dialect.commit_commands() {
+            out.extend(self.execute(cmd)?);
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S> NetDevice<S>
+where
+    S: AsyncExpect + Unpin,
+{
+    /// Answers username and password prompts until a device prompt is reached.
+    ///
+    /// See [`Login`] for details.
+    pub async fn login<U, P>(&mut self, username: U, password: P) -> Result<LoginOutcome, Error>
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        Login::new(username, password)
+            .shell_prompt(Regex(self.dialect.prompt()))
+            .run(&mut self.repl)
+            .await
+    }
+
+    /// Block until a device prompt is found,
+    /// e.g. when a connection doesn't need a login.
+    pub async fn expect_prompt(&mut self) -> Result<String, Error> {
+        self.repl.expect_prompt().await
+    }
+
+    /// Enters a privileged mode, sending a password if it's asked.
+    ///
+    /// Junos has no enable mode so nothing is done for [`Dialect::Juniper`].
+    pub async fn enable(&mut self, password: &str) -> Result<(), Error> {
+        if self.dialect != Dialect::Cisco {
+            return Ok(());
+        }
+
+        self.repl.send_line("enable").await?;
+
+        let (failure, password_prompt, prompt) = enable_needles(self.dialect);
+        let needles: [&dyn Needle; 3] = [&failure, &password_prompt, &prompt];
+        let mut passwords = 0;
+        loop {
+            let arms = ExpectArms::new(&needles);
+            let found = self.repl.expect(&arms).await?;
+            match arms.matched() {
+                0 => return Err(enable_failed(&found)),
+                1 if passwords > 0 => return Err(enable_failed(&found)),
+                1 => {
+                    passwords += 1;
+                    let _guard = SecretGuard::new(password);
+                    self.repl.send_line(password).await?;
+                }
+                _ => return check_privileged(&found),
+            }
+        }
+    }
+
+    /// Turns off a pagination, so a long output is printed at once.
+    pub async fn disable_paging(&mut self) -> Result<(), Error> {
+        let _ = self.execute(self.dialect.disable_paging_command()).await?;
+        Ok(())
+    }
+
+    /// Execute a command and return its output.
+    ///
+    /// If the device rejects the command an error is returned.
+    pub async fn execute(&mut self, cmd: impl AsRef<str>) -> Result<Vec<u8>, Error> {
+        let out = self.repl.execute(cmd).await?;
+        check_output(self.dialect, out)
+    }
+
+    /// Enters a config mode, executes the commands and applies them.
+    ///
+    /// If a command is rejected the config mode is left by [`Dialect::abort_commands`]
+    /// and an error is returned.
+    ///
+    /// Returns an output of all the commands.
+    pub async fn configure<I, C>(&mut self, commands: I) -> Result<Vec<u8>, Error>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<str>,
+    {
+        let mut out = self.execute(self.dialect.configure_command()).await?;
+
+        for cmd in commands {
+            match self.execute(cmd).await {
+                Ok(output) => out.extend(output),
+                Err(err) => {
+                    for cmd in self.dialect.abort_commands() {
+                        let _ = self.repl.execute(cmd).await?;
+                    }
+
+                    return Err(err);
+                }
+            }
+        }
+
+        for cmd in self.dialect.commit_commands() {
+            out.extend(self.execute(cmd).await?);
+        }
+
+        Ok(out)
+    }
+}
+
+/// A prompt of Cisco like CLIs, e.g. `router>`, `router#` and `router(config-if)#`.
+const CISCO_PROMPT: &str = r"(?m)^\r?[\w.\-/:@]+(?:\([\w.\-/]+\))?[>#] ?$";
+
+/// A prompt of Junos, e.g. `user@router>` and `user@router#`.
+const JUNIPER_PROMPT: &str = r"(?m)^\r?[\w.\-]+@[\w.\-]+[>#] ?$";
+
+/// Error messages of Cisco like CLIs, which start with `%`.
+const CISCO_ERROR: &str = r"(?m)^% ?(?:Invalid|Incomplete|Ambiguous|Unknown|Unrecognized|Bad|Error|Access denied)[^\r\n]*";
+
+/// Error messages of Junos.
+const JUNIPER_ERROR: &str = r"(?m)^(?:syntax error|unknown command|error:)[^\r\n]*";
+
+/// A rejected enable password message.
+const ENABLE_FAILURE: &str = r"(?m)^% ?(?:Access denied|Bad secrets|Authentication failed)";
+
+/// A password prompt of an enable command.
+const ENABLE_PASSWORD_PROMPT: &str = r"(?i)password:\s*$";
+
+/// Returns needles of an enable dialog: a failure, a password prompt and a device prompt.
+fn enable_needles(
+    dialect: Dialect,
+) -> (
+    Regex<&'static str>,
+    Regex<&'static str>,
+    Regex<&'static str>,
+) {
+    (
+        Regex(ENABLE_FAILURE),
+        Regex(ENABLE_PASSWORD_PROMPT),
+        Regex(dialect.prompt()),
+    )
+}
+
+/// Verifies that a prompt reached after an enable command is a privileged one.
+fn check_privileged(prompt: &Captures) -> Result<(), Error> {
+    let matched = prompt.get_str_lossy(0).unwrap_or_default();
+    if matched.trim_end().ends_with('#') {
+        Ok(())
+    } else {
+        Err(enable_failed(prompt))
+    }
+}
+
+fn enable_failed(found: &Captures) -> Error {
+    Error::Other {
+        message: "failed to enter an enable mode".to_string(),
+        err: found.as_str_lossy().trim().to_string(),
+    }
+}
+
+/// Returns an error if an output contains an error message of a dialect.
+fn check_output(dialect: Dialect, output: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let regex = regex::bytes::Regex::new(dialect.error()).expect("a valid regex");
+    if let Some(m) = regex.find(&output) {
+        return Err(Error::Other {
+            message: "a command was rejected by a device".to_string(),
+            err: String::from_utf8_lossy(m.as_bytes()).trim_end().to_string(),
+        });
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_prompt(dialect: Dialect, buf: &str) -> bool {
+        let regex = regex::bytes::Regex::new(dialect.prompt()).unwrap();
+        regex.is_match(buf.as_bytes())
+    }
+
+    #[test]
+    fn test_prompt() {
+        assert!(is_prompt(Dialect::Cisco, "\r\nrouter>"));
+        assert!(is_prompt(Dialect::Cisco, "\r\nrouter#"));
+        assert!(is_prompt(Dialect::Cisco, "\r\ncore-sw1.lab(config-if)#"));
+        assert!(!is_prompt(Dialect::Cisco, "\r\nrouter#show version\r\n"));
+        assert!(!is_prompt(Dialect::Cisco, "\r\nPassword: "));
+
+        assert!(is_prompt(Dialect::Juniper, "\r\nadmin@mx1> "));
+        assert!(is_prompt(Dialect::Juniper, "[edit]\r\nadmin@mx1# "));
+        assert!(!is_prompt(
+            Dialect::Juniper,
+            "\r\nadmin@mx1> show version\r\n"
+        ));
+    }
+
+    #[test]
+    fn test_check_output() {
+        let out =
+            b"router#shw\r\n        ^\r\n% Invalid input detected at '^' marker.\r\n".to_vec();
+        assert!(matches!(
+            check_output(Dialect::Cisco, out),
+            Err(Error::Other { err, .. }) if err == "% Invalid input detected at '^' marker."
+        ));
+
+        let out = b"admin@mx1> shw\r\n           ^\r\nunknown command.\r\n".to_vec();
+        assert!(check_output(Dialect::Juniper, out).is_err());
+
+        let out = b"router#show clock\r\n*10:00:00.000 UTC Mon Jan 1 2024\r\n".to_vec();
+        assert!(check_output(Dialect::Cisco, out).is_ok());
+    }
+}
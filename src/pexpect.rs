@@ -0,0 +1,319 @@
+//! This module contains a [`PexpectScript`], an interpreter of pexpect like interactions
+//! described in JSON or YAML.
+//!
+//! It's meant to help migrating existing `pexpect` suites to `expectrl` gradually,
+//! a dialog can be moved to a data file and run by both sides.
+//!
+//! A script consists of a command to spawn, a default timeout (in seconds) and a list of steps.
+//! Each step is one of the following commands, and it may have its own `timeout`.
+//!
+//! - `send`: sends a string as it is.
+//! - `sendline`: sends a string with a line ending.
+//! - `sendcontrol`: sends a control character, e.g. `c` for `Ctrl-C`.
+//! - `expect`: waits for a regex or for any regex from a list.
+//! - `expect_exact`: waits for a string or for any string from a list.
+//! - `expect_eof`: waits for EOF.
+//!
+//! ```yaml
+//! command: python3 -i
+//! timeout: 5
+//! steps:
+//!   - expect: '>>> '
+//!   - sendline: print(1 + 1)
+//!   - expect_exact: ['2', 'Error']
+//!     timeout: 1
+//!   - sendcontrol: d
+//!   - expect_eof: null
+//! ```
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use expectrl::pexpect::PexpectScript;
+//!
+//! let script = PexpectScript::from_yaml(&std::fs::read_to_string("script.yaml").unwrap()).unwrap();
+//! let mut session = script.spawn().unwrap();
+//! script.run(&mut session).unwrap();
+//! ```
+
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{session::OsSession, Any, ControlCode, Eof, Error, Regex, Session};
+
+#[cfg(not(feature = "async"))]
+use crate::Expect;
+
+#[cfg(feature = "async")]
+use crate::AsyncExpect;
+
+/// PexpectScript is a pexpect like interaction described as data.
+///
+/// See the [module level documentation](crate::pexpect) for a format.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PexpectScript {
+    /// A command which is run by [`PexpectScript::spawn`].
+    #[serde(default)]
+    pub command: Option<String>,
+    /// A default expect timeout in seconds.
+    ///
+    /// If it's not set a timeout of a session is used.
+    #[serde(default)]
+    pub timeout: Option<f64>,
+    /// Steps of the script.
+    pub steps: Vec<PexpectStep>,
+}
+
+/// A step of a [`PexpectScript`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PexpectStep {
+    /// An action of the step.
+    #[serde(flatten)]
+    pub command: PexpectCommand,
+    /// An expect timeout of the step in seconds.
+    ///
+    /// It overrides a timeout of the script.
+    #[serde(default)]
+    pub timeout: Option<f64>,
+}
+
+/// A command of a [`PexpectStep`].
+///
+/// The names follow the `pexpect.spawn` methods.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PexpectCommand {
+    /// Send a string.
+    Send(String),
+    /// Send a string followed by a line ending.
+    Sendline(String),
+    /// Send a control character, e.g. `c` stands for `Ctrl-C`.
+    Sendcontrol(char),
+    /// Wait for any of the regex patterns.
+    Expect(#[serde(deserialize_with = "one_or_many")] Vec<String>),
+    /// Wait for any of the strings.
+    ExpectExact(#[serde(deserialize_with = "one_or_many")] Vec<String>),
+    /// Wait for EOF.
+    ExpectEof,
+}
+
+impl PexpectScript {
+    /// Parses a script from JSON.
+    pub fn from_json(s: &str) -> Result<Self, Error> {
+        serde_json::from_str(s).map_err(|err| Error::Other {
+            message: "failed to parse a pexpect script as JSON".to_string(),
+            err: err.to_string(),
+        })
+    }
+
+    /// Parses a script from YAML.
+    pub fn from_yaml(s: &str) -> Result<Self, Error> {
+        serde_yaml::from_str(s).map_err(|err| Error::Other {
+            message: "failed to parse a pexpect script as YAML".to_string(),
+            err: err.to_string(),
+        })
+    }
+
+    /// Spawns a command of the script.
+    pub fn spawn(&self) -> Result<OsSession, Error> {
+        match &self.command {
+            Some(command) => crate::spawn(command),
+            None => Err(Error::CommandParsing),
+        }
+    }
+
+    /// Runs the script steps against a session.
+    ///
+    /// A session's expect timeout is restored when the script is finished.
+    /// If a step fails [`Error::Script`] is returned with an index of the step.
+    #[cfg(not(feature = "async"))]
+    pub fn run<P, S>(&self, session: &mut Session<P, S>) -> Result<(), Error>
+    where
+        Session<P, S>: Expect,
+    {
+        let origin_timeout = session.get_expect_timeout();
+        let timeout = self.timeout.map(secs).or(origin_timeout);
+
+        let mut result = Ok(());
+        for (i, step) in self.steps.iter().enumerate() {
+            session.set_expect_timeout(step.timeout.map(secs).or(timeout));
+
+            if let Err(err) = run_step(session, &step.command) {
+                result = Err(step_error(i, err));
+                break;
+            }
+        }
+
+        session.set_expect_timeout(origin_timeout);
+
+        result
+    }
+
+    /// Runs the script steps against a session.
+    ///
+    /// A session's expect timeout is restored when the script is finished.
+    /// If a step fails [`Error::Script`] is returned with an index of the step.
+    #[cfg(feature = "async")]
+    pub async fn run<P, S>(&self, session: &mut Session<P, S>) -> Result<(), Error>
+    where
+        Session<P, S>: AsyncExpect,
+    {
+        let origin_timeout = session.get_expect_timeout();
+        let timeout = self.timeout.map(secs).or(origin_timeout);
+
+        let mut result = Ok(());
+        for (i, step) in self.steps.iter().enumerate() {
+            session.set_expect_timeout(step.timeout.map(secs).or(timeout));
+
+            if let Err(err) = run_step(session, &step.command).await {
+                result = Err(step_error(i, err));
+                break;
+            }
+        }
+
+        session.set_expect_timeout(origin_timeout);
+
+        result
+    }
+}
+
+#[cfg(not(feature = "async"))]
+fn run_step<E>(session: &mut E, command: &PexpectCommand) -> Result<(), Error>
+where
+    E: Expect,
+{
+    match command {
+        PexpectCommand::Send(s) => session.send(s),
+        PexpectCommand::Sendline(s) => session.send_line(s),
+        PexpectCommand::Sendcontrol(c) => session.send(control_code(*c)?),
+        PexpectCommand::Expect(patterns) => session.expect(regexes(patterns)).map(|_| ()),
+        PexpectCommand::ExpectExact(patterns) => {
+            session.expect(Any(patterns.as_slice())).map(|_| ())
+        }
+        PexpectCommand::ExpectEof => session.expect(Eof).map(|_| ()),
+    }
+}
+
+#[cfg(feature = "async")]
+async fn run_step<E>(session: &mut E, command: &PexpectCommand) -> Result<(), Error>
+where
+    E: AsyncExpect,
+{
+    match command {
+        PexpectCommand::Send(s) => session.send(s).await,
+        PexpectCommand::Sendline(s) => session.send_line(s).await,
+        PexpectCommand::Sendcontrol(c) => session.send(control_code(*c)?).await,
+        PexpectCommand::Expect(patterns) => session.expect(regexes(patterns)).await.map(|_| ()),
+        PexpectCommand::ExpectExact(patterns) => {
+            session.expect(Any(patterns.as_slice())).await.map(|_| ())
+        }
+        PexpectCommand::ExpectEof => session.expect(Eof).await.map(|_| ()),
+    }
+}
+
+fn secs(timeout: f64) -> Duration {
+    Duration::from_secs_f64(timeout)
+}
+
+fn regexes(patterns: &[String]) -> Any<Vec<Regex<&str>>> {
+    Any(patterns.iter().map(|p| Regex(p.as_str())).collect())
+}
+
+fn control_code(c: char) -> Result<ControlCode, Error> {
+    ControlCode::try_from(c).map_err(|_| Error::Other {
+        message: "failed to send a control character".to_string(),
+        err: format!("{:?} is not a control character", c),
+    })
+}
+
+fn step_error(i: usize, err: Error) -> Error {
+    Error::Script {
+        step: vec![i],
+        err: Box::new(err),
+    }
+}
+
+/// Deserializes either a single pattern or a list of them.
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(pattern) => Ok(vec![pattern]),
+        OneOrMany::Many(patterns) => Ok(patterns),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_yaml() {
+        let script = PexpectScript::from_yaml(
+            "command: cat
+timeout: 1.5
+steps:
+  - sendline: Hello
+  - expect: 'H.*o'
+  - expect_exact: ['World', 'Hello']
+    timeout: 3
+  - sendcontrol: d
+  - expect_eof: null
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            script,
+            PexpectScript {
+                command: Some("cat".to_string()),
+                timeout: Some(1.5),
+                steps: vec![
+                    step(PexpectCommand::Sendline("Hello".to_string()), None),
+                    step(PexpectCommand::Expect(vec!["H.*o".to_string()]), None),
+                    step(
+                        PexpectCommand::ExpectExact(vec!["World".to_string(), "Hello".to_string()]),
+                        Some(3.0),
+                    ),
+                    step(PexpectCommand::Sendcontrol('d'), None),
+                    step(PexpectCommand::ExpectEof, None),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_json() {
+        let script = PexpectScript::from_json(
+            r#"{"steps": [{"send": "ls\n"}, {"expect": ["a", "b"], "timeout": 0.5}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(script.command, None);
+        assert_eq!(script.timeout, None);
+        assert_eq!(
+            script.steps,
+            vec![
+                step(PexpectCommand::Send("ls\n".to_string()), None),
+                step(
+                    PexpectCommand::Expect(vec!["a".to_string(), "b".to_string()]),
+                    Some(0.5)
+                ),
+            ]
+        );
+
+        assert!(PexpectScript::from_json(r#"{"steps": [{"interact": null}]}"#).is_err());
+    }
+
+    fn step(command: PexpectCommand, timeout: Option<f64>) -> PexpectStep {
+        PexpectStep { command, timeout }
+    }
+}
@@ -0,0 +1,323 @@
+//! This module contains a [`SessionPool`] which drives a group of sessions at once.
+
+use std::time::{Duration, Instant};
+
+use crate::{select::DEFAULT_TIMEOUT, Captures, Error, Needle};
+
+#[cfg(not(feature = "async"))]
+use crate::{select_timeout, Expect};
+
+#[cfg(feature = "async")]
+use crate::{select_timeout, AsyncExpect};
+
+/// SessionPool holds a group of sessions and runs the same operations on all of them.
+///
+/// It's handy for testing distributed systems,
+/// where the same command is issued to many nodes and all of them must reach a state.
+///
+/// A failure of a particular session is reported as [`Error::Pool`]
+/// which holds an index of the session.
+///
+/// It uses a 10 seconds timeout, see [`SessionPool::set_expect_timeout`] to set a different one.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, SessionPool};
+///
+/// let mut pool = SessionPool::new();
+/// pool.push(spawn("cat").unwrap());
+/// pool.push(spawn("cat").unwrap());
+///
+/// pool.broadcast_send_line("Hello World").unwrap();
+/// pool.expect_all("Hello World").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct SessionPool<E> {
+    sessions: Vec<E>,
+    expect_timeout: Option<Duration>,
+}
+
+impl<E> SessionPool<E> {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::from(Vec::new())
+    }
+
+    /// Adds a session to the pool and returns its index.
+    pub fn push(&mut self, session: E) -> usize {
+        self.sessions.push(session);
+        self.sessions.len() - 1
+    }
+
+    /// Returns an amount of sessions in the pool.
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Checks whether the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Get a reference to a session by its index.
+    pub fn get(&self, index: usize) -> Option<&E> {
+        self.sessions.get(index)
+    }
+
+    /// Get a mut reference to a session by its index.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut E> {
+        self.sessions.get_mut(index)
+    }
+
+    /// Returns an iterator over the sessions.
+    pub fn iter(&self) -> std::slice::Iter<'_, E> {
+        self.sessions.iter()
+    }
+
+    /// Returns an iterator over the sessions which allows to modify them.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, E> {
+        self.sessions.iter_mut()
+    }
+
+    /// Returns the sessions.
+    pub fn into_inner(self) -> Vec<E> {
+        self.sessions
+    }
+
+    /// Set a timeout of [`SessionPool::expect_all`] and [`SessionPool::expect_any`].
+    ///
+    /// If `timeout` is [`None`] they wait forever.
+    pub fn set_expect_timeout(&mut self, expect_timeout: Option<Duration>) {
+        self.expect_timeout = expect_timeout;
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<E> SessionPool<E>
+where
+    E: Expect,
+{
+    /// Sends a buffer to every session.
+    ///
+    /// It stops on a first failed session.
+    pub fn broadcast_send<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        for (index, session) in self.sessions.iter_mut().enumerate() {
+            session
+                .send(buf.as_ref())
+                .map_err(|err| pool_error(index, err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends a line to every session.
+    ///
+    /// It stops on a first failed session.
+    pub fn broadcast_send_line<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        for (index, session) in self.sessions.iter_mut().enumerate() {
+            session
+                .send_line(buf.as_ref())
+                .map_err(|err| pool_error(index, err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Waits until a pattern is matched in every session.
+    ///
+    /// It returns matches in order of the sessions.
+    /// If a session reaches EOF or the timeout is reached
+    /// the error of the first not matched session is returned.
+    pub fn expect_all<N>(&mut self, needle: N) -> Result<Vec<Captures>, Error>
+    where
+        N: Needle,
+    {
+        let start = Instant::now();
+        let mut found = (0..self.sessions.len()).map(|_| None).collect::<Vec<_>>();
+        loop {
+            for (index, session) in self.sessions.iter_mut().enumerate() {
+                if found[index].is_some() {
+                    continue;
+                }
+
+                match session.check(&needle) {
+                    Ok(captures) if !captures.is_empty() => found[index] = Some(captures),
+                    Ok(_) => (),
+                    Err(err) => return Err(pool_error(index, err)),
+                }
+            }
+
+            if let Some(result) = all_found(&mut found, start, self.expect_timeout, &needle) {
+                return result;
+            }
+        }
+    }
+
+    /// Waits until a pattern is matched in any of the sessions.
+    ///
+    /// It returns an index of a session where the match was found and the match itself.
+    ///
+    /// See [`select`].
+    ///
+    /// [`select`]: crate::select
+    pub fn expect_any<N>(&mut self, needle: N) -> Result<(usize, Captures), Error>
+    where
+        N: Needle,
+    {
+        select_timeout(&mut self.sessions, needle, self.expect_timeout)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<E> SessionPool<E>
+where
+    E: AsyncExpect,
+{
+    /// Sends a buffer to every session.
+    ///
+    /// It stops on a first failed session.
+    pub async fn broadcast_send<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        for (index, session) in self.sessions.iter_mut().enumerate() {
+            session
+                .send(buf.as_ref())
+                .await
+                .map_err(|err| pool_error(index, err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends a line to every session.
+    ///
+    /// It stops on a first failed session.
+    pub async fn broadcast_send_line<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        for (index, session) in self.sessions.iter_mut().enumerate() {
+            session
+                .send_line(buf.as_ref())
+                .await
+                .map_err(|err| pool_error(index, err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Waits until a pattern is matched in every session.
+    ///
+    /// It returns matches in order of the sessions.
+    /// If a session reaches EOF or the timeout is reached
+    /// the error of the first not matched session is returned.
+    pub async fn expect_all<N>(&mut self, needle: N) -> Result<Vec<Captures>, Error>
+    where
+        N: Needle,
+    {
+        let start = Instant::now();
+        let mut found = (0..self.sessions.len()).map(|_| None).collect::<Vec<_>>();
+        loop {
+            for (index, session) in self.sessions.iter_mut().enumerate() {
+                if found[index].is_some() {
+                    continue;
+                }
+
+                match session.check(&needle).await {
+                    Ok(captures) if !captures.is_empty() => found[index] = Some(captures),
+                    Ok(_) => (),
+                    Err(err) => return Err(pool_error(index, err)),
+                }
+            }
+
+            if let Some(result) = all_found(&mut found, start, self.expect_timeout, &needle) {
+                return result;
+            }
+
+            // check doesn't wait for data so we give other tasks a chance to run.
+            futures_lite::future::yield_now().await;
+        }
+    }
+
+    /// Waits until a pattern is matched in any of the sessions.
+    ///
+    /// It returns an index of a session where the match was found and the match itself.
+    ///
+    /// See [`select`].
+    ///
+    /// [`select`]: crate::select
+    pub async fn expect_any<N>(&mut self, needle: N) -> Result<(usize, Captures), Error>
+    where
+        N: Needle,
+    {
+        select_timeout(&mut self.sessions, needle, self.expect_timeout).await
+    }
+}
+
+impl<E> Default for SessionPool<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> From<Vec<E>> for SessionPool<E> {
+    fn from(sessions: Vec<E>) -> Self {
+        Self {
+            sessions,
+            expect_timeout: Some(DEFAULT_TIMEOUT),
+        }
+    }
+}
+
+impl<E> FromIterator<E> for SessionPool<E> {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<E> IntoIterator for SessionPool<E> {
+    type Item = E;
+    type IntoIter = std::vec::IntoIter<E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.sessions.into_iter()
+    }
+}
+
+/// Returns a result of [`SessionPool::expect_all`] if it's done.
+fn all_found(
+    found: &mut [Option<Captures>],
+    start: Instant,
+    timeout: Option<Duration>,
+    needle: &dyn Needle,
+) -> Option<Result<Vec<Captures>, Error>> {
+    if found.iter().all(Option::is_some) {
+        let captures = found.iter_mut().filter_map(Option::take).collect();
+        return Some(Ok(captures));
+    }
+
+    if let Some(timeout) = timeout {
+        if start.elapsed() > timeout {
+            let index = found.iter().position(Option::is_none).unwrap_or_default();
+            let err = Error::expect_timeout(Needle::describe(needle), &[]);
+            return Some(Err(pool_error(index, err)));
+        }
+    }
+
+    None
+}
+
+fn pool_error(index: usize, err: Error) -> Error {
+    Error::Pool {
+        index,
+        err: Box::new(err),
+    }
+}
@@ -0,0 +1,222 @@
+//! This module contains a [Daemon] which is a process spawned detached from a terminal.
+//!
+//! It's handy when a test needs a background server
+//! while a client of it is driven by a [`Session`].
+//!
+//! # Example
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use std::process::Command;
+//! use expectrl::{process::daemon::{Daemon, DaemonOptions}, spawn, Expect};
+//!
+//! let mut server = Command::new("python3");
+//! server.args(["-m", "http.server", "8000"]);
+//!
+//! let options = DaemonOptions::new().output("/tmp/server.log");
+//! let mut server = Daemon::spawn(server, &options).unwrap();
+//!
+//! let mut client = spawn("curl --retry 5 --retry-connrefused localhost:8000").unwrap();
+//! client.expect("Directory listing").unwrap();
+//!
+//! server.kill().unwrap();
+//! ```
+//!
+//! [`Session`]: crate::Session
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Result,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+};
+
+use crate::process::{ExitStatus, Wait};
+
+/// DaemonOptions is a set of settings of a [Daemon].
+///
+/// By default an output of a daemon is discarded.
+#[derive(Debug, Clone, Default)]
+pub struct DaemonOptions {
+    stdout: Option<PathBuf>,
+    stderr: Option<PathBuf>,
+    append: bool,
+}
+
+impl DaemonOptions {
+    /// Creates options which discard an output.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Redirects `stdout` to a file.
+    pub fn stdout<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.stdout = Some(path.into());
+        self
+    }
+
+    /// Redirects `stderr` to a file.
+    pub fn stderr<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.stderr = Some(path.into());
+        self
+    }
+
+    /// Redirects both `stdout` and `stderr` to the same file.
+    pub fn output<P>(self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        self.stdout(path.clone()).stderr(path)
+    }
+
+    /// Appends to the files instead of truncating them.
+    pub fn append(mut self, on: bool) -> Self {
+        self.append = on;
+        self
+    }
+
+    fn open(&self, path: &Path) -> Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(self.append)
+            .truncate(!self.append)
+            .open(path)
+    }
+}
+
+/// Daemon is a handle of a process which runs detached from a terminal.
+///
+/// The process has no pty, its `stdin` is closed
+/// and its output goes to files set in [`DaemonOptions`].
+/// On unix it's a leader of a new session, so it doesn't get signals of a current terminal.
+/// On windows it's started without a console in a new process group.
+///
+/// Unlike a [`Session`] the process is left running when the handle is dropped.
+///
+/// [`Session`]: crate::Session
+#[derive(Debug)]
+pub struct Daemon {
+    child: Child,
+    stdout: Option<PathBuf>,
+    stderr: Option<PathBuf>,
+}
+
+impl Daemon {
+    /// Spawns a command detached.
+    pub fn spawn(mut command: Command, options: &DaemonOptions) -> Result<Self> {
+        let stdout = match &options.stdout {
+            Some(path) => Some(options.open(path)?),
+            None => None,
+        };
+
+        let stderr = match (&options.stderr, &stdout) {
+            // the same file is shared so the writes don't overwrite each other.
+            (Some(path), Some(file)) if Some(path) == options.stdout.as_ref() => {
+                Some(file.try_clone()?)
+            }
+            (Some(path), _) => Some(options.open(path)?),
+            (None, _) => None,
+        };
+
+        let _ = command
+            .stdin(Stdio::null())
+            .stdout(stdout.map_or_else(Stdio::null, Stdio::from))
+            .stderr(stderr.map_or_else(Stdio::null, Stdio::from));
+
+        detach(&mut command);
+
+        let child = command.spawn()?;
+
+        Ok(Self {
+            child,
+            stdout: options.stdout.clone(),
+            stderr: options.stderr.clone(),
+        })
+    }
+
+    /// Returns an OS identifier of the process.
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Returns a path of a file where `stdout` is written to.
+    pub fn stdout_path(&self) -> Option<&Path> {
+        self.stdout.as_deref()
+    }
+
+    /// Returns a path of a file where `stderr` is written to.
+    pub fn stderr_path(&self) -> Option<&Path> {
+        self.stderr.as_deref()
+    }
+
+    /// Kills the process.
+    ///
+    /// It's not an error if the process has already exited.
+    pub fn kill(&mut self) -> Result<()> {
+        match self.child.kill() {
+            Ok(()) => Ok(()),
+            Err(_) if self.try_wait()?.is_some() => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Blocks until the process exits.
+    pub fn wait(&mut self) -> Result<ExitStatus> {
+        let status = self.child.wait()?;
+        Ok(to_exit_status(status))
+    }
+}
+
+impl Wait for Daemon {
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        let status = self.child.try_wait()?;
+        Ok(status.map(to_exit_status))
+    }
+}
+
+#[cfg(unix)]
+fn detach(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: setsid is async-signal-safe.
+    unsafe {
+        let _ = command.pre_exec(|| {
+            nix::unistd::setsid()
+                .map(|_| ())
+                .map_err(|err| std::io::Error::from_raw_os_error(err as i32))
+        });
+    }
+}
+
+#[cfg(windows)]
+fn detach(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+
+    let _ = command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(unix)]
+fn to_exit_status(status: std::process::ExitStatus) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+
+    match status.signal() {
+        Some(signal) => ExitStatus::Signaled(signal),
+        None => ExitStatus::Exited(status.code().unwrap_or_default()),
+    }
+}
+
+#[cfg(windows)]
+fn to_exit_status(status: std::process::ExitStatus) -> ExitStatus {
+    ExitStatus::Exited(status.code().unwrap_or_default())
+}
@@ -0,0 +1,605 @@
+//! This module contains a scripted in memory process,
+//! which can be used to test an expect logic without spawning real programs.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(not(feature = "async"))]
+//! # {
+//! use std::time::Duration;
+//! use expectrl::{process::{mock::MockProcess, Process}, Expect, Session};
+//!
+//! let mut process = MockProcess::new()
+//!     .output("login: ")
+//!     .on_input("root\n", "Password: ")
+//!     .on_input_after("secret\n", Duration::from_millis(50), "Welcome\n$ ")
+//!     .on_input("exit\n", "")
+//!     .exit(0);
+//! let stream = process.open_stream().unwrap();
+//!
+//! let mut session = Session::new(process, stream).unwrap();
+//! session.expect("login: ").unwrap();
+//! session.send_line("root").unwrap();
+//! session.expect("Password: ").unwrap();
+//! session.send_line("secret").unwrap();
+//! session.expect("$ ").unwrap();
+//! session.send_line("exit").unwrap();
+//! assert!(session.wait_for_exit(None).unwrap().success());
+//! # }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{self, Read, Result, Write},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    clock::{default_clock, Clock},
+    process::{
+        ExitStatus, Healthcheck, NonBlocking, Process, ResourceUsage, Termios, Usage, Wait,
+        WindowSize,
+    },
+};
+
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+
+/// MockProcess is a process which follows a script instead of running a program.
+///
+/// The script is a sequence of steps.
+/// Each step waits for an input (if it has one), then prints an output after a delay.
+/// The process exits once all steps are done if an exit code was set by [`MockProcess::exit`],
+/// otherwise it keeps running.
+///
+/// The process is its own [`Process::Command`],
+/// so it's started by [`Process::spawn_command`] or just by opening a stream.
+///
+/// Delays are measured by a [`Clock`], so a script can be run without real waiting
+/// with [`ManualClock`](crate::clock::ManualClock).
+#[derive(Debug, Clone)]
+pub struct MockProcess {
+    shared: Arc<Shared>,
+}
+
+/// MockStream is an IO stream of a [`MockProcess`].
+pub struct MockStream {
+    shared: Arc<Shared>,
+    blocking: bool,
+    #[cfg(feature = "async")]
+    delay: Option<crate::clock::Delay>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+#[derive(Debug)]
+struct Step {
+    input: Option<Vec<u8>>,
+    delay: Duration,
+    output: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct State {
+    steps: VecDeque<Step>,
+    exit_code: Option<i32>,
+    echo: bool,
+    window_size: (u16, u16),
+    clock: Arc<dyn Clock>,
+    started: bool,
+    /// An input which was not matched by a step yet.
+    input: Vec<u8>,
+    /// All bytes written to the process.
+    written: Vec<u8>,
+    /// An output which waits for its time.
+    scheduled: VecDeque<(Instant, Vec<u8>)>,
+    /// An output which can be read.
+    output: Vec<u8>,
+    #[cfg(feature = "async")]
+    read_waker: Option<Waker>,
+}
+
+impl MockProcess {
+    /// Creates a process with an empty script.
+    pub fn new() -> Self {
+        let state = State {
+            steps: VecDeque::new(),
+            exit_code: None,
+            echo: false,
+            window_size: (80, 24),
+            clock: default_clock(),
+            started: false,
+            input: Vec::new(),
+            written: Vec::new(),
+            scheduled: VecDeque::new(),
+            output: Vec::new(),
+            #[cfg(feature = "async")]
+            read_waker: None,
+        };
+
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(state),
+                changed: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Add a step which prints an output right after a previous one.
+    pub fn output<B>(self, output: B) -> Self
+    where
+        B: AsRef<[u8]>,
+    {
+        self.step(None, Duration::ZERO, output.as_ref())
+    }
+
+    /// Add a step which prints an output with a delay after a previous one.
+    pub fn output_after<B>(self, delay: Duration, output: B) -> Self
+    where
+        B: AsRef<[u8]>,
+    {
+        self.step(None, delay, output.as_ref())
+    }
+
+    /// Add a step which waits for an `input` and prints an `output` in response.
+    ///
+    /// An input is matched as a substring of everything written after a previous step,
+    /// bytes up to the end of the match are taken by the step.
+    pub fn on_input<I, B>(self, input: I, output: B) -> Self
+    where
+        I: AsRef<[u8]>,
+        B: AsRef<[u8]>,
+    {
+        self.step(Some(input.as_ref()), Duration::ZERO, output.as_ref())
+    }
+
+    /// Add a step which waits for an `input` and prints an `output` after a `delay`.
+    ///
+    /// See [`MockProcess::on_input`].
+    pub fn on_input_after<I, B>(self, input: I, delay: Duration, output: B) -> Self
+    where
+        I: AsRef<[u8]>,
+        B: AsRef<[u8]>,
+    {
+        self.step(Some(input.as_ref()), delay, output.as_ref())
+    }
+
+    /// Makes the process exit with a `code` once all steps are done.
+    pub fn exit(self, code: i32) -> Self {
+        self.lock().exit_code = Some(code);
+        self
+    }
+
+    /// Set whether an input is echoed back as a terminal does.
+    ///
+    /// It's off by default.
+    pub fn echo(self, on: bool) -> Self {
+        self.lock().echo = on;
+        self
+    }
+
+    /// Set a clock which measures delays of the script.
+    pub fn clock<C>(self, clock: C) -> Self
+    where
+        C: Clock + 'static,
+    {
+        self.lock().clock = Arc::new(clock);
+        self
+    }
+
+    /// Returns all bytes which were written to the process.
+    pub fn get_written(&self) -> Vec<u8> {
+        self.lock().written.clone()
+    }
+
+    /// Returns a last window size set on the process, (80, 24) by default.
+    pub fn get_window_size(&self) -> (u16, u16) {
+        self.lock().window_size
+    }
+
+    fn step(self, input: Option<&[u8]>, delay: Duration, output: &[u8]) -> Self {
+        self.lock().steps.push_back(Step {
+            input: input.map(<[u8]>::to_vec),
+            delay,
+            output: output.to_vec(),
+        });
+        self
+    }
+
+    fn start(&self) {
+        let mut state = self.lock();
+        if !state.started {
+            state.started = true;
+            state.advance();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.shared.lock()
+    }
+}
+
+impl Default for MockProcess {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Process for MockProcess {
+    type Command = MockProcess;
+    type Stream = MockStream;
+
+    fn spawn<S>(_: S) -> Result<Self>
+    where
+        S: AsRef<str>,
+    {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "a mock process can't run a command line, use spawn_command",
+        ))
+    }
+
+    fn spawn_command(command: Self::Command) -> Result<Self> {
+        command.start();
+        Ok(command)
+    }
+
+    fn open_stream(&mut self) -> Result<Self::Stream> {
+        self.start();
+
+        Ok(MockStream {
+            shared: self.shared.clone(),
+            blocking: true,
+            #[cfg(feature = "async")]
+            delay: None,
+        })
+    }
+}
+
+impl Healthcheck for MockProcess {
+    type Status = Option<ExitStatus>;
+
+    fn get_status(&self) -> Result<Self::Status> {
+        let mut state = self.lock();
+        state.advance();
+
+        Ok(state.exit_status())
+    }
+
+    fn is_alive(&self) -> Result<bool> {
+        Ok(self.get_status()?.is_none())
+    }
+}
+
+impl Wait for MockProcess {
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        self.get_status()
+    }
+}
+
+impl Termios for MockProcess {
+    fn is_echo(&self) -> Result<bool> {
+        Ok(self.lock().echo)
+    }
+
+    /// Set echo and return a previous value.
+    fn set_echo(&mut self, on: bool) -> Result<bool> {
+        Ok(std::mem::replace(&mut self.lock().echo, on))
+    }
+}
+
+impl WindowSize for MockProcess {
+    fn set_window_size(&mut self, cols: u16, rows: u16) -> Result<()> {
+        self.lock().window_size = (cols, rows);
+        Ok(())
+    }
+}
+
+impl Usage for MockProcess {
+    fn resource_usage(&self) -> Result<Option<ResourceUsage>> {
+        Ok(None)
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl State {
+    /// Runs steps whose input was received and releases an output whose time has come.
+    fn advance(&mut self) {
+        let now = self.clock.now();
+
+        while let Some(step) = self.steps.front() {
+            let end = match &step.input {
+                None => 0,
+                Some(input) => match find(&self.input, input) {
+                    Some(start) => start + input.len(),
+                    None => break,
+                },
+            };
+
+            let _ = self.input.drain(..end);
+            let step = self.steps.pop_front().expect("checked");
+
+            // An output of a step can't outrun an output of a previous one.
+            let after = self.scheduled.back().map_or(now, |(at, _)| now.max(*at));
+            self.scheduled.push_back((after + step.delay, step.output));
+        }
+
+        while matches!(self.scheduled.front(), Some((at, _)) if *at <= now) {
+            let (_, output) = self.scheduled.pop_front().expect("checked");
+            self.output.extend(output);
+        }
+    }
+
+    fn exit_status(&self) -> Option<ExitStatus> {
+        match self.exit_code {
+            Some(code) if self.steps.is_empty() && self.scheduled.is_empty() => {
+                Some(ExitStatus::Exited(code))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a time left till a next scheduled output.
+    fn next_output(&self) -> Option<Duration> {
+        self.scheduled
+            .front()
+            .map(|(at, _)| at.saturating_duration_since(self.clock.now()))
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> usize {
+        let n = buf.len().min(self.output.len());
+        buf[..n].copy_from_slice(&self.output[..n]);
+        let _ = self.output.drain(..n);
+        n
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if self.exit_status().is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "a mock process has exited",
+            ));
+        }
+
+        self.written.extend(buf);
+        self.input.extend(buf);
+        if self.echo {
+            self.output.extend(buf);
+        }
+
+        self.advance();
+
+        #[cfg(feature = "async")]
+        if let Some(waker) = self.read_waker.take() {
+            waker.wake();
+        }
+
+        Ok(buf.len())
+    }
+}
+
+impl MockStream {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.shared.lock()
+    }
+}
+
+impl fmt::Debug for MockStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockStream")
+            .field("shared", &self.shared)
+            .field("blocking", &self.blocking)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Read for MockStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let mut state = self.lock();
+        loop {
+            state.advance();
+
+            if !state.output.is_empty() {
+                return Ok(state.read(buf));
+            }
+
+            if state.exit_status().is_some() {
+                return Ok(0);
+            }
+
+            if !self.blocking {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    "a mock process has no output",
+                ));
+            }
+
+            state = match state.next_output() {
+                Some(delay) => {
+                    let clock = state.clock.clone();
+                    drop(state);
+                    clock.sleep(delay);
+                    self.lock()
+                }
+                None => self
+                    .shared
+                    .changed
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+}
+
+impl Write for MockStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.lock().write(buf)?;
+        self.shared.changed.notify_all();
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl NonBlocking for MockStream {
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        self.blocking = on;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncRead for MockStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        loop {
+            let delay = {
+                let mut state = self.lock();
+                state.advance();
+
+                if !state.output.is_empty() {
+                    return Poll::Ready(Ok(state.read(buf)));
+                }
+
+                if state.exit_status().is_some() {
+                    return Poll::Ready(Ok(0));
+                }
+
+                // An echo of an input may come before a scheduled output.
+                state.read_waker = Some(cx.waker().clone());
+
+                match state.next_output() {
+                    Some(delay) if self.delay.is_none() => Some(state.clock.delay(delay)),
+                    Some(_) => None,
+                    None => return Poll::Pending,
+                }
+            };
+
+            if delay.is_some() {
+                self.delay = delay;
+            }
+
+            let delay = self.delay.as_mut().expect("checked");
+            match delay.as_mut().poll(cx) {
+                Poll::Ready(()) => self.delay = None,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncWrite for MockStream {
+    fn poll_write(self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        Poll::Ready(self.lock().write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::ManualClock;
+
+    use super::*;
+
+    #[test]
+    fn test_mock_process() {
+        let clock = ManualClock::new();
+        let mut process = MockProcess::new()
+            .clock(clock.clone())
+            .output("> ")
+            .on_input_after("ping\n", Duration::from_secs(1), "pong\n")
+            .exit(2);
+        let mut stream = process.open_stream().unwrap();
+        stream.set_blocking(false).unwrap();
+
+        let mut buf = [0; 64];
+        assert_eq!(stream.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"> ");
+        assert_eq!(
+            stream.read(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        stream.write_all(b"pi").unwrap();
+        stream.write_all(b"ng\n").unwrap();
+        assert_eq!(process.try_wait().unwrap(), None);
+        assert_eq!(
+            stream.read(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(stream.read(&mut buf).unwrap(), 5);
+        assert_eq!(&buf[..5], b"pong\n");
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+        assert_eq!(process.try_wait().unwrap(), Some(ExitStatus::Exited(2)));
+        assert!(stream.write_all(b"ping\n").is_err());
+        assert_eq!(process.get_written(), b"ping\n");
+    }
+
+    #[test]
+    fn test_mock_process_blocking_read() {
+        let mut process = MockProcess::new()
+            .clock(ManualClock::new())
+            .output_after(Duration::from_secs(60), "done");
+        let mut stream = process.open_stream().unwrap();
+
+        // A manual clock is advanced by the sleep.
+        let mut buf = [0; 64];
+        assert_eq!(stream.read(&mut buf).unwrap(), 4);
+        assert_eq!(process.try_wait().unwrap(), None);
+    }
+
+    #[test]
+    fn test_mock_process_echo() {
+        let mut process = MockProcess::new().echo(true);
+        let mut stream = process.open_stream().unwrap();
+        stream.write_all(b"hello").unwrap();
+
+        let mut buf = [0; 64];
+        assert_eq!(stream.read(&mut buf).unwrap(), 5);
+        assert!(process.set_echo(false).unwrap());
+        assert!(!process.is_echo().unwrap());
+    }
+}
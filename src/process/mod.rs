@@ -1,7 +1,11 @@
 //! This module contains a platform independent abstraction over an os process.
 
-use std::io::Result;
+use std::{fmt, io::Result, time::Duration};
 
+pub mod daemon;
+pub mod mock;
+#[cfg(all(unix, not(feature = "async")))]
+pub mod pipe;
 #[cfg(unix)]
 pub mod unix;
 #[cfg(windows)]
@@ -72,6 +76,33 @@ where
 pub trait NonBlocking {
     /// Sets a [std::io::Read]er into a non/blocking mode.
     fn set_blocking(&mut self, on: bool) -> Result<()>;
+
+    /// Blocks until the [std::io::Read]er has something to read or the `timeout` passes.
+    /// [`None`] means to wait without a timeout.
+    ///
+    /// It returns `false` right away if the reader can't tell when data arrives,
+    /// then a caller falls back to polling it with pauses.
+    /// The default implementation waits on [`NonBlocking::readiness_fd`] if there's one.
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<bool> {
+        #[cfg(unix)]
+        if let Some(fd) = self.readiness_fd() {
+            unix::poll_readable(&[fd], timeout)?;
+            return Ok(true);
+        }
+
+        let _ = timeout;
+        Ok(false)
+    }
+
+    /// Returns a descriptor which becomes readable when the [std::io::Read]er has something to read.
+    ///
+    /// It lets a caller wait on the reader together with other descriptors.
+    /// The descriptor doesn't account data kept in a buffer of the reader,
+    /// so it must be waited on only after a read returned [`std::io::ErrorKind::WouldBlock`].
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        None
+    }
 }
 
 impl<T> NonBlocking for &mut T
@@ -81,6 +112,21 @@ where
     fn set_blocking(&mut self, on: bool) -> Result<()> {
         T::set_blocking(self, on)
     }
+
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<bool> {
+        T::wait_readable(self, timeout)
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        T::readiness_fd(self)
+    }
+}
+
+impl NonBlocking for std::net::TcpStream {
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        self.set_nonblocking(!on)
+    }
 }
 
 /// Terminal configuration trait, used for IO configuration.
@@ -104,6 +150,179 @@ where
     }
 }
 
+/// WindowSize represents a process which runs on a terminal of a given size.
+pub trait WindowSize {
+    /// Sets a terminal size of a process in columns and rows.
+    fn set_window_size(&mut self, cols: u16, rows: u16) -> Result<()>;
+}
+
+impl<T> WindowSize for &mut T
+where
+    T: WindowSize,
+{
+    fn set_window_size(&mut self, cols: u16, rows: u16) -> Result<()> {
+        T::set_window_size(self, cols, rows)
+    }
+}
+
+/// Wait represents a process which exit status can be checked.
+pub trait Wait {
+    /// Returns an exit status if a process has exited or [`None`] if it's still running.
+    ///
+    /// It doesn't block.
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>>;
+}
+
+impl<T> Wait for &mut T
+where
+    T: Wait,
+{
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        T::try_wait(self)
+    }
+}
+
+/// Usage represents a process which resource consumption can be measured.
+pub trait Usage {
+    /// Returns resources used by a process.
+    ///
+    /// On unix the usage is known only after a process has exited and was reaped,
+    /// e.g. by [`Wait::try_wait`], so [`None`] is returned while it's running.
+    /// On windows the usage so far is returned for a running process as well.
+    fn resource_usage(&self) -> Result<Option<ResourceUsage>>;
+}
+
+impl<T> Usage for &mut T
+where
+    T: Usage,
+{
+    fn resource_usage(&self) -> Result<Option<ResourceUsage>> {
+        T::resource_usage(self)
+    }
+}
+
+/// OnDrop represents a process which fate on drop of its handle can be configured.
+pub trait OnDrop {
+    /// Sets what is done with a running process when its handle is dropped.
+    fn set_drop_policy(&mut self, policy: DropPolicy);
+}
+
+impl<T> OnDrop for &mut T
+where
+    T: OnDrop,
+{
+    fn set_drop_policy(&mut self, policy: DropPolicy) {
+        T::set_drop_policy(self, policy)
+    }
+}
+
+/// DropPolicy defines what happens with a running process when its handle is dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// A platform default.
+    ///
+    /// On unix a process is asked to exit by a sequence of signals, ending with `SIGKILL`.
+    /// On windows a process is killed along with its job.
+    #[default]
+    Default,
+    /// A process is left running.
+    ///
+    /// On unix a handle is kept in a background thread which reaps the process once it exits,
+    /// so the process doesn't become a zombie.
+    /// Keep in mind that a process may still get `SIGHUP` once a current program exits,
+    /// as a pty is closed then.
+    ///
+    /// On windows a process is detached from its job,
+    /// though it may still exit once a pseudo console is closed.
+    Nothing,
+    /// A process is asked to exit, by `SIGTERM` on unix, but it's not waited for.
+    ///
+    /// On windows there's no such request so the process is killed.
+    Terminate,
+    /// A process is killed.
+    Kill,
+    /// A process is given a time to exit by itself, and it's killed afterwards.
+    ///
+    /// A drop blocks for the time at most.
+    Wait(Duration),
+}
+
+/// ResourceUsage is a CPU time and a memory used by a process.
+///
+/// On unix it includes resources of descendants which were waited for by the process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    user_time: Duration,
+    system_time: Duration,
+    max_rss: u64,
+}
+
+impl ResourceUsage {
+    pub(crate) fn new(user_time: Duration, system_time: Duration, max_rss: u64) -> Self {
+        Self {
+            user_time,
+            system_time,
+            max_rss,
+        }
+    }
+
+    /// Returns a CPU time spent in a user mode.
+    pub fn user_time(&self) -> Duration {
+        self.user_time
+    }
+
+    /// Returns a CPU time spent in a kernel mode.
+    pub fn system_time(&self) -> Duration {
+        self.system_time
+    }
+
+    /// Returns a total CPU time, in a user and a kernel modes.
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.system_time
+    }
+
+    /// Returns a maximum resident set size in bytes,
+    /// which is a peak working set size on windows.
+    pub fn max_rss(&self) -> u64 {
+        self.max_rss
+    }
+}
+
+/// ExitStatus is a platform independent status of an exited process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// A process exited with a code.
+    Exited(i32),
+    /// A process was terminated by a signal.
+    ///
+    /// It's only used on unix.
+    Signaled(i32),
+}
+
+impl ExitStatus {
+    /// Returns an exit code if a process exited by itself.
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            ExitStatus::Exited(code) => Some(*code),
+            ExitStatus::Signaled(_) => None,
+        }
+    }
+
+    /// Checks whether a process exited with 0 code.
+    pub fn success(&self) -> bool {
+        self.code() == Some(0)
+    }
+}
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitStatus::Exited(code) => write!(f, "exit code: {}", code),
+            ExitStatus::Signaled(signal) => write!(f, "signal: {}", signal),
+        }
+    }
+}
+
 #[cfg(feature = "async")]
 /// IntoAsyncStream interface turns a [Process::Stream] into an async version.
 /// To be used with `async`/`await`syntax
@@ -0,0 +1,205 @@
+//! This module contains a [PipeProcess] which talks to a child through pipes instead of a pty.
+//!
+//! Without a pty `stdout` and `stderr` are not merged,
+//! so an output of each of them can be expected and logged on its own.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::process::Command;
+//! use expectrl::{Expect, Session};
+//!
+//! let mut cmd = Command::new("sh");
+//! cmd.args(["-c", "echo out; echo err >&2"]);
+//!
+//! let mut p = Session::spawn_piped(cmd).unwrap();
+//! p.get_stderr_mut().enable_log(std::io::stderr());
+//!
+//! p.expect("out").unwrap();
+//! p.expect_stderr("err").unwrap();
+//! ```
+
+use std::{
+    io::{self, Read, Result, Write},
+    os::unix::{
+        io::{AsRawFd, RawFd},
+        process::ExitStatusExt,
+    },
+    process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use crate::{
+    process::{
+        unix::{make_non_blocking, tokenize_command},
+        ExitStatus, NonBlocking, Process, Wait,
+    },
+    stream::dynamic::DynStream,
+    Session,
+};
+
+/// A session which reads `stderr` of a [PipeProcess].
+pub type StderrSession = Session<(), DynStream>;
+
+/// PipeProcess is a process which `stdin`, `stdout` and `stderr` are pipes.
+///
+/// Some programs behave differently without a terminal,
+/// e.g. they don't print prompts or buffer their output,
+/// so it's meant for non interactive programs.
+#[derive(Debug)]
+pub struct PipeProcess {
+    child: Child,
+    stderr: StderrSession,
+}
+
+impl Process for PipeProcess {
+    type Command = Command;
+    type Stream = PipeStream;
+
+    fn spawn<S>(cmd: S) -> Result<Self>
+    where
+        S: AsRef<str>,
+    {
+        let args = tokenize_command(cmd.as_ref());
+        if args.is_empty() {
+            return Err(io::Error::other("failed to parse a command"));
+        }
+
+        let mut command = Command::new(&args[0]);
+        let _ = command.args(args.iter().skip(1));
+
+        Self::spawn_command(command)
+    }
+
+    fn spawn_command(mut command: Self::Command) -> Result<Self> {
+        let _ = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = command.spawn()?;
+
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let stderr = Session::new((), DynStream::new(StderrStream { stderr }))?;
+
+        Ok(Self { child, stderr })
+    }
+
+    fn open_stream(&mut self) -> Result<Self::Stream> {
+        match (self.child.stdin.take(), self.child.stdout.take()) {
+            (Some(stdin), Some(stdout)) => Ok(PipeStream { stdin, stdout }),
+            _ => Err(io::Error::other("a stream was already opened")),
+        }
+    }
+}
+
+impl PipeProcess {
+    /// Returns an OS identifier of the process.
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Kills the process.
+    pub fn kill(&mut self) -> Result<()> {
+        self.child.kill()
+    }
+
+    /// Returns a session which reads `stderr` of the process.
+    pub fn get_stderr(&self) -> &StderrSession {
+        &self.stderr
+    }
+
+    /// Returns a session which reads `stderr` of the process.
+    ///
+    /// It can be used to set a log of `stderr`, see [`Session::enable_log`].
+    pub fn get_stderr_mut(&mut self) -> &mut StderrSession {
+        &mut self.stderr
+    }
+}
+
+impl Wait for PipeProcess {
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        let status = self.child.try_wait()?;
+        Ok(status.map(|status| match status.signal() {
+            Some(signal) => ExitStatus::Signaled(signal),
+            None => ExitStatus::Exited(status.code().unwrap_or_default()),
+        }))
+    }
+}
+
+/// A IO stream of [PipeProcess], which writes to `stdin` and reads `stdout`.
+#[derive(Debug)]
+pub struct PipeStream {
+    stdin: ChildStdin,
+    stdout: ChildStdout,
+}
+
+impl Write for PipeStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.stdin.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stdin.flush()
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
+        self.stdin.write_vectored(bufs)
+    }
+}
+
+impl Read for PipeStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.stdout.read(buf)
+    }
+}
+
+impl NonBlocking for PipeStream {
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        make_non_blocking(self.stdout.as_raw_fd(), !on)
+    }
+
+    fn readiness_fd(&self) -> Option<RawFd> {
+        Some(self.stdout.as_raw_fd())
+    }
+}
+
+impl AsRawFd for PipeStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stdout.as_raw_fd()
+    }
+}
+
+/// A read only stream of `stderr`.
+#[derive(Debug)]
+struct StderrStream {
+    stderr: ChildStderr,
+}
+
+impl Read for StderrStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.stderr.read(buf)
+    }
+}
+
+impl Write for StderrStream {
+    fn write(&mut self, _: &[u8]) -> Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "stderr can't be written to",
+        ))
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl NonBlocking for StderrStream {
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        make_non_blocking(self.stderr.as_raw_fd(), !on)
+    }
+
+    fn readiness_fd(&self) -> Option<RawFd> {
+        Some(self.stderr.as_raw_fd())
+    }
+}
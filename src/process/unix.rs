@@ -2,16 +2,33 @@
 
 use std::{
     io::{self, ErrorKind, Read, Result, Write},
+    mem::ManuallyDrop,
     ops::{Deref, DerefMut},
-    os::unix::prelude::{AsRawFd, RawFd},
+    os::{
+        fd::{AsFd, BorrowedFd},
+        unix::{
+            prelude::{AsRawFd, RawFd},
+            process::CommandExt,
+        },
+    },
+    path::PathBuf,
     process::Command,
+    sync::Mutex,
+    time::{Duration, Instant},
 };
 
 use crate::{
     error::to_io_error,
-    process::{Healthcheck, NonBlocking, Process, Termios},
+    process::{
+        DropPolicy, ExitStatus, Healthcheck, NonBlocking, OnDrop, Process, ResourceUsage, Termios,
+        Usage, Wait, WindowSize,
+    },
 };
 
+use nix::{
+    sys::{signal, wait::waitpid},
+    unistd::Pid,
+};
 use ptyprocess::{errno::Errno, stream::Stream, PtyProcess};
 
 #[cfg(feature = "async")]
@@ -29,7 +46,13 @@ pub use ptyprocess::{Signal, WaitStatus};
 /// A Unix representation of a [Process] via [PtyProcess]
 #[derive(Debug)]
 pub struct UnixProcess {
-    proc: PtyProcess,
+    /// A handle of the process which is taken out on drop according to a drop policy.
+    proc: ManuallyDrop<PtyProcess>,
+    /// A status of an exited process and resources it used.
+    ///
+    /// A process can be reaped only once, so the status is kept for next checks.
+    exit_status: Mutex<Option<(WaitStatus, ResourceUsage)>>,
+    drop_policy: DropPolicy,
 }
 
 impl Process for UnixProcess {
@@ -54,7 +77,11 @@ impl Process for UnixProcess {
     fn spawn_command(command: Self::Command) -> Result<Self> {
         let proc = PtyProcess::spawn(command).map_err(to_io_error("Failed to spawn a command"))?;
 
-        Ok(Self { proc })
+        Ok(Self {
+            proc: ManuallyDrop::new(proc),
+            exit_status: Mutex::new(None),
+            drop_policy: DropPolicy::default(),
+        })
     }
 
     fn open_stream(&mut self) -> Result<Self::Stream> {
@@ -67,17 +94,309 @@ impl Process for UnixProcess {
     }
 }
 
+impl UnixProcess {
+    /// Spawns a command with a hook which is run in a child process right before `exec`.
+    ///
+    /// The hook is called after the pty was set as a controlling terminal of the child,
+    /// so it can be used to set resource limits, close file descriptors, install seccomp filters etc.
+    ///
+    /// An error returned by the hook aborts the `exec`.
+    ///
+    /// # Safety
+    ///
+    /// The hook is run after `fork`, so the same restrictions as for [`CommandExt::pre_exec`] are applied.
+    pub unsafe fn spawn_with_pre_exec<F>(mut command: Command, hook: F) -> Result<Self>
+    where
+        F: FnMut() -> Result<()> + Send + Sync + 'static,
+    {
+        let _ = command.pre_exec(hook);
+        Self::spawn_command(command)
+    }
+
+    /// Sends a signal to the whole process group of the child.
+    ///
+    /// A child is always spawned as a session leader which has the pty as its controlling terminal,
+    /// so it's the leader of a new process group as well.
+    /// Therefore the signal is delivered to all its subprocesses which weren't moved to another group.
+    pub fn signal_group(&mut self, signal: Signal) -> Result<()> {
+        signal::killpg(self.proc.pid(), signal).map_err(nix_error_to_io)
+    }
+
+    /// Kills the process and all its descendants.
+    ///
+    /// On Linux descendants are found by walking `/proc`,
+    /// so it covers processes which left the group (e.g. daemons).
+    /// On other platforms only the process group is killed.
+    pub fn kill_tree(&mut self) -> Result<()> {
+        let pid = self.proc.pid();
+
+        // stop the processes first so they won't fork while we collect them.
+        //
+        // Errors are collected instead of being returned right away,
+        // so no process which was stopped is left frozen.
+        let mut result = Ok(());
+        let mut tree = vec![pid];
+        loop {
+            for &pid in &tree {
+                let _ = signal::kill(pid, Signal::SIGSTOP);
+            }
+
+            let mut found = vec![pid];
+            match descendants(pid) {
+                Ok(pids) => found.extend(pids),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+
+            if found.iter().all(|pid| tree.contains(pid)) {
+                break;
+            }
+
+            // keep processes which left the tree, they were stopped already.
+            for pid in tree {
+                if !found.contains(&pid) {
+                    found.push(pid);
+                }
+            }
+
+            tree = found;
+        }
+
+        let _ = signal::killpg(pid, Signal::SIGKILL);
+        for &pid in &tree {
+            match signal::kill(pid, Signal::SIGKILL) {
+                Ok(()) | Err(Errno::ESRCH) => (),
+                Err(err) => {
+                    let _ = signal::kill(pid, Signal::SIGCONT);
+                    if result.is_ok() {
+                        result = Err(nix_error_to_io(err));
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Spawns a command isolated by a [`Sandbox`].
+    ///
+    /// The sandbox is entered in a child process right before `exec`,
+    /// after the pty was set as a controlling terminal,
+    /// so no wrapper program (like `unshare` or `chroot`) sits between the pty and the command.
+    pub fn spawn_sandboxed(mut command: Command, sandbox: &Sandbox) -> Result<Self> {
+        let hook = sandbox.hook();
+
+        // SAFETY: the hook only makes syscalls and writes files in `/proc`.
+        unsafe {
+            let _ = command.pre_exec(hook);
+        }
+
+        Self::spawn_command(command)
+    }
+}
+
+/// Sandbox is a set of isolation settings applied to a spawned process,
+/// see [`UnixProcess::spawn_sandboxed`].
+///
+/// A process is always spawned as a session leader (as by `setsid`),
+/// so it doesn't have to be requested.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::process::Command;
+/// use expectrl::process::unix::{Sandbox, UnixProcess};
+///
+/// let sandbox = Sandbox::new().chroot("/srv/rootfs").current_dir("/home");
+/// let process = UnixProcess::spawn_sandboxed(Command::new("sh"), &sandbox).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unshare: nix::sched::CloneFlags,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    map_root_user: bool,
+    root: Option<PathBuf>,
+    current_dir: Option<PathBuf>,
+}
+
+impl Sandbox {
+    /// Creates a sandbox which doesn't isolate anything.
+    pub fn new() -> Self {
+        Self {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            unshare: nix::sched::CloneFlags::empty(),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            map_root_user: false,
+            root: None,
+            current_dir: None,
+        }
+    }
+
+    /// Moves a process to new namespaces, as `unshare(2)` does.
+    ///
+    /// A new PID namespace is applied only to children of the process,
+    /// so the process itself keeps its PID.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn unshare(mut self, flags: nix::sched::CloneFlags) -> Self {
+        self.unshare = flags;
+        self
+    }
+
+    /// Maps a current user to `root` in a new user namespace, like `unshare --map-root-user`.
+    ///
+    /// It implies [`CloneFlags::CLONE_NEWUSER`],
+    /// and lets an unprivileged user create other namespaces and call `chroot`.
+    ///
+    /// [`CloneFlags::CLONE_NEWUSER`]: nix::sched::CloneFlags::CLONE_NEWUSER
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn map_root_user(mut self, on: bool) -> Self {
+        self.map_root_user = on;
+        self
+    }
+
+    /// Changes a root directory of a process.
+    ///
+    /// A working directory is set to the new root,
+    /// as [`Command::current_dir`] is applied before the root is changed.
+    /// Use [`Sandbox::current_dir`] to set a directory inside the root.
+    pub fn chroot<P>(mut self, root: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Set a working directory which is changed to after the sandbox is entered.
+    pub fn current_dir<P>(mut self, dir: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Returns a hook which enters the sandbox in a child process.
+    ///
+    /// Everything which needs an allocation is prepared here, before `fork`.
+    fn hook(&self) -> impl FnMut() -> Result<()> + Send + Sync + 'static {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let (unshare, id_maps) = {
+            use nix::{
+                sched::CloneFlags,
+                unistd::{getgid, getuid},
+            };
+
+            let mut flags = self.unshare;
+            let id_maps = if self.map_root_user {
+                flags |= CloneFlags::CLONE_NEWUSER;
+                Some((format!("0 {} 1", getuid()), format!("0 {} 1", getgid())))
+            } else {
+                None
+            };
+
+            (flags, id_maps)
+        };
+
+        let root = self.root.clone();
+        let current_dir = self.current_dir.clone();
+
+        move || {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            {
+                if !unshare.is_empty() {
+                    nix::sched::unshare(unshare).map_err(nix_error_to_io)?;
+                }
+
+                if let Some((uid_map, gid_map)) = &id_maps {
+                    // setgroups must be denied before an unprivileged process writes a gid map.
+                    std::fs::write("/proc/self/setgroups", "deny")?;
+                    std::fs::write("/proc/self/uid_map", uid_map)?;
+                    std::fs::write("/proc/self/gid_map", gid_map)?;
+                }
+            }
+
+            if let Some(root) = &root {
+                nix::unistd::chroot(root.as_path()).map_err(nix_error_to_io)?;
+                nix::unistd::chdir("/").map_err(nix_error_to_io)?;
+            }
+
+            if let Some(dir) = &current_dir {
+                nix::unistd::chdir(dir.as_path()).map_err(nix_error_to_io)?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Healthcheck for UnixProcess {
     type Status = WaitStatus;
 
     fn get_status(&self) -> Result<Self::Status> {
-        get_status(&self.proc)
+        let mut exit_status = self
+            .exit_status
+            .lock()
+            .map_err(|_| io_error("a status lock is poisoned"))?;
+        if let Some((status, _)) = *exit_status {
+            return Ok(status);
+        }
+
+        let (status, usage) = get_status(self.proc.pid())?;
+        if let Some(usage) = usage {
+            *exit_status = Some((status, usage));
+        }
+
+        Ok(status)
     }
 
     fn is_alive(&self) -> Result<bool> {
-        self.proc
-            .is_alive()
-            .map_err(to_io_error("failed to determine if process is alive"))
+        match self.get_status() {
+            Ok(WaitStatus::Exited(..) | WaitStatus::Signaled(..)) => Ok(false),
+            Ok(_) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(err) => Err(io::Error::new(
+                err.kind(),
+                format!("failed to determine if process is alive; {}", err),
+            )),
+        }
+    }
+}
+
+impl Wait for UnixProcess {
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        match self.get_status()? {
+            WaitStatus::Exited(_, code) => Ok(Some(ExitStatus::Exited(code))),
+            WaitStatus::Signaled(_, signal, _) => Ok(Some(ExitStatus::Signaled(signal as i32))),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Usage for UnixProcess {
+    fn resource_usage(&self) -> Result<Option<ResourceUsage>> {
+        match self.get_status() {
+            Ok(_) => (),
+            // the process was reaped by someone else, so the usage is unknown.
+            Err(err) if err.kind() == ErrorKind::WouldBlock => (),
+            Err(err) => return Err(err),
+        }
+
+        let exit_status = self
+            .exit_status
+            .lock()
+            .map_err(|_| io_error("a status lock is poisoned"))?;
+
+        Ok(exit_status.map(|(_, usage)| usage))
     }
 }
 
@@ -95,6 +414,14 @@ impl Termios for UnixProcess {
     }
 }
 
+impl WindowSize for UnixProcess {
+    fn set_window_size(&mut self, cols: u16, rows: u16) -> Result<()> {
+        self.proc.set_window_size(cols, rows)?;
+
+        Ok(())
+    }
+}
+
 impl Deref for UnixProcess {
     type Target = PtyProcess;
 
@@ -109,6 +436,87 @@ impl DerefMut for UnixProcess {
     }
 }
 
+impl OnDrop for UnixProcess {
+    fn set_drop_policy(&mut self, policy: DropPolicy) {
+        self.drop_policy = policy;
+    }
+}
+
+impl Drop for UnixProcess {
+    fn drop(&mut self) {
+        let reaped = self
+            .exit_status
+            .get_mut()
+            .is_ok_and(|status| status.is_some());
+
+        // Once a process is reaped the drop of PtyProcess only closes a pty,
+        // as it can't find the process anymore.
+        let in_background = match self.drop_policy {
+            _ if reaped => false,
+            DropPolicy::Default => false,
+            DropPolicy::Nothing => true,
+            DropPolicy::Terminate => {
+                let _ = signal::kill(self.proc.pid(), Signal::SIGTERM);
+                true
+            }
+            DropPolicy::Kill => {
+                self.kill_and_reap();
+                false
+            }
+            DropPolicy::Wait(timeout) => {
+                if !self.wait_exit(timeout) {
+                    self.kill_and_reap();
+                }
+
+                false
+            }
+        };
+
+        // SAFETY: The field is not used after it's taken.
+        let proc = unsafe { ManuallyDrop::take(&mut self.proc) };
+        if in_background {
+            reap_in_background(proc);
+        }
+    }
+}
+
+impl UnixProcess {
+    /// Waits for a process to exit, returning `false` if it didn't within a timeout.
+    fn wait_exit(&self, timeout: Duration) -> bool {
+        let start = Instant::now();
+        loop {
+            match self.is_alive() {
+                Ok(true) => {}
+                _ => return true,
+            }
+
+            if start.elapsed() >= timeout {
+                return false;
+            }
+
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn kill_and_reap(&self) {
+        if signal::kill(self.proc.pid(), Signal::SIGKILL).is_ok() {
+            let _ = waitpid(self.proc.pid(), None);
+        }
+    }
+}
+
+/// Keeps a process handle till the process exits,
+/// so the process is neither signaled by the drop of [PtyProcess] nor left a zombie.
+fn reap_in_background(proc: PtyProcess) {
+    // The handle is dropped as usual if a thread can't be spawned.
+    let _ = std::thread::Builder::new()
+        .name(String::from("expectrl-reaper"))
+        .spawn(move || {
+            let _ = waitpid(proc.pid(), None);
+            drop(proc);
+        });
+}
+
 /// A IO stream (write/read) of [UnixProcess].
 #[derive(Debug)]
 pub struct PtyStream {
@@ -149,6 +557,10 @@ impl NonBlocking for PtyStream {
             false => make_non_blocking(fd, true),
         }
     }
+
+    fn readiness_fd(&self) -> Option<RawFd> {
+        Some(self.handle.as_raw_fd())
+    }
 }
 
 impl AsRawFd for PtyStream {
@@ -157,6 +569,13 @@ impl AsRawFd for PtyStream {
     }
 }
 
+impl AsFd for PtyStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor is owned by the stream so it's valid while the stream is borrowed.
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
+
 #[cfg(feature = "async")]
 impl IntoAsyncStream for PtyStream {
     type AsyncStream = AsyncPtyStream;
@@ -167,6 +586,10 @@ impl IntoAsyncStream for PtyStream {
 }
 
 /// An async version of IO stream of [UnixProcess].
+///
+/// It's driven by `async-io` reactor and it's a default async stream.
+/// It can be used with any executor,
+/// as `async-io` runs its reactor in a separate thread in such case.
 #[cfg(feature = "async")]
 #[derive(Debug)]
 pub struct AsyncPtyStream {
@@ -175,10 +598,30 @@ pub struct AsyncPtyStream {
 
 #[cfg(feature = "async")]
 impl AsyncPtyStream {
-    fn new(stream: PtyStream) -> Result<Self> {
+    /// Creates a new async stream.
+    pub fn new(stream: PtyStream) -> Result<Self> {
         let stream = async_io::Async::new(stream)?;
         Ok(Self { stream })
     }
+
+    /// Returns a reference to an inner sync stream.
+    pub fn get_ref(&self) -> &PtyStream {
+        self.stream.get_ref()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsRawFd for AsyncPtyStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsFd for AsyncPtyStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.get_ref().as_fd()
+    }
 }
 
 #[cfg(feature = "async")]
@@ -211,6 +654,228 @@ impl AsyncRead for AsyncPtyStream {
     }
 }
 
+/// An async version of IO stream of [UnixProcess] for `async-std` runtime.
+///
+/// `async-std` drives its IO by `async-io` reactor,
+/// so the stream is registered in the same reactor as sockets and files of the runtime.
+#[cfg(feature = "runtime-async-std")]
+#[derive(Debug)]
+pub struct AsyncStdPtyStream {
+    stream: async_io::Async<PtyStream>,
+}
+
+#[cfg(feature = "runtime-async-std")]
+impl AsyncStdPtyStream {
+    /// Creates a new async stream.
+    pub fn new(stream: PtyStream) -> Result<Self> {
+        let stream = async_io::Async::new(stream)?;
+        Ok(Self { stream })
+    }
+
+    /// Returns a reference to an inner sync stream.
+    pub fn get_ref(&self) -> &PtyStream {
+        self.stream.get_ref()
+    }
+}
+
+#[cfg(feature = "runtime-async-std")]
+impl AsRawFd for AsyncStdPtyStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+#[cfg(feature = "runtime-async-std")]
+impl AsFd for AsyncStdPtyStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.get_ref().as_fd()
+    }
+}
+
+#[cfg(feature = "runtime-async-std")]
+impl AsyncWrite for AsyncStdPtyStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "runtime-async-std")]
+impl AsyncRead for AsyncStdPtyStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+/// An async version of IO stream of [UnixProcess] for `smol` runtime.
+///
+/// It's registered in a reactor via [`smol::Async`].
+#[cfg(feature = "runtime-smol")]
+#[derive(Debug)]
+pub struct SmolPtyStream {
+    stream: smol::Async<PtyStream>,
+}
+
+#[cfg(feature = "runtime-smol")]
+impl SmolPtyStream {
+    /// Creates a new async stream.
+    pub fn new(stream: PtyStream) -> Result<Self> {
+        let stream = smol::Async::new(stream)?;
+        Ok(Self { stream })
+    }
+
+    /// Returns a reference to an inner sync stream.
+    pub fn get_ref(&self) -> &PtyStream {
+        self.stream.get_ref()
+    }
+}
+
+#[cfg(feature = "runtime-smol")]
+impl AsRawFd for SmolPtyStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+#[cfg(feature = "runtime-smol")]
+impl AsFd for SmolPtyStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.get_ref().as_fd()
+    }
+}
+
+#[cfg(feature = "runtime-smol")]
+impl AsyncWrite for SmolPtyStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "runtime-smol")]
+impl AsyncRead for SmolPtyStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+/// An async version of IO stream of [UnixProcess] which is driven by `tokio` reactor.
+///
+/// It must be created within a context of a `tokio` runtime.
+#[cfg(feature = "runtime-tokio")]
+#[derive(Debug)]
+pub struct TokioPtyStream {
+    stream: tokio::io::unix::AsyncFd<PtyStream>,
+}
+
+#[cfg(feature = "runtime-tokio")]
+impl TokioPtyStream {
+    /// Creates a new async stream.
+    ///
+    /// # Panics
+    ///
+    /// It panics if it's called outside of a `tokio` runtime.
+    pub fn new(mut stream: PtyStream) -> Result<Self> {
+        stream.set_blocking(false)?;
+        let stream = tokio::io::unix::AsyncFd::new(stream)?;
+        Ok(Self { stream })
+    }
+
+    /// Returns a reference to an inner sync stream.
+    pub fn get_ref(&self) -> &PtyStream {
+        self.stream.get_ref()
+    }
+}
+
+#[cfg(feature = "runtime-tokio")]
+impl AsRawFd for TokioPtyStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+#[cfg(feature = "runtime-tokio")]
+impl AsFd for TokioPtyStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.get_ref().as_fd()
+    }
+}
+
+#[cfg(feature = "runtime-tokio")]
+impl AsyncWrite for TokioPtyStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        loop {
+            let mut guard = futures_lite::ready!(self.stream.poll_write_ready_mut(cx))?;
+            if let Ok(result) = guard.try_io(|stream| stream.get_mut().write(buf)) {
+                return Poll::Ready(result);
+            }
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            let mut guard = futures_lite::ready!(self.stream.poll_write_ready_mut(cx))?;
+            if let Ok(result) = guard.try_io(|stream| stream.get_mut().flush()) {
+                return Poll::Ready(result);
+            }
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+#[cfg(feature = "runtime-tokio")]
+impl AsyncRead for TokioPtyStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        loop {
+            let mut guard = futures_lite::ready!(self.stream.poll_read_ready_mut(cx))?;
+            if let Ok(result) = guard.try_io(|stream| stream.get_mut().read(buf)) {
+                return Poll::Ready(result);
+            }
+        }
+    }
+}
+
 #[cfg(feature = "polling")]
 impl polling::Source for PtyStream {
     fn raw(&self) -> RawFd {
@@ -218,6 +883,31 @@ impl polling::Source for PtyStream {
     }
 }
 
+#[cfg(feature = "mio")]
+impl mio::event::Source for PtyStream {
+    fn register(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> Result<()> {
+        mio::unix::SourceFd(&self.as_raw_fd()).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> Result<()> {
+        mio::unix::SourceFd(&self.as_raw_fd()).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio::Registry) -> Result<()> {
+        mio::unix::SourceFd(&self.as_raw_fd()).deregister(registry)
+    }
+}
+
 pub(crate) fn make_non_blocking(fd: RawFd, blocking: bool) -> Result<()> {
     use nix::fcntl::{fcntl, FcntlArg, OFlag};
 
@@ -228,6 +918,87 @@ pub(crate) fn make_non_blocking(fd: RawFd, blocking: bool) -> Result<()> {
     Ok(())
 }
 
+/// Blocks until one of the descriptors is readable or hung up, the `timeout` passes
+/// or a signal is caught.
+pub(crate) fn poll_readable(fds: &[RawFd], timeout: Option<Duration>) -> Result<()> {
+    let n = wait_fds(fds, timeout)?;
+    if n < 0 {
+        // A caller is woken up by a signal so it could handle it, e.g. `SIGWINCH`.
+        let err = io::Error::last_os_error();
+        if err.kind() != ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+fn wait_fds(fds: &[RawFd], timeout: Option<Duration>) -> Result<libc::c_int> {
+    // A timeout is rounded up so a caller doesn't wake up right before a deadline.
+    let timeout = timeout.map_or(-1, |timeout| {
+        let millis = timeout.as_micros().div_ceil(1000);
+        millis.min(libc::c_int::MAX as u128) as libc::c_int
+    });
+
+    let mut fds = fds
+        .iter()
+        .map(|&fd| libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect::<Vec<_>>();
+
+    // SAFETY: the array is valid for the call and its length is passed along.
+    let n = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) };
+
+    Ok(n)
+}
+
+// poll() doesn't support terminal devices on macOS, so select() is used instead.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn wait_fds(fds: &[RawFd], timeout: Option<Duration>) -> Result<libc::c_int> {
+    if fds
+        .iter()
+        .any(|&fd| fd < 0 || fd >= libc::FD_SETSIZE as RawFd)
+    {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "a descriptor doesn't fit into select()",
+        ));
+    }
+
+    // SAFETY: an all zero fd_set is a valid empty set, and descriptors were checked to fit it.
+    let mut set = unsafe { std::mem::zeroed::<libc::fd_set>() };
+    for &fd in fds {
+        unsafe { libc::FD_SET(fd, &mut set) };
+    }
+
+    let mut timeval = timeout.map(|timeout| libc::timeval {
+        tv_sec: timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+        tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+    });
+    let timeval = timeval.as_mut().map_or(std::ptr::null_mut(), |timeval| {
+        timeval as *mut libc::timeval
+    });
+
+    let nfds = fds.iter().max().map_or(0, |fd| fd + 1);
+
+    // SAFETY: the set and the timeval are valid for the call.
+    let n = unsafe {
+        libc::select(
+            nfds,
+            &mut set,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            timeval,
+        )
+    };
+
+    Ok(n)
+}
+
 fn nix_error_to_io(err: nix::Error) -> io::Error {
     io::Error::new(io::ErrorKind::Other, err)
 }
@@ -237,7 +1008,7 @@ fn nix_error_to_io(err: nix::Error) -> io::Error {
 ///
 /// It doesn't cover all edge cases.
 /// So it may not be compatible with real shell arguments parsing.
-fn tokenize_command(program: &str) -> Vec<String> {
+pub(crate) fn tokenize_command(program: &str) -> Vec<String> {
     let re = regex::Regex::new(r#""[^"]+"|'[^']+'|[^'" ]+"#).unwrap();
     let mut res = vec![];
     for cap in re.captures_iter(program) {
@@ -246,16 +1017,105 @@ fn tokenize_command(program: &str) -> Vec<String> {
     res
 }
 
-fn get_status(proc: &PtyProcess) -> std::prelude::v1::Result<WaitStatus, io::Error> {
-    match proc.status() {
-        Ok(status) => Ok(status),
-        Err(err) => match err {
-            Errno::ECHILD | Errno::ESRCH => Err(io::Error::new(ErrorKind::WouldBlock, err)),
+/// Checks a status of a process without blocking.
+///
+/// Resources used by the process are returned once it's reaped.
+fn get_status(pid: Pid) -> Result<(WaitStatus, Option<ResourceUsage>)> {
+    let mut status = 0;
+    // SAFETY: rusage is a plain C structure, so zeroes are a valid value.
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+
+    // wait4 is used instead of waitpid to get the resources of the reaped process.
+    let ret = unsafe { libc::wait4(pid.as_raw(), &mut status, libc::WNOHANG, &mut usage) };
+    match ret {
+        -1 => match Errno::last() {
+            err @ (Errno::ECHILD | Errno::ESRCH) => Err(io::Error::new(ErrorKind::WouldBlock, err)),
             err => Err(io::Error::new(ErrorKind::Other, err)),
         },
+        0 => Ok((WaitStatus::StillAlive, None)),
+        _ => {
+            let status = WaitStatus::from_raw(pid, status).map_err(nix_error_to_io)?;
+            let usage = matches!(status, WaitStatus::Exited(..) | WaitStatus::Signaled(..))
+                .then(|| rusage_to_usage(&usage));
+
+            Ok((status, usage))
+        }
     }
 }
 
+fn rusage_to_usage(usage: &libc::rusage) -> ResourceUsage {
+    let to_duration = |time: libc::timeval| {
+        Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+    };
+
+    // ru_maxrss is in kilobytes everywhere but on apple platforms.
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    let max_rss = usage.ru_maxrss as u64;
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    let max_rss = usage.ru_maxrss as u64 * 1024;
+
+    ResourceUsage::new(
+        to_duration(usage.ru_utime),
+        to_duration(usage.ru_stime),
+        max_rss,
+    )
+}
+
+/// Returns all descendants of a process.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn descendants(pid: Pid) -> Result<Vec<Pid>> {
+    let mut parents = Vec::new();
+    for entry in std::fs::read_dir("/proc")? {
+        let entry = entry?;
+        let child = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+            Some(child) => child,
+            None => continue,
+        };
+
+        // a process could exit in between.
+        let stat = match std::fs::read_to_string(entry.path().join("stat")) {
+            Ok(stat) => stat,
+            Err(_) => continue,
+        };
+
+        if let Some(parent) = parse_stat_ppid(&stat) {
+            parents.push((Pid::from_raw(child), Pid::from_raw(parent)));
+        }
+    }
+
+    let mut tree = vec![pid];
+    let mut i = 0;
+    while i < tree.len() {
+        let pid = tree[i];
+        tree.extend(
+            parents
+                .iter()
+                .filter(|(_, parent)| *parent == pid)
+                .map(|(child, _)| *child),
+        );
+        i += 1;
+    }
+
+    let _ = tree.remove(0);
+
+    Ok(tree)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn descendants(_: Pid) -> Result<Vec<Pid>> {
+    Ok(Vec::new())
+}
+
+/// Parses a parent pid from a `/proc/<pid>/stat` content.
+///
+/// A command name is wrapped in parentheses and may contain spaces and parentheses itself,
+/// so fields are taken after the last `)`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn parse_stat_ppid(stat: &str) -> Option<i32> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
 fn io_error(msg: &str) -> io::Error {
     io::Error::new(io::ErrorKind::Other, msg)
 }
@@ -264,6 +1124,26 @@ fn io_error(msg: &str) -> io::Error {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_poll_readable() {
+        let (read, write) = nix::unistd::pipe().unwrap();
+        let (other_read, other_write) = nix::unistd::pipe().unwrap();
+
+        let start = std::time::Instant::now();
+        poll_readable(&[read, other_read], Some(Duration::from_millis(50))).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        let _ = nix::unistd::write(other_write, b"a").unwrap();
+
+        let start = std::time::Instant::now();
+        poll_readable(&[read, other_read], None).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        for fd in [read, write, other_read, other_write] {
+            nix::unistd::close(fd).unwrap();
+        }
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_tokenize_command() {
@@ -279,4 +1159,16 @@ mod tests {
         let res = tokenize_command(r#"prog "my text""#);
         assert_eq!(vec!["prog", r#""my text""#], res);
     }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_parse_stat_ppid() {
+        let ppid = parse_stat_ppid("1234 (sleep) S 1200 1234 1200 34817 1234 4194304");
+        assert_eq!(ppid, Some(1200));
+
+        let ppid = parse_stat_ppid("1234 (my (weird) name) R 17 1234 1200 0 -1 4194304");
+        assert_eq!(ppid, Some(17));
+
+        assert_eq!(parse_stat_ppid("1234 sleep"), None);
+    }
 }
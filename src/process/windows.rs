@@ -4,6 +4,11 @@ use std::{
     io::{self, Read, Result, Write},
     ops::{Deref, DerefMut},
     process::Command,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+        Arc,
+    },
+    time::Duration,
 };
 
 use conpty::{
@@ -11,7 +16,30 @@ use conpty::{
     spawn, Process,
 };
 
-use super::{Healthcheck, NonBlocking, Process as ProcessTrait};
+use windows_sys::Win32::{
+    Foundation::{CloseHandle, FILETIME, HANDLE, WAIT_FAILED, WAIT_OBJECT_0},
+    System::{
+        JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JobObjectCpuRateControlInformation,
+            JobObjectExtendedLimitInformation, SetInformationJobObject, TerminateJobObject,
+            JOBOBJECTINFOCLASS, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
+            JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_CPU_RATE_CONTROL_ENABLE, JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+            JOB_OBJECT_LIMIT_JOB_MEMORY, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        },
+        ProcessStatus::{K32GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
+        Threading::{
+            GetExitCodeProcess, GetProcessTimes, OpenProcess, WaitForSingleObject,
+            PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_QUOTA, PROCESS_SYNCHRONIZE,
+            PROCESS_TERMINATE, PROCESS_VM_READ,
+        },
+    },
+};
+
+use super::{
+    DropPolicy, ExitStatus, Healthcheck, NonBlocking, OnDrop, Process as ProcessTrait,
+    ResourceUsage, Usage, Wait, WindowSize,
+};
 use crate::error::to_io_error;
 
 #[cfg(feature = "async")]
@@ -25,9 +53,18 @@ use std::{
 };
 
 /// A windows representation of a [Process] via [conpty::Process].
+///
+/// By default a process is spawned inside a Job Object,
+/// so its descendants are terminated together with it
+/// on [`WinProcess::exit`] or when the [`WinProcess`] is dropped.
+///
+/// See [`WinProcess::spawn_with_job`].
 #[derive(Debug)]
 pub struct WinProcess {
     proc: Process,
+    job: Option<Job>,
+    skip_preamble: bool,
+    drop_policy: DropPolicy,
 }
 
 impl ProcessTrait for WinProcess {
@@ -35,24 +72,300 @@ impl ProcessTrait for WinProcess {
     type Stream = ProcessStream;
 
     fn spawn<S: AsRef<str>>(cmd: S) -> Result<Self> {
-        spawn(cmd.as_ref())
-            .map_err(to_io_error(""))
-            .map(|proc| WinProcess { proc })
+        let proc = spawn(cmd.as_ref()).map_err(to_io_error(""))?;
+        // A job is a best effort here as it may be not allowed to nest jobs.
+        let job = Job::new(&JobOptions::default())
+            .and_then(|job| job.assign(&proc).map(|_| job))
+            .ok();
+
+        Ok(WinProcess {
+            proc,
+            job,
+            skip_preamble: false,
+            drop_policy: DropPolicy::default(),
+        })
     }
 
     fn spawn_command(command: Self::Command) -> Result<Self> {
-        conpty::Process::spawn(command)
-            .map_err(to_io_error(""))
-            .map(|proc| WinProcess { proc })
+        let proc = conpty::Process::spawn(command).map_err(to_io_error(""))?;
+        let job = Job::new(&JobOptions::default())
+            .and_then(|job| job.assign(&proc).map(|_| job))
+            .ok();
+
+        Ok(WinProcess {
+            proc,
+            job,
+            skip_preamble: false,
+            drop_policy: DropPolicy::default(),
+        })
     }
 
     fn open_stream(&mut self) -> Result<Self::Stream> {
         let input = self.proc.input().map_err(to_io_error(""))?;
         let output = self.proc.output().map_err(to_io_error(""))?;
+        let handle = ExitHandle::open(self.proc.pid())?;
+        let mut output = ProcessReader::new(output, Arc::new(handle)).map_err(to_io_error(""))?;
+        output.skip_preamble = self.skip_preamble;
         Ok(Self::Stream::new(output, input))
     }
 }
 
+impl WinProcess {
+    /// Spawns a command inside a Job Object configured by [`JobOptions`].
+    ///
+    /// If `job` is [`None`] a process is spawned without a Job Object.
+    ///
+    /// Unlike [`WinProcess::spawn_command`] it returns an error if the job can't be set up.
+    pub fn spawn_with_job(command: Command, job: Option<JobOptions>) -> Result<Self> {
+        Self::spawn_with_options(command, &SpawnOptions::new().job(job))
+    }
+
+    /// Spawns a command with a pseudo console and a Job Object configured by [`SpawnOptions`].
+    ///
+    /// Unlike [`WinProcess::spawn_command`] it returns an error if the job can't be set up.
+    pub fn spawn_with_options(command: Command, options: &SpawnOptions) -> Result<Self> {
+        let mut proc = conpty::Process::spawn(command).map_err(to_io_error(""))?;
+        if let Some((cols, rows)) = options.console_size {
+            let cols = i16::try_from(cols).unwrap_or(i16::MAX);
+            let rows = i16::try_from(rows).unwrap_or(i16::MAX);
+            proc.resize(cols, rows).map_err(to_io_error(""))?;
+        }
+
+        let job = match &options.job {
+            Some(options) => {
+                let job = Job::new(options)?;
+                job.assign(&proc)?;
+                Some(job)
+            }
+            None => None,
+        };
+
+        Ok(WinProcess {
+            proc,
+            job,
+            skip_preamble: options.skip_preamble,
+            drop_policy: DropPolicy::default(),
+        })
+    }
+
+    /// Terminates the process.
+    ///
+    /// If the process is run inside a Job Object all processes of the job are terminated.
+    pub fn exit(&mut self, code: u32) -> Result<()> {
+        match &self.job {
+            Some(job) => job.terminate(code),
+            None => self.proc.exit(code).map_err(to_io_error("")),
+        }
+    }
+
+    /// Kills the process and all its descendants.
+    ///
+    /// If the process is run inside a Job Object the job is terminated,
+    /// otherwise it's done by `taskkill /T`.
+    pub fn kill_tree(&mut self) -> Result<()> {
+        if let Some(job) = &self.job {
+            return job.terminate(1);
+        }
+
+        let status = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &self.proc.pid().to_string()])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()?;
+
+        if !status.success() && self.proc.is_alive() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "failed to kill a process tree",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// SpawnOptions is a configuration of a [`WinProcess`].
+///
+/// A pseudo console is 80 columns wide by default,
+/// so long lines are wrapped and may break expected strings.
+/// A bigger console can be requested by [`SpawnOptions::console_size`].
+///
+/// ConPTY starts an output with a sequence which clears the screen
+/// (e.g. `\x1b[?25l\x1b[2J\x1b[m\x1b[H`),
+/// which can be skipped by [`SpawnOptions::skip_preamble`]
+/// so an output is the same as on other platforms.
+///
+/// Creation flags such as `PSEUDOCONSOLE_INHERIT_CURSOR` are not exposed by `conpty`,
+/// so they can't be set for now.
+#[derive(Debug, Clone)]
+pub struct SpawnOptions {
+    console_size: Option<(u16, u16)>,
+    job: Option<JobOptions>,
+    skip_preamble: bool,
+}
+
+impl SpawnOptions {
+    /// Creates a default options,
+    /// a process gets a default console and it's spawned inside a Job Object with no limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a size of a pseudo console in columns and rows.
+    ///
+    /// It's applied right after the console is created.
+    pub fn console_size(mut self, cols: u16, rows: u16) -> Self {
+        self.console_size = Some((cols, rows));
+        self
+    }
+
+    /// Skip control sequences which ConPTY writes before an actual output of a process.
+    ///
+    /// Only sequences which clear the screen, move or hide a cursor and set a title are skipped,
+    /// and only at the very beginning of the output.
+    pub fn skip_preamble(mut self, on: bool) -> Self {
+        self.skip_preamble = on;
+        self
+    }
+
+    /// Sets options of a Job Object a process is spawned in.
+    ///
+    /// If `job` is [`None`] a process is spawned without a Job Object.
+    pub fn job(mut self, job: Option<JobOptions>) -> Self {
+        self.job = job;
+        self
+    }
+}
+
+impl Default for SpawnOptions {
+    fn default() -> Self {
+        Self {
+            console_size: None,
+            job: Some(JobOptions::default()),
+            skip_preamble: false,
+        }
+    }
+}
+
+/// JobOptions is a configuration of a Job Object a [`WinProcess`] is spawned in.
+///
+/// All processes of the job are terminated once the job is closed.
+#[derive(Debug, Default, Clone)]
+pub struct JobOptions {
+    memory_limit: Option<usize>,
+    cpu_rate: Option<u32>,
+}
+
+impl JobOptions {
+    /// Creates a default options with no limits set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a limit of committed memory (in bytes) for all processes of the job.
+    pub fn memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    /// Sets a hard cap of CPU usage in percents (1-100) for all processes of the job.
+    pub fn cpu_rate(mut self, percent: u32) -> Self {
+        self.cpu_rate = Some(percent.clamp(1, 100));
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Job {
+    handle: HANDLE,
+}
+
+impl Job {
+    fn new(options: &JobOptions) -> Result<Self> {
+        let handle = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+        if handle == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let job = Self { handle };
+
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+        info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        if let Some(limit) = options.memory_limit {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+            info.JobMemoryLimit = limit;
+        }
+
+        job.set_information(JobObjectExtendedLimitInformation, &info)?;
+
+        if let Some(percent) = options.cpu_rate {
+            let info = JOBOBJECT_CPU_RATE_CONTROL_INFORMATION {
+                ControlFlags: JOB_OBJECT_CPU_RATE_CONTROL_ENABLE
+                    | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+                Anonymous: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0 {
+                    // a rate is set in 1/100 of a percent.
+                    CpuRate: percent * 100,
+                },
+            };
+
+            job.set_information(JobObjectCpuRateControlInformation, &info)?;
+        }
+
+        Ok(job)
+    }
+
+    fn set_information<T>(&self, class: JOBOBJECTINFOCLASS, info: &T) -> Result<()> {
+        let info: *const T = info;
+        let ok = unsafe {
+            SetInformationJobObject(
+                self.handle,
+                class,
+                info.cast(),
+                std::mem::size_of::<T>() as u32,
+            )
+        };
+
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    fn assign(&self, proc: &Process) -> Result<()> {
+        let handle = unsafe { OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, proc.pid()) };
+        if handle == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let ok = unsafe { AssignProcessToJobObject(self.handle, handle) };
+        let err = io::Error::last_os_error();
+        let _ = unsafe { CloseHandle(handle) };
+
+        if ok == 0 {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    fn terminate(&self, code: u32) -> Result<()> {
+        let ok = unsafe { TerminateJobObject(self.handle, code) };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        // All processes of the job are killed on close because of JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE.
+        let _ = unsafe { CloseHandle(self.handle) };
+    }
+}
+
 impl Healthcheck for WinProcess {
     // todo: We could implement it by using WaitForObject and return -> u32 code
     type Status = ();
@@ -66,6 +379,63 @@ impl Healthcheck for WinProcess {
     }
 }
 
+impl Wait for WinProcess {
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        let handle = ExitHandle::open(self.proc.pid())?;
+        if !handle.wait(Duration::ZERO)? {
+            return Ok(None);
+        }
+
+        let code = handle.exit_code()?;
+        // NTSTATUS codes such as 0xC000013A are kept as they are.
+        Ok(Some(ExitStatus::Exited(code as i32)))
+    }
+}
+
+impl Usage for WinProcess {
+    fn resource_usage(&self) -> Result<Option<ResourceUsage>> {
+        let handle = ExitHandle::open(self.proc.pid())?;
+        handle.usage().map(Some)
+    }
+}
+
+impl WindowSize for WinProcess {
+    fn set_window_size(&mut self, cols: u16, rows: u16) -> Result<()> {
+        let cols = i16::try_from(cols).unwrap_or(i16::MAX);
+        let rows = i16::try_from(rows).unwrap_or(i16::MAX);
+        self.proc.resize(cols, rows).map_err(to_io_error(""))
+    }
+}
+
+impl OnDrop for WinProcess {
+    fn set_drop_policy(&mut self, policy: DropPolicy) {
+        self.drop_policy = policy;
+    }
+}
+
+impl Drop for WinProcess {
+    fn drop(&mut self) {
+        match self.drop_policy {
+            DropPolicy::Default => {}
+            DropPolicy::Nothing => {
+                // A job kills its processes on close, so its handle is leaked.
+                std::mem::forget(self.job.take());
+            }
+            DropPolicy::Terminate | DropPolicy::Kill => {
+                let _ = self.exit(1);
+            }
+            DropPolicy::Wait(timeout) => {
+                let exited = ExitHandle::open(self.proc.pid())
+                    .and_then(|handle| handle.wait(timeout))
+                    .unwrap_or(false);
+                if !exited {
+                    let _ = self.exit(1);
+                }
+            }
+        }
+    }
+}
+
 impl Deref for WinProcess {
     type Target = Process;
 
@@ -84,11 +454,11 @@ impl DerefMut for WinProcess {
 #[derive(Debug)]
 pub struct ProcessStream {
     input: PipeWriter,
-    output: PipeReader,
+    output: ProcessReader,
 }
 
 impl ProcessStream {
-    fn new(output: PipeReader, input: PipeWriter) -> Self {
+    fn new(output: ProcessReader, input: PipeWriter) -> Self {
         Self { input, output }
     }
 
@@ -123,9 +493,363 @@ impl Read for ProcessStream {
 
 impl NonBlocking for ProcessStream {
     fn set_blocking(&mut self, on: bool) -> Result<()> {
-        self.output.blocking(on);
+        self.output.blocking = on;
+        Ok(())
+    }
+
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<bool> {
+        self.output.wait_readable(timeout)?;
+        Ok(true)
+    }
+}
+
+/// ProcessReader reads an output of a process and reports EOF once the process is exited.
+///
+/// ConPTY creates anonymous pipes which support neither overlapped IO nor a wait for data,
+/// so the pipe is read by a background thread with blocking reads
+/// and chunks are delivered through a channel.
+/// It lets a read wait for data with a real timeout instead of checking the pipe periodically.
+///
+/// ConPTY doesn't close an output pipe when a child process exits
+/// (<https://github.com/microsoft/terminal/issues/4564>),
+/// so a read would never return 0.
+/// Instead another thread waits for the process exit,
+/// and once all delivered data is read EOF is reported.
+///
+/// It also can skip an initialization sequence of ConPTY, see [`SpawnOptions::skip_preamble`].
+#[derive(Debug)]
+struct ProcessReader {
+    pipe: PipeReader,
+    events: Receiver<Event>,
+    process: Arc<ExitHandle>,
+    buffer: Vec<u8>,
+    exited: bool,
+    eof: bool,
+    blocking: bool,
+    skip_preamble: bool,
+    pending: Vec<u8>,
+}
+
+/// An event sent by threads which watch a process.
+#[derive(Debug)]
+enum Event {
+    /// A chunk of an output, an empty one means the pipe is closed.
+    Output(Result<Vec<u8>>),
+    Exit,
+}
+
+impl ProcessReader {
+    /// A time given to the reader thread to deliver an output written right before a process exit.
+    const EXIT_DRAIN_TIMEOUT: Duration = Duration::from_millis(50);
+
+    fn new(
+        pipe: PipeReader,
+        process: Arc<ExitHandle>,
+    ) -> std::result::Result<Self, conpty::error::Error> {
+        let (sender, events) = mpsc::channel();
+        spawn_pipe_reader(pipe.try_clone()?, sender.clone());
+        spawn_exit_watcher(process.clone(), sender);
+
+        Ok(Self {
+            pipe,
+            events,
+            process,
+            buffer: Vec::new(),
+            exited: false,
+            eof: false,
+            blocking: true,
+            skip_preamble: false,
+            pending: Vec::new(),
+        })
+    }
+
+    fn try_clone(&self) -> std::result::Result<Self, conpty::error::Error> {
+        let mut reader = Self::new(self.pipe.try_clone()?, self.process.clone())?;
+        reader.blocking = self.blocking;
+        reader.skip_preamble = self.skip_preamble;
+        Ok(reader)
+    }
+
+    fn read_pending(&mut self, buf: &mut [u8]) -> usize {
+        let n = std::cmp::min(buf.len(), self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        let _ = self.pending.drain(..n);
+        n
+    }
+
+    fn read_output(&mut self, buf: &mut [u8]) -> Result<usize> {
+        loop {
+            if !self.buffer.is_empty() {
+                let n = std::cmp::min(buf.len(), self.buffer.len());
+                buf[..n].copy_from_slice(&self.buffer[..n]);
+                let _ = self.buffer.drain(..n);
+                return Ok(n);
+            }
+
+            if self.eof {
+                return Ok(0);
+            }
+
+            let timeout = match (self.exited, self.blocking) {
+                // The process might have written something right before its exit,
+                // so the output is drained before EOF is reported.
+                (true, _) => Some(Self::EXIT_DRAIN_TIMEOUT),
+                (false, true) => None,
+                (false, false) => Some(Duration::ZERO),
+            };
+
+            if !self.poll_event(timeout)? {
+                if self.exited {
+                    self.eof = true;
+                    continue;
+                }
+
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, ""));
+            }
+        }
+    }
+
+    /// Blocks until there's something to read or the `timeout` passes.
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<()> {
+        let ready = !self.buffer.is_empty() || !self.pending.is_empty();
+        if ready || self.exited || self.eof {
+            return Ok(());
+        }
+
+        let _ = self.poll_event(timeout)?;
         Ok(())
     }
+
+    /// Receives an event waiting no longer than the `timeout`.
+    ///
+    /// Returns false if nothing was received.
+    fn poll_event(&mut self, timeout: Option<Duration>) -> Result<bool> {
+        let event = match timeout {
+            Some(timeout) if timeout.is_zero() => match self.events.try_recv() {
+                Ok(event) => Some(event),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => Some(Event::Output(Ok(Vec::new()))),
+            },
+            Some(timeout) => match self.events.recv_timeout(timeout) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => Some(Event::Output(Ok(Vec::new()))),
+            },
+            None => Some(
+                self.events
+                    .recv()
+                    .unwrap_or_else(|_| Event::Output(Ok(Vec::new()))),
+            ),
+        };
+
+        match event {
+            Some(Event::Output(Ok(chunk))) if chunk.is_empty() => self.eof = true,
+            Some(Event::Output(Ok(chunk))) => self.buffer.extend(chunk),
+            Some(Event::Output(Err(err))) => return Err(err),
+            Some(Event::Exit) => self.exited = true,
+            None => return Ok(false),
+        }
+
+        Ok(true)
+    }
+}
+
+/// Reads the pipe on a background thread until it's closed or the receiver is dropped.
+///
+/// Note that ConPTY keeps the pipe open till a pseudo console is closed.
+fn spawn_pipe_reader(mut pipe: PipeReader, events: Sender<Event>) {
+    pipe.blocking(true);
+
+    let _ = std::thread::spawn(move || {
+        let mut buf = [0; 4096];
+        loop {
+            let (event, last) = match pipe.read(&mut buf) {
+                Ok(n) => (Event::Output(Ok(buf[..n].to_vec())), n == 0),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => (Event::Output(Err(err)), true),
+            };
+
+            if events.send(event).is_err() || last {
+                return;
+            }
+        }
+    });
+}
+
+/// Waits for the process exit on a background thread.
+fn spawn_exit_watcher(process: Arc<ExitHandle>, events: Sender<Event>) {
+    let _ = std::thread::spawn(move || {
+        let event = match process.wait(Duration::MAX) {
+            Ok(_) => Event::Exit,
+            Err(err) => Event::Output(Err(err)),
+        };
+
+        let _ = events.send(event);
+    });
+}
+
+impl Read for ProcessReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if !self.pending.is_empty() && !self.skip_preamble {
+            return Ok(self.read_pending(buf));
+        }
+
+        if !self.skip_preamble {
+            return self.read_output(buf);
+        }
+
+        loop {
+            let n = self.read_output(buf)?;
+            if n == 0 {
+                // Whatever was read is a preamble.
+                self.skip_preamble = false;
+                self.pending.clear();
+                return Ok(0);
+            }
+
+            self.pending.extend_from_slice(&buf[..n]);
+            if let Some(len) = preamble_len(&self.pending) {
+                self.skip_preamble = false;
+                let _ = self.pending.drain(..len);
+                return Ok(self.read_pending(buf));
+            }
+        }
+    }
+}
+
+/// Returns a length of a ConPTY initialization sequence at the beginning of `buf`.
+///
+/// [`None`] is returned when the whole `buf` may be a part of the sequence.
+fn preamble_len(buf: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    while i < buf.len() {
+        match escape_len(&buf[i..]) {
+            Some(len) if is_preamble_sequence(&buf[i..i + len]) => i += len,
+            Some(_) => return Some(i),
+            None if buf[i] == 0x1b => return None,
+            None => return Some(i),
+        }
+    }
+
+    None
+}
+
+/// Returns a length of a CSI or OSC escape sequence at the beginning of `buf`.
+fn escape_len(buf: &[u8]) -> Option<usize> {
+    match buf {
+        [0x1b, b'[', rest @ ..] => {
+            let end = rest.iter().position(|b| (0x40..=0x7e).contains(b))?;
+            Some(end + 3)
+        }
+        [0x1b, b']', rest @ ..] => {
+            let end = rest.iter().position(|&b| b == 0x07)?;
+            Some(end + 3)
+        }
+        _ => None,
+    }
+}
+
+fn is_preamble_sequence(seq: &[u8]) -> bool {
+    match seq {
+        // a title
+        [0x1b, b']', ..] => true,
+        [0x1b, b'[', params @ .., last] => match last {
+            b'J' => params.is_empty() || params == b"2",
+            b'm' => params.is_empty() || params == b"0",
+            b'H' => params.is_empty() || params == b"1;1",
+            b'K' => params.is_empty(),
+            b'h' | b'l' => params == b"?25",
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// A process handle which is used only to wait for the process exit and get its code.
+#[derive(Debug)]
+struct ExitHandle {
+    handle: HANDLE,
+}
+
+impl ExitHandle {
+    fn open(pid: u32) -> Result<Self> {
+        // PROCESS_VM_READ is required by K32GetProcessMemoryInfo.
+        let access = PROCESS_SYNCHRONIZE | PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_VM_READ;
+        let handle = unsafe { OpenProcess(access, 0, pid) };
+        if handle == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { handle })
+    }
+
+    /// Waits for the process exit and returns true if it's exited.
+    fn wait(&self, timeout: Duration) -> Result<bool> {
+        let timeout = u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX);
+        match unsafe { WaitForSingleObject(self.handle, timeout) } {
+            WAIT_OBJECT_0 => Ok(true),
+            WAIT_FAILED => Err(io::Error::last_os_error()),
+            _ => Ok(false),
+        }
+    }
+
+    fn exit_code(&self) -> Result<u32> {
+        let mut code = 0;
+        let ok = unsafe { GetExitCodeProcess(self.handle, &mut code) };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(code)
+    }
+
+    fn usage(&self) -> Result<ResourceUsage> {
+        let mut creation = FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        };
+        let mut exit = creation;
+        let mut kernel = creation;
+        let mut user = creation;
+        let ok = unsafe {
+            GetProcessTimes(
+                self.handle,
+                &mut creation,
+                &mut exit,
+                &mut kernel,
+                &mut user,
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: the counters is a plain C structure, so zeroes are a valid value.
+        let mut memory: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+        let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        let ok = unsafe { K32GetProcessMemoryInfo(self.handle, &mut memory, size) };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(ResourceUsage::new(
+            filetime_to_duration(user),
+            filetime_to_duration(kernel),
+            memory.PeakWorkingSetSize as u64,
+        ))
+    }
+}
+
+/// Converts a time interval in 100-nanosecond units into a duration.
+fn filetime_to_duration(time: FILETIME) -> Duration {
+    let ticks = (u64::from(time.dwHighDateTime) << 32) | u64::from(time.dwLowDateTime);
+    Duration::from_nanos(ticks.saturating_mul(100))
+}
+
+impl Drop for ExitHandle {
+    fn drop(&mut self) {
+        let _ = unsafe { CloseHandle(self.handle) };
+    }
 }
 
 #[cfg(feature = "async")]
@@ -138,10 +862,13 @@ impl IntoAsyncStream for ProcessStream {
 }
 
 /// An async version of IO stream of [WinProcess].
+///
+/// Pipes are read in a thread pool of `blocking` crate,
+/// so the stream doesn't depend on a reactor and it's used by all `runtime-*` features.
 #[cfg(feature = "async")]
 #[derive(Debug)]
 pub struct AsyncProcessStream {
-    output: blocking::Unblock<PipeReader>,
+    output: blocking::Unblock<ProcessReader>,
     input: blocking::Unblock<PipeWriter>,
 }
 
@@ -183,3 +910,22 @@ impl AsyncRead for AsyncProcessStream {
         Pin::new(&mut self.output).poll_read(cx, buf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preamble_len() {
+        let preamble = b"\x1b[?25l\x1b[2J\x1b[m\x1b[H\x1b]0;C:\\Windows\\cmd.exe\x07\x1b[?25h";
+
+        let mut output = preamble.to_vec();
+        output.extend_from_slice(b"Hello World\r\n");
+        assert_eq!(preamble_len(&output), Some(preamble.len()));
+
+        assert_eq!(preamble_len(b"Hello World"), Some(0));
+        assert_eq!(preamble_len(b"\x1b[31mred"), Some(0));
+        assert_eq!(preamble_len(&preamble[..preamble.len() - 2]), None);
+        assert_eq!(preamble_len(preamble), None);
+    }
+}
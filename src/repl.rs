@@ -1,15 +1,22 @@
 //! This module contains a list of special Sessions that can be spawned.
 
-use std::io::{self, BufRead, Read, Write};
+use std::{
+    io::{self, BufRead, Read, Write},
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use std::ffi::{OsStr, OsString};
 
 #[cfg(unix)]
-use std::process::Command;
+use std::path::PathBuf;
 
 use crate::{
     error::Error,
-    process::{Healthcheck, Termios},
+    process::{ExitStatus, Healthcheck, Termios},
     session::OsSession,
-    spawn, Captures, Expect, Needle,
+    spawn, Any, Captures, Expect, Needle, Regex,
 };
 
 #[cfg(feature = "async")]
@@ -31,27 +38,50 @@ use crate::AsyncExpect;
 /// If you wan't to use [`Session::interact`] method it is better to use just Session.
 /// Because we don't handle echoes here (currently). Ideally we need to.
 ///
+/// See [`spawn_bash_with`] to customize the session.
+///
 /// [`Session::interact`]: crate::Session::interact
 #[cfg(unix)]
 #[cfg(not(feature = "async"))]
 pub fn spawn_bash() -> Result<ReplSession<OsSession>, Error> {
-    const DEFAULT_PROMPT: &str = "EXPECT_PROMPT";
+    spawn_bash_with(BashOptions::default())
+}
 
-    let mut cmd = Command::new("bash");
-    let _ = cmd.env("PS1", DEFAULT_PROMPT);
-    // bind 'set enable-bracketed-paste off' turns off paste mode,
-    // without it each command in bash starts and ends with an invisible sequence.
-    //
-    // We might need to turn it off optionally?
-    let _ = cmd.env(
-        "PROMPT_COMMAND",
-        "PS1=EXPECT_PROMPT; unset PROMPT_COMMAND; bind 'set enable-bracketed-paste off'",
-    );
+/// Spawn a bash session.
+///
+/// It uses a custom prompt to be able to controll shell better.
+///
+/// See [`spawn_bash_with`] to customize the session.
+#[cfg(unix)]
+#[cfg(feature = "async")]
+pub async fn spawn_bash() -> Result<ReplSession<OsSession>, Error> {
+    spawn_bash_with(BashOptions::default()).await
+}
 
-    let session = crate::session::Session::spawn(cmd)?;
+/// Spawn a bash session configured by [`BashOptions`].
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::repl::{spawn_bash_with, BashOptions};
+///
+/// let options = BashOptions::new()
+///     .rcfile("/dev/null")
+///     .env("LANG", "C")
+///     .init_command("set -o pipefail");
+///
+/// let mut bash = spawn_bash_with(options).unwrap();
+/// ```
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+pub fn spawn_bash_with(options: BashOptions) -> Result<ReplSession<OsSession>, Error> {
+    let mut session = crate::session::Session::spawn(options.command())?;
+    session.set_bracketed_paste(options.bracketed_paste);
 
-    let mut bash = ReplSession::new(session, DEFAULT_PROMPT);
+    let mut bash = ReplSession::new(session, options.prompt);
+    bash.set_continuation_prompt(BASH_CONTINUATION_PROMPT);
     bash.set_quit_command("quit");
+    bash.set_exit_code_command(BASH_EXIT_CODE_COMMAND);
 
     // read a prompt to make it not available on next read.
     //
@@ -59,41 +89,138 @@ pub fn spawn_bash() -> Result<ReplSession<OsSession>, Error> {
     //      the issue most likely that with this line in interact mode ENTER produces CTRL-M
     //      when without the line it produces \r\n
 
-    bash.expect_prompt()?;
+    let _ = bash.expect_prompt()?;
+
+    for cmd in &options.init_commands {
+        let _ = bash.execute(cmd)?;
+    }
 
     Ok(bash)
 }
 
-/// Spawn a bash session.
-///
-/// It uses a custom prompt to be able to controll shell better.
+/// Spawn a bash session configured by [`BashOptions`].
 #[cfg(unix)]
 #[cfg(feature = "async")]
-pub async fn spawn_bash() -> Result<ReplSession<OsSession>, Error> {
-    const DEFAULT_PROMPT: &str = "EXPECT_PROMPT";
-    let mut cmd = Command::new("bash");
-    let _ = cmd.env("PS1", DEFAULT_PROMPT);
-    // bind 'set enable-bracketed-paste off' turns off paste mode,
-    // without it each command in bash starts and ends with an invisible sequence.
-    //
-    // We might need to turn it off optionally?
-    let _ = cmd.env(
-        "PROMPT_COMMAND",
-        "PS1=EXPECT_PROMPT; unset PROMPT_COMMAND; bind 'set enable-bracketed-paste off'",
-    );
-
-    let session = crate::session::Session::spawn(cmd)?;
+pub async fn spawn_bash_with(options: BashOptions) -> Result<ReplSession<OsSession>, Error> {
+    let mut session = crate::session::Session::spawn(options.command())?;
+    session.set_bracketed_paste(options.bracketed_paste);
 
-    let mut bash = ReplSession::new(session, DEFAULT_PROMPT);
+    let mut bash = ReplSession::new(session, options.prompt);
+    bash.set_continuation_prompt(BASH_CONTINUATION_PROMPT);
     bash.set_quit_command("quit");
+    bash.set_exit_code_command(BASH_EXIT_CODE_COMMAND);
     bash.set_echo(false);
 
     // read a prompt to make it not available on next read.
-    bash.expect_prompt().await?;
+    let _ = bash.expect_prompt().await?;
+
+    for cmd in &options.init_commands {
+        let _ = bash.execute(cmd).await?;
+    }
 
     Ok(bash)
 }
 
+#[cfg(unix)]
+const BASH_CONTINUATION_PROMPT: &str = "EXPECT_CONTINUATION_PROMPT";
+
+#[cfg(unix)]
+const BASH_EXIT_CODE_COMMAND: &str = "echo $?";
+
+/// BashOptions is a configuration of a bash session spawned by [`spawn_bash_with`].
+#[cfg(unix)]
+#[derive(Debug, Clone)]
+pub struct BashOptions {
+    prompt: String,
+    rcfile: Option<PathBuf>,
+    env: Vec<(OsString, OsString)>,
+    init_commands: Vec<String>,
+    bracketed_paste: bool,
+}
+
+#[cfg(unix)]
+impl BashOptions {
+    /// Creates a default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a prompt which is used to detect the end of a command.
+    ///
+    /// It must not contain a single quote.
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Set a file which is used instead of `~/.bashrc`.
+    pub fn rcfile(mut self, path: impl Into<PathBuf>) -> Self {
+        self.rcfile = Some(path.into());
+        self
+    }
+
+    /// Add an environment variable.
+    pub fn env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Add a command which is executed right after bash is started.
+    pub fn init_command(mut self, cmd: impl Into<String>) -> Self {
+        self.init_commands.push(cmd.into());
+        self
+    }
+
+    /// Set whether bracketed paste mode is left on.
+    ///
+    /// It's off by default,
+    /// otherwise each command in bash starts and ends with an invisible sequence.
+    pub fn bracketed_paste(mut self, on: bool) -> Self {
+        self.bracketed_paste = on;
+        self
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new("bash");
+        if let Some(rcfile) = &self.rcfile {
+            let _ = cmd.arg("--rcfile").arg(rcfile);
+        }
+
+        for (key, value) in &self.env {
+            let _ = cmd.env(key, value);
+        }
+
+        let _ = cmd.env("PS1", &self.prompt);
+        let _ = cmd.env("PS2", BASH_CONTINUATION_PROMPT);
+
+        // Prompts are set once again as a rc file may override them.
+        let mut prompt_command = format!(
+            "PS1='{}'; PS2='{}'; unset PROMPT_COMMAND",
+            self.prompt, BASH_CONTINUATION_PROMPT
+        );
+        if !self.bracketed_paste {
+            prompt_command.push_str("; bind 'set enable-bracketed-paste off'");
+        }
+
+        let _ = cmd.env("PROMPT_COMMAND", prompt_command);
+
+        cmd
+    }
+}
+
+#[cfg(unix)]
+impl Default for BashOptions {
+    fn default() -> Self {
+        Self {
+            prompt: String::from("EXPECT_PROMPT"),
+            rcfile: None,
+            env: Vec::new(),
+            init_commands: Vec::new(),
+            bracketed_paste: false,
+        }
+    }
+}
+
 /// Spawn default python's IDLE.
 #[cfg(not(feature = "async"))]
 pub fn spawn_python() -> Result<ReplSession<OsSession>, Error> {
@@ -103,8 +230,9 @@ pub fn spawn_python() -> Result<ReplSession<OsSession>, Error> {
     let session = spawn("python")?;
 
     let mut idle = ReplSession::new(session, ">>> ");
+    idle.set_continuation_prompt("... ");
     idle.set_quit_command("quit()");
-    idle.expect_prompt()?;
+    let _ = idle.expect_prompt()?;
 
     Ok(idle)
 }
@@ -118,13 +246,114 @@ pub async fn spawn_python() -> Result<ReplSession<OsSession>, Error> {
     let session = spawn("python")?;
 
     let mut idle = ReplSession::new(session, ">>> ");
+    idle.set_continuation_prompt("... ");
     idle.set_quit_command("quit()");
     idle.set_echo(false);
 
-    idle.expect_prompt().await?;
+    let _ = idle.expect_prompt().await?;
     Ok(idle)
 }
 
+/// Spawn an IPython session.
+///
+/// It's started with a simple prompt and no colors,
+/// so an output isn't cluttered with terminal escape sequences.
+///
+/// A prompt is matched by a regex as it contains a counter, e.g. `In [1]: `.
+#[cfg(not(feature = "async"))]
+pub fn spawn_ipython() -> Result<ReplSession<OsSession>, Error> {
+    let session = crate::session::Session::spawn(ipython_command())?;
+
+    let mut ipython = ReplSession::new(session, IPYTHON_PROMPT);
+    ipython.set_prompt_regex(true);
+    ipython.set_continuation_prompt(IPYTHON_CONTINUATION_PROMPT);
+    ipython.set_quit_command("exit");
+    let _ = ipython.expect_prompt()?;
+
+    Ok(ipython)
+}
+
+/// Spawn an IPython session.
+///
+/// It's started with a simple prompt and no colors,
+/// so an output isn't cluttered with terminal escape sequences.
+///
+/// A prompt is matched by a regex as it contains a counter, e.g. `In [1]: `.
+#[cfg(feature = "async")]
+pub async fn spawn_ipython() -> Result<ReplSession<OsSession>, Error> {
+    let session = crate::session::Session::spawn(ipython_command())?;
+
+    let mut ipython = ReplSession::new(session, IPYTHON_PROMPT);
+    ipython.set_prompt_regex(true);
+    ipython.set_continuation_prompt(IPYTHON_CONTINUATION_PROMPT);
+    ipython.set_quit_command("exit");
+    ipython.set_echo(false);
+
+    let _ = ipython.expect_prompt().await?;
+    Ok(ipython)
+}
+
+const IPYTHON_PROMPT: &str = r"In \[\d+\]: ";
+const IPYTHON_CONTINUATION_PROMPT: &str = r"\.\.\.: ";
+
+fn ipython_command() -> Command {
+    let mut cmd = Command::new("ipython");
+    let _ = cmd.args([
+        "--simple-prompt",
+        "--no-banner",
+        "--no-confirm-exit",
+        "--colors=NoColor",
+    ]);
+
+    cmd
+}
+
+/// Spawn a Node.js REPL.
+///
+/// It uses a custom prompt to be able to controll the REPL.
+#[cfg(not(feature = "async"))]
+pub fn spawn_node() -> Result<ReplSession<OsSession>, Error> {
+    let session = crate::session::Session::spawn(node_command())?;
+
+    let mut node = ReplSession::new(session, NODE_PROMPT);
+    node.set_continuation_prompt("... ");
+    node.set_quit_command(".exit");
+    let _ = node.expect_prompt()?;
+
+    Ok(node)
+}
+
+/// Spawn a Node.js REPL.
+///
+/// It uses a custom prompt to be able to controll the REPL.
+#[cfg(feature = "async")]
+pub async fn spawn_node() -> Result<ReplSession<OsSession>, Error> {
+    let session = crate::session::Session::spawn(node_command())?;
+
+    let mut node = ReplSession::new(session, NODE_PROMPT);
+    node.set_continuation_prompt("... ");
+    node.set_quit_command(".exit");
+    node.set_echo(false);
+
+    let _ = node.expect_prompt().await?;
+    Ok(node)
+}
+
+const NODE_PROMPT: &str = "EXPECT_PROMPT> ";
+
+fn node_command() -> Command {
+    // A REPL which is not in a terminal mode doesn't use colors and line editing escape sequences.
+    let script = format!(
+        "require('repl').start({{ prompt: '{}', terminal: false, useColors: false }})",
+        NODE_PROMPT
+    );
+
+    let mut cmd = Command::new("node");
+    let _ = cmd.args(["-e", &script]);
+
+    cmd
+}
+
 /// Spawn a powershell session.
 ///
 /// It uses a custom prompt to be able to controll the shell.
@@ -135,6 +364,7 @@ pub fn spawn_powershell() -> Result<ReplSession<OsSession>, Error> {
     let session = spawn("pwsh -NoProfile -NonInteractive -NoLogo")?;
     let mut powershell = ReplSession::new(session, DEFAULT_PROMPT);
     powershell.set_quit_command("exit");
+    powershell.set_exit_code_command(POWERSHELL_EXIT_CODE_COMMAND);
     powershell.set_echo(true);
 
     // https://stackoverflow.com/questions/5725888/windows-powershell-changing-the-command-prompt
@@ -163,6 +393,7 @@ pub async fn spawn_powershell() -> Result<ReplSession<OsSession>, Error> {
     let session = spawn("pwsh -NoProfile -NonInteractive -NoLogo")?;
     let mut powershell = ReplSession::new(session, DEFAULT_PROMPT);
     powershell.set_quit_command("exit");
+    powershell.set_exit_code_command(POWERSHELL_EXIT_CODE_COMMAND);
     powershell.set_echo(true);
 
     // https://stackoverflow.com/questions/5725888/windows-powershell-changing-the-command-prompt
@@ -185,6 +416,283 @@ pub async fn spawn_powershell() -> Result<ReplSession<OsSession>, Error> {
     Ok(powershell)
 }
 
+/// A command which prints an exit code of a previous command in powershell.
+///
+/// `$LASTEXITCODE` is set only by native programs, so `$?` is checked first for cmdlets.
+#[cfg(windows)]
+const POWERSHELL_EXIT_CODE_COMMAND: &str =
+    "if ($?) { 0 } elseif ($LASTEXITCODE) { $LASTEXITCODE } else { 1 }";
+
+/// Spawn a GDB session for a given binary.
+///
+/// Pagination, confirmations and line wrapping are turned off,
+/// so an output is not blocked by `--Type <RET> for more--` prompts.
+///
+/// See [`spawn_gdb_with`] to pass arguments or to use the machine interface.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::repl::spawn_gdb;
+///
+/// let mut gdb = spawn_gdb("./target/debug/app").unwrap();
+/// gdb.break_at("main").unwrap();
+/// gdb.run_until_break().unwrap();
+/// let backtrace = gdb.execute("bt").unwrap();
+/// ```
+#[cfg(not(feature = "async"))]
+pub fn spawn_gdb<B>(binary: B) -> Result<ReplSession<OsSession>, Error>
+where
+    B: AsRef<OsStr>,
+{
+    spawn_gdb_with(binary, GdbOptions::default())
+}
+
+/// Spawn a GDB session for a given binary.
+///
+/// Pagination, confirmations and line wrapping are turned off,
+/// so an output is not blocked by `--Type <RET> for more--` prompts.
+///
+/// See [`spawn_gdb_with`] to pass arguments or to use the machine interface.
+#[cfg(feature = "async")]
+pub async fn spawn_gdb<B>(binary: B) -> Result<ReplSession<OsSession>, Error>
+where
+    B: AsRef<OsStr>,
+{
+    spawn_gdb_with(binary, GdbOptions::default()).await
+}
+
+/// Spawn a GDB session configured by [`GdbOptions`].
+#[cfg(not(feature = "async"))]
+pub fn spawn_gdb_with<B>(binary: B, options: GdbOptions) -> Result<ReplSession<OsSession>, Error>
+where
+    B: AsRef<OsStr>,
+{
+    let session = crate::session::Session::spawn(options.command(binary.as_ref()))?;
+
+    let mut gdb = ReplSession::new(session, GDB_PROMPT);
+    gdb.set_quit_command("quit");
+    gdb.set_debugger(options.debugger());
+    let _ = gdb.expect_prompt()?;
+
+    Ok(gdb)
+}
+
+/// Spawn a GDB session configured by [`GdbOptions`].
+#[cfg(feature = "async")]
+pub async fn spawn_gdb_with<B>(
+    binary: B,
+    options: GdbOptions,
+) -> Result<ReplSession<OsSession>, Error>
+where
+    B: AsRef<OsStr>,
+{
+    let session = crate::session::Session::spawn(options.command(binary.as_ref()))?;
+
+    let mut gdb = ReplSession::new(session, GDB_PROMPT);
+    gdb.set_quit_command("quit");
+    gdb.set_debugger(options.debugger());
+    gdb.set_echo(false);
+
+    let _ = gdb.expect_prompt().await?;
+    Ok(gdb)
+}
+
+/// Spawn a LLDB session for a given binary.
+///
+/// Colors and confirmations are turned off.
+#[cfg(not(feature = "async"))]
+pub fn spawn_lldb<B>(binary: B) -> Result<ReplSession<OsSession>, Error>
+where
+    B: AsRef<str>,
+{
+    let session = crate::session::Session::spawn(lldb_command())?;
+
+    let mut lldb = ReplSession::new(session, LLDB_PROMPT);
+    lldb.set_quit_command("quit");
+    lldb.set_debugger(Debugger::Lldb);
+    let _ = lldb.expect_prompt()?;
+
+    // A target is created after the first prompt,
+    // as commands passed by arguments are echoed with a prompt and could be taken for it.
+    for cmd in LLDB_INIT_COMMANDS {
+        let _ = lldb.execute(cmd)?;
+    }
+
+    let _ = lldb.execute(format!("target create {:?}", binary.as_ref()))?;
+
+    Ok(lldb)
+}
+
+/// Spawn a LLDB session for a given binary.
+///
+/// Colors and confirmations are turned off.
+#[cfg(feature = "async")]
+pub async fn spawn_lldb<B>(binary: B) -> Result<ReplSession<OsSession>, Error>
+where
+    B: AsRef<str>,
+{
+    let session = crate::session::Session::spawn(lldb_command())?;
+
+    let mut lldb = ReplSession::new(session, LLDB_PROMPT);
+    lldb.set_quit_command("quit");
+    lldb.set_debugger(Debugger::Lldb);
+    lldb.set_echo(false);
+
+    let _ = lldb.expect_prompt().await?;
+
+    // A target is created after the first prompt,
+    // as commands passed by arguments are echoed with a prompt and could be taken for it.
+    for cmd in LLDB_INIT_COMMANDS {
+        let _ = lldb.execute(cmd).await?;
+    }
+
+    let _ = lldb
+        .execute(format!("target create {:?}", binary.as_ref()))
+        .await?;
+
+    Ok(lldb)
+}
+
+const GDB_PROMPT: &str = "(gdb) ";
+const LLDB_PROMPT: &str = "(lldb) ";
+
+/// Settings which keep GDB from waiting for a user in the middle of an output.
+const GDB_INIT_COMMANDS: [&str; 4] = [
+    "set pagination off",
+    "set confirm off",
+    "set width 0",
+    "set height 0",
+];
+
+const LLDB_INIT_COMMANDS: [&str; 2] = [
+    "settings set auto-confirm true",
+    "settings set use-color false",
+];
+
+fn lldb_command() -> Command {
+    let mut cmd = Command::new("lldb");
+    let _ = cmd.args(["--no-lldbinit", "--no-use-colors"]);
+    let _ = cmd.env("TERM", "dumb");
+
+    cmd
+}
+
+/// A debugger which is run by a [`ReplSession`].
+///
+/// It defines commands and an output format used by debugger helpers,
+/// like [`ReplSession::run_until_break`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Debugger {
+    /// GDB with a command line interface.
+    Gdb,
+    /// GDB with a machine interface (`--interpreter=mi`).
+    GdbMi,
+    /// LLDB.
+    Lldb,
+}
+
+impl Debugger {
+    fn break_command(&self, location: &str) -> String {
+        match self {
+            Debugger::Gdb => format!("break {}", location),
+            Debugger::GdbMi => format!("-break-insert {}", location),
+            Debugger::Lldb => format!("b {}", location),
+        }
+    }
+
+    fn run_command(&self) -> &'static str {
+        match self {
+            Debugger::Gdb | Debugger::Lldb => "run",
+            Debugger::GdbMi => "-exec-run",
+        }
+    }
+
+    fn continue_command(&self) -> &'static str {
+        match self {
+            Debugger::Gdb | Debugger::Lldb => "continue",
+            Debugger::GdbMi => "-exec-continue",
+        }
+    }
+}
+
+/// GdbOptions configures a GDB session spawned by [`spawn_gdb_with`].
+///
+/// # Example
+///
+/// ```
+/// use expectrl::repl::GdbOptions;
+///
+/// let options = GdbOptions::new().mi(true).args(["--verbose"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GdbOptions {
+    mi: bool,
+    args: Vec<OsString>,
+    init_commands: Vec<String>,
+}
+
+impl GdbOptions {
+    /// Creates default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the machine interface (GDB/MI) instead of the command line one.
+    ///
+    /// Debugger helpers of [`ReplSession`] use MI commands in such case,
+    /// while [`ReplSession::execute`] returns raw MI records.
+    pub fn mi(mut self, on: bool) -> Self {
+        self.mi = on;
+        self
+    }
+
+    /// Set arguments of a debugged program.
+    pub fn args<I, A>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<OsString>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Add a command which is run by GDB on start, e.g. `set print pretty on`.
+    pub fn init_command(mut self, cmd: impl Into<String>) -> Self {
+        self.init_commands.push(cmd.into());
+        self
+    }
+
+    fn debugger(&self) -> Debugger {
+        match self.mi {
+            true => Debugger::GdbMi,
+            false => Debugger::Gdb,
+        }
+    }
+
+    fn command(&self, binary: &OsStr) -> Command {
+        let mut cmd = Command::new("gdb");
+        let _ = cmd.args(["-q", "-nx"]);
+        if self.mi {
+            let _ = cmd.arg("--interpreter=mi");
+        }
+
+        // `-ex` commands are not echoed unlike ones typed after a prompt.
+        let init_commands = GDB_INIT_COMMANDS
+            .iter()
+            .copied()
+            .chain(self.init_commands.iter().map(String::as_str));
+        for init in init_commands {
+            let _ = cmd.arg("-ex").arg(init);
+        }
+
+        let _ = cmd.arg("--args").arg(binary).args(&self.args);
+        let _ = cmd.env("TERM", "dumb");
+
+        cmd
+    }
+}
+
 /// A repl session: e.g. bash or the python shell:
 /// you have a prompt where a user inputs commands and the shell
 /// which executes them and manages IO streams.
@@ -195,8 +703,18 @@ pub struct ReplSession<S> {
     /// The prompt, used for `wait_for_prompt`,
     /// e.g. ">>> " for python.
     prompt: String,
+    /// A secondary prompt which is shown when a command is not complete,
+    /// e.g. "... " for python.
+    continuation_prompt: Option<String>,
+    /// Flag to see if the prompts are regexes.
+    is_prompt_regex: bool,
     /// A command which will be called before termination.
     quit_command: Option<String>,
+    /// A command which prints an exit code of a previous command,
+    /// e.g. `echo $?` for bash.
+    exit_code_command: Option<String>,
+    /// A debugger which is run by the session.
+    debugger: Option<Debugger>,
     /// Flag to see if a echo is turned on.
     is_echo_on: bool,
 }
@@ -213,7 +731,11 @@ impl<S> ReplSession<S> {
         Self {
             session,
             prompt: prompt.into(),
+            continuation_prompt: None,
+            is_prompt_regex: false,
             quit_command: None,
+            exit_code_command: None,
+            debugger: None,
             is_echo_on: false,
         }
     }
@@ -223,21 +745,69 @@ impl<S> ReplSession<S> {
         self.is_echo_on = on;
     }
 
+    /// Set a continuation prompt, which a repl shows when an entered command is not complete.
+    ///
+    /// When it's set a multi-line command is sent line by line,
+    /// waiting for a continuation prompt after each line.
+    pub fn set_continuation_prompt(&mut self, prompt: impl Into<String>) {
+        self.continuation_prompt = Some(prompt.into());
+    }
+
+    /// Set whether the prompt and the continuation prompt are regexes.
+    ///
+    /// It's useful for prompts which are changed from command to command,
+    /// e.g. `In [1]: ` in IPython.
+    pub fn set_prompt_regex(&mut self, on: bool) {
+        self.is_prompt_regex = on;
+    }
+
     /// Set quit command which will be called on `exit`.
     pub fn set_quit_command(&mut self, cmd: impl Into<String>) {
         self.quit_command = Some(cmd.into());
     }
 
+    /// Set a command which prints an exit code of a previous command,
+    /// it's used by `execute_checked`.
+    pub fn set_exit_code_command(&mut self, cmd: impl Into<String>) {
+        self.exit_code_command = Some(cmd.into());
+    }
+
+    /// Set a debugger which is run by the session,
+    /// it's used by debugger helpers like [`ReplSession::run_until_break`].
+    pub fn set_debugger(&mut self, debugger: Debugger) {
+        self.debugger = Some(debugger);
+    }
+
     /// Get a used prompt.
     pub fn get_prompt(&self) -> &str {
         &self.prompt
     }
 
+    /// Get a used continuation prompt.
+    pub fn get_continuation_prompt(&self) -> Option<&str> {
+        self.continuation_prompt.as_deref()
+    }
+
+    /// Get whether the prompts are regexes.
+    pub fn is_prompt_regex(&self) -> bool {
+        self.is_prompt_regex
+    }
+
     /// Get a used quit command.
     pub fn get_quit_command(&self) -> Option<&str> {
         self.quit_command.as_deref()
     }
 
+    /// Get a used exit code command.
+    pub fn get_exit_code_command(&self) -> Option<&str> {
+        self.exit_code_command.as_deref()
+    }
+
+    /// Get a used debugger.
+    pub fn get_debugger(&self) -> Option<Debugger> {
+        self.debugger
+    }
+
     /// Get a echo settings.
     pub fn is_echo(&self) -> bool {
         self.is_echo_on
@@ -264,14 +834,27 @@ impl<S> ReplSession<S>
 where
     S: Expect,
 {
-    /// Block until prompt is found
-    pub fn expect_prompt(&mut self) -> Result<(), Error> {
-        let _ = self._expect_prompt()?;
-        Ok(())
+    /// Block until prompt is found.
+    ///
+    /// Returns a text produced since the previous prompt, e.g. an output of a command,
+    /// an invalid UTF-8 is replaced lossily.
+    pub fn expect_prompt(&mut self) -> Result<String, Error> {
+        let found = self._expect_prompt()?;
+        Ok(found.before_str_lossy().into_owned())
     }
 
     fn _expect_prompt(&mut self) -> Result<Captures, Error> {
-        self.session.expect(&self.prompt)
+        let prompt = prompt_needle(&self.prompt, self.is_prompt_regex);
+        self.session.expect(prompt)
+    }
+
+    fn _expect_continuation_prompt(&mut self) -> Result<Captures, Error> {
+        let needle = continuation_needle(
+            &self.prompt,
+            self.continuation_prompt.as_deref(),
+            self.is_prompt_regex,
+        );
+        self.session.expect(needle)
     }
 }
 
@@ -280,14 +863,27 @@ impl<S> ReplSession<S>
 where
     S: AsyncExpect + Unpin,
 {
-    /// Block until prompt is found
-    pub async fn expect_prompt(&mut self) -> Result<(), Error> {
-        let _ = self._expect_prompt().await?;
-        Ok(())
+    /// Block until prompt is found.
+    ///
+    /// Returns a text produced since the previous prompt, e.g. an output of a command,
+    /// an invalid UTF-8 is replaced lossily.
+    pub async fn expect_prompt(&mut self) -> Result<String, Error> {
+        let found = self._expect_prompt().await?;
+        Ok(found.before_str_lossy().into_owned())
     }
 
     async fn _expect_prompt(&mut self) -> Result<Captures, Error> {
-        self.session.expect(&self.prompt).await
+        let prompt = prompt_needle(&self.prompt, self.is_prompt_regex);
+        self.session.expect(prompt).await
+    }
+
+    async fn _expect_continuation_prompt(&mut self) -> Result<Captures, Error> {
+        let needle = continuation_needle(
+            &self.prompt,
+            self.continuation_prompt.as_deref(),
+            self.is_prompt_regex,
+        );
+        self.session.expect(needle).await
     }
 }
 
@@ -298,17 +894,87 @@ where
 {
     /// Send a command to a repl and verifies that it exited.
     /// Returning it's output.
+    ///
+    /// If a continuation prompt is set a multi-line command is sent line by line.
+    /// A trailing newline results in an empty line being sent,
+    /// which some repls (e.g. python) require to finish a block.
     pub fn execute<C>(&mut self, cmd: C) -> Result<Vec<u8>, Error>
     where
         C: AsRef<str>,
     {
-        self.send_line(cmd)?;
+        let cmd = cmd.as_ref();
+        let mut out = Vec::new();
+        let mut lines = split_command(cmd, self.continuation_prompt.is_some());
+        let last = lines.pop().unwrap_or_default();
+
+        for line in lines {
+            self.send_line(line)?;
+            let found = self._expect_continuation_prompt()?;
+            out.extend(found.before());
+        }
+
+        self.send_line(last)?;
         let found = self._expect_prompt()?;
-        let out = found.before().to_vec();
+        out.extend(found.before());
 
         Ok(out)
     }
 
+    /// Execute a command in a shell and return its output and exit code.
+    ///
+    /// The exit code is taken by a command set by [`ReplSession::set_exit_code_command`],
+    /// which is `echo $?` in bash and `$LASTEXITCODE` based one in powershell.
+    pub fn execute_checked<C>(&mut self, cmd: C) -> Result<(Vec<u8>, i32), Error>
+    where
+        C: AsRef<str>,
+    {
+        let exit_code_command = self
+            .exit_code_command
+            .clone()
+            .ok_or_else(no_exit_code_command)?;
+
+        let out = self.execute(cmd)?;
+        let code = self.execute(&exit_code_command)?;
+        let code = parse_exit_code(&code, &exit_code_command)?;
+
+        Ok((out, code))
+    }
+
+    /// Execute a command in a shell and verify it succeeded.
+    ///
+    /// A non-zero exit code is returned as [`Error::UnexpectedExit`] with the command output.
+    pub fn execute_success<C>(&mut self, cmd: C) -> Result<Vec<u8>, Error>
+    where
+        C: AsRef<str>,
+    {
+        let (out, code) = self.execute_checked(cmd)?;
+        check_exit_code(out, code)
+    }
+
+    /// Execute a command and return its output split by lines.
+    ///
+    /// An echoed command line and trailing empty lines are stripped.
+    pub fn execute_lines<C>(&mut self, cmd: C) -> Result<Vec<String>, Error>
+    where
+        C: AsRef<str>,
+    {
+        let out = self.execute(cmd.as_ref())?;
+        Ok(parse_lines(&out, cmd.as_ref()))
+    }
+
+    /// Execute a command and parse its output as JSON.
+    ///
+    /// An echoed command line is stripped before parsing.
+    #[cfg(feature = "json")]
+    pub fn execute_json<T, C>(&mut self, cmd: C) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+        C: AsRef<str>,
+    {
+        let lines = self.execute_lines(cmd)?;
+        parse_json(&lines)
+    }
+
     /// Sends line to repl (and flush the output).
     ///
     /// If echo_on=true wait for the input to appear.
@@ -326,6 +992,65 @@ where
         Ok(())
     }
 
+    /// Synchronize with a repl by sending `echo <marker>` with a random marker
+    /// and waiting for the marker and a prompt after it.
+    ///
+    /// It guarantees that all prior output has been consumed,
+    /// so a stale output doesn't match a following expect call.
+    /// The marker may follow a prompt on the same line,
+    /// so it's matched by a line ending after it rather than by a line start.
+    pub fn sync(&mut self) -> Result<(), Error> {
+        let marker = sync_marker();
+        self.send_line(format!("echo {}", marker))?;
+        let _ = self.session.expect(sync_needle(&marker))?;
+        let _ = self._expect_prompt()?;
+
+        Ok(())
+    }
+
+    /// Set a breakpoint in a debugger session, see [`spawn_gdb`] and [`spawn_lldb`].
+    ///
+    /// Returns an output of the debugger.
+    pub fn break_at<L>(&mut self, location: L) -> Result<Vec<u8>, Error>
+    where
+        L: AsRef<str>,
+    {
+        let debugger = self.debugger.ok_or_else(no_debugger)?;
+        let out = self.execute(debugger.break_command(location.as_ref()))?;
+        check_debugger_output(debugger, out)
+    }
+
+    /// Run a debugged program and wait until it stops, e.g. on a breakpoint.
+    ///
+    /// Returns an output produced till the stop.
+    /// If the program exits instead [`Error::UnexpectedExit`] is returned.
+    pub fn run_until_break(&mut self) -> Result<Vec<u8>, Error> {
+        let debugger = self.debugger.ok_or_else(no_debugger)?;
+        self.debugger_resume(debugger, debugger.run_command())
+    }
+
+    /// Continue a stopped program and wait until it stops again.
+    ///
+    /// If the program exits instead [`Error::UnexpectedExit`] is returned.
+    pub fn continue_until_break(&mut self) -> Result<Vec<u8>, Error> {
+        let debugger = self.debugger.ok_or_else(no_debugger)?;
+        self.debugger_resume(debugger, debugger.continue_command())
+    }
+
+    fn debugger_resume(&mut self, debugger: Debugger, cmd: &str) -> Result<Vec<u8>, Error> {
+        let mut out = self.execute(cmd)?;
+
+        // A MI prompt is printed right after a command is accepted,
+        // a stop is reported later by an async record.
+        if debugger == Debugger::GdbMi {
+            let found = self.session.expect(Regex(GDB_MI_STOPPED))?;
+            out.extend(found.as_bytes());
+            let _ = self._expect_prompt()?;
+        }
+
+        check_debugger_stop(debugger, out)
+    }
+
     /// Send a quit command.
     ///
     /// In async version we it won't be send on Drop so,
@@ -345,10 +1070,72 @@ where
     S: AsyncExpect + Unpin,
 {
     /// Send a command to a repl and verifies that it exited.
+    ///
+    /// If a continuation prompt is set a multi-line command is sent line by line.
+    /// A trailing newline results in an empty line being sent,
+    /// which some repls (e.g. python) require to finish a block.
     pub async fn execute(&mut self, cmd: impl AsRef<str>) -> Result<Vec<u8>, Error> {
-        self.send_line(cmd).await?;
+        let cmd = cmd.as_ref();
+        let mut out = Vec::new();
+        let mut lines = split_command(cmd, self.continuation_prompt.is_some());
+        let last = lines.pop().unwrap_or_default();
+
+        for line in lines {
+            self.send_line(line).await?;
+            let found = self._expect_continuation_prompt().await?;
+            out.extend(found.before());
+        }
+
+        self.send_line(last).await?;
         let found = self._expect_prompt().await?;
-        Ok(found.before().to_vec())
+        out.extend(found.before());
+
+        Ok(out)
+    }
+
+    /// Execute a command in a shell and return its output and exit code.
+    ///
+    /// The exit code is taken by a command set by [`ReplSession::set_exit_code_command`],
+    /// which is `echo $?` in bash and `$LASTEXITCODE` based one in powershell.
+    pub async fn execute_checked(&mut self, cmd: impl AsRef<str>) -> Result<(Vec<u8>, i32), Error> {
+        let exit_code_command = self
+            .exit_code_command
+            .clone()
+            .ok_or_else(no_exit_code_command)?;
+
+        let out = self.execute(cmd).await?;
+        let code = self.execute(&exit_code_command).await?;
+        let code = parse_exit_code(&code, &exit_code_command)?;
+
+        Ok((out, code))
+    }
+
+    /// Execute a command in a shell and verify it succeeded.
+    ///
+    /// A non-zero exit code is returned as [`Error::UnexpectedExit`] with the command output.
+    pub async fn execute_success(&mut self, cmd: impl AsRef<str>) -> Result<Vec<u8>, Error> {
+        let (out, code) = self.execute_checked(cmd).await?;
+        check_exit_code(out, code)
+    }
+
+    /// Execute a command and return its output split by lines.
+    ///
+    /// An echoed command line and trailing empty lines are stripped.
+    pub async fn execute_lines(&mut self, cmd: impl AsRef<str>) -> Result<Vec<String>, Error> {
+        let out = self.execute(cmd.as_ref()).await?;
+        Ok(parse_lines(&out, cmd.as_ref()))
+    }
+
+    /// Execute a command and parse its output as JSON.
+    ///
+    /// An echoed command line is stripped before parsing.
+    #[cfg(feature = "json")]
+    pub async fn execute_json<T>(&mut self, cmd: impl AsRef<str>) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let lines = self.execute_lines(cmd).await?;
+        parse_json(&lines)
     }
 
     /// Sends line to repl (and flush the output).
@@ -362,6 +1149,65 @@ where
         Ok(())
     }
 
+    /// Synchronize with a repl by sending `echo <marker>` with a random marker
+    /// and waiting for the marker and a prompt after it.
+    ///
+    /// It guarantees that all prior output has been consumed,
+    /// so a stale output doesn't match a following expect call.
+    /// The marker may follow a prompt on the same line,
+    /// so it's matched by a line ending after it rather than by a line start.
+    pub async fn sync(&mut self) -> Result<(), Error> {
+        let marker = sync_marker();
+        self.send_line(format!("echo {}", marker)).await?;
+        let _ = self.session.expect(sync_needle(&marker)).await?;
+        let _ = self._expect_prompt().await?;
+
+        Ok(())
+    }
+
+    /// Set a breakpoint in a debugger session, see [`spawn_gdb`] and [`spawn_lldb`].
+    ///
+    /// Returns an output of the debugger.
+    pub async fn break_at(&mut self, location: impl AsRef<str>) -> Result<Vec<u8>, Error> {
+        let debugger = self.debugger.ok_or_else(no_debugger)?;
+        let out = self
+            .execute(debugger.break_command(location.as_ref()))
+            .await?;
+        check_debugger_output(debugger, out)
+    }
+
+    /// Run a debugged program and wait until it stops, e.g. on a breakpoint.
+    ///
+    /// Returns an output produced till the stop.
+    /// If the program exits instead [`Error::UnexpectedExit`] is returned.
+    pub async fn run_until_break(&mut self) -> Result<Vec<u8>, Error> {
+        let debugger = self.debugger.ok_or_else(no_debugger)?;
+        self.debugger_resume(debugger, debugger.run_command()).await
+    }
+
+    /// Continue a stopped program and wait until it stops again.
+    ///
+    /// If the program exits instead [`Error::UnexpectedExit`] is returned.
+    pub async fn continue_until_break(&mut self) -> Result<Vec<u8>, Error> {
+        let debugger = self.debugger.ok_or_else(no_debugger)?;
+        self.debugger_resume(debugger, debugger.continue_command())
+            .await
+    }
+
+    async fn debugger_resume(&mut self, debugger: Debugger, cmd: &str) -> Result<Vec<u8>, Error> {
+        let mut out = self.execute(cmd).await?;
+
+        // A MI prompt is printed right after a command is accepted,
+        // a stop is reported later by an async record.
+        if debugger == Debugger::GdbMi {
+            let found = self.session.expect(Regex(GDB_MI_STOPPED)).await?;
+            out.extend(found.as_bytes());
+            let _ = self._expect_prompt().await?;
+        }
+
+        check_debugger_stop(debugger, out)
+    }
+
     /// Send a quit command.
     ///
     /// In async version we it won't be send on Drop so,
@@ -375,6 +1221,187 @@ where
     }
 }
 
+fn prompt_needle(prompt: &str, is_regex: bool) -> Box<dyn Needle + '_> {
+    match is_regex {
+        true => Box::new(Regex(prompt)),
+        false => Box::new(prompt),
+    }
+}
+
+/// Returns a needle which matches either of prompts,
+/// as a command can be completed before all lines are sent.
+fn continuation_needle<'a>(
+    prompt: &'a str,
+    continuation: Option<&'a str>,
+    is_regex: bool,
+) -> Any<Vec<Box<dyn Needle + 'a>>> {
+    let mut needles = vec![prompt_needle(prompt, is_regex)];
+    if let Some(continuation) = continuation {
+        needles.push(prompt_needle(continuation, is_regex));
+    }
+
+    Any(needles)
+}
+
+/// Returns a unique marker used by `ReplSession::sync`.
+fn sync_marker() -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.subsec_nanos())
+        .unwrap_or_default();
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    format!("EXPECTRL_SYNC_{:x}_{:x}", nanos, n)
+}
+
+/// Returns a needle which matches a printed marker ended by a new line.
+///
+/// An echoed command has the marker as well,
+/// but it's consumed by `ReplSession::send_line` when echo is on.
+fn sync_needle(marker: &str) -> Regex<String> {
+    Regex(format!(r"{}\r?\n", marker))
+}
+
+/// Splits a command into lines which are sent one by one.
+fn split_command(cmd: &str, is_multiline: bool) -> Vec<&str> {
+    if !is_multiline {
+        return vec![cmd];
+    }
+
+    cmd.split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect()
+}
+
+/// Splits an output into lines,
+/// removing an echoed command and trailing empty lines.
+fn parse_lines(output: &[u8], cmd: &str) -> Vec<String> {
+    let output = String::from_utf8_lossy(output);
+    let mut lines = output
+        .lines()
+        .map(|line| line.trim_end_matches('\r').to_owned())
+        .collect::<Vec<_>>();
+
+    let cmd = cmd.trim();
+    let is_echoed = lines.first().is_some_and(|line| line.trim().ends_with(cmd));
+    if !cmd.is_empty() && is_echoed {
+        let _ = lines.remove(0);
+    }
+
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        let _ = lines.pop();
+    }
+
+    lines
+}
+
+/// Parses an output of an exit code command.
+fn parse_exit_code(output: &[u8], cmd: &str) -> Result<i32, Error> {
+    let lines = parse_lines(output, cmd);
+    let line = lines.last().map(|line| line.trim()).unwrap_or_default();
+
+    line.parse().map_err(|_| Error::Other {
+        message: "failed to parse an exit code".to_string(),
+        err: format!("{:?}", line),
+    })
+}
+
+fn check_exit_code(output: Vec<u8>, code: i32) -> Result<Vec<u8>, Error> {
+    if code != 0 {
+        return Err(Error::UnexpectedExit {
+            status: ExitStatus::Exited(code),
+            buffer: output,
+        });
+    }
+
+    Ok(output)
+}
+
+/// Matches an async record of GDB/MI which reports a stop of a program.
+///
+/// A line ending is required so a partially read record is not matched.
+const GDB_MI_STOPPED: &str = r"\*stopped,[^\r\n]*\r?\n";
+
+/// Returns an error if a debugger reported a failed command.
+fn check_debugger_output(debugger: Debugger, output: Vec<u8>) -> Result<Vec<u8>, Error> {
+    if debugger == Debugger::GdbMi {
+        let text = String::from_utf8_lossy(&output);
+        if let Some(line) = text.lines().find(|line| line.starts_with("^error")) {
+            return Err(Error::Other {
+                message: "a debugger command failed".to_string(),
+                err: line.trim_end().to_string(),
+            });
+        }
+    }
+
+    Ok(output)
+}
+
+/// Returns an error if a debugged program exited instead of being stopped.
+fn check_debugger_stop(debugger: Debugger, output: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let output = check_debugger_output(debugger, output)?;
+    match parse_debugger_exit(debugger, &output) {
+        Some(code) => Err(Error::UnexpectedExit {
+            status: ExitStatus::Exited(code),
+            buffer: output,
+        }),
+        None => Ok(output),
+    }
+}
+
+/// Looks up a report of a program exit in a debugger output.
+///
+/// GDB prints an exit code in octal.
+fn parse_debugger_exit(debugger: Debugger, output: &[u8]) -> Option<i32> {
+    let (pattern, radix) = match debugger {
+        Debugger::Gdb => (
+            r"\[Inferior \d+ \(process \d+\) exited (?:normally|with code (\d+))\]",
+            8,
+        ),
+        Debugger::GdbMi => (
+            r#"\*stopped,reason="exited(?:-normally)?"(?:,exit-code="(\d+)")?"#,
+            8,
+        ),
+        Debugger::Lldb => (r"Process \d+ exited with status = (-?\d+)", 10),
+    };
+
+    let regex = regex::bytes::Regex::new(pattern).expect("a valid regex");
+    let captures = regex.captures(output)?;
+    let code = match captures.get(1) {
+        Some(code) => i32::from_str_radix(&String::from_utf8_lossy(code.as_bytes()), radix).ok()?,
+        None => 0,
+    };
+
+    Some(code)
+}
+
+fn no_debugger() -> Error {
+    Error::Other {
+        message: "a debugger is not set".to_string(),
+        err: "see ReplSession::set_debugger".to_string(),
+    }
+}
+
+fn no_exit_code_command() -> Error {
+    Error::Other {
+        message: "an exit code command is not set".to_string(),
+        err: "see ReplSession::set_exit_code_command".to_string(),
+    }
+}
+
+#[cfg(feature = "json")]
+fn parse_json<T>(lines: &[String]) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_json::from_str(&lines.join("\n")).map_err(|err| Error::Other {
+        message: "failed to parse an output as JSON".to_string(),
+        err: err.to_string(),
+    })
+}
+
 impl<S> Healthcheck for ReplSession<S>
 where
     S: Healthcheck,
@@ -568,3 +1595,82 @@ where
         S::consume(Pin::new(self.get_session_mut()), amt)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(split_command("a\nb", false), ["a\nb"]);
+        assert_eq!(split_command("echo 1", true), ["echo 1"]);
+        assert_eq!(split_command("a\r\nb\n", true), ["a", "b", ""]);
+    }
+
+    #[test]
+    fn test_sync_needle() {
+        let marker = sync_marker();
+        assert_ne!(marker, sync_marker());
+
+        let needle = sync_needle(&marker);
+        let partial = format!("$ {}", marker);
+        assert!(needle.check(partial.as_bytes(), false).unwrap().is_empty());
+
+        let output = format!("$ {}\r\n$ ", marker);
+        assert!(!needle.check(output.as_bytes(), false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_exit_code() {
+        assert_eq!(
+            parse_exit_code(b"echo $?\r\n127\r\n", "echo $?").unwrap(),
+            127
+        );
+        assert_eq!(parse_exit_code(b"0\n", "echo $?").unwrap(), 0);
+        assert!(parse_exit_code(b"echo $?\r\n", "echo $?").is_err());
+    }
+
+    #[test]
+    fn test_parse_debugger_exit() {
+        let gdb = b"[Inferior 1 (process 42) exited with code 012]\r\n";
+        assert_eq!(parse_debugger_exit(Debugger::Gdb, gdb), Some(10));
+
+        let gdb = b"[Inferior 1 (process 42) exited normally]\r\n";
+        assert_eq!(parse_debugger_exit(Debugger::Gdb, gdb), Some(0));
+
+        let gdb = b"Breakpoint 1, main () at main.c:3\r\n";
+        assert_eq!(parse_debugger_exit(Debugger::Gdb, gdb), None);
+
+        let mi = b"*stopped,reason=\"exited\",exit-code=\"03\"\r\n";
+        assert_eq!(parse_debugger_exit(Debugger::GdbMi, mi), Some(3));
+
+        let mi = b"*stopped,reason=\"breakpoint-hit\",bkptno=\"1\"\r\n";
+        assert_eq!(parse_debugger_exit(Debugger::GdbMi, mi), None);
+
+        let lldb = b"Process 42 exited with status = 1 (0x00000001)\r\n";
+        assert_eq!(parse_debugger_exit(Debugger::Lldb, lldb), Some(1));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let lines = parse_lines(b"ls\r\nfile1\r\nfile2\r\n\r\n", "ls");
+        assert_eq!(lines, ["file1", "file2"]);
+
+        let lines = parse_lines(b"file1\nfile2", "ls");
+        assert_eq!(lines, ["file1", "file2"]);
+
+        let lines = parse_lines(b"\r\n", "true");
+        assert!(lines.is_empty());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_parse_json() {
+        let lines = parse_lines(b"cat data.json\r\n{\"a\": [1,\r\n 2]}\r\n", "cat data.json");
+        let value: std::collections::HashMap<String, Vec<u32>> = parse_json(&lines).unwrap();
+        assert_eq!(value["a"], [1, 2]);
+
+        let err = parse_json::<Vec<u32>>(&["{".to_owned()]).unwrap_err();
+        assert!(matches!(err, Error::Other { .. }));
+    }
+}
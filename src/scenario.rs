@@ -0,0 +1,423 @@
+//! This module contains a [`Scenario`] which records steps run against a session
+//! and renders them as a human readable report.
+//!
+//! It's meant for acceptance tests of command line programs,
+//! where a report is attached to a test run.
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use expectrl::{scenario::Scenario, spawn, Regex};
+//!
+//! let session = spawn("ftp speedtest.tele2.net").unwrap();
+//!
+//! let mut scenario = Scenario::new("anonymous login", session);
+//! scenario.expect(Regex("Name \\(.*\\):")).unwrap();
+//! scenario.send_line("anonymous").unwrap();
+//! scenario.expect("Password").unwrap();
+//!
+//! std::fs::write("report.md", scenario.to_markdown()).unwrap();
+//! ```
+
+use std::{
+    fmt::{self, Write},
+    time::{Duration, Instant},
+};
+
+use crate::{Captures, Error, Needle};
+
+#[cfg(not(feature = "async"))]
+use crate::Expect;
+
+#[cfg(feature = "async")]
+use crate::AsyncExpect;
+
+/// A kind of a recorded step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Bytes were sent to a process.
+    Send,
+    /// A line was sent to a process.
+    SendLine,
+    /// A pattern was expected.
+    Expect,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Send => f.write_str("send"),
+            Action::SendLine => f.write_str("send line"),
+            Action::Expect => f.write_str("expect"),
+        }
+    }
+}
+
+/// A step recorded by a [`Scenario`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScenarioStep {
+    action: Action,
+    argument: String,
+    error: Option<String>,
+    duration: Duration,
+    output: Vec<u8>,
+}
+
+impl ScenarioStep {
+    /// Returns a kind of the step.
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    /// Returns a description of an argument of the step,
+    /// a sent text or an expected pattern.
+    pub fn argument(&self) -> &str {
+        &self.argument
+    }
+
+    /// Checks whether the step succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Returns an error message of a failed step.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns a time the step took.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns an output captured by an expect step, including the match.
+    ///
+    /// It's empty for send steps.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+}
+
+/// Scenario is a wrapper of a session which records each send and expect call as a step.
+///
+/// The steps can be rendered as a markdown or an HTML report,
+/// see [`Scenario::to_markdown`] and [`Scenario::to_html`].
+#[derive(Debug)]
+pub struct Scenario<E> {
+    name: String,
+    session: E,
+    steps: Vec<ScenarioStep>,
+}
+
+impl<E> Scenario<E> {
+    /// Creates a scenario which runs steps against the session.
+    pub fn new<N>(name: N, session: E) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            session,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Returns a name of the scenario.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns recorded steps.
+    pub fn steps(&self) -> &[ScenarioStep] {
+        &self.steps
+    }
+
+    /// Checks whether all recorded steps succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.steps.iter().all(ScenarioStep::is_ok)
+    }
+
+    /// Returns a reference to the session.
+    pub fn get_ref(&self) -> &E {
+        &self.session
+    }
+
+    /// Returns a mutable reference to the session.
+    ///
+    /// Calls made directly on the session are not recorded.
+    pub fn get_mut(&mut self) -> &mut E {
+        &mut self.session
+    }
+
+    /// Returns the session.
+    pub fn into_inner(self) -> E {
+        self.session
+    }
+
+    /// Renders the recorded steps as a markdown document.
+    pub fn to_markdown(&self) -> String {
+        let mut doc = String::new();
+        let _ = writeln!(doc, "# {}", self.name);
+        let _ = writeln!(doc);
+        let _ = writeln!(doc, "Status: **{}**", status(self.is_ok()));
+        let _ = writeln!(doc);
+        let _ = writeln!(doc, "| # | Step | Argument | Status | Duration |");
+        let _ = writeln!(doc, "|---|------|----------|--------|----------|");
+        for (i, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(
+                doc,
+                "| {} | {} | `{}` | {} | {:?} |",
+                i + 1,
+                step.action,
+                escape_markdown(&step.argument),
+                status(step.is_ok()),
+                step.duration,
+            );
+        }
+
+        for (i, step) in self.steps.iter().enumerate() {
+            if step.output.is_empty() && step.error.is_none() {
+                continue;
+            }
+
+            let _ = writeln!(doc);
+            let _ = writeln!(doc, "## {}. {}", i + 1, step.action);
+            if let Some(err) = &step.error {
+                let _ = writeln!(doc);
+                let _ = writeln!(doc, "Error: {}", err);
+            }
+
+            if !step.output.is_empty() {
+                let output = String::from_utf8_lossy(&step.output);
+                let _ = writeln!(doc);
+                let _ = writeln!(doc, "```text");
+                let _ = writeln!(doc, "{}", output.trim_end_matches('\n'));
+                let _ = writeln!(doc, "```");
+            }
+        }
+
+        doc
+    }
+
+    /// Renders the recorded steps as a standalone HTML page.
+    pub fn to_html(&self) -> String {
+        let name = escape_html(&self.name);
+
+        let mut doc = String::new();
+        let _ = writeln!(doc, "<!DOCTYPE html>");
+        let _ = writeln!(doc, "<html>");
+        let _ = writeln!(
+            doc,
+            "<head><meta charset=\"utf-8\"><title>{}</title></head>",
+            name
+        );
+        let _ = writeln!(doc, "<body>");
+        let _ = writeln!(doc, "<h1>{}</h1>", name);
+        let _ = writeln!(
+            doc,
+            "<p>Status: <strong class=\"{0}\">{0}</strong></p>",
+            status(self.is_ok())
+        );
+        let _ = writeln!(doc, "<table>");
+        let _ = writeln!(
+            doc,
+            "<tr><th>#</th><th>Step</th><th>Argument</th><th>Status</th><th>Duration</th><th>Output</th></tr>"
+        );
+        for (i, step) in self.steps.iter().enumerate() {
+            let mut output = escape_html(&String::from_utf8_lossy(&step.output));
+            if let Some(err) = &step.error {
+                output = escape_html(err);
+            }
+
+            let _ = writeln!(
+                doc,
+                "<tr class=\"{}\"><td>{}</td><td>{}</td><td><code>{}</code></td><td>{}</td><td>{:?}</td><td><pre>{}</pre></td></tr>",
+                status(step.is_ok()),
+                i + 1,
+                step.action,
+                escape_html(&step.argument),
+                status(step.is_ok()),
+                step.duration,
+                output,
+            );
+        }
+        let _ = writeln!(doc, "</table>");
+        let _ = writeln!(doc, "</body>");
+        let _ = writeln!(doc, "</html>");
+
+        doc
+    }
+
+    fn record<T>(
+        &mut self,
+        action: Action,
+        argument: String,
+        start: Instant,
+        result: &Result<T, Error>,
+        output: &[u8],
+    ) {
+        self.steps.push(ScenarioStep {
+            action,
+            argument,
+            error: result.as_ref().err().map(ToString::to_string),
+            duration: start.elapsed(),
+            output: output.to_vec(),
+        });
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<E> Scenario<E>
+where
+    E: Expect,
+{
+    /// Sends bytes to a process and records the step.
+    pub fn send<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        let argument = String::from_utf8_lossy(buf.as_ref()).into_owned();
+        let start = Instant::now();
+        let result = self.session.send(buf);
+        self.record(Action::Send, argument, start, &result, &[]);
+        result
+    }
+
+    /// Sends a line to a process and records the step.
+    pub fn send_line<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        let argument = String::from_utf8_lossy(buf.as_ref()).into_owned();
+        let start = Instant::now();
+        let result = self.session.send_line(buf);
+        self.record(Action::SendLine, argument, start, &result, &[]);
+        result
+    }
+
+    /// Expects a pattern and records the step.
+    pub fn expect<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: Needle + fmt::Debug,
+    {
+        let argument = format!("{:?}", needle);
+        let start = Instant::now();
+        let result = self.session.expect(needle);
+        let output = result.as_ref().map(Captures::as_bytes).unwrap_or_default();
+        self.record(Action::Expect, argument, start, &result, output);
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+impl<E> Scenario<E>
+where
+    E: AsyncExpect,
+{
+    /// Sends bytes to a process and records the step.
+    pub async fn send<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        let argument = String::from_utf8_lossy(buf.as_ref()).into_owned();
+        let start = Instant::now();
+        let result = self.session.send(buf).await;
+        self.record(Action::Send, argument, start, &result, &[]);
+        result
+    }
+
+    /// Sends a line to a process and records the step.
+    pub async fn send_line<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        let argument = String::from_utf8_lossy(buf.as_ref()).into_owned();
+        let start = Instant::now();
+        let result = self.session.send_line(buf).await;
+        self.record(Action::SendLine, argument, start, &result, &[]);
+        result
+    }
+
+    /// Expects a pattern and records the step.
+    pub async fn expect<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: Needle + fmt::Debug,
+    {
+        let argument = format!("{:?}", needle);
+        let start = Instant::now();
+        let result = self.session.expect(needle).await;
+        let output = result.as_ref().map(Captures::as_bytes).unwrap_or_default();
+        self.record(Action::Expect, argument, start, &result, output);
+        result
+    }
+}
+
+fn status(ok: bool) -> &'static str {
+    match ok {
+        true => "passed",
+        false => "failed",
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace('`', "'")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario() -> Scenario<()> {
+        let mut scenario = Scenario::new("login <test>", ());
+        let start = Instant::now();
+        scenario.record::<()>(Action::SendLine, "root".into(), start, &Ok(()), &[]);
+        scenario.record::<()>(
+            Action::Expect,
+            "\"a|b\"".into(),
+            start,
+            &Err(Error::expect_timeout(String::new(), &[])),
+            b"",
+        );
+        scenario.record::<()>(Action::Expect, "\"$\"".into(), start, &Ok(()), b"<ok>\n$");
+        scenario
+    }
+
+    #[test]
+    fn test_markdown() {
+        let scenario = scenario();
+        assert!(!scenario.is_ok());
+        assert_eq!(scenario.steps().len(), 3);
+
+        let doc = scenario.to_markdown();
+        assert!(doc.starts_with("# login <test>\n\nStatus: **failed**\n"));
+        assert!(doc.contains("| 1 | send line | `root` | passed |"));
+        assert!(doc.contains("| 2 | expect | `\"a\\|b\"` | failed |"));
+        assert!(doc.contains("## 2. expect\n\nError: Reached a timeout"));
+        assert!(doc.contains("## 3. expect\n\n```text\n<ok>\n$\n```\n"));
+    }
+
+    #[test]
+    fn test_html() {
+        let doc = scenario().to_html();
+        assert!(doc.contains("<h1>login &lt;test&gt;</h1>"));
+        assert!(doc.contains("<pre>&lt;ok&gt;\n$</pre>"));
+        assert!(doc.contains("<tr class=\"failed\"><td>2</td>"));
+    }
+}
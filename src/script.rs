@@ -0,0 +1,335 @@
+//! This module contains a [`Script`] which runs a declarative list of steps against a session.
+//!
+//! It allows to describe common dialogs (login, configuration wizards and alike) as data.
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use expectrl::{script::{Script, Step}, spawn, Regex};
+//!
+//! let mut session = spawn("ftp speedtest.tele2.net").unwrap();
+//!
+//! let script = Script::new()
+//!     .expect(Regex("Name \\(.*\\):"))
+//!     .send_line("anonymous")
+//!     .expect("Password")
+//!     .send_line("test")
+//!     .branch(vec![
+//!         (Box::new("ftp>"), vec![Step::SendLine(b"cd upload".to_vec())]),
+//!         (Box::new("Login failed"), vec![Step::SendLine(b"bye".to_vec())]),
+//!     ]);
+//!
+//! script.run(&mut session).unwrap();
+//! ```
+
+use std::fmt;
+
+use crate::{check_macros::ExpectArms, Error, Needle};
+
+#[cfg(not(feature = "async"))]
+use crate::Expect;
+
+#[cfg(feature = "async")]
+use crate::AsyncExpect;
+
+/// A step of a [`Script`].
+pub enum Step {
+    /// Send bytes to a process.
+    Send(Vec<u8>),
+    /// Send a line to a process.
+    SendLine(Vec<u8>),
+    /// Wait until a pattern is matched.
+    Expect(Box<dyn Needle>),
+    /// Wait until any of the patterns is matched and run the steps of the matched one.
+    ///
+    /// Patterns are checked in order so the first one has a priority.
+    Branch(Vec<(Box<dyn Needle>, Vec<Step>)>),
+    /// Run the steps repeatedly until a [`Step::Break`] is reached.
+    Loop(Vec<Step>),
+    /// Stop the closest [`Step::Loop`],
+    /// or the whole script if it's not inside of a loop.
+    Break,
+}
+
+impl fmt::Debug for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Send(buf) => f
+                .debug_tuple("Send")
+                .field(&String::from_utf8_lossy(buf))
+                .finish(),
+            Step::SendLine(buf) => f
+                .debug_tuple("SendLine")
+                .field(&String::from_utf8_lossy(buf))
+                .finish(),
+            Step::Expect(_) => f.debug_tuple("Expect").finish_non_exhaustive(),
+            Step::Branch(arms) => {
+                let steps = arms.iter().map(|(_, steps)| steps).collect::<Vec<_>>();
+                f.debug_tuple("Branch").field(&steps).finish()
+            }
+            Step::Loop(steps) => f.debug_tuple("Loop").field(steps).finish(),
+            Step::Break => f.write_str("Break"),
+        }
+    }
+}
+
+/// Script is a list of [`Step`]s which are run one by one.
+///
+/// If a step fails [`Error::Script`] is returned,
+/// it contains a position of the step and the original error.
+#[derive(Debug, Default)]
+pub struct Script {
+    steps: Vec<Step>,
+}
+
+impl Script {
+    /// Creates an empty script.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step.
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Add a [`Step::Send`].
+    pub fn send(self, buf: impl Into<Vec<u8>>) -> Self {
+        self.step(Step::Send(buf.into()))
+    }
+
+    /// Add a [`Step::SendLine`].
+    pub fn send_line(self, line: impl Into<Vec<u8>>) -> Self {
+        self.step(Step::SendLine(line.into()))
+    }
+
+    /// Add a [`Step::Expect`].
+    pub fn expect<N>(self, needle: N) -> Self
+    where
+        N: Needle + 'static,
+    {
+        self.step(Step::Expect(Box::new(needle)))
+    }
+
+    /// Add a [`Step::Branch`].
+    pub fn branch(self, arms: Vec<(Box<dyn Needle>, Vec<Step>)>) -> Self {
+        self.step(Step::Branch(arms))
+    }
+
+    /// Add a [`Step::Loop`].
+    pub fn repeat(self, steps: Vec<Step>) -> Self {
+        self.step(Step::Loop(steps))
+    }
+
+    /// Returns the steps of the script.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Runs the script.
+    #[cfg(not(feature = "async"))]
+    pub fn run<E>(&self, session: &mut E) -> Result<(), Error>
+    where
+        E: Expect,
+    {
+        let mut runner = Runner::new(&self.steps);
+        while let Some(step) = runner.next_step() {
+            let result = match step {
+                Step::Send(buf) => session.send(buf),
+                Step::SendLine(buf) => session.send_line(buf),
+                Step::Expect(needle) => session.expect(needle).map(|_| ()),
+                Step::Branch(arms) => {
+                    let needles: Vec<&dyn Needle> =
+                        arms.iter().map(|(needle, _)| needle.as_ref()).collect();
+                    let arms_needle = ExpectArms::new(&needles);
+                    session
+                        .expect(&arms_needle)
+                        .map(|_| runner.enter(&arms[arms_needle.matched()].1, false))
+                }
+                Step::Loop(steps) => {
+                    runner.enter(steps, true);
+                    Ok(())
+                }
+                Step::Break => {
+                    runner.exit();
+                    Ok(())
+                }
+            };
+
+            if let Err(err) = result {
+                return Err(runner.error(err));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the script.
+    #[cfg(feature = "async")]
+    pub async fn run<E>(&self, session: &mut E) -> Result<(), Error>
+    where
+        E: AsyncExpect,
+    {
+        let mut runner = Runner::new(&self.steps);
+        while let Some(step) = runner.next_step() {
+            let result = match step {
+                Step::Send(buf) => session.send(buf).await,
+                Step::SendLine(buf) => session.send_line(buf).await,
+                Step::Expect(needle) => session.expect(needle).await.map(|_| ()),
+                Step::Branch(arms) => {
+                    let needles: Vec<&dyn Needle> =
+                        arms.iter().map(|(needle, _)| needle.as_ref()).collect();
+                    let arms_needle = ExpectArms::new(&needles);
+                    session
+                        .expect(&arms_needle)
+                        .await
+                        .map(|_| runner.enter(&arms[arms_needle.matched()].1, false))
+                }
+                Step::Loop(steps) => {
+                    runner.enter(steps, true);
+                    Ok(())
+                }
+                Step::Break => {
+                    runner.exit();
+                    Ok(())
+                }
+            };
+
+            if let Err(err) = result {
+                return Err(runner.error(err));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Vec<Step>> for Script {
+    fn from(steps: Vec<Step>) -> Self {
+        Self { steps }
+    }
+}
+
+/// Runner walks through nested steps.
+///
+/// It uses an explicit stack instead of recursion,
+/// so sync and async versions share the same logic.
+#[derive(Debug)]
+struct Runner<'a> {
+    stack: Vec<Frame<'a>>,
+}
+
+#[derive(Debug)]
+struct Frame<'a> {
+    steps: &'a [Step],
+    next: usize,
+    is_loop: bool,
+}
+
+impl<'a> Runner<'a> {
+    fn new(steps: &'a [Step]) -> Self {
+        Self {
+            stack: vec![Frame {
+                steps,
+                next: 0,
+                is_loop: false,
+            }],
+        }
+    }
+
+    /// Returns a next step to run.
+    fn next_step(&mut self) -> Option<&'a Step> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            if let Some(step) = frame.steps.get(frame.next) {
+                frame.next += 1;
+                return Some(step);
+            }
+
+            if frame.is_loop && !frame.steps.is_empty() {
+                frame.next = 0;
+            } else {
+                let _ = self.stack.pop();
+            }
+        }
+    }
+
+    /// Starts running nested steps.
+    fn enter(&mut self, steps: &'a [Step], is_loop: bool) {
+        self.stack.push(Frame {
+            steps,
+            next: 0,
+            is_loop,
+        });
+    }
+
+    /// Stops the closest loop or the script if there's no loop.
+    fn exit(&mut self) {
+        while let Some(frame) = self.stack.pop() {
+            if frame.is_loop {
+                break;
+            }
+        }
+    }
+
+    /// Wraps an error of a current step.
+    fn error(&self, err: Error) -> Error {
+        Error::Script {
+            step: self.stack.iter().map(|frame| frame.next - 1).collect(),
+            err: Box::new(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runner() {
+        let steps = vec![
+            Step::Send(b"1".to_vec()),
+            Step::Loop(vec![
+                Step::Send(b"2".to_vec()),
+                Step::Branch(vec![(Box::new("3"), vec![Step::Break])]),
+            ]),
+            Step::Send(b"4".to_vec()),
+            Step::Break,
+            Step::Send(b"5".to_vec()),
+        ];
+
+        let mut runner = Runner::new(&steps);
+        assert!(matches!(runner.next_step(), Some(Step::Send(buf)) if buf == b"1"));
+        match runner.next_step() {
+            Some(Step::Loop(steps)) => runner.enter(steps, true),
+            step => panic!("unexpected step {:?}", step),
+        }
+
+        for _ in 0..2 {
+            assert!(matches!(runner.next_step(), Some(Step::Send(buf)) if buf == b"2"));
+            assert!(matches!(runner.next_step(), Some(Step::Branch(_))));
+        }
+
+        match runner.next_step() {
+            Some(Step::Send(_)) => (),
+            step => panic!("unexpected step {:?}", step),
+        }
+
+        match runner.next_step() {
+            Some(Step::Branch(arms)) => runner.enter(&arms[0].1, false),
+            step => panic!("unexpected step {:?}", step),
+        }
+
+        assert!(matches!(runner.next_step(), Some(Step::Break)));
+
+        let err = runner.error(Error::Eof);
+        assert!(matches!(err, Error::Script { step, .. } if step == [1, 1, 0]));
+
+        runner.exit();
+
+        assert!(matches!(runner.next_step(), Some(Step::Send(buf)) if buf == b"4"));
+        assert!(matches!(runner.next_step(), Some(Step::Break)));
+        runner.exit();
+        assert!(runner.next_step().is_none());
+    }
+}
@@ -0,0 +1,150 @@
+//! This module contains a [select] function which waits on a set of sessions.
+
+use std::time::{Duration, Instant};
+
+use crate::{Captures, Error, Needle};
+
+#[cfg(not(feature = "async"))]
+use crate::Expect;
+
+#[cfg(feature = "async")]
+use crate::AsyncExpect;
+
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_millis(10000);
+
+/// Select waits until a pattern is matched in any of the given sessions.
+///
+/// It returns an index of a session where the match was found and the match itself.
+/// Sessions are checked in order so the first one has a priority.
+///
+/// A session which reached EOF is skipped;
+/// [`Error::Eof`] is returned only when all of the sessions are reached EOF.
+///
+/// It uses a 10 seconds timeout, see [`select_timeout`] to set a different one.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{select, spawn, Expect};
+///
+/// let mut sessions = [spawn("cat").unwrap(), spawn("cat").unwrap()];
+/// sessions[1].send_line("Hello World").unwrap();
+///
+/// let (index, _) = select(&mut sessions, "Hello").unwrap();
+/// assert_eq!(index, 1);
+/// ```
+#[cfg(not(feature = "async"))]
+pub fn select<E, N>(sessions: &mut [E], needle: N) -> Result<(usize, Captures), Error>
+where
+    E: Expect,
+    N: Needle,
+{
+    select_timeout(sessions, needle, Some(DEFAULT_TIMEOUT))
+}
+
+/// Select waits until a pattern is matched in any of the given sessions.
+///
+/// If `timeout` is [`None`] it waits forever.
+///
+/// See [`select`].
+#[cfg(not(feature = "async"))]
+pub fn select_timeout<E, N>(
+    sessions: &mut [E],
+    needle: N,
+    timeout: Option<Duration>,
+) -> Result<(usize, Captures), Error>
+where
+    E: Expect,
+    N: Needle,
+{
+    let start = Instant::now();
+    let mut eof = vec![false; sessions.len()];
+    loop {
+        for (i, session) in sessions.iter_mut().enumerate() {
+            if eof[i] {
+                continue;
+            }
+
+            match session.check(&needle) {
+                Ok(found) if !found.is_empty() => return Ok((i, found)),
+                Ok(_) => (),
+                Err(Error::Eof) => eof[i] = true,
+                Err(err) => return Err(err),
+            }
+        }
+
+        if eof.iter().all(|eof| *eof) {
+            return Err(Error::Eof);
+        }
+
+        if let Some(timeout) = timeout {
+            if start.elapsed() > timeout {
+                return Err(Error::expect_timeout(Needle::describe(&needle), &[]));
+            }
+        }
+    }
+}
+
+/// Select waits until a pattern is matched in any of the given sessions.
+///
+/// It returns an index of a session where the match was found and the match itself.
+/// Sessions are checked in order so the first one has a priority.
+///
+/// A session which reached EOF is skipped;
+/// [`Error::Eof`] is returned only when all of the sessions are reached EOF.
+///
+/// It uses a 10 seconds timeout, see [`select_timeout`] to set a different one.
+#[cfg(feature = "async")]
+pub async fn select<E, N>(sessions: &mut [E], needle: N) -> Result<(usize, Captures), Error>
+where
+    E: AsyncExpect,
+    N: Needle,
+{
+    select_timeout(sessions, needle, Some(DEFAULT_TIMEOUT)).await
+}
+
+/// Select waits until a pattern is matched in any of the given sessions.
+///
+/// If `timeout` is [`None`] it waits forever.
+///
+/// See [`select`].
+#[cfg(feature = "async")]
+pub async fn select_timeout<E, N>(
+    sessions: &mut [E],
+    needle: N,
+    timeout: Option<Duration>,
+) -> Result<(usize, Captures), Error>
+where
+    E: AsyncExpect,
+    N: Needle,
+{
+    let start = Instant::now();
+    let mut eof = vec![false; sessions.len()];
+    loop {
+        for (i, session) in sessions.iter_mut().enumerate() {
+            if eof[i] {
+                continue;
+            }
+
+            match session.check(&needle).await {
+                Ok(found) if !found.is_empty() => return Ok((i, found)),
+                Ok(_) => (),
+                Err(Error::Eof) => eof[i] = true,
+                Err(err) => return Err(err),
+            }
+        }
+
+        if eof.iter().all(|eof| *eof) {
+            return Err(Error::Eof);
+        }
+
+        if let Some(timeout) = timeout {
+            if start.elapsed() > timeout {
+                return Err(Error::expect_timeout(Needle::describe(&needle), &[]));
+            }
+        }
+
+        // check doesn't wait for data so we give other tasks a chance to run.
+        futures_lite::future::yield_now().await;
+    }
+}
@@ -0,0 +1,90 @@
+//! This module contains a set of abort patterns of a session.
+
+use std::fmt::{self, Debug};
+
+use crate::{Captures, Error, Needle};
+
+/// AbortPatterns is a list of needles which must never appear in an output.
+///
+/// They are checked on each iteration of an `expect` call,
+/// so a crash of a process is reported right away instead of a timeout.
+#[derive(Default)]
+pub(crate) struct AbortPatterns {
+    patterns: Vec<(String, Box<dyn Needle + Send>)>,
+}
+
+impl AbortPatterns {
+    pub(crate) fn push<N>(&mut self, needle: N)
+    where
+        N: Needle + Debug + Send + 'static,
+    {
+        let name = format!("{:?}", needle);
+        self.patterns.push((name, Box::new(needle)));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.patterns.clear();
+    }
+
+    /// Checks a buffer against all patterns.
+    /// `buf[..checked]` is expected to be checked already.
+    ///
+    /// Returns a description of a first matched pattern and a match.
+    pub(crate) fn check(
+        &self,
+        buf: &[u8],
+        checked: usize,
+        eof: bool,
+    ) -> Result<Option<(String, Captures)>, Error> {
+        for (name, needle) in &self.patterns {
+            let found = needle.check_from(buf, checked, eof)?;
+            if !found.is_empty() {
+                let end_index = Captures::right_most_index(&found);
+                let captures = Captures::new(buf[..end_index].to_vec(), found);
+                return Ok(Some((name.clone(), captures)));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Debug for AbortPatterns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.patterns.iter().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Regex;
+
+    #[test]
+    fn test_abort_patterns() {
+        let mut patterns = AbortPatterns::default();
+        assert!(patterns.check(b"panic", 0, false).unwrap().is_none());
+
+        patterns.push(Regex("panic|Segmentation fault"));
+        patterns.push("ERROR");
+
+        assert!(patterns.check(b"Hello World", 0, false).unwrap().is_none());
+
+        let (name, captures) = patterns.check(b"1\nERROR 2\n", 0, false).unwrap().unwrap();
+        assert_eq!(name, "\"ERROR\"");
+        assert_eq!(captures.before(), b"1\n");
+        assert_eq!(captures.as_bytes(), b"1\nERROR");
+
+        let (name, _) = patterns
+            .check(b"Segmentation fault", 0, false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(name, "Regex(\"panic|Segmentation fault\")");
+
+        patterns.clear();
+        assert!(patterns.check(b"panic", 0, false).unwrap().is_none());
+    }
+}
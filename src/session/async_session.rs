@@ -1,10 +1,12 @@
 //! Module contains an async version of Session structure.
 
 use std::{
+    future::Future,
     io::{self, IoSliceMut},
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use futures_lite::{
@@ -12,16 +14,47 @@ use futures_lite::{
 };
 
 use crate::{
-    process::{Healthcheck, Termios},
-    AsyncExpect, Captures, Error, Expect, Needle,
+    clock::{default_clock, Clock},
+    needle::Match,
+    process::{
+        DropPolicy, ExitStatus, Healthcheck, OnDrop, ResourceUsage, Termios, Usage, Wait,
+        WindowSize,
+    },
+    session::SessionMetrics,
+    session::{
+        abort::AbortPatterns,
+        diff::{assert_lines_eq, trim_line_ending},
+        keepalive::Keepalive,
+        overflow::{BufferLimit, OverflowPolicy},
+        pager::Pager,
+        retry::RetryPolicy,
+        timestamps::Timestamps,
+        typing::TypingOptions,
+        unknown_capture_group,
+        watchdog::Watchdog,
+        within::Within,
+        ConsumeMode, DetachedSession, WatchdogAction, BRACKETED_PASTE_END, BRACKETED_PASTE_START,
+        PASTE_CHUNK_DELAY, PASTE_CHUNK_SIZE, POLL_INTERVAL, READ_CHUNK_SIZE,
+    },
+    Any, AsyncExpect, AsyncNeedle, Captures, CapturesRef, Eof, Error, Expect, LineEnding, Needle,
+    Regex,
 };
 
+#[cfg(feature = "snapshot")]
+use crate::session::Snapshot;
+
 /// Session represents a spawned process and its streams.
 /// It controlls process and communication with it.
 #[derive(Debug)]
 pub struct Session<P, S> {
     process: P,
     stream: Stream<S>,
+    line_ending: LineEnding,
+    bracketed_paste: bool,
+    name: Option<String>,
+    poll_interval: Duration,
+    metrics: SessionMetrics,
+    within: Option<Within>,
 }
 
 // GEt back to the solution where Logger is just dyn Write instead of all these magic with type system.....
@@ -32,6 +65,12 @@ impl<P, S> Session<P, S> {
         Ok(Self {
             process,
             stream: Stream::new(stream),
+            line_ending: LineEnding::default(),
+            bracketed_paste: false,
+            name: None,
+            poll_interval: POLL_INTERVAL,
+            metrics: SessionMetrics::default(),
+            within: None,
         })
     }
 
@@ -60,6 +99,11 @@ impl<P, S> Session<P, S> {
         self.stream.set_expect_timeout(expect_timeout);
     }
 
+    /// Get the pty session's expect timeout.
+    pub fn get_expect_timeout(&self) -> Option<Duration> {
+        self.stream.expect_timeout
+    }
+
     /// Set a expect algorithm to be either gready or lazy.
     ///
     /// Default algorithm is gready.
@@ -69,19 +113,290 @@ impl<P, S> Session<P, S> {
         self.stream.expect_lazy = is_lazy;
     }
 
+    /// Set what is consumed from a buffer by a successful expect call.
+    ///
+    /// By default bytes after a match are left in the buffer ([`ConsumeMode::UpToMatch`]).
+    pub fn set_consume_mode(&mut self, mode: ConsumeMode) {
+        self.stream.consume_mode = mode;
+    }
+
+    /// Returns bytes which were read from a process but not yet consumed by expect calls.
+    ///
+    /// It doesn't read anything from the process.
+    pub fn peek_buffer(&self) -> &[u8] {
+        self.stream.get_available()
+    }
+
+    /// Returns a time passed since the last output was read from a process.
+    ///
+    /// It's `None` if nothing was read yet.
+    /// The output is read only by [`AsyncExpect`] calls and other reads from the session,
+    /// so it doesn't account data which is waiting in a process stream.
+    ///
+    /// See also [`Captures::timestamp`].
+    pub fn time_since_last_output(&self) -> Option<Duration> {
+        self.stream.stream.last_output().map(|time| time.elapsed())
+    }
+
+    /// Set a line ending which is used by [`AsyncExpect::send_line`].
+    ///
+    /// Default is `\r\n` on windows and `\n` on other platforms.
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        self.line_ending = line_ending;
+    }
+
+    /// Get a line ending which is used by [`AsyncExpect::send_line`].
+    pub fn get_line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Set whether a process has bracketed paste mode enabled.
+    ///
+    /// It changes the way [`Session::paste`] sends a text.
+    /// It's off by default.
+    pub fn set_bracketed_paste(&mut self, on: bool) {
+        self.bracketed_paste = on;
+    }
+
+    /// Set a name of the session.
+    ///
+    /// The name is included in errors returned by [`AsyncExpect::expect`],
+    /// see [`Error::Session`], and in a log set by [`session::log`].
+    ///
+    /// [`session::log`]: crate::session::log
+    pub fn set_name<N>(&mut self, name: N)
+    where
+        N: Into<String>,
+    {
+        self.name = Some(name.into());
+    }
+
+    /// Get a name of the session.
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Set a watchdog which is triggered if no output was received
+    /// for the `period` during an [`AsyncExpect::expect`] call.
+    ///
+    /// The callback gets a time passed since the last output,
+    /// and decides whether to keep waiting, to nudge a process or to abort the call.
+    pub fn set_watchdog<F>(&mut self, period: Duration, callback: F)
+    where
+        F: FnMut(Duration) -> WatchdogAction + Send + 'static,
+    {
+        self.stream.watchdog = Some(Watchdog::new(period, callback, self.stream.clock.now()));
+    }
+
+    /// Remove a watchdog set by [`Session::set_watchdog`].
+    pub fn remove_watchdog(&mut self) {
+        self.stream.watchdog = None;
+    }
+
+    /// Set a keepalive which writes `bytes` to a process each `interval`
+    /// while an [`AsyncExpect::expect`] call is waiting for an output.
+    ///
+    /// It keeps alive remote consoles and SSH sessions which disconnect idle clients.
+    /// An interval starts over on each call,
+    /// and the bytes are written only while the session waits inside a call,
+    /// as nothing is running in background.
+    pub fn set_keepalive<B>(&mut self, interval: Duration, bytes: B)
+    where
+        B: AsRef<[u8]>,
+    {
+        let now = self.stream.clock.now();
+        self.stream.keepalive = Some(Keepalive::new(interval, bytes.as_ref().to_vec(), now));
+    }
+
+    /// Remove a keepalive set by [`Session::set_keepalive`].
+    pub fn remove_keepalive(&mut self) {
+        self.stream.keepalive = None;
+    }
+
+    /// Add a pattern which must never appear in an output.
+    ///
+    /// Abort patterns are checked by every [`AsyncExpect`] call before a given needle,
+    /// and if one is found the call fails with [`Error::Aborted`].
+    /// The output up to the end of the match is consumed.
+    ///
+    /// It helps to turn a crash of a process into a descriptive error instead of a timeout.
+    pub fn add_abort_pattern<N>(&mut self, needle: N)
+    where
+        N: Needle + std::fmt::Debug + Send + 'static,
+    {
+        self.stream.abort_patterns.push(needle);
+    }
+
+    /// Remove all patterns added by [`Session::add_abort_pattern`].
+    pub fn clear_abort_patterns(&mut self) {
+        self.stream.abort_patterns.clear();
+    }
+
+    /// Set a maximum size of a buffer which keeps an output not consumed by [`AsyncExpect`] calls.
+    ///
+    /// The `policy` decides what happens when the limit is reached.
+    /// By default the buffer is unbounded.
+    pub fn set_buffer_limit(&mut self, limit: usize, policy: OverflowPolicy) {
+        self.stream.buffer_limit = Some(BufferLimit::new(limit, policy));
+    }
+
+    /// Remove a limit set by [`Session::set_buffer_limit`].
+    pub fn remove_buffer_limit(&mut self) {
+        self.stream.buffer_limit = None;
+    }
+
+    /// Set a pager handler which answers pager prompts, like `--More--`,
+    /// while an [`AsyncExpect::expect`] call is waiting for an output.
+    ///
+    /// A prompt is removed from the buffer so it never gets into a match.
+    pub fn set_pager(&mut self, pager: Pager) {
+        self.stream.pager = Some(pager);
+    }
+
+    /// Remove a pager handler set by [`Session::set_pager`].
+    pub fn remove_pager(&mut self) {
+        self.stream.pager = None;
+    }
+
+    /// Set a clock which measures expect timeouts, a watchdog and a keepalive intervals
+    /// and delays of [`Session::expect_with_retry`].
+    ///
+    /// It's [`SystemClock`] by default,
+    /// [`ManualClock`] makes it possible to test a timeout handling without real waiting.
+    ///
+    /// [`SystemClock`]: crate::clock::SystemClock
+    /// [`ManualClock`]: crate::clock::ManualClock
+    pub fn set_clock<C>(&mut self, clock: C)
+    where
+        C: Clock + 'static,
+    {
+        self.stream.clock = Arc::new(clock);
+    }
+
+    /// Returns counters of the session, see [`SessionMetrics`].
+    pub fn metrics(&self) -> SessionMetrics {
+        let stream = &self.stream.stream;
+        self.metrics.add_io(stream.bytes_read, stream.bytes_written)
+    }
+
+    /// Reset counters of the session to zero.
+    pub fn reset_metrics(&mut self) {
+        self.metrics = SessionMetrics::default();
+        self.stream.stream.bytes_read = 0;
+        self.stream.stream.bytes_written = 0;
+    }
+
+    /// Set a maximum amount of bytes which is read from a process at once.
+    ///
+    /// Bigger chunks reduce an amount of reads for a process which prints a lot,
+    /// it's 248 bytes by default.
+    pub fn set_read_chunk_size(&mut self, size: usize) {
+        self.stream.stream.chunk_size = size.max(1);
+    }
+
+    /// Get a maximum amount of bytes which is read from a process at once.
+    pub fn get_read_chunk_size(&self) -> usize {
+        self.stream.stream.chunk_size
+    }
+
+    /// Preallocate the session buffer for at least `additional` more bytes.
+    ///
+    /// The buffer keeps its capacity when data is consumed,
+    /// so it saves reallocations for processes which print a lot at once.
+    pub fn reserve_buffer(&mut self, additional: usize) {
+        self.stream.stream.reserve(additional);
+    }
+
+    /// Set a pause between checks in [`Session::wait_for_exit`].
+    ///
+    /// It's 10 milliseconds by default.
+    /// Expect calls don't poll a process as they're woken up when an output is ready.
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
+
+    /// Get a pause between checks in [`Session::wait_for_exit`].
+    pub fn get_poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
     pub(crate) fn swap_stream<F: FnOnce(S) -> R, R>(
         mut self,
         new_stream: F,
     ) -> Result<Session<P, R>, Error> {
         let buf = self.stream.get_available().to_owned();
+        let consumed = self.stream.stream.consumed();
+        let consume_mode = self.stream.consume_mode;
+        let watchdog = self.stream.watchdog.take();
+        let keepalive = self.stream.keepalive.take();
+        let abort_patterns = std::mem::take(&mut self.stream.abort_patterns);
+        let buffer_limit = self.stream.buffer_limit.take();
+        let pager = self.stream.pager.take();
+        let clock = self.stream.clock.clone();
+        let chunk_size = self.stream.stream.chunk_size;
+        let metrics = self.metrics();
 
         let stream = self.stream.into_inner();
         let stream = new_stream(stream);
         let mut session = Session::new(self.process, stream)?;
         session.stream.keep(&buf);
+        session.stream.stream.consumed = consumed;
+        session.stream.consume_mode = consume_mode;
+        session.stream.watchdog = watchdog;
+        session.stream.keepalive = keepalive;
+        session.stream.abort_patterns = abort_patterns;
+        session.stream.buffer_limit = buffer_limit;
+        session.stream.pager = pager;
+        session.stream.clock = clock;
+        session.stream.stream.chunk_size = chunk_size;
+        session.line_ending = self.line_ending;
+        session.bracketed_paste = self.bracketed_paste;
+        session.name = self.name;
+        session.poll_interval = self.poll_interval;
+        session.metrics = metrics;
         Ok(session)
     }
 
+    /// Detaches the session from a controlling side.
+    ///
+    /// The process and the stream are left as they are,
+    /// so the session can be restored by [`DetachedSession::attach`].
+    pub fn detach(mut self) -> DetachedSession<P, S> {
+        let buffer = self.stream.get_available().to_owned();
+        let expect_timeout = self.stream.expect_timeout;
+        let expect_lazy = self.stream.expect_lazy;
+        let consume_mode = self.stream.consume_mode;
+        let watchdog = self.stream.watchdog.take();
+        let keepalive = self.stream.keepalive.take();
+        let abort_patterns = std::mem::take(&mut self.stream.abort_patterns);
+        let buffer_limit = self.stream.buffer_limit.take();
+        let pager = self.stream.pager.take();
+        let clock = self.stream.clock.clone();
+        let read_chunk_size = self.stream.stream.chunk_size;
+        let metrics = self.metrics();
+
+        DetachedSession {
+            process: self.process,
+            stream: self.stream.into_inner(),
+            buffer,
+            expect_timeout,
+            expect_lazy,
+            consume_mode,
+            line_ending: self.line_ending,
+            bracketed_paste: self.bracketed_paste,
+            name: self.name,
+            watchdog,
+            keepalive,
+            abort_patterns,
+            buffer_limit,
+            pager,
+            clock,
+            read_chunk_size,
+            poll_interval: self.poll_interval,
+            metrics,
+        }
+    }
+
     /// Verifyes if stream is empty or not.
     pub async fn is_empty(&mut self) -> io::Result<bool>
     where
@@ -91,54 +406,797 @@ impl<P, S> Session<P, S> {
     }
 }
 
-impl<P, S> AsyncExpect for Session<P, S>
+impl<P, S> DetachedSession<P, S> {
+    /// Attaches the session back.
+    ///
+    /// All the settings and a not consumed data are restored.
+    pub fn attach(self) -> Result<Session<P, S>, Error> {
+        let mut session = Session::new(self.process, self.stream)?;
+        session.stream.keep(&self.buffer);
+        session.stream.expect_timeout = self.expect_timeout;
+        session.stream.expect_lazy = self.expect_lazy;
+        session.stream.consume_mode = self.consume_mode;
+        session.stream.watchdog = self.watchdog;
+        session.stream.keepalive = self.keepalive;
+        session.stream.abort_patterns = self.abort_patterns;
+        session.stream.buffer_limit = self.buffer_limit;
+        session.stream.pager = self.pager;
+        session.stream.clock = self.clock;
+        session.stream.stream.chunk_size = self.read_chunk_size;
+        session.line_ending = self.line_ending;
+        session.bracketed_paste = self.bracketed_paste;
+        session.name = self.name;
+        session.poll_interval = self.poll_interval;
+        session.metrics = self.metrics;
+
+        Ok(session)
+    }
+}
+
+impl<P, S> AsyncExpect for Session<P, S>
+where
+    S: AsyncWrite + AsyncRead + Unpin,
+{
+    async fn expect<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        match self.find(needle, |_| ()).await {
+            Ok(found) => Ok(self.stream.take_match(found)),
+            Err(err) => Err(err.with_session_name(self.name.as_deref())),
+        }
+    }
+
+    async fn check<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        self.stream.check(needle).await
+    }
+
+    async fn is_matched<N>(&mut self, needle: N) -> Result<bool, Error>
+    where
+        N: Needle,
+    {
+        self.stream.is_matched(needle).await
+    }
+
+    async fn send<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        self.begin_send()?;
+        self.stream
+            .write_all(buf.as_ref())
+            .await
+            .map_err(Error::io("write"))
+    }
+
+    async fn send_line<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        let line_ending = self.line_ending;
+        self.send_line_with(buf, line_ending).await
+    }
+}
+
+impl<P, S> Session<P, S>
+where
+    P: Wait,
+    S: AsyncRead + Unpin,
+{
+    /// Waits for a process to exit and returns its status.
+    ///
+    /// An output is read while waiting so the process isn't blocked on a full terminal,
+    /// it's kept in the session buffer.
+    ///
+    /// [`Error::ProcessAlive`] with a not consumed output is returned
+    /// if the process is still running once the `timeout` is reached.
+    pub async fn wait_for_exit(&mut self, timeout: Option<Duration>) -> Result<ExitStatus, Error> {
+        let start = self.stream.clock.now();
+        loop {
+            self.stream.read_available().await?;
+
+            if let Some(status) = self.process.try_wait().map_err(Error::io("wait"))? {
+                return Ok(status);
+            }
+
+            if let Some(timeout) = timeout {
+                if self.stream.clock.now().saturating_duration_since(start) > timeout {
+                    let buffer = self.stream.get_available().to_vec();
+                    return Err(
+                        Error::ProcessAlive { buffer }.with_session_name(self.name.as_deref())
+                    );
+                }
+            }
+
+            futures_timer::Delay::new(self.poll_interval).await;
+        }
+    }
+
+    /// Checks that a process is still running.
+    ///
+    /// [`Error::ProcessExited`] is returned if it has exited,
+    /// so a script can stop early instead of waiting for an expect timeout.
+    pub fn check_alive(&mut self) -> Result<(), Error> {
+        match self.process.try_wait().map_err(Error::io("wait"))? {
+            Some(status) => {
+                Err(Error::ProcessExited { status }.with_session_name(self.name.as_deref()))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Waits for a process to exit with a given code.
+    ///
+    /// It uses the session's expect timeout.
+    /// [`Error::UnexpectedExit`] with a not consumed output is returned if the code is different,
+    /// see [`Session::wait_for_exit`].
+    pub async fn expect_exit(&mut self, code: i32) -> Result<ExitStatus, Error> {
+        let status = self.wait_for_exit(self.stream.expect_timeout).await?;
+        if status.code() != Some(code) {
+            let buffer = self.stream.get_available().to_vec();
+            return Err(
+                Error::UnexpectedExit { status, buffer }.with_session_name(self.name.as_deref())
+            );
+        }
+
+        Ok(status)
+    }
+}
+
+impl<P, S> Session<P, S>
+where
+    S: AsyncWrite + AsyncRead + Unpin,
+{
+    /// Expect a needle until it's found or `cancel` future is resolved.
+    ///
+    /// It lets a supervisor abort a pending call without dropping the session,
+    /// the call returns [`Error::Cancelled`] in such case.
+    /// An output which was read is kept in the session buffer,
+    /// so it can be matched by a following call.
+    ///
+    /// The session's timeout is applied as well.
+    ///
+    /// # Example
+    ///
+    /// ```no_run,ignore
+    /// use std::time::Duration;
+    /// use expectrl::spawn;
+    ///
+    /// let mut p = spawn("cat").unwrap();
+    ///
+    /// let (stop, cancel) = futures_channel::oneshot::channel::<()>();
+    /// // pass `stop` to a supervisor
+    ///
+    /// p.expect_cancellable("Hello World", async { let _ = cancel.await; }).await.unwrap();
+    /// ```
+    pub async fn expect_cancellable<N, F>(
+        &mut self,
+        needle: N,
+        cancel: F,
+    ) -> Result<Captures, Error>
+    where
+        N: Needle,
+        F: Future<Output = ()>,
+    {
+        futures_lite::future::or(self.expect(needle), async {
+            cancel.await;
+            Err(Error::Cancelled)
+        })
+        .await
+    }
+
+    /// Reads everything a process has produced so far and throws it away,
+    /// together with a not consumed data in the session buffer.
+    ///
+    /// It doesn't wait for an output.
+    /// It lets a script resynchronize at a known point before sending a next command,
+    /// so a stale output doesn't match a later expect call.
+    ///
+    /// Returns a number of discarded bytes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, AsyncExpect};
+    ///
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = spawn("bash").unwrap();
+    /// p.send_line("make").await.unwrap();
+    /// // ...
+    /// let _ = p.flush_output().await.unwrap();
+    /// p.send_line("echo $?").await.unwrap();
+    /// # });
+    /// ```
+    pub async fn flush_output(&mut self) -> Result<usize, Error> {
+        self.stream.read_available().await?;
+        Ok(self.discard_buffered())
+    }
+
+    /// Throws away a data in the session buffer which was not consumed by expect calls.
+    ///
+    /// Unlike [`Session::flush_output`] it doesn't read anything from the process.
+    ///
+    /// Returns a number of discarded bytes.
+    pub fn discard_buffered(&mut self) -> usize {
+        let n = self.stream.get_available().len();
+        self.stream.consume(n);
+        n
+    }
+
+    /// Expect a needle until a deadline is reached.
+    ///
+    /// It's the same as [`AsyncExpect::expect`] but it uses an absolute deadline instead of the session's timeout,
+    /// so a few calls may share one time budget.
+    pub async fn expect_until<N>(&mut self, needle: N, deadline: Instant) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let timeout = self.stream.expect_timeout;
+        self.stream.expect_timeout =
+            Some(deadline.saturating_duration_since(self.stream.clock.now()));
+        let result = self.expect(needle).await;
+        self.stream.expect_timeout = timeout;
+
+        result
+    }
+
+    /// Runs a block of steps within a total time limit.
+    ///
+    /// Each expect call of the block waits no longer than the time left,
+    /// and a send call fails if the time is already over.
+    /// The block is dropped once the time is over, so a blocked send is interrupted as well.
+    /// Either way [`Error::Within`] is returned, which points to the step in flight.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use expectrl::{spawn, AsyncExpect};
+    ///
+    /// let mut p = spawn("ftp bks4-speedtest-1.tele2.net").unwrap();
+    ///
+    /// futures_lite::future::block_on(async {
+    ///     p.within(Duration::from_secs(30), |p| {
+    ///         Box::pin(async move {
+    ///             p.expect("Name").await?;
+    ///             p.send_line("anonymous").await?;
+    ///             p.expect("Password").await?;
+    ///             Ok(())
+    ///         })
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// })
+    /// ```
+    pub async fn within<F, T>(&mut self, limit: Duration, block: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Self) -> Pin<Box<dyn Future<Output = Result<T, Error>> + Send + '_>>,
+    {
+        let within = Within::new(self.stream.clock.now(), limit).nest(self.within.as_ref());
+        let delay = self
+            .stream
+            .clock
+            .delay(within.left(self.stream.clock.now()));
+        let outer = self.within.replace(within);
+
+        let result = futures_lite::future::or(async { Some(block(self).await) }, async {
+            delay.await;
+            None
+        })
+        .await;
+
+        let within = std::mem::replace(&mut self.within, outer).expect("a block has a limit");
+        if let Some(outer) = &mut self.within {
+            outer.add_steps(&within);
+        }
+
+        match result {
+            Some(result) => result,
+            None => Err(within.error()),
+        }
+    }
+
+    /// Expect waits until an [`AsyncNeedle`] is matched.
+    ///
+    /// It's the same as [`AsyncExpect::expect`] but the needle is awaited on each check,
+    /// so it may do an async work in between.
+    /// The session's expect timeout covers the checks as well.
+    ///
+    /// The buffer is checked as a whole on each read, so lazy mode is not taken into account.
+    pub async fn expect_awaitable<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: AsyncNeedle,
+    {
+        self.stream.expect_awaitable(needle).await
+    }
+
+    /// Expect a regex and return its named group as a lossy string.
+    ///
+    /// It's a shortcut for an expect call followed by a look up of the group.
+    /// An error is returned if the regex has no such group
+    /// or if the group didn't participate in the match.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, AsyncExpect, Regex};
+    ///
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = spawn("cat").unwrap();
+    /// p.send_line("pid=1234").await.unwrap();
+    /// let pid = p.expect_capture(Regex(r"pid=(?P<pid>\d+)"), "pid").await.unwrap();
+    /// assert_eq!(pid, "1234");
+    /// # });
+    /// ```
+    pub async fn expect_capture<Re>(
+        &mut self,
+        regex: Regex<Re>,
+        name: &str,
+    ) -> Result<String, Error>
+    where
+        Re: AsRef<str>,
+    {
+        let regex = regex::bytes::Regex::new(regex.0.as_ref()).map_err(|_| Error::RegexParsing)?;
+        if !regex.capture_names().any(|group| group == Some(name)) {
+            return Err(unknown_capture_group(name));
+        }
+
+        let found = self.expect(&regex).await?;
+
+        regex
+            .captures(found.as_bytes())
+            .and_then(|captures| captures.name(name))
+            .map(|group| String::from_utf8_lossy(group.as_bytes()).into_owned())
+            .ok_or_else(|| unknown_capture_group(name))
+    }
+
+    /// Expect a needle, retrying it according to a policy if the call times out.
+    ///
+    /// Each attempt uses the session's expect timeout.
+    /// Before a retry the policy's delay is awaited and its nudge is sent.
+    /// If all retries time out [`Error::ExpectTimeout`] is returned,
+    /// any other error is returned right away.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use expectrl::{spawn, session::RetryPolicy};
+    ///
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = spawn("cat").unwrap();
+    /// p.set_expect_timeout(Some(Duration::from_secs(1)));
+    ///
+    /// let policy = RetryPolicy::new(3).backoff(Duration::from_millis(200)).nudge("\n");
+    /// p.expect_with_retry("login:", &policy).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn expect_with_retry<N>(
+        &mut self,
+        needle: N,
+        policy: &RetryPolicy,
+    ) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let mut retry = 0;
+        loop {
+            match self.expect(&needle).await {
+                Err(err)
+                    if matches!(err.root_error(), Error::ExpectTimeout { .. })
+                        && retry < policy.get_max_retries() => {}
+                result => return result,
+            }
+
+            let delay = policy.get_delay(retry);
+            if !delay.is_zero() {
+                self.stream.clock.delay(delay).await;
+            }
+
+            if let Some(nudge) = policy.get_nudge() {
+                self.send(nudge).await?;
+            }
+
+            retry += 1;
+        }
+    }
+
+    /// Read a line until a deadline is reached.
+    ///
+    /// A line including `\n` is appended to the `buf`.
+    /// Returns a number of bytes read, which is 0 when EOF is reached.
+    pub async fn read_line_until(
+        &mut self,
+        buf: &mut String,
+        deadline: Instant,
+    ) -> Result<usize, Error> {
+        let needles: [&dyn Needle; 2] = [&"\n", &Eof];
+        let found = self.expect_until(Any(needles), deadline).await?;
+        let line = String::from_utf8_lossy(found.as_bytes());
+        buf.push_str(&line);
+
+        Ok(found.as_bytes().len())
+    }
+
+    /// Expects a needle and compares an output with a snapshot.
+    ///
+    /// The output is everything consumed by the call,
+    /// from the end of the previous match up to the end of this one.
+    ///
+    /// # Panics
+    ///
+    /// It panics with a diff if the output doesn't match the snapshot,
+    /// see [`Snapshot::assert`].
+    #[cfg(feature = "snapshot")]
+    pub async fn expect_snapshot<N>(
+        &mut self,
+        needle: N,
+        snapshot: &Snapshot,
+    ) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let found = self.expect(needle).await?;
+        snapshot.assert(found.as_bytes())?;
+
+        Ok(found)
+    }
+
+    /// Reads a line and asserts that it's equal to the `expected` one.
+    ///
+    /// A line ending (`\n` or `\r\n`) is not compared.
+    /// It's meant to be used in tests instead of checking a matched output by hand.
+    ///
+    /// # Panics
+    ///
+    /// Like [`assert_eq!`] it panics if the line differs,
+    /// with a diff where control characters are escaped.
+    pub async fn expect_eq_line(&mut self, expected: &str) -> Result<(), Error> {
+        self.expect_eq_lines(&[expected]).await
+    }
+
+    /// Reads lines and asserts that they are equal to the `expected` ones.
+    ///
+    /// It reads as many lines as expected or until EOF.
+    /// See [`Session::expect_eq_line`].
+    ///
+    /// # Panics
+    ///
+    /// It panics with a diff of the lines if they differ.
+    pub async fn expect_eq_lines<L>(&mut self, expected: &[L]) -> Result<(), Error>
+    where
+        L: AsRef<str>,
+    {
+        let mut lines = Vec::with_capacity(expected.len());
+        while lines.len() < expected.len() {
+            let needles: [&dyn Needle; 2] = [&"\n", &Eof];
+            let found = self.expect(Any(needles)).await?;
+            let line = found.as_bytes();
+            if !line.ends_with(b"\n") {
+                if !line.is_empty() {
+                    lines.push(String::from_utf8_lossy(line).into_owned());
+                }
+
+                break;
+            }
+
+            lines.push(String::from_utf8_lossy(trim_line_ending(line)).into_owned());
+        }
+
+        assert_lines_eq(expected, &lines);
+
+        Ok(())
+    }
+}
+
+impl<P, S> Session<P, S>
+where
+    S: AsyncWrite + Unpin,
+{
+    /// Send a line to the stream ending it with `\r\n`
+    /// regardless of [`Session::set_line_ending`].
+    pub async fn send_line_crlf<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        self.send_line_with(buf, LineEnding::CrLf).await
+    }
+
+    /// Send a line to the stream ending it with `\n`
+    /// regardless of [`Session::set_line_ending`].
+    pub async fn send_line_lf<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        self.send_line_with(buf, LineEnding::Lf).await
+    }
+
+    async fn send_line_with<B>(&mut self, buf: B, line_ending: LineEnding) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        self.begin_send()?;
+        self.stream
+            .write_all(buf.as_ref())
+            .await
+            .map_err(Error::io("write"))?;
+        self.stream
+            .write_all(line_ending.as_bytes())
+            .await
+            .map_err(Error::io("write"))?;
+
+        Ok(())
+    }
+
+    /// Accounts a send call in a block run by [`Session::within`].
+    fn begin_send(&mut self) -> Result<(), Error> {
+        if let Some(within) = &mut self.within {
+            let _ = within.begin("send", self.stream.clock.now())?;
+        }
+
+        Ok(())
+    }
+
+    /// Paste a text, which may contain many lines, into a process.
+    ///
+    /// If bracketed paste is on (see [`Session::set_bracketed_paste`])
+    /// the text is wrapped into bracketed paste sequences,
+    /// so a shell or a REPL doesn't treat it as typed input (autocompletion, auto-indent).
+    /// Otherwise the text is written by small chunks with a short delay between them.
+    pub async fn paste<B>(&mut self, text: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        self.begin_send()?;
+
+        let text = text.as_ref();
+        if self.bracketed_paste {
+            self.stream
+                .write_all(BRACKETED_PASTE_START)
+                .await
+                .map_err(Error::io("write"))?;
+            self.stream
+                .write_all(text)
+                .await
+                .map_err(Error::io("write"))?;
+            self.stream
+                .write_all(BRACKETED_PASTE_END)
+                .await
+                .map_err(Error::io("write"))?;
+            self.stream.flush().await.map_err(Error::io("write"))?;
+
+            return Ok(());
+        }
+
+        for (i, chunk) in text.chunks(PASTE_CHUNK_SIZE).enumerate() {
+            if i > 0 {
+                futures_timer::Delay::new(PASTE_CHUNK_DELAY).await;
+            }
+
+            self.stream
+                .write_all(chunk)
+                .await
+                .map_err(Error::io("write"))?;
+            self.stream.flush().await.map_err(Error::io("write"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Send a text character by character as if it was typed by a human.
+    ///
+    /// See [`TypingOptions`].
+    pub async fn send_typed<T>(&mut self, text: T, options: &TypingOptions) -> Result<(), Error>
+    where
+        T: AsRef<str>,
+    {
+        self.begin_send()?;
+        self.type_text(text.as_ref(), options).await
+    }
+
+    /// Writes a text character by character with delays of [`TypingOptions`] between them.
+    async fn type_text(&mut self, text: &str, options: &TypingOptions) -> Result<(), Error> {
+        let mut delays = options.delays();
+        let mut buf = [0; 4];
+        for (i, c) in text.chars().enumerate() {
+            if i > 0 {
+                if let Some(delay) = delays.next() {
+                    futures_timer::Delay::new(delay).await;
+                }
+            }
+
+            self.stream
+                .write_all(c.encode_utf8(&mut buf).as_bytes())
+                .await
+                .map_err(Error::io("write"))?;
+            self.stream.flush().await.map_err(Error::io("write"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Send a line character by character with a `delay` between keystrokes.
+    ///
+    /// The line ending is sent as the last keystroke, see [`Session::set_line_ending`].
+    pub async fn send_line_slow<T>(&mut self, text: T, delay: Duration) -> Result<(), Error>
+    where
+        T: AsRef<str>,
+    {
+        self.begin_send()?;
+
+        let options = TypingOptions::new(delay);
+        self.type_text(text.as_ref(), &options).await?;
+        futures_timer::Delay::new(delay).await;
+        self.stream
+            .write_all(self.line_ending.as_bytes())
+            .await
+            .map_err(Error::io("write"))?;
+        self.stream.flush().await.map_err(Error::io("write"))?;
+
+        Ok(())
+    }
+}
+
+impl<P, S> Session<P, S>
+where
+    P: Unpin,
+    S: AsyncRead + Unpin,
+{
+    /// Returns a stream of output lines.
+    ///
+    /// Line endings (`\n` and `\r\n`) are stripped.
+    /// The stream ends on EOF.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # futures_lite::future::block_on(async {
+    /// use futures_lite::StreamExt;
+    ///
+    /// let mut p = expectrl::spawn("ls -l").unwrap();
+    /// let mut lines = p.lines();
+    /// while let Some(line) = lines.next().await {
+    ///     println!("{}", line.unwrap());
+    /// }
+    /// # });
+    /// ```
+    pub fn lines(&mut self) -> futures_lite::io::Lines<&mut Self> {
+        AsyncBufReadExt::lines(self)
+    }
+
+    /// Try to match a pattern without waiting for it.
+    ///
+    /// It makes at most one read attempt which doesn't wait for data and one match attempt,
+    /// so unlike [`AsyncExpect::expect`] it returns `Ok(None)` right away if there's no match yet.
+    /// It can be used to drive a session from a custom event loop.
+    ///
+    /// [`Error::Eof`] is returned if the stream has reached EOF and there's no match.
+    pub async fn try_expect<N>(&mut self, needle: N) -> Result<Option<Captures>, Error>
+    where
+        N: Needle,
+    {
+        let found = self.stream.check(needle).await?;
+        if found.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(found))
+    }
+}
+
+impl<P, S> Session<P, S>
 where
-    S: AsyncWrite + AsyncRead + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
 {
-    async fn expect<N>(&mut self, needle: N) -> Result<Captures, Error>
+    /// Expect waits until a pattern is matched, like [`AsyncExpect::expect`],
+    /// and calls `inspect` with each chunk of data received while waiting.
+    ///
+    /// It can be used to show a progress of a long running command.
+    pub async fn expect_with_inspect<N, F>(
+        &mut self,
+        needle: N,
+        inspect: F,
+    ) -> Result<Captures, Error>
     where
         N: Needle,
+        F: FnMut(&[u8]),
     {
-        match self.stream.expect_lazy {
-            true => self.stream.expect_lazy(needle).await,
-            false => self.stream.expect_gready(needle).await,
+        match self.find(needle, inspect).await {
+            Ok(found) => Ok(self.stream.take_match(found)),
+            Err(err) => Err(err.with_session_name(self.name.as_deref())),
         }
     }
 
-    async fn check<N>(&mut self, needle: N) -> Result<Captures, Error>
+    /// Expect waits until a pattern is matched, like [`AsyncExpect::expect`],
+    /// but the returned captures borrow the session buffer instead of copying it.
+    ///
+    /// It saves an allocation per match,
+    /// which matters in tight loops processing a lot of output.
+    /// The matched bytes are dropped from the buffer once the session is used again.
+    pub async fn expect_ref<N>(&mut self, needle: N) -> Result<CapturesRef<'_>, Error>
     where
         N: Needle,
     {
-        self.stream.check(needle).await
+        match self.find(needle, |_| ()).await {
+            Ok(found) => Ok(self.stream.lend_match(found)),
+            Err(err) => Err(err.with_session_name(self.name.as_deref())),
+        }
     }
 
-    async fn is_matched<N>(&mut self, needle: N) -> Result<bool, Error>
+    /// Expect waits until a pattern is matched, like [`AsyncExpect::expect`],
+    /// and writes the match into `captures`.
+    ///
+    /// The storage of `captures` is reused,
+    /// so there's no allocation per call once it's big enough for a match.
+    pub async fn expect_into<N>(&mut self, needle: N, captures: &mut Captures) -> Result<(), Error>
     where
         N: Needle,
     {
-        self.stream.is_matched(needle).await
+        match self.find(needle, |_| ()).await {
+            Ok(found) => {
+                self.stream.take_match_into(&found, captures);
+                Ok(())
+            }
+            Err(err) => Err(err.with_session_name(self.name.as_deref())),
+        }
     }
 
-    async fn send<B>(&mut self, buf: B) -> Result<(), Error>
+    /// Waits until a pattern is matched in a way set by [Session::set_expect_lazy].
+    ///
+    /// The match is left at the start of the buffer.
+    async fn find<N, F>(&mut self, needle: N, inspect: F) -> Result<Vec<Match>, Error>
     where
-        B: AsRef<[u8]>,
+        N: Needle,
+        F: FnMut(&[u8]),
     {
-        self.stream.write_all(buf.as_ref()).await.map_err(Error::IO)
-    }
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!("expect", session = self.name.as_deref());
+
+        let start = self.stream.clock.now();
+        let timeout = self.stream.expect_timeout;
+        let limited = match &mut self.within {
+            Some(within) => {
+                let left = within.begin("expect", start)?;
+                let (bound, limited) = Within::bound(timeout, left);
+                self.stream.expect_timeout = bound;
+                limited
+            }
+            None => false,
+        };
 
-    async fn send_line<B>(&mut self, buf: B) -> Result<(), Error>
-    where
-        B: AsRef<[u8]>,
-    {
-        #[cfg(windows)]
-        const LINE_ENDING: &[u8] = b"\r\n";
-        #[cfg(not(windows))]
-        const LINE_ENDING: &[u8] = b"\n";
+        let stream = &mut self.stream;
+        let find = async move {
+            match stream.expect_lazy {
+                true => stream.find_lazy(needle, inspect).await,
+                false => stream.find_gready(needle, inspect).await,
+            }
+        };
 
-        self.stream.write_all(buf.as_ref()).await?;
-        self.stream.write_all(LINE_ENDING).await?;
+        #[cfg(feature = "tracing")]
+        let find = tracing::Instrument::instrument(find, span.clone());
 
-        Ok(())
+        let result = find.await;
+
+        self.stream.expect_timeout = timeout;
+
+        let elapsed = self.stream.clock.now().saturating_duration_since(start);
+        self.metrics.record_expect(&result, elapsed);
+
+        let result = match (result, &self.within) {
+            (Err(Error::ExpectTimeout { .. }), Some(within)) if limited => Err(within.error()),
+            (result, _) => result,
+        };
+
+        #[cfg(feature = "tracing")]
+        span.in_scope(|| match &result {
+            Ok(found) => tracing::debug!(bytes = Captures::right_most_index(found), "matched"),
+            Err(err) => tracing::debug!(error = %err, "not matched"),
+        });
+
+        result
     }
 }
 
@@ -216,6 +1274,26 @@ where
     }
 }
 
+#[cfg(unix)]
+impl<P, S> std::os::fd::AsRawFd for Session<P, S>
+where
+    S: std::os::fd::AsRawFd,
+{
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.get_stream().as_raw_fd()
+    }
+}
+
+#[cfg(unix)]
+impl<P, S> std::os::fd::AsFd for Session<P, S>
+where
+    S: std::os::fd::AsFd,
+{
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        self.get_stream().as_fd()
+    }
+}
+
 impl<P, S> Termios for Session<P, S>
 where
     P: Termios,
@@ -229,6 +1307,33 @@ where
     }
 }
 
+impl<P, S> WindowSize for Session<P, S>
+where
+    P: WindowSize,
+{
+    fn set_window_size(&mut self, cols: u16, rows: u16) -> io::Result<()> {
+        P::set_window_size(self.get_process_mut(), cols, rows)
+    }
+}
+
+impl<P, S> Usage for Session<P, S>
+where
+    P: Usage,
+{
+    fn resource_usage(&self) -> io::Result<Option<ResourceUsage>> {
+        P::resource_usage(self.get_process())
+    }
+}
+
+impl<P, S> OnDrop for Session<P, S>
+where
+    P: OnDrop,
+{
+    fn set_drop_policy(&mut self, policy: DropPolicy) {
+        P::set_drop_policy(self.get_process_mut(), policy)
+    }
+}
+
 /// Session represents a spawned process and its streams.
 /// It controlls process and communication with it.
 #[derive(Debug)]
@@ -236,6 +1341,13 @@ struct Stream<S> {
     stream: BufferedStream<S>,
     expect_timeout: Option<Duration>,
     expect_lazy: bool,
+    consume_mode: ConsumeMode,
+    watchdog: Option<Watchdog>,
+    keepalive: Option<Keepalive>,
+    abort_patterns: AbortPatterns,
+    buffer_limit: Option<BufferLimit>,
+    pager: Option<Pager>,
+    clock: Arc<dyn Clock>,
 }
 
 impl<S> Stream<S> {
@@ -245,6 +1357,13 @@ impl<S> Stream<S> {
             stream: BufferedStream::new(stream),
             expect_timeout: Some(Duration::from_millis(10000)),
             expect_lazy: false,
+            consume_mode: ConsumeMode::default(),
+            watchdog: None,
+            keepalive: None,
+            abort_patterns: AbortPatterns::default(),
+            buffer_limit: None,
+            pager: None,
+            clock: default_clock(),
         }
     }
 
@@ -270,7 +1389,7 @@ impl<S> Stream<S> {
     }
 
     /// Get an inner buffer.
-    fn get_available(&mut self) -> &[u8] {
+    fn get_available(&self) -> &[u8] {
         self.stream.buffer()
     }
 
@@ -282,51 +1401,149 @@ impl<S> Stream<S> {
 
 impl<S> Stream<S>
 where
-    S: AsyncRead + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
 {
-    async fn expect_gready<N: Needle>(&mut self, needle: N) -> Result<Captures, Error> {
-        let expect_timeout = self.expect_timeout;
+    #[cfg(test)]
+    async fn expect_gready<N, F>(&mut self, needle: N, inspect: F) -> Result<Captures, Error>
+    where
+        N: Needle,
+        F: FnMut(&[u8]),
+    {
+        let found = self.find_gready(needle, inspect).await?;
+        Ok(self.take_match(found))
+    }
+
+    #[cfg(test)]
+    async fn expect_lazy<N, F>(&mut self, needle: N, inspect: F) -> Result<Captures, Error>
+    where
+        N: Needle,
+        F: FnMut(&[u8]),
+    {
+        let found = self.find_lazy(needle, inspect).await?;
+        Ok(self.take_match(found))
+    }
+
+    /// Consumes a match from the buffer and returns a copy of it.
+    fn take_match(&mut self, found: Vec<Match>) -> Captures {
+        let end_index = Captures::right_most_index(&found);
+        let involved_bytes = self.stream.buffer()[..end_index].to_vec();
+        let timestamp = self.stream.timestamp(end_index);
+        let offset = self.stream.consumed();
+        self.consume_match(end_index);
+
+        Captures::new(involved_bytes, found)
+            .with_offset(offset)
+            .with_timestamp(timestamp)
+    }
+
+    /// Consumes a match from the buffer and copies it into `captures`.
+    fn take_match_into(&mut self, found: &[Match], captures: &mut Captures) {
+        let end_index = Captures::right_most_index(found);
+        let involved_bytes = &self.stream.buffer()[..end_index];
+        let timestamp = self.stream.timestamp(end_index);
+        let offset = self.stream.consumed();
+        captures.replace(involved_bytes, found, offset, timestamp);
+        self.consume_match(end_index);
+    }
+
+    /// Consumes a match from the buffer and lends it.
+    ///
+    /// The bytes are kept in the buffer till a next use of the stream.
+    fn lend_match(&mut self, found: Vec<Match>) -> CapturesRef<'_> {
+        let end_index = Captures::right_most_index(&found);
+        let timestamp = self.stream.timestamp(end_index);
+        let offset = self.stream.consumed();
+        let n = match self.consume_mode {
+            ConsumeMode::UpToMatch => end_index,
+            ConsumeMode::WholeBuffer => self.stream.buffer().len(),
+        };
+
+        let involved_bytes = &self.stream.lend(n)[..end_index];
+
+        CapturesRef::new(involved_bytes, found)
+            .with_offset(offset)
+            .with_timestamp(timestamp)
+    }
+
+    /// Waits until a pattern is matched filling as much as possible to the buffer.
+    ///
+    /// The match is left at the start of the buffer.
+    async fn find_gready<N, F>(&mut self, needle: N, mut inspect: F) -> Result<Vec<Match>, Error>
+    where
+        N: Needle,
+        F: FnMut(&[u8]),
+    {
+        let timeout_future = self.expect_timeout.map(|timeout| self.clock.delay(timeout));
+        self.reset_timers();
 
         let expect_future = async {
+            let mut checked: usize = 0;
             let mut eof = false;
             loop {
                 let data = self.stream.buffer();
 
-                let found = Needle::check(&needle, data, eof)?;
+                if let Some((pattern, captures)) = self.abort_patterns.check(data, checked, eof)? {
+                    return Err(self.abort(pattern, captures));
+                }
+
+                let found = Needle::check_from(&needle, data, checked, eof)?;
 
                 if !found.is_empty() {
-                    let end_index = Captures::right_most_index(&found);
-                    let involved_bytes = data[..end_index].to_vec();
-                    self.stream.consume(end_index);
-
-                    return Ok(Captures::new(involved_bytes, found));
+                    return Ok(found);
                 }
 
+                checked = data.len();
+
                 if eof {
                     return Err(Error::Eof);
                 }
 
-                eof = self.stream.fill().await? == 0;
+                let length = self.stream.buffer().len();
+                eof = self.fill().await? == 0;
+                let dropped = self.limit_buffer()?;
+                self.handle_pager().await?;
+                let data = self.stream.buffer();
+                checked = checked.saturating_sub(dropped).min(data.len());
+
+                let received = data
+                    .get(length.saturating_sub(dropped)..)
+                    .unwrap_or_default();
+                if !received.is_empty() {
+                    inspect(received);
+                }
+            }
+        };
+
+        let result = match timeout_future {
+            Some(timeout_future) => {
+                futures_lite::future::or(async { Some(expect_future.await) }, async {
+                    timeout_future.await;
+                    None
+                })
+                .await
             }
+            None => Some(expect_future.await),
         };
 
-        if let Some(timeout) = expect_timeout {
-            let timeout_future = futures_timer::Delay::new(timeout);
-            futures_lite::future::or(expect_future, async {
-                timeout_future.await;
-                Err(Error::ExpectTimeout)
-            })
-            .await
-        } else {
-            expect_future.await
+        match result {
+            Some(result) => result,
+            None => Err(Error::expect_timeout(
+                Needle::describe(&needle),
+                self.stream.buffer(),
+            )),
         }
     }
 
-    async fn expect_lazy<N>(&mut self, needle: N) -> Result<Captures, Error>
+    /// Waits until a pattern is matched reading byte by byte.
+    ///
+    /// The match is left at the start of the buffer.
+    async fn find_lazy<N, F>(&mut self, needle: N, mut inspect: F) -> Result<Vec<Match>, Error>
     where
         N: Needle,
+        F: FnMut(&[u8]),
     {
-        let expect_timeout = self.expect_timeout;
+        let timeout_future = self.expect_timeout.map(|timeout| self.clock.delay(timeout));
+        self.reset_timers();
         let expect_future = async {
             // We read by byte to make things as lazy as possible.
             //
@@ -343,13 +1560,29 @@ where
             // which is OK if EOF happens onces, but I am not sure if this is a case.
 
             let mut checked_length = 0;
+            let mut checked: usize = 0;
             let mut eof = false;
             loop {
                 let available = self.stream.buffer();
                 let is_buffer_checked = checked_length == available.len();
                 if is_buffer_checked {
-                    let n = self.stream.fill().await?;
+                    let length = available.len();
+                    let n = self.fill().await?;
                     eof = n == 0;
+
+                    let dropped = self.limit_buffer()?;
+                    self.handle_pager().await?;
+
+                    let available = self.stream.buffer();
+                    checked_length = checked_length.saturating_sub(dropped).min(available.len());
+                    checked = checked.saturating_sub(dropped).min(available.len());
+
+                    let received = available
+                        .get(length.saturating_sub(dropped)..)
+                        .unwrap_or_default();
+                    if !received.is_empty() {
+                        inspect(received);
+                    }
                 }
 
                 // We intentinally not increase the counter
@@ -361,36 +1594,230 @@ where
                 }
 
                 let data = &available[..checked_length];
-                let found = Needle::check(&needle, data, eof)?;
+
+                if let Some((pattern, captures)) = self.abort_patterns.check(data, checked, eof)? {
+                    return Err(self.abort(pattern, captures));
+                }
+
+                let found = Needle::check_from(&needle, data, checked, eof)?;
+                if !found.is_empty() {
+                    return Ok(found);
+                }
+
+                checked = data.len();
+
+                if eof {
+                    return Err(Error::Eof);
+                }
+            }
+        };
+
+        let result = match timeout_future {
+            Some(timeout_future) => {
+                futures_lite::future::or(async { Some(expect_future.await) }, async {
+                    timeout_future.await;
+                    None
+                })
+                .await
+            }
+            None => Some(expect_future.await),
+        };
+
+        match result {
+            Some(result) => result,
+            None => Err(Error::expect_timeout(
+                Needle::describe(&needle),
+                self.stream.buffer(),
+            )),
+        }
+    }
+
+    async fn expect_awaitable<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: AsyncNeedle,
+    {
+        let timeout_future = self.expect_timeout.map(|timeout| self.clock.delay(timeout));
+        self.reset_timers();
+
+        let expect_future = async {
+            let mut checked: usize = 0;
+            let mut eof = false;
+            loop {
+                let data = self.stream.buffer();
+
+                if let Some((pattern, captures)) = self.abort_patterns.check(data, checked, eof)? {
+                    return Err(self.abort(pattern, captures));
+                }
+
+                let found = needle.check(data, eof).await?;
+                let data = self.stream.buffer();
+
                 if !found.is_empty() {
                     let end_index = Captures::right_most_index(&found);
                     let involved_bytes = data[..end_index].to_vec();
-                    self.stream.consume(end_index);
-                    return Ok(Captures::new(involved_bytes, found));
+                    let timestamp = self.stream.timestamp(end_index);
+                    let offset = self.stream.consumed();
+                    self.consume_match(end_index);
+
+                    return Ok(Captures::new(involved_bytes, found)
+                        .with_offset(offset)
+                        .with_timestamp(timestamp));
                 }
 
+                checked = data.len();
+
                 if eof {
                     return Err(Error::Eof);
                 }
+
+                eof = self.fill().await? == 0;
+                let dropped = self.limit_buffer()?;
+                self.handle_pager().await?;
+                checked = checked
+                    .saturating_sub(dropped)
+                    .min(self.stream.buffer().len());
+            }
+        };
+
+        let result = match timeout_future {
+            Some(timeout_future) => {
+                futures_lite::future::or(async { Some(expect_future.await) }, async {
+                    timeout_future.await;
+                    None
+                })
+                .await
             }
+            None => Some(expect_future.await),
         };
 
-        if let Some(timeout) = expect_timeout {
-            let timeout_future = futures_timer::Delay::new(timeout);
-            futures_lite::future::or(expect_future, async {
-                timeout_future.await;
-                Err(Error::ExpectTimeout)
+        match result {
+            Some(result) => result,
+            None => Err(Error::expect_timeout(
+                AsyncNeedle::describe(&needle),
+                self.stream.buffer(),
+            )),
+        }
+    }
+
+    /// Fills the buffer, checking a watchdog and a keepalive while waiting for data.
+    async fn fill(&mut self) -> Result<usize, Error> {
+        loop {
+            let now = self.clock.now();
+            let watchdog = self.watchdog.as_ref().map(|w| w.remaining(now));
+            let keepalive = self.keepalive.as_ref().map(|k| k.remaining(now));
+            let remaining = match (watchdog, keepalive) {
+                (Some(a), Some(b)) => a.min(b),
+                (Some(a), None) | (None, Some(a)) => a,
+                (None, None) => return self.stream.fill().await.map_err(Error::io("read")),
+            };
+
+            let stream = &mut self.stream;
+            let delay = self.clock.delay(remaining);
+            let n = futures_lite::future::or(async { Some(stream.fill().await) }, async {
+                delay.await;
+                None
             })
-            .await
-        } else {
-            expect_future.await
+            .await;
+
+            match n {
+                Some(n) => {
+                    self.reset_watchdog();
+                    return n.map_err(Error::io("read"));
+                }
+                None => self.check_timers().await?,
+            }
+        }
+    }
+
+    /// Answers a pager prompt at the end of the buffer if a pager handler is set.
+    ///
+    /// The prompt is removed from the buffer.
+    async fn handle_pager(&mut self) -> Result<(), Error> {
+        let pager = match &self.pager {
+            Some(pager) => pager,
+            None => return Ok(()),
+        };
+
+        if let Some(start) = pager.check(self.stream.buffer()) {
+            self.stream.truncate(start);
+            self.stream
+                .get_mut()
+                .write_all(pager.get_response())
+                .await
+                .map_err(Error::io("write"))?;
+            self.stream
+                .get_mut()
+                .flush()
+                .await
+                .map_err(Error::io("write"))?;
+        }
+
+        Ok(())
+    }
+
+    fn reset_watchdog(&mut self) {
+        let length = self.stream.buffer().len();
+        let now = self.clock.now();
+        if let Some(watchdog) = &mut self.watchdog {
+            watchdog.reset(length, now);
         }
     }
 
+    fn reset_timers(&mut self) {
+        self.reset_watchdog();
+
+        let now = self.clock.now();
+        if let Some(keepalive) = &mut self.keepalive {
+            keepalive.reset(now);
+        }
+    }
+
+    async fn check_timers(&mut self) -> Result<(), Error> {
+        let length = self.stream.buffer().len();
+        let now = self.clock.now();
+        if let Some(watchdog) = &mut self.watchdog {
+            if let Some(buf) = watchdog.check(length, now)? {
+                self.stream
+                    .get_mut()
+                    .write_all(&buf)
+                    .await
+                    .map_err(Error::io("write"))?;
+                self.stream
+                    .get_mut()
+                    .flush()
+                    .await
+                    .map_err(Error::io("write"))?;
+            }
+        }
+
+        if let Some(keepalive) = &mut self.keepalive {
+            if let Some(buf) = keepalive.check(now) {
+                self.stream
+                    .get_mut()
+                    .write_all(buf)
+                    .await
+                    .map_err(Error::io("write"))?;
+                self.stream
+                    .get_mut()
+                    .flush()
+                    .await
+                    .map_err(Error::io("write"))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S> Stream<S>
+where
+    S: AsyncRead + Unpin,
+{
     /// Is matched checks if a pattern is matched.
     /// It doesn't consumes bytes from stream.
     async fn is_matched<E: Needle>(&mut self, needle: E) -> Result<bool, Error> {
         let eof = self.try_fill().await?;
+        let _ = self.limit_buffer()?;
         let buf = self.stream.buffer();
 
         let found = needle.check(buf, eof)?;
@@ -412,14 +1839,24 @@ where
         E: Needle,
     {
         let eof = self.try_fill().await?;
+        let _ = self.limit_buffer()?;
 
         let buf = self.stream.buffer();
+
+        if let Some((pattern, captures)) = self.abort_patterns.check(buf, 0, eof)? {
+            return Err(self.abort(pattern, captures));
+        }
+
         let found = needle.check(buf, eof)?;
         if !found.is_empty() {
             let end_index = Captures::right_most_index(&found);
             let involved_bytes = buf[..end_index].to_vec();
-            self.stream.consume(end_index);
-            return Ok(Captures::new(involved_bytes, found));
+            let timestamp = self.stream.timestamp(end_index);
+            let offset = self.stream.consumed();
+            self.consume_match(end_index);
+            return Ok(Captures::new(involved_bytes, found)
+                .with_offset(offset)
+                .with_timestamp(timestamp));
         }
 
         if eof {
@@ -429,6 +1866,43 @@ where
         Ok(Captures::new(Vec::new(), Vec::new()))
     }
 
+    /// Applies a buffer limit.
+    ///
+    /// Returns an amount of dropped bytes.
+    fn limit_buffer(&mut self) -> Result<usize, Error> {
+        let limit = match &mut self.buffer_limit {
+            Some(limit) => limit,
+            None => return Ok(0),
+        };
+
+        let dropped = limit.check(self.stream.buffer())?;
+        self.stream.consume(dropped);
+
+        Ok(dropped)
+    }
+
+    /// Consumes a matched data according to a consume mode.
+    fn consume_match(&mut self, end_index: usize) {
+        let n = match self.consume_mode {
+            ConsumeMode::UpToMatch => end_index,
+            ConsumeMode::WholeBuffer => self.stream.buffer().len(),
+        };
+
+        self.stream.consume(n);
+    }
+
+    fn abort(&mut self, pattern: String, captures: Captures) -> Error {
+        let offset = self.stream.consumed();
+        let end_index = captures.as_bytes().len();
+        let timestamp = self.stream.timestamp(end_index);
+        self.stream.consume(end_index);
+
+        Error::Aborted {
+            pattern,
+            captures: captures.with_offset(offset).with_timestamp(timestamp),
+        }
+    }
+
     /// Verifyes if stream is empty or not.
     async fn is_empty(&mut self) -> io::Result<bool> {
         match futures_lite::future::poll_once(self.read(&mut [])).await {
@@ -439,10 +1913,22 @@ where
         }
     }
 
+    /// Reads everything what's available without waiting.
+    async fn read_available(&mut self) -> Result<(), Error> {
+        loop {
+            let length = self.stream.buffer().len();
+            let eof = self.try_fill().await?;
+            let _ = self.limit_buffer()?;
+            if eof || self.stream.buffer().len() <= length {
+                return Ok(());
+            }
+        }
+    }
+
     async fn try_fill(&mut self) -> Result<bool, Error> {
         match futures_lite::future::poll_once(self.stream.fill()).await {
             Some(Ok(n)) => Ok(n == 0),
-            Some(Err(err)) => Err(err.into()),
+            Some(Err(err)) => Err(Error::io("read")(err)),
             None => Ok(false),
         }
     }
@@ -457,7 +1943,13 @@ where
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        Pin::new(&mut *self.stream.get_mut()).poll_write(cx, buf)
+        let n = ready!(Pin::new(&mut *self.stream.get_mut()).poll_write(cx, buf))?;
+        self.stream.bytes_written += n as u64;
+
+        #[cfg(feature = "tracing")]
+        crate::trace::write(&buf[..n]);
+
+        Poll::Ready(Ok(n))
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -473,7 +1965,13 @@ where
         cx: &mut Context<'_>,
         bufs: &[io::IoSlice<'_>],
     ) -> Poll<io::Result<usize>> {
-        Pin::new(&mut *self.stream.get_mut()).poll_write_vectored(cx, bufs)
+        let n = ready!(Pin::new(&mut *self.stream.get_mut()).poll_write_vectored(cx, bufs))?;
+        self.stream.bytes_written += n as u64;
+
+        #[cfg(feature = "tracing")]
+        crate::trace::write_vectored(bufs, n);
+
+        Poll::Ready(Ok(n))
     }
 }
 
@@ -524,6 +2022,16 @@ struct BufferedStream<S> {
     stream: S,
     buffer: Vec<u8>,
     length: usize,
+    consumed: usize,
+    timestamps: Timestamps,
+    chunk_size: usize,
+    /// A buffer reused by reads from the inner stream.
+    read_buf: Vec<u8>,
+    bytes_read: u64,
+    bytes_written: u64,
+    /// An amount of consumed bytes which are still kept at the start of the buffer,
+    /// as they are borrowed by [CapturesRef].
+    lent: usize,
 }
 
 impl<S> BufferedStream<S> {
@@ -532,16 +2040,71 @@ impl<S> BufferedStream<S> {
             stream,
             buffer: Vec::new(),
             length: 0,
+            consumed: 0,
+            timestamps: Timestamps::new(),
+            chunk_size: READ_CHUNK_SIZE,
+            read_buf: Vec::new(),
+            bytes_read: 0,
+            bytes_written: 0,
+            lent: 0,
         }
     }
 
     fn keep(&mut self, buf: &[u8]) {
+        self.release();
         self.buffer.extend(buf);
         self.length += buf.len();
+        self.timestamps.push(buf.len(), self.length);
     }
 
     fn buffer(&self) -> &[u8] {
-        &self.buffer[..self.length]
+        &self.buffer[self.lent..self.length]
+    }
+
+    /// Consumes `n` bytes but keeps them in the buffer till a next use of the stream.
+    ///
+    /// Returns the consumed bytes.
+    fn lend(&mut self, n: usize) -> &[u8] {
+        self.release();
+        self.lent = n;
+        self.consumed += n;
+        &self.buffer[..n]
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.buffer.reserve(additional);
+    }
+
+    /// Drops lent bytes from the buffer.
+    fn release(&mut self) {
+        if self.lent > 0 {
+            let _ = self.buffer.drain(..self.lent);
+            self.length -= self.lent;
+            self.lent = 0;
+        }
+    }
+
+    /// Removes not consumed bytes after `n`.
+    fn truncate(&mut self, n: usize) {
+        self.release();
+        self.buffer.truncate(n);
+        self.timestamps.truncate(self.length.saturating_sub(n));
+        self.length = self.length.min(n);
+    }
+
+    /// Returns an amount of bytes consumed from the stream.
+    fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Returns a time when a byte before `end` position of the buffer was read.
+    fn timestamp(&self, end: usize) -> Option<Instant> {
+        self.timestamps.get(self.lent + end, self.length)
+    }
+
+    /// Returns a time of the latest read from the stream.
+    fn last_output(&self) -> Option<Instant> {
+        self.timestamps.last()
     }
 
     fn get_mut(&mut self) -> &mut S {
@@ -551,10 +2114,21 @@ impl<S> BufferedStream<S> {
 
 impl<S: AsyncRead + Unpin> BufferedStream<S> {
     async fn fill(&mut self) -> io::Result<usize> {
-        let mut buf = [0; 128];
-        let n = self.stream.read(&mut buf).await?;
-        self.keep(&buf[..n]);
-        Ok(n)
+        // The buffer is lost if the future is dropped in the middle, which is fine.
+        let mut buf = std::mem::take(&mut self.read_buf);
+        buf.resize(self.chunk_size, 0);
+        let result = self.stream.read(&mut buf).await;
+        if let Ok(n) = result {
+            self.bytes_read += n as u64;
+
+            #[cfg(feature = "tracing")]
+            crate::trace::read(&buf[..n]);
+
+            self.keep(&buf[..n]);
+        }
+        self.read_buf = buf;
+
+        result
     }
 }
 
@@ -584,10 +2158,23 @@ impl<S: AsyncRead + Unpin> AsyncRead for BufferedStream<S> {
 
 impl<S: AsyncRead + Unpin> AsyncBufRead for BufferedStream<S> {
     fn poll_fill_buf(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.release();
         if self.buffer.is_empty() {
-            let mut buf = [0; 128];
-            let n = ready!(Pin::new(&mut self.stream).poll_read(cx, &mut buf))?;
-            self.keep(&buf[..n]);
+            let this = self.as_mut().get_mut();
+            let mut buf = std::mem::take(&mut this.read_buf);
+            buf.resize(this.chunk_size, 0);
+            let result = Pin::new(&mut this.stream).poll_read(cx, &mut buf);
+            if let Poll::Ready(Ok(n)) = result {
+                this.bytes_read += n as u64;
+
+                #[cfg(feature = "tracing")]
+                crate::trace::read(&buf[..n]);
+
+                this.keep(&buf[..n]);
+            }
+            this.read_buf = buf;
+
+            let _ = ready!(result)?;
         }
 
         let buf = self.get_mut().buffer();
@@ -595,8 +2182,10 @@ impl<S: AsyncRead + Unpin> AsyncBufRead for BufferedStream<S> {
     }
 
     fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        self.release();
         let _ = self.buffer.drain(..amt);
         self.length -= amt;
+        self.consumed += amt;
     }
 }
 
@@ -615,7 +2204,7 @@ mod tests {
         let mut stream = Stream::new(cursor);
 
         futures_lite::future::block_on(async {
-            let found = stream.expect_lazy("World").await.unwrap();
+            let found = stream.expect_lazy("World", |_| ()).await.unwrap();
             assert_eq!(b"Hello ", found.before());
             assert_eq!(vec![b"World"], found.matches().collect::<Vec<_>>());
         });
@@ -628,7 +2217,7 @@ mod tests {
         let mut stream = Stream::new(cursor);
 
         futures_lite::future::block_on(async {
-            let found = stream.expect_lazy(Eof).await.unwrap();
+            let found = stream.expect_lazy(Eof, |_| ()).await.unwrap();
             assert_eq!(b"", found.before());
             assert_eq!(vec![b"Hello World"], found.matches().collect::<Vec<_>>());
         });
@@ -637,7 +2226,7 @@ mod tests {
         let mut stream = Stream::new(cursor);
 
         futures_lite::future::block_on(async {
-            let err = stream.expect_lazy("").await.unwrap_err();
+            let err = stream.expect_lazy("", |_| ()).await.unwrap_err();
             assert!(matches!(err, Error::Eof));
         });
     }
@@ -650,11 +2239,11 @@ mod tests {
 
             stream.write_all(b"Hello").await.unwrap();
 
-            let err = stream.expect_lazy("Hello World").await.unwrap_err();
-            assert!(matches!(err, Error::ExpectTimeout));
+            let err = stream.expect_lazy("Hello World", |_| ()).await.unwrap_err();
+            assert!(matches!(err, Error::ExpectTimeout { .. }));
 
             stream.write_all(b" World").await.unwrap();
-            let found = stream.expect_lazy("World").await.unwrap();
+            let found = stream.expect_lazy("World", |_| ()).await.unwrap();
             assert_eq!(b"Hello ", found.before());
             assert_eq!(vec![b"World"], found.matches().collect::<Vec<_>>());
         });
@@ -667,7 +2256,7 @@ mod tests {
         let mut stream = Stream::new(cursor);
 
         futures_lite::future::block_on(async {
-            let found = stream.expect_gready("World").await.unwrap();
+            let found = stream.expect_gready("World", |_| ()).await.unwrap();
             assert_eq!(b"Hello ", found.before());
             assert_eq!(vec![b"World"], found.matches().collect::<Vec<_>>());
         });
@@ -680,7 +2269,7 @@ mod tests {
         let mut stream = Stream::new(cursor);
 
         futures_lite::future::block_on(async {
-            let found = stream.expect_gready(Eof).await.unwrap();
+            let found = stream.expect_gready(Eof, |_| ()).await.unwrap();
             assert_eq!(b"", found.before());
             assert_eq!(vec![b"Hello World"], found.matches().collect::<Vec<_>>());
         });
@@ -689,7 +2278,7 @@ mod tests {
         let mut stream = Stream::new(cursor);
 
         futures_lite::future::block_on(async {
-            let err = stream.expect_gready("").await.unwrap_err();
+            let err = stream.expect_gready("", |_| ()).await.unwrap_err();
             assert!(matches!(err, Error::Eof));
         });
     }
@@ -702,16 +2291,55 @@ mod tests {
 
             stream.write_all(b"Hello").await.unwrap();
 
-            let err = stream.expect_gready("Hello World").await.unwrap_err();
-            assert!(matches!(err, Error::ExpectTimeout));
+            let err = stream
+                .expect_gready("Hello World", |_| ())
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::ExpectTimeout { .. }));
 
             stream.write_all(b" World").await.unwrap();
-            let found = stream.expect_gready("World").await.unwrap();
+            let found = stream.expect_gready("World", |_| ()).await.unwrap();
             assert_eq!(b"Hello ", found.before());
             assert_eq!(vec![b"World"], found.matches().collect::<Vec<_>>());
         });
     }
 
+    #[test]
+    fn test_expect_watchdog() {
+        futures_lite::future::block_on(async {
+            let mut stream = Stream::new(NoEofReader::default());
+            stream.set_expect_timeout(Some(Duration::from_millis(1000)));
+            stream.watchdog = Some(Watchdog::new(
+                Duration::from_millis(50),
+                |_| WatchdogAction::Send(b" World".to_vec()),
+                Instant::now(),
+            ));
+
+            stream.write_all(b"Hello").await.unwrap();
+
+            let found = stream.expect_gready("Hello World", |_| ()).await.unwrap();
+            assert_eq!(b"", found.before());
+        });
+    }
+
+    #[test]
+    fn test_expect_keepalive() {
+        futures_lite::future::block_on(async {
+            let mut stream = Stream::new(NoEofReader::default());
+            stream.set_expect_timeout(Some(Duration::from_millis(1000)));
+            stream.keepalive = Some(Keepalive::new(
+                Duration::from_millis(50),
+                b"\n".to_vec(),
+                Instant::now(),
+            ));
+
+            stream.write_all(b"Hello").await.unwrap();
+
+            let found = stream.expect_gready("Hello\n\n", |_| ()).await.unwrap();
+            assert_eq!(b"", found.before());
+        });
+    }
+
     #[test]
     fn test_check() {
         let buf = b"Hello World".to_vec();
@@ -739,6 +2367,24 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_lines() {
+        let buf = b"Hello\r\nWorld\nand the rest".to_vec();
+        let cursor = futures_lite::io::Cursor::new(buf);
+        let mut session = Session::new((), cursor).unwrap();
+
+        futures_lite::future::block_on(async {
+            use futures_lite::StreamExt;
+
+            let lines = session
+                .lines()
+                .collect::<io::Result<Vec<String>>>()
+                .await
+                .unwrap();
+            assert_eq!(lines, ["Hello", "World", "and the rest"]);
+        });
+    }
+
     #[derive(Debug, Default)]
     struct NoEofReader {
         data: Vec<u8>,
@@ -0,0 +1,215 @@
+//! This module contains a [DetachedSession] structure.
+
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    clock::{default_clock, Clock},
+    session::{
+        abort::AbortPatterns, keepalive::Keepalive, overflow::BufferLimit, pager::Pager,
+        watchdog::Watchdog, ConsumeMode, OsProcess, SessionMetrics,
+    },
+    LineEnding,
+};
+
+/// DetachedSession holds a state of a [`Session`] which was detached from its controlling side.
+///
+/// The process and its stream are kept untouched,
+/// so a child keeps running and nothing written by it is lost.
+/// Data which was already read but not consumed by an `expect` call is kept as well.
+///
+/// The handle can be moved to a different thread (when `P` and `S` are [`Send`])
+/// and attached back by [`DetachedSession::attach`].
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Expect};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.send_line("Hello World").unwrap();
+///
+/// let detached = p.detach();
+///
+/// let handle = std::thread::spawn(move || {
+///     let mut p = detached.attach().unwrap();
+///     p.expect("Hello World").unwrap();
+/// });
+///
+/// handle.join().unwrap();
+/// ```
+///
+/// A serializable part of the handle can be taken by [`DetachedSession::state`]
+/// and be used to restore it by [`DetachedSession::from_state`].
+///
+/// [`Session`]: crate::Session
+#[derive(Debug)]
+pub struct DetachedSession<P, S> {
+    pub(crate) process: P,
+    pub(crate) stream: S,
+    pub(crate) buffer: Vec<u8>,
+    pub(crate) expect_timeout: Option<Duration>,
+    pub(crate) expect_lazy: bool,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) bracketed_paste: bool,
+    pub(crate) consume_mode: ConsumeMode,
+    pub(crate) name: Option<String>,
+    pub(crate) watchdog: Option<Watchdog>,
+    pub(crate) keepalive: Option<Keepalive>,
+    pub(crate) abort_patterns: AbortPatterns,
+    pub(crate) buffer_limit: Option<BufferLimit>,
+    pub(crate) pager: Option<Pager>,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) read_chunk_size: usize,
+    pub(crate) poll_interval: Duration,
+    pub(crate) metrics: SessionMetrics,
+}
+
+impl<P, S> DetachedSession<P, S> {
+    /// Get a reference to a process running program.
+    pub fn get_process(&self) -> &P {
+        &self.process
+    }
+
+    /// Get a mut reference to a process running program.
+    pub fn get_process_mut(&mut self) -> &mut P {
+        &mut self.process
+    }
+
+    /// Get a reference to original stream.
+    pub fn get_stream(&self) -> &S {
+        &self.stream
+    }
+
+    /// Get a mut reference to original stream.
+    pub fn get_stream_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Get a data which was read from the stream but not yet consumed.
+    pub fn get_buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Get an expect timeout of the session.
+    pub fn get_expect_timeout(&self) -> Option<Duration> {
+        self.expect_timeout
+    }
+
+    /// Get a line ending of the session.
+    pub fn get_line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Get a name of the session.
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Splits the handle into a process, a stream and a not consumed data.
+    pub fn into_parts(self) -> (P, S, Vec<u8>) {
+        (self.process, self.stream, self.buffer)
+    }
+}
+
+impl<P, S> DetachedSession<P, S> {
+    /// Restores a handle from a process, its stream and a saved state.
+    ///
+    /// Settings which are not a part of [`DetachedState`]
+    /// (a watchdog, a keepalive, abort patterns, a buffer limit, a pager and a clock)
+    /// are set to defaults.
+    pub fn from_state(process: P, stream: S, state: DetachedState) -> Self {
+        Self {
+            process,
+            stream,
+            buffer: state.buffer,
+            expect_timeout: state.expect_timeout,
+            expect_lazy: state.expect_lazy,
+            line_ending: state.line_ending,
+            bracketed_paste: state.bracketed_paste,
+            consume_mode: state.consume_mode,
+            name: state.name,
+            watchdog: None,
+            keepalive: None,
+            abort_patterns: AbortPatterns::default(),
+            buffer_limit: None,
+            pager: None,
+            clock: default_clock(),
+            read_chunk_size: state.read_chunk_size,
+            poll_interval: state.poll_interval,
+            metrics: SessionMetrics::default(),
+        }
+    }
+}
+
+impl<S> DetachedSession<OsProcess, S> {
+    /// Returns a serializable state of the handle.
+    ///
+    /// It contains a process id, a not consumed data and settings of the session.
+    pub fn state(&self) -> DetachedState {
+        #[cfg(unix)]
+        let pid = self.process.pid().as_raw() as u32;
+        #[cfg(windows)]
+        let pid = self.process.pid();
+
+        DetachedState {
+            pid,
+            buffer: self.buffer.clone(),
+            expect_timeout: self.expect_timeout,
+            expect_lazy: self.expect_lazy,
+            line_ending: self.line_ending,
+            bracketed_paste: self.bracketed_paste,
+            consume_mode: self.consume_mode,
+            name: self.name.clone(),
+            read_chunk_size: self.read_chunk_size,
+            poll_interval: self.poll_interval,
+        }
+    }
+}
+
+/// DetachedState is a serializable state of a [`DetachedSession`].
+///
+/// It lets a supervisor store a session while a process keeps running,
+/// e.g. to hand its pty over to a different tool and restore the session afterwards.
+///
+/// It implements `serde::Serialize` and `serde::Deserialize` if the `serde` feature is on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DetachedState {
+    pid: u32,
+    buffer: Vec<u8>,
+    expect_timeout: Option<Duration>,
+    expect_lazy: bool,
+    line_ending: LineEnding,
+    bracketed_paste: bool,
+    consume_mode: ConsumeMode,
+    name: Option<String>,
+    read_chunk_size: usize,
+    poll_interval: Duration,
+}
+
+impl DetachedState {
+    /// Get an OS identifier of the process.
+    pub fn get_pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Get a data which was read from the stream but not yet consumed.
+    pub fn get_buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Get an expect timeout of the session.
+    pub fn get_expect_timeout(&self) -> Option<Duration> {
+        self.expect_timeout
+    }
+
+    /// Get a line ending of the session.
+    pub fn get_line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Get a name of the session.
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
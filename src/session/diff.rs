@@ -0,0 +1,93 @@
+//! This module contains a rendering of a difference between expected and actual lines.
+
+use std::fmt::Write;
+
+/// Panics with a diff of lines if they are not equal.
+#[track_caller]
+pub(crate) fn assert_lines_eq<L>(expected: &[L], actual: &[String])
+where
+    L: AsRef<str>,
+{
+    let expected = expected.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    let actual = actual.iter().map(String::as_str).collect::<Vec<_>>();
+    if expected != actual {
+        panic!(
+            "an output doesn't match expected lines\n{}",
+            diff(&expected, &actual)
+        );
+    }
+}
+
+/// Renders a line by line diff.
+///
+/// Lines are escaped so control characters and trailing spaces are visible.
+pub(crate) fn diff(expected: &[&str], actual: &[&str]) -> String {
+    let mut out = String::from("--- expected\n+++ actual\n");
+
+    // A length of the longest common subsequence of suffixes of the lines.
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            push_line(&mut out, ' ', expected[i]);
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            push_line(&mut out, '-', expected[i]);
+            i += 1;
+        } else {
+            push_line(&mut out, '+', actual[j]);
+            j += 1;
+        }
+    }
+
+    out
+}
+
+/// Removes a trailing `\n` or `\r\n`.
+pub(crate) fn trim_line_ending(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+fn push_line(out: &mut String, sign: char, line: &str) {
+    let _ = writeln!(out, "{} \"{}\"", sign, line.escape_debug());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(
+            diff(&["a", "b", "c"], &["a", "x\t", "c", "\u{1b}[0m"]),
+            concat!(
+                "--- expected\n",
+                "+++ actual\n",
+                "  \"a\"\n",
+                "- \"b\"\n",
+                "+ \"x\\t\"\n",
+                "  \"c\"\n",
+                "+ \"\\u{1b}[0m\"\n",
+            )
+        );
+        assert_eq!(diff(&["a"], &[]), "--- expected\n+++ actual\n- \"a\"\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "- \"hello\"\n+ \"hello \"")]
+    fn test_assert_lines_eq() {
+        assert_lines_eq(&["hello"], &["hello ".to_owned()]);
+    }
+}
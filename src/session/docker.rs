@@ -0,0 +1,87 @@
+//! This module contains a constructor of a session inside a docker container.
+
+use std::{ffi::OsString, process::Command};
+
+use crate::{
+    session::{OsProc, OsProcStream, Session},
+    Error,
+};
+
+impl Session<OsProc, OsProcStream> {
+    /// Spawns a session with a command running inside a docker container by `docker exec -it`.
+    ///
+    /// A program, arguments, environment variables and a working directory
+    /// are taken from the `command` and passed to the container.
+    ///
+    /// The `docker` client gets a pseudo terminal of the session,
+    /// so a TTY is allocated in the container and [`WindowSize::set_window_size`]
+    /// is propagated to it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use expectrl::Session;
+    ///
+    /// let mut cmd = Command::new("bash");
+    /// cmd.env("PS1", "$ ");
+    ///
+    /// let p = Session::spawn_docker("my-container", cmd).unwrap();
+    /// ```
+    ///
+    /// [`WindowSize::set_window_size`]: crate::process::WindowSize::set_window_size
+    pub fn spawn_docker<C>(container: C, command: Command) -> Result<Self, Error>
+    where
+        C: Into<OsString>,
+    {
+        Self::spawn(docker_exec_command(container.into(), &command))
+    }
+}
+
+/// Builds a `docker exec -it` command which runs a given command in a container.
+fn docker_exec_command(container: OsString, command: &Command) -> Command {
+    let mut cmd = Command::new("docker");
+    let _ = cmd.args(["exec", "-it"]);
+
+    if let Some(dir) = command.get_current_dir() {
+        let _ = cmd.arg("-w").arg(dir);
+    }
+
+    // Removed variables (a `None` value) are not present in a container anyway.
+    for (key, value) in command.get_envs() {
+        if let Some(value) = value {
+            let mut env = key.to_owned();
+            env.push("=");
+            env.push(value);
+
+            let _ = cmd.arg("-e").arg(env);
+        }
+    }
+
+    let _ = cmd.arg(container);
+    let _ = cmd.arg(command.get_program());
+    let _ = cmd.args(command.get_args());
+
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docker_exec_command() {
+        let mut command = Command::new("sh");
+        let _ = command
+            .args(["-c", "echo $A"])
+            .env("A", "1")
+            .current_dir("/tmp");
+
+        let cmd = docker_exec_command("app".into(), &command);
+        assert_eq!(cmd.get_program(), "docker");
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            ["exec", "-it", "-w", "/tmp", "-e", "A=1", "app", "sh", "-c", "echo $A"]
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! This module contains a keepalive writer of a session.
+
+use std::time::{Duration, Instant};
+
+/// Keepalive writes given bytes to a process each interval
+/// while a session is waiting for an output.
+#[derive(Debug, Clone)]
+pub(crate) struct Keepalive {
+    interval: Duration,
+    bytes: Vec<u8>,
+    last_write: Instant,
+}
+
+impl Keepalive {
+    pub(crate) fn new(interval: Duration, bytes: Vec<u8>, now: Instant) -> Self {
+        Self {
+            interval,
+            bytes,
+            last_write: now,
+        }
+    }
+
+    /// Starts a new interval.
+    pub(crate) fn reset(&mut self, now: Instant) {
+        self.last_write = now;
+    }
+
+    /// Returns a time left till next bytes must be written.
+    pub(crate) fn remaining(&self, now: Instant) -> Duration {
+        self.interval
+            .saturating_sub(now.saturating_duration_since(self.last_write))
+    }
+
+    /// Returns bytes which must be written if the interval has passed.
+    pub(crate) fn check(&mut self, now: Instant) -> Option<&[u8]> {
+        if now.saturating_duration_since(self.last_write) < self.interval {
+            return None;
+        }
+
+        self.reset(now);
+
+        Some(&self.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keepalive() {
+        let now = Instant::now();
+        let mut keepalive = Keepalive::new(Duration::from_millis(50), b"\0".to_vec(), now);
+        assert_eq!(keepalive.check(now), None);
+
+        let now = now + Duration::from_millis(60);
+        assert_eq!(keepalive.check(now), Some(&b"\0"[..]));
+        assert_eq!(keepalive.check(now), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! This module contains counters of a session.
+
+use std::time::Duration;
+
+use crate::Error;
+
+/// SessionMetrics is a snapshot of counters of a session.
+///
+/// It can be exported to a monitoring system by a long running process
+/// which orchestrates many sessions.
+///
+/// # Example
+///
+/// ```no_run
+/// use expectrl::{spawn, Expect};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.send_line("Hello World").unwrap();
+/// p.expect("Hello World").unwrap();
+///
+/// let metrics = p.metrics();
+/// assert_eq!(metrics.expects(), 1);
+/// println!("average latency {:?}", metrics.average_match_latency());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionMetrics {
+    bytes_read: u64,
+    bytes_written: u64,
+    expects: u64,
+    matches: u64,
+    timeouts: u64,
+    match_time: Duration,
+}
+
+impl SessionMetrics {
+    /// Returns an amount of bytes read from a process.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Returns an amount of bytes written to a process.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Returns an amount of expect calls, successful or not.
+    pub fn expects(&self) -> u64 {
+        self.expects
+    }
+
+    /// Returns an amount of expect calls which found a match.
+    pub fn matches(&self) -> u64 {
+        self.matches
+    }
+
+    /// Returns an amount of expect calls which failed by a timeout.
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts
+    }
+
+    /// Returns an average time an expect call took to find a match.
+    ///
+    /// It's `None` if nothing was matched yet.
+    pub fn average_match_latency(&self) -> Option<Duration> {
+        if self.matches == 0 {
+            return None;
+        }
+
+        let nanos = self.match_time.as_nanos() / u128::from(self.matches);
+        Some(Duration::from_nanos(nanos as u64))
+    }
+
+    /// Accounts a finished expect call.
+    pub(crate) fn record_expect<T>(&mut self, result: &Result<T, Error>, elapsed: Duration) {
+        self.expects += 1;
+        match result {
+            Ok(_) => {
+                self.matches += 1;
+                self.match_time += elapsed;
+            }
+            Err(Error::ExpectTimeout { .. }) => self.timeouts += 1,
+            Err(_) => {}
+        }
+    }
+
+    /// Returns the metrics with bytes added to IO counters.
+    pub(crate) fn add_io(mut self, bytes_read: u64, bytes_written: u64) -> Self {
+        self.bytes_read += bytes_read;
+        self.bytes_written += bytes_written;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_expect() {
+        let mut metrics = SessionMetrics::default();
+        assert_eq!(metrics.average_match_latency(), None);
+
+        metrics.record_expect(&Ok(()), Duration::from_millis(10));
+        metrics.record_expect(&Ok(()), Duration::from_millis(30));
+        metrics.record_expect::<()>(
+            &Err(Error::expect_timeout(String::new(), &[])),
+            Duration::from_secs(1),
+        );
+        metrics.record_expect::<()>(&Err(Error::Eof), Duration::from_secs(1));
+
+        assert_eq!(metrics.expects(), 4);
+        assert_eq!(metrics.matches(), 2);
+        assert_eq!(metrics.timeouts(), 1);
+        assert_eq!(
+            metrics.average_match_latency(),
+            Some(Duration::from_millis(20))
+        );
+
+        let metrics = metrics.add_io(10, 20).add_io(1, 2);
+        assert_eq!(metrics.bytes_read(), 11);
+        assert_eq!(metrics.bytes_written(), 22);
+    }
+}
@@ -20,16 +20,68 @@ mod async_session;
 #[cfg(not(feature = "async"))]
 mod sync_session;
 
-use std::{io::Write, process::Command};
+mod abort;
+mod detached;
+mod diff;
+mod docker;
+mod keepalive;
+mod metrics;
+mod options;
+mod overflow;
+mod pager;
+mod retry;
+#[cfg(feature = "snapshot")]
+mod snapshot;
+mod timestamps;
+mod typing;
+mod watchdog;
+mod within;
 
-use crate::{interact::InteractSession, process::Process, stream::log::LogStream, Error};
+#[cfg(all(feature = "ssh", not(feature = "async")))]
+pub mod ssh;
+
+use std::{io::Write, path::Path, process::Command};
+
+use crate::{
+    interact::InteractSession,
+    process::{DropPolicy, OnDrop, Process, WindowSize},
+    stream::{
+        capture::RotatingFile,
+        cast::CastStream,
+        chaos::{Chaos, ChaosStream},
+        dynamic::{DynStream, IoStream},
+        log::LogStream,
+        pretty::PrettyLogStream,
+        tee::TeeStream,
+        throttle::ThrottledStream,
+    },
+    Error,
+};
+
+#[cfg(not(feature = "async"))]
+use std::{
+    io::Read,
+    net::{TcpStream, ToSocketAddrs},
+};
 
 #[cfg(not(feature = "async"))]
-use std::io::Read;
+use crate::stream::telnet::TelnetStream;
+
+#[cfg(all(unix, not(feature = "async")))]
+use crate::{
+    process::pipe::{PipeProcess, PipeStream, StderrSession},
+    Captures, Expect, Needle,
+};
 
 #[cfg(feature = "async")]
 use crate::process::IntoAsyncStream;
 
+#[cfg(feature = "encoding")]
+use crate::stream::encoding::EncodingStream;
+
+#[cfg(all(target_os = "linux", feature = "uring", not(feature = "async")))]
+use crate::stream::uring::UringStream;
+
 #[cfg(unix)]
 type OsProc = crate::process::unix::UnixProcess;
 #[cfg(windows)]
@@ -44,6 +96,36 @@ type OsProcStream = crate::process::windows::ProcessStream;
 #[cfg(all(windows, feature = "async"))]
 type OsProcStream = crate::process::windows::AsyncProcessStream;
 
+/// A sequence which starts a bracketed paste.
+const BRACKETED_PASTE_START: &[u8] = b"\x1b[200~";
+/// A sequence which ends a bracketed paste.
+const BRACKETED_PASTE_END: &[u8] = b"\x1b[201~";
+/// A size of a chunk which is written at once when bracketed paste is off.
+const PASTE_CHUNK_SIZE: usize = 64;
+/// A delay between chunks which are written when bracketed paste is off.
+const PASTE_CHUNK_DELAY: std::time::Duration = std::time::Duration::from_millis(10);
+/// A default pause between polls of a process which has nothing to read,
+/// and between checks of a process status while waiting for its exit.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+/// A default size of a chunk which is read from a process at once.
+const READ_CHUNK_SIZE: usize = 248;
+
+/// Returns a function which wraps an error of spawning a command.
+fn spawn_failed(cmd: &str) -> impl FnOnce(std::io::Error) -> Error + '_ {
+    move |source| Error::SpawnFailed {
+        cmd: cmd.to_owned(),
+        source,
+    }
+}
+
+/// Returns an error of a missing named group of a regex.
+fn unknown_capture_group(name: &str) -> Error {
+    Error::Other {
+        message: "a capture group was not matched".to_string(),
+        err: format!("no group named {:?}", name),
+    }
+}
+
 /// A type alias for OS process which can run a [`Session`] and a default one.
 pub type OsProcess = OsProc;
 /// A type alias for OS process stream which is a default one for [`Session`].
@@ -57,6 +139,30 @@ pub use async_session::Session;
 #[cfg(not(feature = "async"))]
 pub use sync_session::Session;
 
+pub use detached::{DetachedSession, DetachedState};
+pub use metrics::SessionMetrics;
+pub use options::{default_options, reset_default_options, set_default_options, SessionOptions};
+pub use overflow::OverflowPolicy;
+pub use pager::Pager;
+pub use retry::RetryPolicy;
+#[cfg(feature = "snapshot")]
+pub use snapshot::{Snapshot, UPDATE_SNAPSHOTS_ENV};
+pub use typing::TypingOptions;
+pub use watchdog::WatchdogAction;
+
+/// ConsumeMode decides what is consumed from a session buffer by a successful expect call.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConsumeMode {
+    /// Consume bytes up to the end of a match.
+    ///
+    /// Bytes after the match are left in the buffer for next calls.
+    #[default]
+    UpToMatch,
+    /// Consume the whole buffer including bytes which were read after the match.
+    WholeBuffer,
+}
+
 impl Session<OsProc, OsProcStream> {
     /// Spawns a session on a platform process.
     ///
@@ -69,13 +175,40 @@ impl Session<OsProc, OsProcStream> {
     /// let p = Session::spawn(Command::new("cat"));
     /// ```
     pub fn spawn(command: Command) -> Result<Self, Error> {
-        let mut process = OsProcess::spawn_command(command)?;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("spawn", command = ?command).entered();
+
+        let cmd = format!("{:?}", command);
+        let mut process = OsProcess::spawn_command(command).map_err(spawn_failed(&cmd))?;
+        let stream = process.open_stream()?;
+
+        #[cfg(feature = "async")]
+        let stream = stream.into_async_stream()?;
+
+        let mut session = Self::new(process, stream)?;
+        session.set_default_options();
+
+        Ok(session)
+    }
+
+    /// Spawns a session on a platform process and applies the options to it.
+    ///
+    /// Default options (see [`set_default_options`]) are not used.
+    ///
+    /// See [`SessionOptions`].
+    pub fn spawn_with(command: Command, options: &SessionOptions) -> Result<Self, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("spawn", command = ?command).entered();
+
+        let cmd = format!("{:?}", command);
+        let mut process = OsProcess::spawn_command(command).map_err(spawn_failed(&cmd))?;
         let stream = process.open_stream()?;
 
         #[cfg(feature = "async")]
         let stream = stream.into_async_stream()?;
 
-        let session = Self::new(process, stream)?;
+        let mut session = Self::new(process, stream)?;
+        session.set_options(options);
 
         Ok(session)
     }
@@ -83,13 +216,324 @@ impl Session<OsProc, OsProcStream> {
     /// Spawns a session on a platform process.
     /// Using a string commandline.
     pub(crate) fn spawn_cmd(cmd: &str) -> Result<Self, Error> {
-        let mut process = OsProcess::spawn(cmd)?;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("spawn", command = cmd).entered();
+
+        let mut process = OsProcess::spawn(cmd).map_err(spawn_failed(cmd))?;
+        let stream = process.open_stream()?;
+
+        #[cfg(feature = "async")]
+        let stream = stream.into_async_stream()?;
+
+        let mut session = Self::new(process, stream)?;
+        session.set_default_options();
+
+        Ok(session)
+    }
+}
+
+#[cfg(unix)]
+impl Session<OsProc, OsProcStream> {
+    /// Spawns a session with a hook which is run in a child process right before `exec`.
+    ///
+    /// See [`UnixProcess::spawn_with_pre_exec`].
+    ///
+    /// # Safety
+    ///
+    /// The hook is run after `fork`, so the same restrictions as for
+    /// [`CommandExt::pre_exec`] are applied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use expectrl::Session;
+    ///
+    /// let p = unsafe {
+    ///     Session::spawn_with_pre_exec(Command::new("cat"), || {
+    ///         nix::unistd::chdir("/")?;
+    ///         Ok(())
+    ///     })
+    /// };
+    /// ```
+    ///
+    /// [`UnixProcess::spawn_with_pre_exec`]: crate::process::unix::UnixProcess::spawn_with_pre_exec
+    /// [`CommandExt::pre_exec`]: std::os::unix::process::CommandExt::pre_exec
+    pub unsafe fn spawn_with_pre_exec<F>(command: Command, hook: F) -> Result<Self, Error>
+    where
+        F: FnMut() -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        let cmd = format!("{:?}", command);
+        let mut process =
+            OsProcess::spawn_with_pre_exec(command, hook).map_err(spawn_failed(&cmd))?;
         let stream = process.open_stream()?;
 
         #[cfg(feature = "async")]
         let stream = stream.into_async_stream()?;
 
-        let session = Self::new(process, stream)?;
+        let mut session = Self::new(process, stream)?;
+        session.set_default_options();
+
+        Ok(session)
+    }
+
+    /// Spawns a session isolated by a [`Sandbox`],
+    /// e.g. in new Linux namespaces or in a `chroot`.
+    ///
+    /// See [`UnixProcess::spawn_sandboxed`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use expectrl::{process::unix::Sandbox, Session};
+    ///
+    /// let sandbox = Sandbox::new().chroot("/srv/rootfs");
+    ///
+    /// let p = Session::spawn_sandboxed(Command::new("sh"), &sandbox);
+    /// ```
+    ///
+    /// [`Sandbox`]: crate::process::unix::Sandbox
+    /// [`UnixProcess::spawn_sandboxed`]: crate::process::unix::UnixProcess::spawn_sandboxed
+    pub fn spawn_sandboxed(
+        command: Command,
+        sandbox: &crate::process::unix::Sandbox,
+    ) -> Result<Self, Error> {
+        let mut process = OsProcess::spawn_sandboxed(command, sandbox)?;
+        let stream = process.open_stream()?;
+
+        #[cfg(feature = "async")]
+        let stream = stream.into_async_stream()?;
+
+        let mut session = Self::new(process, stream)?;
+        session.set_default_options();
+
+        Ok(session)
+    }
+}
+
+impl<S> Session<OsProc, S> {
+    /// Kills the spawned process and all its descendants.
+    ///
+    /// It's useful when a program starts background processes which would outlive a session otherwise.
+    ///
+    /// See `kill_tree` of [`OsProcess`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let mut p = expectrl::spawn("bash").unwrap();
+    /// p.kill_tree().unwrap();
+    /// ```
+    pub fn kill_tree(&mut self) -> Result<(), Error> {
+        self.get_process_mut().kill_tree()?;
+        Ok(())
+    }
+
+    /// Sends a signal to the whole process group of the spawned process.
+    ///
+    /// It's useful to interrupt a program which spawns its own subprocesses.
+    ///
+    /// See [`UnixProcess::signal_group`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use expectrl::{process::unix::Signal, Session};
+    ///
+    /// let mut cmd = Command::new("sh");
+    /// cmd.args(["-c", "sleep 100 | cat"]);
+    ///
+    /// let mut p = Session::spawn(cmd).unwrap();
+    /// p.signal_group(Signal::SIGINT).unwrap();
+    /// ```
+    ///
+    /// [`UnixProcess::signal_group`]: crate::process::unix::UnixProcess::signal_group
+    #[cfg(unix)]
+    pub fn signal_group(&mut self, signal: crate::process::unix::Signal) -> Result<(), Error> {
+        self.get_process_mut().signal_group(signal)?;
+        Ok(())
+    }
+}
+
+#[cfg(all(unix, feature = "runtime-async-std"))]
+impl Session<OsProc, crate::process::unix::AsyncStdPtyStream> {
+    /// Spawns a session which stream is driven by `async-std` runtime.
+    ///
+    /// See [`AsyncStdPtyStream`].
+    ///
+    /// [`AsyncStdPtyStream`]: crate::process::unix::AsyncStdPtyStream
+    pub fn spawn_async_std(command: Command) -> Result<Self, Error> {
+        let cmd = format!("{:?}", command);
+        let mut process = OsProcess::spawn_command(command).map_err(spawn_failed(&cmd))?;
+        let stream = process.open_stream()?;
+        let stream = crate::process::unix::AsyncStdPtyStream::new(stream)?;
+
+        let mut session = Self::new(process, stream)?;
+        session.set_default_options();
+
+        Ok(session)
+    }
+}
+
+#[cfg(all(unix, feature = "runtime-smol"))]
+impl Session<OsProc, crate::process::unix::SmolPtyStream> {
+    /// Spawns a session which stream is driven by `smol` runtime.
+    ///
+    /// See [`SmolPtyStream`].
+    ///
+    /// [`SmolPtyStream`]: crate::process::unix::SmolPtyStream
+    pub fn spawn_smol(command: Command) -> Result<Self, Error> {
+        let cmd = format!("{:?}", command);
+        let mut process = OsProcess::spawn_command(command).map_err(spawn_failed(&cmd))?;
+        let stream = process.open_stream()?;
+        let stream = crate::process::unix::SmolPtyStream::new(stream)?;
+
+        let mut session = Self::new(process, stream)?;
+        session.set_default_options();
+
+        Ok(session)
+    }
+}
+
+#[cfg(all(unix, feature = "runtime-tokio"))]
+impl Session<OsProc, crate::process::unix::TokioPtyStream> {
+    /// Spawns a session which stream is driven by `tokio` runtime.
+    ///
+    /// # Panics
+    ///
+    /// It panics if it's called outside of a `tokio` runtime.
+    ///
+    /// See [`TokioPtyStream`].
+    ///
+    /// [`TokioPtyStream`]: crate::process::unix::TokioPtyStream
+    pub fn spawn_tokio(command: Command) -> Result<Self, Error> {
+        let cmd = format!("{:?}", command);
+        let mut process = OsProcess::spawn_command(command).map_err(spawn_failed(&cmd))?;
+        let stream = process.open_stream()?;
+        let stream = crate::process::unix::TokioPtyStream::new(stream)?;
+
+        let mut session = Self::new(process, stream)?;
+        session.set_default_options();
+
+        Ok(session)
+    }
+}
+
+#[cfg(all(unix, not(feature = "async")))]
+impl Session<PipeProcess, PipeStream> {
+    /// Spawns a session on a process which is connected by pipes rather than a pty.
+    ///
+    /// Its `stderr` is kept apart from `stdout`,
+    /// see [`Session::expect_stderr`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use expectrl::Session;
+    ///
+    /// let p = Session::spawn_piped(Command::new("cat"));
+    /// ```
+    pub fn spawn_piped(command: Command) -> Result<Self, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("spawn", command = ?command).entered();
+
+        let cmd = format!("{:?}", command);
+        let mut process = PipeProcess::spawn_command(command).map_err(spawn_failed(&cmd))?;
+        let stream = process.open_stream()?;
+
+        let mut session = Self::new(process, stream)?;
+        session.set_default_options();
+
+        Ok(session)
+    }
+}
+
+#[cfg(all(unix, not(feature = "async")))]
+impl<S> Session<PipeProcess, S> {
+    /// Expects a needle in `stderr` of the process.
+    ///
+    /// It's the same as [`Expect::expect`] but for `stderr`,
+    /// and the expect timeout of the session is used.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use expectrl::Session;
+    ///
+    /// let mut cmd = Command::new("ls");
+    /// cmd.arg("/missing");
+    ///
+    /// let mut p = Session::spawn_piped(cmd).unwrap();
+    /// p.expect_stderr("No such file or directory").unwrap();
+    /// ```
+    pub fn expect_stderr<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let timeout = self.get_expect_timeout();
+        let stderr = self.get_stderr_mut();
+        stderr.set_expect_timeout(timeout);
+        stderr.expect(needle)
+    }
+
+    /// Returns a session which reads `stderr` of the process.
+    ///
+    /// A log of `stderr` is set on it independently from a log of `stdout`.
+    pub fn get_stderr_mut(&mut self) -> &mut StderrSession {
+        self.get_process_mut().get_stderr_mut()
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl Session<(), TcpStream> {
+    /// Connects to a socket and wraps it in a session.
+    ///
+    /// There's no child process behind such session,
+    /// so [`Error::Eof`] is returned once the connection is closed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{Expect, Session};
+    ///
+    /// let mut p = Session::connect_tcp("127.0.0.1:2323").unwrap();
+    /// p.expect("login:").unwrap();
+    /// ```
+    pub fn connect_tcp<A>(addr: A) -> Result<Self, Error>
+    where
+        A: ToSocketAddrs,
+    {
+        let stream = TcpStream::connect(addr)?;
+        let session = Self::new((), stream)?;
+
+        Ok(session)
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl Session<(), TelnetStream<TcpStream>> {
+    /// Connects to a telnet server and wraps the connection in a session.
+    ///
+    /// Telnet option negotiation is handled by [`TelnetStream`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{Expect, Session};
+    ///
+    /// let mut p = Session::connect_telnet("127.0.0.1:23").unwrap();
+    /// p.expect("login:").unwrap();
+    /// ```
+    pub fn connect_telnet<A>(addr: A) -> Result<Self, Error>
+    where
+        A: ToSocketAddrs,
+    {
+        let stream = TcpStream::connect(addr)?;
+        let session = Self::new((), TelnetStream::new(stream))?;
 
         Ok(session)
     }
@@ -114,7 +558,9 @@ impl<P, S> Session<P, S> {
     /// BEWARE that interact finishes after a process stops.
     /// So after the return you may not obtain a correct status of a process.
     ///
-    /// In not `async` mode the default version uses a buzy loop.
+    /// In not `async` mode the default version waits on a process with `poll()` (`select()` on macOS),
+    /// while an input is checked each few milliseconds
+    /// unless `InteractSession::wait_on_input_fd` is set. On `windows` it uses a buzy loop.
     ///
     /// - On `linux` you can use a `polling` version using the corresponding feature.
     /// - On `windows` the feature is also present but it spawns a thread for pooling which creates a set of obsticales.
@@ -147,8 +593,160 @@ impl<P, S> Session<P, S> {
     }
 }
 
+impl<P, S> Session<P, S> {
+    /// Copy everything read from a process to `stdout` as it arrives.
+    ///
+    /// It's handy to debug a hanging `expect` call.
+    ///
+    /// See [`tee`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let p = expectrl::spawn("cat").unwrap().with_tee_stdout().unwrap();
+    /// ```
+    #[cfg(not(feature = "async"))]
+    pub fn with_tee_stdout(self) -> Result<Session<P, TeeStream<S, std::io::Stdout>>, Error>
+    where
+        S: Read,
+    {
+        tee(self, std::io::stdout())
+    }
+
+    /// Copy everything read from a process to `stdout` as it arrives.
+    ///
+    /// It's handy to debug a hanging `expect` call.
+    ///
+    /// See [`tee`].
+    #[cfg(feature = "async")]
+    pub fn with_tee_stdout(self) -> Result<Session<P, TeeStream<S, std::io::Stdout>>, Error> {
+        tee(self, std::io::stdout())
+    }
+}
+
+impl<P, S> Session<P, S> {
+    /// Hides a type of the stream behind a dynamic dispatch.
+    ///
+    /// Sessions which streams are wrapped differently get the same type,
+    /// so they can be kept in a collection.
+    /// A log can be turned on and off by [`Session::enable_log`] and [`Session::disable_log`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut p = expectrl::spawn("cat").unwrap().into_dyn().unwrap();
+    /// p.enable_log(std::io::stderr());
+    /// p.disable_log();
+    /// ```
+    pub fn into_dyn(self) -> Result<Session<P, DynStream>, Error>
+    where
+        S: IoStream + 'static,
+    {
+        let name = self.get_name().map(ToOwned::to_owned);
+        self.swap_stream(|s| DynStream::new(s).with_name(name))
+    }
+}
+
+impl<P, S> Session<P, S>
+where
+    P: OnDrop,
+{
+    /// Drops the session leaving the process running.
+    ///
+    /// It's the same as dropping the session with [`DropPolicy::Nothing`] set,
+    /// see [`OnDrop::set_drop_policy`] for other options.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{process::{DropPolicy, OnDrop}, spawn};
+    ///
+    /// let mut p = spawn("sleep 1").unwrap();
+    /// p.set_drop_policy(DropPolicy::Wait(std::time::Duration::from_secs(2)));
+    ///
+    /// let p = spawn("sleep 100").unwrap();
+    /// p.leak();
+    /// ```
+    pub fn leak(mut self) {
+        self.set_drop_policy(DropPolicy::Nothing);
+    }
+}
+
+impl<P, S> Session<P, S> {
+    /// Applies the options to the session.
+    ///
+    /// All settings covered by [`SessionOptions`] are replaced,
+    /// so a buffer limit and a pager handler are removed if they're not set in the options.
+    /// A name is kept if the options don't have one.
+    pub fn set_options(&mut self, options: &SessionOptions) {
+        self.set_expect_timeout(options.expect_timeout);
+        self.set_expect_lazy(options.expect_lazy);
+        self.set_consume_mode(options.consume_mode);
+        self.set_line_ending(options.line_ending);
+        self.set_bracketed_paste(options.bracketed_paste);
+        self.set_read_chunk_size(options.read_chunk_size);
+        self.set_poll_interval(options.poll_interval);
+        if let Some(name) = &options.name {
+            self.set_name(name.clone());
+        }
+
+        match &options.buffer_limit {
+            Some((limit, policy)) => self.set_buffer_limit(*limit, policy.clone()),
+            None => self.remove_buffer_limit(),
+        }
+
+        match &options.pager {
+            Some(pager) => self.set_pager(pager.clone()),
+            None => self.remove_pager(),
+        }
+    }
+
+    fn set_default_options(&mut self) {
+        if let Some(options) = default_options() {
+            self.set_options(&options);
+        }
+    }
+}
+
+impl<P> Session<P, DynStream> {
+    /// Turns a log on, each Read/Write operation is written into the `logger`.
+    ///
+    /// If the session has a name (see `Session::set_name`) each log line is prefixed by it.
+    ///
+    /// See [`log`].
+    pub fn enable_log<W>(&mut self, logger: W)
+    where
+        W: Write + Send + 'static,
+    {
+        let name = self.get_name().map(ToOwned::to_owned);
+        let stream = self.get_stream_mut();
+        stream.enable_log(logger);
+        if let Some(name) = name {
+            stream.set_name(name);
+        }
+    }
+
+    /// Turns a log off and returns the logger which was used.
+    pub fn disable_log(&mut self) -> Option<Box<dyn Write + Send>> {
+        self.get_stream_mut().disable_log()
+    }
+}
+
+impl<P, S> Session<P, TeeStream<S, RotatingFile>> {
+    /// Returns a path of a file where an output is captured.
+    ///
+    /// See [`capture`].
+    pub fn capture_path(&self) -> &Path {
+        self.get_stream().get_writer().path()
+    }
+}
+
 /// Set a logger which will write each Read/Write operation into the writter.
 ///
+/// If the session has a name (see `Session::set_name`) each log line is prefixed by it.
+///
+/// To write a log into several writers at once use [`LogWriters`](crate::stream::log::LogWriters).
+///
 /// # Example
 ///
 /// ```
@@ -163,11 +761,16 @@ where
     W: Write,
     S: Read,
 {
-    session.swap_stream(|s| LogStream::new(s, dst))
+    let name = session.get_name().map(ToOwned::to_owned);
+    session.swap_stream(|s| LogStream::new(s, dst).with_name(name))
 }
 
 /// Set a logger which will write each Read/Write operation into the writter.
 ///
+/// If the session has a name (see `Session::set_name`) each log line is prefixed by it.
+///
+/// To write a log into several writers at once use [`LogWriters`](crate::stream::log::LogWriters).
+///
 /// # Example
 ///
 /// ```
@@ -181,5 +784,369 @@ pub fn log<W, P, S>(session: Session<P, S>, dst: W) -> Result<Session<P, LogStre
 where
     W: Write,
 {
-    session.swap_stream(|s| LogStream::new(s, dst))
+    let name = session.get_name().map(ToOwned::to_owned);
+    session.swap_stream(|s| LogStream::new(s, dst).with_name(name))
+}
+
+/// Set a logger which writes a session in a human friendly way, like a terminal transcript.
+///
+/// A sent input is prefixed with `>>` and colored, control characters are rendered as `^C` and `<ESC>`.
+/// See [`PrettyLogStream`].
+///
+/// # Example
+///
+/// ```
+/// use expectrl::{spawn, session::log_pretty};
+///
+/// let p = spawn("cat").unwrap();
+/// let p = log_pretty(p, std::io::stderr());
+/// ```
+#[cfg(not(feature = "async"))]
+pub fn log_pretty<W, P, S>(
+    session: Session<P, S>,
+    dst: W,
+) -> Result<Session<P, PrettyLogStream<S, W>>, Error>
+where
+    W: Write,
+    S: Read,
+{
+    let name = session.get_name().map(ToOwned::to_owned);
+    session.swap_stream(|s| PrettyLogStream::new(s, dst).with_name(name))
+}
+
+/// Set a logger which writes a session in a human friendly way, like a terminal transcript.
+///
+/// A sent input is prefixed with `>>` and colored, control characters are rendered as `^C` and `<ESC>`.
+/// See [`PrettyLogStream`].
+///
+/// # Example
+///
+/// ```
+/// use expectrl::{spawn, session::log_pretty};
+///
+/// let p = spawn("cat").unwrap();
+/// let p = log_pretty(p, std::io::stderr());
+/// ```
+#[cfg(feature = "async")]
+pub fn log_pretty<W, P, S>(
+    session: Session<P, S>,
+    dst: W,
+) -> Result<Session<P, PrettyLogStream<S, W>>, Error>
+where
+    W: Write,
+{
+    let name = session.get_name().map(ToOwned::to_owned);
+    session.swap_stream(|s| PrettyLogStream::new(s, dst).with_name(name))
+}
+
+/// Record a session into a writer in an asciinema v2 cast format.
+///
+/// The output is timed as it's read, so the recording can be replayed by `asciinema play`,
+/// which is handy to look at a session which failed in CI.
+/// A size of the terminal is set on the process as well.
+///
+/// See [`CastStream`].
+///
+/// # Example
+///
+/// ```no_run
+/// use expectrl::{spawn, session::cast};
+///
+/// let p = spawn("cat").unwrap();
+/// let file = std::fs::File::create("session.cast").unwrap();
+/// let p = cast(p, file, 80, 24).unwrap();
+/// ```
+#[cfg(not(feature = "async"))]
+pub fn cast<W, P, S>(
+    session: Session<P, S>,
+    dst: W,
+    cols: u16,
+    rows: u16,
+) -> Result<Session<P, CastStream<S, W>>, Error>
+where
+    W: Write,
+    P: WindowSize,
+    S: Read,
+{
+    let mut session = session.swap_stream(|s| CastStream::new(s, dst, cols, rows))?;
+    session.get_process_mut().set_window_size(cols, rows)?;
+    Ok(session)
+}
+
+/// Record a session into a writer in an asciinema v2 cast format.
+///
+/// The output is timed as it's read, so the recording can be replayed by `asciinema play`,
+/// which is handy to look at a session which failed in CI.
+/// A size of the terminal is set on the process as well.
+///
+/// See [`CastStream`].
+#[cfg(feature = "async")]
+pub fn cast<W, P, S>(
+    session: Session<P, S>,
+    dst: W,
+    cols: u16,
+    rows: u16,
+) -> Result<Session<P, CastStream<S, W>>, Error>
+where
+    W: Write,
+    P: WindowSize,
+{
+    let mut session = session.swap_stream(|s| CastStream::new(s, dst, cols, rows))?;
+    session.get_process_mut().set_window_size(cols, rows)?;
+    Ok(session)
+}
+
+/// Set a writer which gets a copy of everything read from a process, as it arrives.
+///
+/// Unlike [`log`] the output is written as it is,
+/// so it can be used to watch a session live while it's still buffered for `expect`.
+///
+/// # Example
+///
+/// ```
+/// use expectrl::{spawn, session::tee};
+///
+/// let p = spawn("cat").unwrap();
+/// let p = tee(p, std::io::stdout());
+/// ```
+#[cfg(not(feature = "async"))]
+pub fn tee<W, P, S>(session: Session<P, S>, dst: W) -> Result<Session<P, TeeStream<S, W>>, Error>
+where
+    W: Write,
+    S: Read,
+{
+    session.swap_stream(|s| TeeStream::new(s, dst))
+}
+
+/// Set a writer which gets a copy of everything read from a process, as it arrives.
+///
+/// Unlike [`log`] the output is written as it is,
+/// so it can be used to watch a session live while it's still buffered for `expect`.
+///
+/// # Example
+///
+/// ```
+/// use expectrl::{spawn, session::tee};
+///
+/// let p = spawn("cat").unwrap();
+/// let p = tee(p, std::io::stdout());
+/// ```
+#[cfg(feature = "async")]
+pub fn tee<W, P, S>(session: Session<P, S>, dst: W) -> Result<Session<P, TeeStream<S, W>>, Error>
+where
+    W: Write,
+{
+    session.swap_stream(|s| TeeStream::new(s, dst))
+}
+
+/// Wrap a session stream into [`ChaosStream`] which injects failures according to a schedule.
+///
+/// It's meant for tests of a code which must survive an unreliable process or connection.
+///
+/// # Example
+///
+/// ```
+/// use expectrl::{spawn, session::chaos, stream::chaos::Chaos};
+///
+/// let p = spawn("cat").unwrap();
+/// let p = chaos(p, Chaos::new(42).short_reads(0.5).would_block(0.2, 5));
+/// ```
+#[cfg(not(feature = "async"))]
+pub fn chaos<P, S>(
+    session: Session<P, S>,
+    chaos: Chaos,
+) -> Result<Session<P, ChaosStream<S>>, Error>
+where
+    S: Read,
+{
+    session.swap_stream(|s| ChaosStream::new(s, chaos))
+}
+
+/// Wrap a session stream into [`ChaosStream`] which injects failures according to a schedule.
+///
+/// It's meant for tests of a code which must survive an unreliable process or connection.
+///
+/// # Example
+///
+/// ```
+/// use expectrl::{spawn, session::chaos, stream::chaos::Chaos};
+///
+/// let p = spawn("cat").unwrap();
+/// let p = chaos(p, Chaos::new(42).short_reads(0.5).would_block(0.2, 5));
+/// ```
+#[cfg(feature = "async")]
+pub fn chaos<P, S>(
+    session: Session<P, S>,
+    chaos: Chaos,
+) -> Result<Session<P, ChaosStream<S>>, Error> {
+    session.swap_stream(|s| ChaosStream::new(s, chaos))
+}
+
+/// Limit a rate of writes to a process by [`ThrottledStream`].
+///
+/// Each write, including `send` and `send_line` calls, is paced,
+/// so slow serial consoles don't drop characters.
+///
+/// # Example
+///
+/// ```
+/// use expectrl::{spawn, session::throttle};
+///
+/// let p = spawn("cat").unwrap();
+/// let p = throttle(p, 960).unwrap();
+/// ```
+#[cfg(not(feature = "async"))]
+pub fn throttle<P, S>(
+    session: Session<P, S>,
+    bytes_per_sec: u32,
+) -> Result<Session<P, ThrottledStream<S>>, Error>
+where
+    S: Read,
+{
+    session.swap_stream(|s| ThrottledStream::new(s, bytes_per_sec))
+}
+
+/// Limit a rate of writes to a process by [`ThrottledStream`].
+///
+/// Each write, including `send` and `send_line` calls, is paced,
+/// so slow serial consoles don't drop characters.
+///
+/// # Example
+///
+/// ```
+/// use expectrl::{spawn, session::throttle};
+///
+/// let p = spawn("cat").unwrap();
+/// let p = throttle(p, 960).unwrap();
+/// ```
+#[cfg(feature = "async")]
+pub fn throttle<P, S>(
+    session: Session<P, S>,
+    bytes_per_sec: u32,
+) -> Result<Session<P, ThrottledStream<S>>, Error> {
+    session.swap_stream(|s| ThrottledStream::new(s, bytes_per_sec))
+}
+
+/// Read a process output through `io_uring` by [`UringStream`].
+///
+/// It's meant for processes which produce a lot of output quickly,
+/// as a next read is kept in flight while a previous chunk is matched.
+/// It fails if `io_uring` is not available.
+///
+/// It's better to be called right after a spawn.
+///
+/// # Example
+///
+/// ```no_run
+/// use expectrl::{spawn, session::uring, Expect};
+///
+/// let p = spawn("seq 100000").unwrap();
+/// let mut p = uring(p).unwrap();
+/// p.expect("100000").unwrap();
+/// ```
+#[cfg(all(target_os = "linux", feature = "uring", not(feature = "async")))]
+pub fn uring<P, S>(session: Session<P, S>) -> Result<Session<P, UringStream<S>>, Error>
+where
+    S: Read + std::os::unix::io::AsRawFd,
+{
+    let ring = io_uring::IoUring::new(crate::stream::uring::RING_ENTRIES)?;
+    session.swap_stream(|s| UringStream::with_ring(s, ring))
+}
+
+/// Persist everything read from a process to a file on disk.
+///
+/// The capture is independent from a buffer used by `expect` calls,
+/// and the file may be rotated by size or by time, see [`RotatingFile`].
+///
+/// # Example
+///
+/// ```no_run
+/// use expectrl::{spawn, session::capture, stream::capture::RotatingFile};
+///
+/// let file = RotatingFile::create("/tmp/capture.log").unwrap().max_size(1024 * 1024);
+/// let p = capture(spawn("cat").unwrap(), file).unwrap();
+/// assert_eq!(p.capture_path(), std::path::Path::new("/tmp/capture.log"));
+/// ```
+#[cfg(not(feature = "async"))]
+pub fn capture<P, S>(
+    session: Session<P, S>,
+    file: RotatingFile,
+) -> Result<Session<P, TeeStream<S, RotatingFile>>, Error>
+where
+    S: Read,
+{
+    tee(session, file)
+}
+
+/// Persist everything read from a process to a file on disk.
+///
+/// The capture is independent from a buffer used by `expect` calls,
+/// and the file may be rotated by size or by time, see [`RotatingFile`].
+#[cfg(feature = "async")]
+pub fn capture<P, S>(
+    session: Session<P, S>,
+    file: RotatingFile,
+) -> Result<Session<P, TeeStream<S, RotatingFile>>, Error> {
+    tee(session, file)
+}
+
+/// Transcode a session of a legacy charset, like Latin-1, Shift-JIS or GBK.
+///
+/// Everything read from a process is decoded into UTF-8,
+/// so it can be matched by regular Rust strings,
+/// and everything sent is encoded into the charset.
+/// See [`EncodingStream`].
+///
+/// It's better to be called right after a spawn,
+/// as bytes which are already buffered by the session are not transcoded.
+///
+/// # Example
+///
+/// ```no_run
+/// use expectrl::{spawn, session::encoding, Expect};
+///
+/// let p = spawn("cat").unwrap();
+/// let mut p = encoding(p, encoding_rs::SHIFT_JIS).unwrap();
+/// p.send_line("こんにちは").unwrap();
+/// p.expect("こんにちは").unwrap();
+/// ```
+#[cfg(all(feature = "encoding", not(feature = "async")))]
+pub fn encoding<P, S>(
+    session: Session<P, S>,
+    encoding: &'static encoding_rs::Encoding,
+) -> Result<Session<P, EncodingStream<S>>, Error>
+where
+    S: Read,
+{
+    session.swap_stream(|s| EncodingStream::new(s, encoding))
+}
+
+/// Transcode a session of a legacy charset, like Latin-1, Shift-JIS or GBK.
+///
+/// Everything read from a process is decoded into UTF-8,
+/// so it can be matched by regular Rust strings,
+/// and everything sent is encoded into the charset.
+/// See [`EncodingStream`].
+///
+/// It's better to be called right after a spawn,
+/// as bytes which are already buffered by the session are not transcoded.
+///
+/// # Example
+///
+/// ```no_run
+/// use expectrl::{spawn, session::encoding, AsyncExpect};
+///
+/// # futures_lite::future::block_on(async {
+/// let p = spawn("cat").unwrap();
+/// let mut p = encoding(p, encoding_rs::SHIFT_JIS).unwrap();
+/// p.send_line("こんにちは").await.unwrap();
+/// p.expect("こんにちは").await.unwrap();
+/// # });
+/// ```
+#[cfg(all(feature = "encoding", feature = "async"))]
+pub fn encoding<P, S>(
+    session: Session<P, S>,
+    encoding: &'static encoding_rs::Encoding,
+) -> Result<Session<P, EncodingStream<S>>, Error> {
+    session.swap_stream(|s| EncodingStream::new(s, encoding))
 }
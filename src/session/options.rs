@@ -0,0 +1,192 @@
+//! This module contains a set of session settings which can be reused across sessions.
+
+use std::{sync::RwLock, time::Duration};
+
+use crate::{
+    session::{ConsumeMode, OverflowPolicy, Pager, POLL_INTERVAL, READ_CHUNK_SIZE},
+    LineEnding,
+};
+
+/// Options which are applied to each spawned session.
+static DEFAULT_OPTIONS: RwLock<Option<SessionOptions>> = RwLock::new(None);
+
+/// Set options which are applied to each session spawned by [`spawn`] or `Session::spawn`.
+///
+/// It lets a test harness configure all sessions once.
+/// Sessions created by `Session::new` and `Session::spawn_with` are not affected.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use expectrl::session::{set_default_options, SessionOptions};
+///
+/// set_default_options(SessionOptions::new().expect_timeout(Some(Duration::from_secs(10))));
+///
+/// let p = expectrl::spawn("cat").unwrap();
+/// assert_eq!(p.get_expect_timeout(), Some(Duration::from_secs(10)));
+/// ```
+///
+/// [`spawn`]: crate::spawn
+pub fn set_default_options(options: SessionOptions) {
+    let mut default = DEFAULT_OPTIONS
+        .write()
+        .unwrap_or_else(|err| err.into_inner());
+    *default = Some(options);
+}
+
+/// Removes options set by [`set_default_options`].
+pub fn reset_default_options() {
+    let mut default = DEFAULT_OPTIONS
+        .write()
+        .unwrap_or_else(|err| err.into_inner());
+    *default = None;
+}
+
+/// Returns options set by [`set_default_options`].
+pub fn default_options() -> Option<SessionOptions> {
+    DEFAULT_OPTIONS
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+/// SessionOptions is a set of [`Session`] settings.
+///
+/// It can be created once and applied to many sessions,
+/// e.g. to all sessions of a test suite,
+/// instead of calling the same setters on each of them.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::{process::Command, time::Duration};
+/// use expectrl::{session::SessionOptions, LineEnding, Session};
+///
+/// let options = SessionOptions::new()
+///     .expect_timeout(Some(Duration::from_secs(30)))
+///     .line_ending(LineEnding::Lf);
+///
+/// let p = Session::spawn_with(Command::new("cat"), &options).unwrap();
+/// ```
+///
+/// [`Session`]: crate::session::Session
+#[derive(Debug, Clone)]
+pub struct SessionOptions {
+    pub(crate) expect_timeout: Option<Duration>,
+    pub(crate) expect_lazy: bool,
+    pub(crate) consume_mode: ConsumeMode,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) bracketed_paste: bool,
+    pub(crate) name: Option<String>,
+    pub(crate) buffer_limit: Option<(usize, OverflowPolicy)>,
+    pub(crate) pager: Option<Pager>,
+    pub(crate) read_chunk_size: usize,
+    pub(crate) poll_interval: Duration,
+}
+
+impl SessionOptions {
+    /// Creates options with the same values a new session has.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set an expect timeout, see `Session::set_expect_timeout`.
+    pub fn expect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.expect_timeout = timeout;
+        self
+    }
+
+    /// Set a lazy mode of expect calls, see `Session::set_expect_lazy`.
+    pub fn expect_lazy(mut self, lazy: bool) -> Self {
+        self.expect_lazy = lazy;
+        self
+    }
+
+    /// Set what a successful expect call consumes, see `Session::set_consume_mode`.
+    pub fn consume_mode(mut self, mode: ConsumeMode) -> Self {
+        self.consume_mode = mode;
+        self
+    }
+
+    /// Set a line ending used by `send_line`, see `Session::set_line_ending`.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Turn bracketed paste on or off, see `Session::set_bracketed_paste`.
+    pub fn bracketed_paste(mut self, on: bool) -> Self {
+        self.bracketed_paste = on;
+        self
+    }
+
+    /// Set a session name, see `Session::set_name`.
+    pub fn name<N>(mut self, name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set a limit of a session buffer, see `Session::set_buffer_limit`.
+    ///
+    /// [`None`] means the buffer is not limited.
+    pub fn buffer_limit(mut self, limit: Option<(usize, OverflowPolicy)>) -> Self {
+        self.buffer_limit = limit;
+        self
+    }
+
+    /// Set a pager handler, see `Session::set_pager`.
+    ///
+    /// [`None`] means pager prompts are not handled.
+    pub fn pager(mut self, pager: Option<Pager>) -> Self {
+        self.pager = pager;
+        self
+    }
+
+    /// Set a maximum amount of bytes read at once, see `Session::set_read_chunk_size`.
+    pub fn read_chunk_size(mut self, size: usize) -> Self {
+        self.read_chunk_size = size;
+        self
+    }
+
+    /// Set a pause between polls of a process, see `Session::set_poll_interval`.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Returns an expect timeout.
+    pub fn get_expect_timeout(&self) -> Option<Duration> {
+        self.expect_timeout
+    }
+
+    /// Returns a line ending.
+    pub fn get_line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Returns a session name.
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            expect_timeout: Some(Duration::from_millis(10000)),
+            expect_lazy: false,
+            consume_mode: ConsumeMode::default(),
+            line_ending: LineEnding::default(),
+            bracketed_paste: false,
+            name: None,
+            buffer_limit: None,
+            pager: None,
+            read_chunk_size: READ_CHUNK_SIZE,
+            poll_interval: POLL_INTERVAL,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! This module contains a limit of a session buffer.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use crate::Error;
+
+/// A policy which is applied when a session buffer exceeds its limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Fail a current `expect` call with an error.
+    Error,
+    /// Drop the oldest bytes, so the buffer keeps only the latest output.
+    DropOldest,
+    /// Move the oldest bytes to the end of a file.
+    SpillToFile(PathBuf),
+}
+
+/// BufferLimit decides what to do with a buffer which is over the limit.
+#[derive(Debug)]
+pub(crate) struct BufferLimit {
+    limit: usize,
+    policy: OverflowPolicy,
+    file: Option<File>,
+}
+
+impl BufferLimit {
+    pub(crate) fn new(limit: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            limit,
+            policy,
+            file: None,
+        }
+    }
+
+    /// Checks a buffer size.
+    ///
+    /// Returns an amount of the oldest bytes which must be dropped from the buffer.
+    pub(crate) fn check(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if buf.len() <= self.limit {
+            return Ok(0);
+        }
+
+        let overflow = buf.len() - self.limit;
+        match &self.policy {
+            OverflowPolicy::Error => Err(Error::Other {
+                message: "session buffer limit is reached".to_string(),
+                err: format!(
+                    "{} bytes are buffered, the limit is {}",
+                    buf.len(),
+                    self.limit
+                ),
+            }),
+            OverflowPolicy::DropOldest => Ok(overflow),
+            OverflowPolicy::SpillToFile(path) => {
+                let file = match &mut self.file {
+                    Some(file) => file,
+                    None => self
+                        .file
+                        .insert(OpenOptions::new().create(true).append(true).open(path)?),
+                };
+
+                file.write_all(&buf[..overflow])?;
+                file.flush()?;
+
+                Ok(overflow)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_limit_error() {
+        let mut limit = BufferLimit::new(3, OverflowPolicy::Error);
+        assert_eq!(limit.check(b"123").unwrap(), 0);
+        assert!(matches!(limit.check(b"1234"), Err(Error::Other { .. })));
+    }
+
+    #[test]
+    fn test_buffer_limit_drop_oldest() {
+        let mut limit = BufferLimit::new(3, OverflowPolicy::DropOldest);
+        assert_eq!(limit.check(b"").unwrap(), 0);
+        assert_eq!(limit.check(b"12345").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_buffer_limit_spill_to_file() {
+        let path = std::env::temp_dir().join(format!("expectrl-spill-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut limit = BufferLimit::new(3, OverflowPolicy::SpillToFile(path.clone()));
+        assert_eq!(limit.check(b"12345").unwrap(), 2);
+        assert_eq!(limit.check(b"34567").unwrap(), 2);
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"1234");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+//! This module contains an automatic handling of pager prompts.
+
+use regex::bytes::Regex;
+
+/// Matches `--More--` of `more` and network devices,
+/// including `--More--(42%)` and Juniper's `---(more 42%)---`, possibly highlighted.
+const MORE_PATTERN: &str = r"(?i)(?:\x1b\[[0-9;?]*[A-Za-z])*-+\s*\(?more(?:\s*\d+%)?\)?\s*-+(?:\(\d+%\))?(?:\x1b\[[0-9;?]*[A-Za-z])*\s*\z";
+/// Matches a `:` prompt of `less` on its own line.
+const LESS_PATTERN: &str = r"(?m)^(?:\x1b\[[0-9;?]*[A-Za-z])*:(?:\x1b\[[0-9;?]*[A-Za-z])*\z";
+
+/// Pager makes a session answer pager prompts while it waits for an output.
+///
+/// When an output ends with a pager prompt, like `--More--` or `:` of `less`,
+/// the prompt is removed from the session buffer and a response (a space by default) is sent,
+/// so a paged output can be matched as if it was printed at once.
+///
+/// # Example
+///
+/// ```
+/// use expectrl::session::Pager;
+///
+/// let pager = Pager::new()
+///     .pattern(regex::bytes::Regex::new(r"<Press any key>\z").unwrap())
+///     .response(" ");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pager {
+    patterns: Vec<Regex>,
+    response: Vec<u8>,
+}
+
+impl Pager {
+    /// Creates a pager handler which recognizes `--More--` and `less` prompts.
+    pub fn new() -> Self {
+        let patterns = [MORE_PATTERN, LESS_PATTERN]
+            .iter()
+            .map(|pattern| Regex::new(pattern).expect("a valid regex"))
+            .collect();
+
+        Self {
+            patterns,
+            response: b" ".to_vec(),
+        }
+    }
+
+    /// Creates a pager handler without any prompt patterns.
+    pub fn empty() -> Self {
+        Self {
+            patterns: Vec::new(),
+            response: b" ".to_vec(),
+        }
+    }
+
+    /// Add a pattern of a pager prompt.
+    ///
+    /// The pattern must match only at the end of an output, e.g. be anchored by `\z`,
+    /// as a pager waits for an input right after the prompt.
+    pub fn pattern(mut self, regex: Regex) -> Self {
+        self.patterns.push(regex);
+        self
+    }
+
+    /// Set bytes which are sent to a pager, a space by default.
+    pub fn response<B>(mut self, response: B) -> Self
+    where
+        B: AsRef<[u8]>,
+    {
+        self.response = response.as_ref().to_vec();
+        self
+    }
+
+    /// Returns bytes which are sent to a pager.
+    pub fn get_response(&self) -> &[u8] {
+        &self.response
+    }
+
+    /// Returns a start of a pager prompt if the buffer ends with one.
+    pub(crate) fn check(&self, buf: &[u8]) -> Option<usize> {
+        self.patterns
+            .iter()
+            .filter_map(|regex| regex.find(buf))
+            .filter(|m| m.end() == buf.len())
+            .map(|m| m.start())
+            .min()
+    }
+}
+
+impl Default for Pager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let pager = Pager::new();
+
+        assert_eq!(pager.check(b"line 1\r\nline 2\r\n--More--"), Some(16));
+        assert_eq!(pager.check(b"line 1\r\n --More-- "), Some(9));
+        assert_eq!(pager.check(b"line 1\r\n--More--(42%)"), Some(8));
+        assert_eq!(pager.check(b"line 1\r\n---(more 42%)---"), Some(8));
+        assert_eq!(pager.check(b"line 1\r\n\x1b[7m--More--\x1b[27m"), Some(8));
+        assert_eq!(pager.check(b"line 1\r\n:"), Some(8));
+        assert_eq!(pager.check(b"line 1\r\n\x1b[K:\x1b[K"), Some(8));
+
+        assert_eq!(pager.check(b"--More-- line 1\r\n"), None);
+        assert_eq!(pager.check(b"Password:"), None);
+        assert_eq!(pager.check(b"line 1\r\n"), None);
+
+        assert_eq!(Pager::empty().check(b"--More--"), None);
+    }
+}
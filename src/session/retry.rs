@@ -0,0 +1,126 @@
+//! This module contains a retry policy of an expect call.
+
+use std::time::Duration;
+
+/// RetryPolicy configures [`Session::expect_with_retry`].
+///
+/// When an expect call times out it's repeated up to a number of retries,
+/// optionally sending a "nudge" to a process before each retry,
+/// like pressing enter again on a serial console which missed a prompt.
+///
+/// A delay between retries starts from a `backoff` and is multiplied by a `factor` each time,
+/// up to a `max_backoff`.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use expectrl::session::RetryPolicy;
+///
+/// let policy = RetryPolicy::new(3)
+///     .backoff(Duration::from_millis(100))
+///     .factor(2)
+///     .nudge("\r\n");
+/// ```
+///
+/// [`Session::expect_with_retry`]: crate::session::Session::expect_with_retry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: usize,
+    backoff: Duration,
+    factor: u32,
+    max_backoff: Option<Duration>,
+    nudge: Option<Vec<u8>>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy which retries an expect call up to `max_retries` times without a delay.
+    pub fn new(max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            backoff: Duration::ZERO,
+            factor: 1,
+            max_backoff: None,
+            nudge: None,
+        }
+    }
+
+    /// Set a delay before a first retry.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set a factor a delay is multiplied by after each retry.
+    ///
+    /// The default is 1, which means a constant delay.
+    pub fn factor(mut self, factor: u32) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    /// Set a maximum delay between retries.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = Some(max_backoff);
+        self
+    }
+
+    /// Set bytes which are sent to a process before each retry, e.g. a newline.
+    pub fn nudge<B>(mut self, nudge: B) -> Self
+    where
+        B: AsRef<[u8]>,
+    {
+        self.nudge = Some(nudge.as_ref().to_vec());
+        self
+    }
+
+    /// Returns a maximum number of retries.
+    pub fn get_max_retries(&self) -> usize {
+        self.max_retries
+    }
+
+    /// Returns bytes which are sent before each retry.
+    pub fn get_nudge(&self) -> Option<&[u8]> {
+        self.nudge.as_deref()
+    }
+
+    /// Returns a delay before a given retry, counting from 0.
+    pub fn get_delay(&self, retry: usize) -> Duration {
+        let mut delay = self.backoff;
+        for _ in 0..retry {
+            delay = delay.saturating_mul(self.factor);
+
+            if let Some(max) = self.max_backoff {
+                if delay >= max {
+                    return max;
+                }
+            }
+        }
+
+        match self.max_backoff {
+            Some(max) => delay.min(max),
+            None => delay,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::new(5).backoff(Duration::from_millis(10));
+        assert_eq!(policy.get_delay(0), Duration::from_millis(10));
+        assert_eq!(policy.get_delay(4), Duration::from_millis(10));
+
+        let policy = policy.factor(2).max_backoff(Duration::from_millis(50));
+        assert_eq!(policy.get_delay(0), Duration::from_millis(10));
+        assert_eq!(policy.get_delay(1), Duration::from_millis(20));
+        assert_eq!(policy.get_delay(2), Duration::from_millis(40));
+        assert_eq!(policy.get_delay(3), Duration::from_millis(50));
+        assert_eq!(policy.get_delay(100), Duration::from_millis(50));
+
+        assert_eq!(RetryPolicy::new(1).get_delay(3), Duration::ZERO);
+    }
+}
@@ -0,0 +1,176 @@
+//! This module contains snapshot testing of a session output.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+
+use crate::{session::diff::diff, Error};
+
+/// An environment variable which makes snapshots be overwritten by an actual output.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "EXPECTRL_UPDATE_SNAPSHOTS";
+
+type Normalize = Box<dyn Fn(String) -> String + Send + Sync>;
+
+/// Snapshot compares an output of a session against a stored file.
+///
+/// An output is normalized before a comparison:
+/// `\r\n` is replaced by `\n`, then redactions are applied in the order they were added,
+/// and then a normalize hook is called.
+///
+/// A snapshot file is created if it doesn't exist,
+/// and it's overwritten if [`UPDATE_SNAPSHOTS_ENV`] environment variable is set (to anything but `0`).
+///
+/// # Example
+///
+/// ```no_run
+/// use expectrl::{session::Snapshot, spawn, Expect};
+/// use regex::Regex;
+///
+/// let mut p = spawn("ls -l /tmp").unwrap();
+///
+/// let snapshot = Snapshot::new("tests/snapshots/ls.snap")
+///     .redact(Regex::new(r"\w{3} +\d+ \d\d:\d\d").unwrap(), "[DATE]");
+///
+/// p.expect_snapshot(expectrl::Eof, &snapshot).unwrap();
+/// ```
+pub struct Snapshot {
+    path: PathBuf,
+    redactions: Vec<(Regex, String)>,
+    normalize: Option<Normalize>,
+}
+
+impl Snapshot {
+    /// Creates a snapshot stored in a file by the `path`.
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            redactions: Vec::new(),
+            normalize: None,
+        }
+    }
+
+    /// Replaces all matches of the `regex` by the `replacement`,
+    /// e.g. to hide timestamps or temporary paths.
+    ///
+    /// The replacement may refer to capture groups, see [`Regex::replace_all`].
+    pub fn redact<R>(mut self, regex: Regex, replacement: R) -> Self
+    where
+        R: Into<String>,
+    {
+        self.redactions.push((regex, replacement.into()));
+        self
+    }
+
+    /// Set a hook which normalizes an output after redactions.
+    pub fn normalize<F>(mut self, normalize: F) -> Self
+    where
+        F: Fn(String) -> String + Send + Sync + 'static,
+    {
+        self.normalize = Some(Box::new(normalize));
+        self
+    }
+
+    /// Returns a path of the snapshot file.
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Compares the `output` against the snapshot.
+    ///
+    /// # Panics
+    ///
+    /// It panics with a diff of lines if the snapshot doesn't match.
+    #[track_caller]
+    pub fn assert<B>(&self, output: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        let actual = self.apply(output.as_ref());
+
+        if update_snapshots() {
+            return self.write(&actual);
+        }
+
+        let expected = match fs::read_to_string(&self.path) {
+            Ok(expected) => expected,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return self.write(&actual),
+            Err(err) => return Err(err.into()),
+        };
+
+        if expected != actual {
+            let expected = expected.split('\n').collect::<Vec<_>>();
+            let actual = actual.split('\n').collect::<Vec<_>>();
+            panic!(
+                "an output doesn't match a snapshot {} (set {}=1 to update it)\n{}",
+                self.path.display(),
+                UPDATE_SNAPSHOTS_ENV,
+                diff(&expected, &actual)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns a normalized output.
+    fn apply(&self, output: &[u8]) -> String {
+        let mut output = String::from_utf8_lossy(output).replace("\r\n", "\n");
+        for (regex, replacement) in &self.redactions {
+            output = regex
+                .replace_all(&output, replacement.as_str())
+                .into_owned();
+        }
+
+        match &self.normalize {
+            Some(normalize) => normalize(output),
+            None => output,
+        }
+    }
+
+    fn write(&self, output: &str) -> Result<(), Error> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(&self.path, output)?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("path", &self.path)
+            .field("redactions", &self.redactions)
+            .field("normalize", &self.normalize.is_some())
+            .finish()
+    }
+}
+
+fn update_snapshots() -> bool {
+    std::env::var_os(UPDATE_SNAPSHOTS_ENV).map_or(false, |value| !value.is_empty() && value != "0")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let snapshot = Snapshot::new("snapshot")
+            .redact(Regex::new(r"\d{2}:\d{2}").unwrap(), "[TIME]")
+            .redact(Regex::new(r"/tmp/(\w+)").unwrap(), "[TMP]/$1")
+            .normalize(|output| output.trim_end().to_owned());
+
+        assert_eq!(
+            snapshot.apply(b"started at 10:42\r\nwrote /tmp/file\r\n\r\n"),
+            "started at [TIME]\nwrote [TMP]/file"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! This module contains an SSH backed [`Session`].
+//!
+//! The stream of such session is an SSH channel instead of a local PTY,
+//! so a remote host can be driven by the very same `expect`/`send_line`/`interact` API.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use expectrl::{session::ssh, Expect};
+//!
+//! let mut p = ssh::connect("localhost:22", "user", "password").unwrap();
+//! p.send_line("echo Hello World").unwrap();
+//! p.expect("Hello World").unwrap();
+//! ```
+
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{
+    error::to_io_error,
+    process::{Healthcheck, NonBlocking, Termios},
+    Error, Session,
+};
+
+#[cfg(unix)]
+use crate::process::unix::WaitStatus;
+
+/// A type alias for a session running over an SSH channel.
+pub type SshSession = Session<SshProcess, SshStream>;
+
+/// Connects to a remote host, authenticates by a password and spawns a login shell on it.
+pub fn connect<A>(addr: A, user: &str, password: &str) -> Result<SshSession, Error>
+where
+    A: ToSocketAddrs,
+{
+    let tcp = TcpStream::connect(addr)?;
+
+    let mut session =
+        ssh2::Session::new().map_err(to_io_error("Failed to create a ssh session"))?;
+    session.set_tcp_stream(tcp);
+    session
+        .handshake()
+        .map_err(to_io_error("Failed to make a ssh handshake"))?;
+    session
+        .userauth_password(user, password)
+        .map_err(to_io_error("Failed to authenticate"))?;
+
+    spawn(session)
+}
+
+/// Spawns a shell on an already authenticated [`ssh2::Session`].
+pub fn spawn(session: ssh2::Session) -> Result<SshSession, Error> {
+    spawn_channel(session, None)
+}
+
+/// Runs a command on an already authenticated [`ssh2::Session`].
+pub fn spawn_command(session: ssh2::Session, cmd: &str) -> Result<SshSession, Error> {
+    spawn_channel(session, Some(cmd))
+}
+
+fn spawn_channel(session: ssh2::Session, cmd: Option<&str>) -> Result<SshSession, Error> {
+    let mut channel = session
+        .channel_session()
+        .map_err(to_io_error("Failed to open a ssh channel"))?;
+    channel
+        .request_pty("xterm", None, None)
+        .map_err(to_io_error("Failed to request a pty"))?;
+
+    match cmd {
+        Some(cmd) => channel.exec(cmd),
+        None => channel.shell(),
+    }
+    .map_err(to_io_error("Failed to spawn a command"))?;
+
+    let channel = Arc::new(Mutex::new(channel));
+    let process = SshProcess {
+        session: session.clone(),
+        channel: channel.clone(),
+        is_echo: true,
+    };
+    let stream = SshStream { session, channel };
+
+    let session = Session::new(process, stream)?;
+
+    Ok(session)
+}
+
+/// A remote program running on an SSH channel.
+pub struct SshProcess {
+    session: ssh2::Session,
+    channel: Arc<Mutex<ssh2::Channel>>,
+    is_echo: bool,
+}
+
+impl SshProcess {
+    /// Returns an underlying [`ssh2::Session`].
+    pub fn get_ssh_session(&self) -> &ssh2::Session {
+        &self.session
+    }
+
+    /// Returns an exit code of a remote program.
+    ///
+    /// It's valid only after the channel was closed.
+    pub fn exit_status(&self) -> io::Result<i32> {
+        lock(&self.channel)?
+            .exit_status()
+            .map_err(to_io_error("Failed to get an exit status"))
+    }
+
+    /// Closes the channel, so the remote program gets EOF.
+    pub fn close(&mut self) -> io::Result<()> {
+        let mut channel = lock(&self.channel)?;
+        channel
+            .send_eof()
+            .map_err(to_io_error("Failed to send EOF"))?;
+        channel
+            .close()
+            .map_err(to_io_error("Failed to close a channel"))
+    }
+
+    /// Changes the size of a remote pty.
+    pub fn set_window_size(&mut self, cols: u32, rows: u32) -> io::Result<()> {
+        lock(&self.channel)?
+            .request_pty_size(cols, rows, None, None)
+            .map_err(to_io_error("Failed to change a pty size"))
+    }
+}
+
+impl Healthcheck for SshProcess {
+    #[cfg(unix)]
+    type Status = WaitStatus;
+    #[cfg(not(unix))]
+    type Status = ();
+
+    #[cfg(unix)]
+    fn get_status(&self) -> io::Result<Self::Status> {
+        if self.is_alive()? {
+            return Ok(WaitStatus::StillAlive);
+        }
+
+        let code = self.exit_status()?;
+        Ok(WaitStatus::Exited(nix::unistd::Pid::from_raw(0), code))
+    }
+
+    #[cfg(not(unix))]
+    fn get_status(&self) -> io::Result<Self::Status> {
+        Ok(())
+    }
+
+    fn is_alive(&self) -> io::Result<bool> {
+        Ok(!lock(&self.channel)?.eof())
+    }
+}
+
+impl Termios for SshProcess {
+    fn is_echo(&self) -> io::Result<bool> {
+        Ok(self.is_echo)
+    }
+
+    fn set_echo(&mut self, on: bool) -> io::Result<bool> {
+        // stty is the only portable way to change echo settings of a remote pty.
+        let cmd: &[u8] = match on {
+            true => b"stty echo\n",
+            false => b"stty -echo\n",
+        };
+
+        lock(&self.channel)?.write_all(cmd)?;
+
+        let prev = self.is_echo;
+        self.is_echo = on;
+
+        Ok(prev)
+    }
+}
+
+impl std::fmt::Debug for SshProcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SshProcess")
+            .field("is_echo", &self.is_echo)
+            .finish()
+    }
+}
+
+/// An IO stream of [`SshProcess`].
+pub struct SshStream {
+    session: ssh2::Session,
+    channel: Arc<Mutex<ssh2::Channel>>,
+}
+
+impl Write for SshStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        lock(&self.channel)?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        lock(&self.channel)?.flush()
+    }
+}
+
+impl Read for SshStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        lock(&self.channel)?.read(buf)
+    }
+}
+
+impl NonBlocking for SshStream {
+    fn set_blocking(&mut self, on: bool) -> io::Result<()> {
+        self.session.set_blocking(on);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for SshStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SshStream").finish()
+    }
+}
+
+fn lock(channel: &Mutex<ssh2::Channel>) -> io::Result<MutexGuard<'_, ssh2::Channel>> {
+    channel
+        .lock()
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "A ssh channel lock is poisoned"))
+}
@@ -2,17 +2,41 @@
 
 use std::{
     io::{self, BufRead, BufReader, Read, Write},
-    time::{self, Duration},
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 use crate::{
+    clock::{default_clock, Clock},
     error::Error,
     expect::Expect,
-    needle::Needle,
-    process::{Healthcheck, NonBlocking, Termios},
-    Captures,
+    needle::{Any, Eof, Match, Needle, Regex},
+    process::{
+        DropPolicy, ExitStatus, Healthcheck, NonBlocking, OnDrop, ResourceUsage, Termios, Usage,
+        Wait, WindowSize,
+    },
+    session::SessionMetrics,
+    session::{
+        abort::AbortPatterns,
+        diff::{assert_lines_eq, trim_line_ending},
+        keepalive::Keepalive,
+        overflow::{BufferLimit, OverflowPolicy},
+        pager::Pager,
+        retry::RetryPolicy,
+        timestamps::Timestamps,
+        typing::TypingOptions,
+        unknown_capture_group,
+        watchdog::Watchdog,
+        within::Within,
+        ConsumeMode, DetachedSession, WatchdogAction, BRACKETED_PASTE_END, BRACKETED_PASTE_START,
+        PASTE_CHUNK_DELAY, PASTE_CHUNK_SIZE, POLL_INTERVAL, READ_CHUNK_SIZE,
+    },
+    Captures, CapturesRef, LineEnding,
 };
 
+#[cfg(feature = "snapshot")]
+use crate::session::Snapshot;
+
 /// Session represents a spawned process and its streams.
 /// It controlls process and communication with it.
 #[derive(Debug)]
@@ -21,6 +45,19 @@ pub struct Session<P, S> {
     stream: TryStream<S>,
     expect_timeout: Option<Duration>,
     expect_lazy: bool,
+    consume_mode: ConsumeMode,
+    line_ending: LineEnding,
+    bracketed_paste: bool,
+    name: Option<String>,
+    watchdog: Option<Watchdog>,
+    keepalive: Option<Keepalive>,
+    abort_patterns: AbortPatterns,
+    buffer_limit: Option<BufferLimit>,
+    pager: Option<Pager>,
+    clock: Arc<dyn Clock>,
+    poll_interval: Duration,
+    metrics: SessionMetrics,
+    within: Option<Within>,
 }
 
 impl<P, S> Session<P, S>
@@ -36,6 +73,19 @@ where
             stream,
             expect_timeout: Some(Duration::from_millis(10000)),
             expect_lazy: false,
+            consume_mode: ConsumeMode::default(),
+            line_ending: LineEnding::default(),
+            bracketed_paste: false,
+            name: None,
+            watchdog: None,
+            keepalive: None,
+            abort_patterns: AbortPatterns::default(),
+            buffer_limit: None,
+            pager: None,
+            clock: default_clock(),
+            poll_interval: POLL_INTERVAL,
+            metrics: SessionMetrics::default(),
+            within: None,
         })
     }
 
@@ -46,181 +96,1346 @@ where
     {
         self.stream.flush_in_buffer();
         let buf = self.stream.get_available().to_owned();
+        let consumed = self.stream.consumed;
+        let chunk_size = self.stream.chunk_size;
+        let metrics = self.metrics();
 
         let stream = self.stream.into_inner();
         let stream = new(stream);
 
         let mut session = Session::new(self.proc, stream)?;
         session.stream.keep_in_buffer(&buf);
+        session.stream.consumed = consumed;
+        session.stream.chunk_size = chunk_size;
+        session.metrics = metrics;
+        session.consume_mode = self.consume_mode;
+        session.line_ending = self.line_ending;
+        session.bracketed_paste = self.bracketed_paste;
+        session.name = self.name;
+        session.watchdog = self.watchdog;
+        session.keepalive = self.keepalive;
+        session.abort_patterns = self.abort_patterns;
+        session.buffer_limit = self.buffer_limit;
+        session.pager = self.pager;
+        session.clock = self.clock;
+        session.poll_interval = self.poll_interval;
+
+        Ok(session)
+    }
+
+    /// Detaches the session from a controlling side.
+    ///
+    /// The process and the stream are left as they are,
+    /// so the session can be restored by [`DetachedSession::attach`].
+    pub fn detach(mut self) -> DetachedSession<P, S> {
+        self.stream.flush_in_buffer();
+        let buffer = self.stream.get_available().to_owned();
+        let read_chunk_size = self.stream.chunk_size;
+        let metrics = self.metrics();
+
+        DetachedSession {
+            process: self.proc,
+            stream: self.stream.into_inner(),
+            buffer,
+            expect_timeout: self.expect_timeout,
+            expect_lazy: self.expect_lazy,
+            consume_mode: self.consume_mode,
+            line_ending: self.line_ending,
+            bracketed_paste: self.bracketed_paste,
+            name: self.name,
+            watchdog: self.watchdog,
+            keepalive: self.keepalive,
+            abort_patterns: self.abort_patterns,
+            buffer_limit: self.buffer_limit,
+            pager: self.pager,
+            clock: self.clock,
+            read_chunk_size,
+            poll_interval: self.poll_interval,
+            metrics,
+        }
+    }
+}
+
+impl<P, S> DetachedSession<P, S>
+where
+    S: Read,
+{
+    /// Attaches the session back.
+    ///
+    /// All the settings and a not consumed data are restored.
+    pub fn attach(self) -> Result<Session<P, S>, Error> {
+        let mut session = Session::new(self.process, self.stream)?;
+        session.stream.keep_in_buffer(&self.buffer);
+        session.expect_timeout = self.expect_timeout;
+        session.expect_lazy = self.expect_lazy;
+        session.consume_mode = self.consume_mode;
+        session.line_ending = self.line_ending;
+        session.bracketed_paste = self.bracketed_paste;
+        session.name = self.name;
+        session.watchdog = self.watchdog;
+        session.keepalive = self.keepalive;
+        session.abort_patterns = self.abort_patterns;
+        session.buffer_limit = self.buffer_limit;
+        session.pager = self.pager;
+        session.clock = self.clock;
+        session.stream.chunk_size = self.read_chunk_size;
+        session.poll_interval = self.poll_interval;
+        session.metrics = self.metrics;
+
+        Ok(session)
+    }
+}
+
+impl<P, S> Session<P, S> {
+    /// Set the pty session's expect timeout.
+    pub fn set_expect_timeout(&mut self, expect_timeout: Option<Duration>) {
+        self.expect_timeout = expect_timeout;
+    }
+
+    /// Get the pty session's expect timeout.
+    pub fn get_expect_timeout(&self) -> Option<Duration> {
+        self.expect_timeout
+    }
+
+    /// Set a expect algorithm to be either gready or lazy.
+    ///
+    /// Default algorithm is gready.
+    ///
+    /// See [Session::expect].
+    pub fn set_expect_lazy(&mut self, lazy: bool) {
+        self.expect_lazy = lazy;
+    }
+
+    /// Set what is consumed from a buffer by a successful expect call.
+    ///
+    /// By default bytes after a match are left in the buffer ([`ConsumeMode::UpToMatch`]).
+    pub fn set_consume_mode(&mut self, mode: ConsumeMode) {
+        self.consume_mode = mode;
+    }
+
+    /// Returns bytes which were read from a process but not yet consumed by expect calls.
+    ///
+    /// It doesn't read anything from the process.
+    pub fn peek_buffer(&self) -> &[u8] {
+        self.stream.get_available()
+    }
+
+    /// Returns a time passed since the last output was read from a process.
+    ///
+    /// It's `None` if nothing was read yet.
+    /// The output is read only by [`Expect`] calls and other reads from the session,
+    /// so it doesn't account data which is waiting in a process stream.
+    ///
+    /// See also [`Captures::timestamp`].
+    pub fn time_since_last_output(&self) -> Option<Duration> {
+        self.stream.last_output().map(|time| time.elapsed())
+    }
+
+    /// Set a line ending which is used by [`Expect::send_line`].
+    ///
+    /// Default is `\r\n` on windows and `\n` on other platforms.
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        self.line_ending = line_ending;
+    }
+
+    /// Get a line ending which is used by [`Expect::send_line`].
+    pub fn get_line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Set whether a process has bracketed paste mode enabled.
+    ///
+    /// It changes the way [`Session::paste`] sends a text.
+    /// It's off by default.
+    pub fn set_bracketed_paste(&mut self, on: bool) {
+        self.bracketed_paste = on;
+    }
+
+    /// Set a name of the session.
+    ///
+    /// The name is included in errors returned by [`Expect::expect`],
+    /// see [`Error::Session`], and in a log set by [`session::log`].
+    ///
+    /// [`session::log`]: crate::session::log
+    pub fn set_name<N>(&mut self, name: N)
+    where
+        N: Into<String>,
+    {
+        self.name = Some(name.into());
+    }
+
+    /// Get a name of the session.
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Set a watchdog which is triggered if no output was received
+    /// for the `period` during an [`Expect::expect`] call.
+    ///
+    /// The callback gets a time passed since the last output,
+    /// and decides whether to keep waiting, to nudge a process or to abort the call.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use expectrl::{spawn, session::WatchdogAction, Expect};
+    ///
+    /// let mut p = spawn("cat").unwrap();
+    /// p.set_watchdog(Duration::from_secs(5), |_| WatchdogAction::Send(b"\n".to_vec()));
+    /// p.expect("login:").unwrap();
+    /// ```
+    pub fn set_watchdog<F>(&mut self, period: Duration, callback: F)
+    where
+        F: FnMut(Duration) -> WatchdogAction + Send + 'static,
+    {
+        self.watchdog = Some(Watchdog::new(period, callback, self.clock.now()));
+    }
+
+    /// Remove a watchdog set by [`Session::set_watchdog`].
+    pub fn remove_watchdog(&mut self) {
+        self.watchdog = None;
+    }
+
+    /// Set a keepalive which writes `bytes` to a process each `interval`
+    /// while an [`Expect::expect`] call is waiting for an output.
+    ///
+    /// It keeps alive remote consoles and SSH sessions which disconnect idle clients.
+    /// An interval starts over on each call,
+    /// and the bytes are written only while the session waits inside a call,
+    /// as nothing is running in background.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use expectrl::{spawn, Expect};
+    ///
+    /// let mut p = spawn("ssh jump-host").unwrap();
+    /// p.set_keepalive(Duration::from_secs(30), "\0");
+    /// p.expect("done").unwrap();
+    /// ```
+    pub fn set_keepalive<B>(&mut self, interval: Duration, bytes: B)
+    where
+        B: AsRef<[u8]>,
+    {
+        self.keepalive = Some(Keepalive::new(
+            interval,
+            bytes.as_ref().to_vec(),
+            self.clock.now(),
+        ));
+    }
+
+    /// Remove a keepalive set by [`Session::set_keepalive`].
+    pub fn remove_keepalive(&mut self) {
+        self.keepalive = None;
+    }
+
+    /// Add a pattern which must never appear in an output.
+    ///
+    /// Abort patterns are checked by every [`Expect`] call before a given needle,
+    /// and if one is found the call fails with [`Error::Aborted`].
+    /// The output up to the end of the match is consumed.
+    ///
+    /// It helps to turn a crash of a process into a descriptive error instead of a timeout.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, Expect, Regex};
+    ///
+    /// let mut p = spawn("cat").unwrap();
+    /// p.add_abort_pattern(Regex("panic|Segmentation fault"));
+    /// p.expect("Done").unwrap();
+    /// ```
+    pub fn add_abort_pattern<N>(&mut self, needle: N)
+    where
+        N: Needle + std::fmt::Debug + Send + 'static,
+    {
+        self.abort_patterns.push(needle);
+    }
+
+    /// Remove all patterns added by [`Session::add_abort_pattern`].
+    pub fn clear_abort_patterns(&mut self) {
+        self.abort_patterns.clear();
+    }
+
+    /// Set a maximum size of a buffer which keeps an output not consumed by [`Expect`] calls.
+    ///
+    /// The `policy` decides what happens when the limit is reached.
+    /// By default the buffer is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, session::OverflowPolicy, Expect};
+    ///
+    /// let mut p = spawn("yes").unwrap();
+    /// p.set_buffer_limit(1024 * 1024, OverflowPolicy::DropOldest);
+    /// p.expect("no").unwrap_err();
+    /// ```
+    pub fn set_buffer_limit(&mut self, limit: usize, policy: OverflowPolicy) {
+        self.buffer_limit = Some(BufferLimit::new(limit, policy));
+    }
+
+    /// Remove a limit set by [`Session::set_buffer_limit`].
+    pub fn remove_buffer_limit(&mut self) {
+        self.buffer_limit = None;
+    }
+
+    /// Set a pager handler which answers pager prompts, like `--More--`,
+    /// while an [`Expect::expect`] call is waiting for an output.
+    ///
+    /// A prompt is removed from the buffer so it never gets into a match.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, session::Pager, Expect};
+    ///
+    /// let mut p = spawn("man ls").unwrap();
+    /// p.set_pager(Pager::new());
+    /// p.expect("SEE ALSO").unwrap();
+    /// ```
+    pub fn set_pager(&mut self, pager: Pager) {
+        self.pager = Some(pager);
+    }
+
+    /// Remove a pager handler set by [`Session::set_pager`].
+    pub fn remove_pager(&mut self) {
+        self.pager = None;
+    }
+
+    /// Set a clock which measures expect timeouts, a watchdog and a keepalive intervals
+    /// and delays of [`Session::expect_with_retry`].
+    ///
+    /// It's [`SystemClock`] by default,
+    /// [`ManualClock`] makes it possible to test a timeout handling without real waiting.
+    ///
+    /// [`SystemClock`]: crate::clock::SystemClock
+    /// [`ManualClock`]: crate::clock::ManualClock
+    pub fn set_clock<C>(&mut self, clock: C)
+    where
+        C: Clock + 'static,
+    {
+        self.clock = Arc::new(clock);
+    }
+
+    /// Returns counters of the session, see [`SessionMetrics`].
+    pub fn metrics(&self) -> SessionMetrics {
+        self.metrics
+            .add_io(self.stream.bytes_read(), self.stream.bytes_written)
+    }
+
+    /// Reset counters of the session to zero.
+    pub fn reset_metrics(&mut self) {
+        self.metrics = SessionMetrics::default();
+        self.stream.reset_io_counters();
+    }
+
+    /// Set a maximum amount of bytes which is read from a process at once.
+    ///
+    /// Bigger chunks reduce an amount of reads for a process which prints a lot,
+    /// it's 248 bytes by default.
+    /// A lazy expect (see [`Session::set_expect_lazy`]) reads by byte regardless of it.
+    pub fn set_read_chunk_size(&mut self, size: usize) {
+        self.stream.chunk_size = size.max(1);
+    }
+
+    /// Get a maximum amount of bytes which is read from a process at once.
+    pub fn get_read_chunk_size(&self) -> usize {
+        self.stream.chunk_size
+    }
+
+    /// Preallocate the session buffer for at least `additional` more bytes.
+    ///
+    /// The buffer keeps its capacity when data is consumed,
+    /// so it saves reallocations for processes which print a lot at once.
+    pub fn reserve_buffer(&mut self, additional: usize) {
+        self.stream.reserve(additional);
+    }
+
+    /// Set a pause between checks in [`Session::wait_for_exit`].
+    ///
+    /// An expect call blocks until a process prints something or a timeout is reached,
+    /// the interval is used only if a stream can't tell when data arrives
+    /// (see [`NonBlocking::wait_readable`]) or a clock set by [`Session::set_clock`]
+    /// doesn't go along with a real time.
+    /// A shorter interval reduces a latency for a price of CPU time,
+    /// it's 10 milliseconds by default.
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
+
+    /// Get a pause between checks in [`Session::wait_for_exit`].
+    pub fn get_poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    /// Get a reference to original stream.
+    pub fn get_stream(&self) -> &S {
+        self.stream.as_ref()
+    }
+
+    /// Get a mut reference to original stream.
+    pub fn get_stream_mut(&mut self) -> &mut S {
+        self.stream.as_mut()
+    }
+
+    /// Get a reference to a process running program.
+    pub fn get_process(&self) -> &P {
+        &self.proc
+    }
+
+    /// Get a mut reference to a process running program.
+    pub fn get_process_mut(&mut self) -> &mut P {
+        &mut self.proc
+    }
+}
+
+impl<P, S> Expect for Session<P, S>
+where
+    S: Write + Read + NonBlocking,
+{
+    fn expect<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        match self.find(needle, |_| ()) {
+            Ok(found) => Ok(self.take_match(found)),
+            Err(err) => Err(err.with_session_name(self.name.as_deref())),
+        }
+    }
+
+    fn check<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let eof = self.stream.read_available().map_err(Error::io("read"))?;
+        let _ = self.limit_buffer()?;
+        let buf = self.stream.get_available();
+
+        if let Some((pattern, captures)) = self.abort_patterns.check(buf, 0, eof)? {
+            return Err(self.abort(pattern, captures));
+        }
+
+        let found = needle.check(buf, eof)?;
+        if !found.is_empty() {
+            let end_index = Captures::right_most_index(&found);
+            let involved_bytes = buf[..end_index].to_vec();
+            let timestamp = self.stream.timestamp(end_index);
+            let offset = self.stream.consumed();
+            self.consume_match(end_index);
+            return Ok(Captures::new(involved_bytes, found)
+                .with_offset(offset)
+                .with_timestamp(timestamp));
+        }
+
+        if eof {
+            return Err(Error::Eof);
+        }
+
+        Ok(Captures::new(Vec::new(), Vec::new()))
+    }
+
+    fn is_matched<N>(&mut self, needle: N) -> Result<bool, Error>
+    where
+        N: Needle,
+    {
+        let eof = self.stream.read_available().map_err(Error::io("read"))?;
+        let _ = self.limit_buffer()?;
+        let buf = self.stream.get_available();
+
+        let found = needle.check(buf, eof)?;
+        if !found.is_empty() {
+            return Ok(true);
+        }
+
+        if eof {
+            return Err(Error::Eof);
+        }
+
+        Ok(false)
+    }
+
+    fn send<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        self.begin_send()?;
+        self.stream
+            .write_all(buf.as_ref())
+            .map_err(Error::io("write"))?;
+
+        Ok(())
+    }
+
+    fn send_line<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        let line_ending = self.line_ending;
+        self.send_line_with(buf, line_ending)
+    }
+}
+
+impl<P, S> Session<P, S>
+where
+    P: Wait,
+    S: Read + Write + NonBlocking,
+{
+    /// Waits for a process to exit and returns its status.
+    ///
+    /// An output is read while waiting so the process isn't blocked on a full terminal,
+    /// it's kept in the session buffer.
+    ///
+    /// [`Error::ProcessAlive`] with a not consumed output is returned
+    /// if the process is still running once the `timeout` is reached.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use expectrl::spawn;
+    ///
+    /// let mut p = spawn("echo Hello World").unwrap();
+    /// let status = p.wait_for_exit(Some(Duration::from_secs(5))).unwrap();
+    /// assert!(status.success());
+    /// ```
+    pub fn wait_for_exit(&mut self, timeout: Option<Duration>) -> Result<ExitStatus, Error> {
+        let start = self.clock.now();
+        loop {
+            let _ = self.stream.read_available().map_err(Error::io("read"))?;
+            let _ = self.limit_buffer()?;
+
+            if let Some(status) = self.proc.try_wait().map_err(Error::io("wait"))? {
+                return Ok(status);
+            }
+
+            if let Some(timeout) = timeout {
+                if self.clock.now().saturating_duration_since(start) > timeout {
+                    let buffer = self.stream.get_available().to_vec();
+                    return Err(
+                        Error::ProcessAlive { buffer }.with_session_name(self.name.as_deref())
+                    );
+                }
+            }
+
+            std::thread::sleep(self.poll_interval);
+        }
+    }
+
+    /// Checks that a process is still running.
+    ///
+    /// [`Error::ProcessExited`] is returned if it has exited,
+    /// so a script can stop early instead of waiting for an expect timeout.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, Error};
+    ///
+    /// let mut p = spawn("true").unwrap();
+    /// # std::thread::sleep(std::time::Duration::from_millis(100));
+    /// match p.check_alive() {
+    ///     Err(Error::ProcessExited { status }) => println!("exited with {}", status),
+    ///     result => result.unwrap(),
+    /// }
+    /// ```
+    pub fn check_alive(&mut self) -> Result<(), Error> {
+        match self.proc.try_wait().map_err(Error::io("wait"))? {
+            Some(status) => {
+                Err(Error::ProcessExited { status }.with_session_name(self.name.as_deref()))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Waits for a process to exit with a given code.
+    ///
+    /// It uses the session's expect timeout.
+    /// [`Error::UnexpectedExit`] with a not consumed output is returned if the code is different,
+    /// see [`Session::wait_for_exit`].
+    pub fn expect_exit(&mut self, code: i32) -> Result<ExitStatus, Error> {
+        let status = self.wait_for_exit(self.expect_timeout)?;
+        if status.code() != Some(code) {
+            let buffer = self.stream.get_available().to_vec();
+            return Err(
+                Error::UnexpectedExit { status, buffer }.with_session_name(self.name.as_deref())
+            );
+        }
+
+        Ok(status)
+    }
+}
+
+impl<P, S> Session<P, S>
+where
+    S: Write + Read + NonBlocking,
+{
+    /// Reads everything a process has produced so far and throws it away,
+    /// together with a not consumed data in the session buffer.
+    ///
+    /// It doesn't wait for an output.
+    /// It lets a script resynchronize at a known point before sending a next command,
+    /// so a stale output doesn't match a later expect call.
+    ///
+    /// Returns a number of discarded bytes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, Expect};
+    ///
+    /// let mut p = spawn("bash").unwrap();
+    /// p.send_line("make").unwrap();
+    /// // ...
+    /// let _ = p.flush_output().unwrap();
+    /// p.send_line("echo $?").unwrap();
+    /// ```
+    pub fn flush_output(&mut self) -> Result<usize, Error> {
+        let _ = self.stream.read_available().map_err(Error::io("read"))?;
+        let _ = self.limit_buffer()?;
+        Ok(self.discard_buffered())
+    }
+
+    /// Throws away a data in the session buffer which was not consumed by expect calls.
+    ///
+    /// Unlike [`Session::flush_output`] it doesn't read anything from the process.
+    ///
+    /// Returns a number of discarded bytes.
+    pub fn discard_buffered(&mut self) -> usize {
+        let n = self.stream.get_available().len();
+        self.stream.consume_available(n);
+        n
+    }
+
+    /// Expect a needle until a deadline is reached.
+    ///
+    /// It's the same as [`Expect::expect`] but it uses an absolute deadline instead of the session's timeout,
+    /// so a few calls may share one time budget.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::{Duration, Instant};
+    /// use expectrl::{spawn, Expect, Regex};
+    ///
+    /// let mut p = spawn("ftp bks4-speedtest-1.tele2.net").unwrap();
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(30);
+    /// p.expect_until(Regex("Name \\(.*\\):"), deadline).unwrap();
+    /// p.send_line("anonymous").unwrap();
+    /// p.expect_until("Password", deadline).unwrap();
+    /// ```
+    pub fn expect_until<N>(&mut self, needle: N, deadline: Instant) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let timeout = self.expect_timeout;
+        self.expect_timeout = Some(deadline.saturating_duration_since(self.clock.now()));
+        let result = self.expect(needle);
+        self.expect_timeout = timeout;
+
+        result
+    }
+
+    /// Runs a block of steps within a total time limit.
+    ///
+    /// Each expect call of the block waits no longer than the time left,
+    /// and a send call fails if the time is already over.
+    /// Either way [`Error::Within`] is returned, which points to the step in flight.
+    ///
+    /// A send which is blocked by a process not reading its input can't be interrupted.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use expectrl::{spawn, Expect};
+    ///
+    /// let mut p = spawn("ftp bks4-speedtest-1.tele2.net").unwrap();
+    ///
+    /// p.within(Duration::from_secs(30), |p| {
+    ///     p.expect("Name")?;
+    ///     p.send_line("anonymous")?;
+    ///     p.expect("Password")?;
+    ///     Ok(())
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn within<F, T>(&mut self, limit: Duration, block: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error>,
+    {
+        let within = Within::new(self.clock.now(), limit).nest(self.within.as_ref());
+        let outer = self.within.replace(within);
+
+        let result = block(self);
+
+        let within = std::mem::replace(&mut self.within, outer);
+        if let (Some(outer), Some(within)) = (&mut self.within, &within) {
+            outer.add_steps(within);
+        }
+
+        result
+    }
+
+    /// Expect a regex and return its named group as a lossy string.
+    ///
+    /// It's a shortcut for an expect call followed by a look up of the group.
+    /// An error is returned if the regex has no such group
+    /// or if the group didn't participate in the match.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, Expect, Regex};
+    ///
+    /// let mut p = spawn("cat").unwrap();
+    /// p.send_line("pid=1234").unwrap();
+    /// let pid = p.expect_capture(Regex(r"pid=(?P<pid>\d+)"), "pid").unwrap();
+    /// assert_eq!(pid, "1234");
+    /// ```
+    pub fn expect_capture<Re>(&mut self, regex: Regex<Re>, name: &str) -> Result<String, Error>
+    where
+        Re: AsRef<str>,
+    {
+        let regex = regex::bytes::Regex::new(regex.0.as_ref()).map_err(|_| Error::RegexParsing)?;
+        if !regex.capture_names().any(|group| group == Some(name)) {
+            return Err(unknown_capture_group(name));
+        }
+
+        let found = self.expect(&regex)?;
+
+        regex
+            .captures(found.as_bytes())
+            .and_then(|captures| captures.name(name))
+            .map(|group| String::from_utf8_lossy(group.as_bytes()).into_owned())
+            .ok_or_else(|| unknown_capture_group(name))
+    }
+
+    /// Expect a needle, retrying it according to a policy if the call times out.
+    ///
+    /// Each attempt uses the session's expect timeout.
+    /// Before a retry the policy's delay is awaited and its nudge is sent.
+    /// If all retries time out [`Error::ExpectTimeout`] is returned,
+    /// any other error is returned right away.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use expectrl::{spawn, session::RetryPolicy};
+    ///
+    /// let mut p = spawn("cat").unwrap();
+    /// p.set_expect_timeout(Some(Duration::from_secs(1)));
+    ///
+    /// let policy = RetryPolicy::new(3).backoff(Duration::from_millis(200)).nudge("\n");
+    /// p.expect_with_retry("login:", &policy).unwrap();
+    /// ```
+    pub fn expect_with_retry<N>(
+        &mut self,
+        needle: N,
+        policy: &RetryPolicy,
+    ) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let mut retry = 0;
+        loop {
+            match self.expect(&needle) {
+                Err(err)
+                    if matches!(err.root_error(), Error::ExpectTimeout { .. })
+                        && retry < policy.get_max_retries() => {}
+                result => return result,
+            }
+
+            let delay = policy.get_delay(retry);
+            if !delay.is_zero() {
+                self.clock.sleep(delay);
+            }
+
+            if let Some(nudge) = policy.get_nudge() {
+                self.send(nudge)?;
+            }
+
+            retry += 1;
+        }
+    }
+
+    /// Read a line until a deadline is reached.
+    ///
+    /// A line including `\n` is appended to the `buf`.
+    /// Returns a number of bytes read, which is 0 when EOF is reached.
+    pub fn read_line_until(&mut self, buf: &mut String, deadline: Instant) -> Result<usize, Error> {
+        let needles: [&dyn Needle; 2] = [&"\n", &Eof];
+        let found = self.expect_until(Any(needles), deadline)?;
+        let line = String::from_utf8_lossy(found.as_bytes());
+        buf.push_str(&line);
+
+        Ok(found.as_bytes().len())
+    }
+
+    /// Expects a needle and compares an output with a snapshot.
+    ///
+    /// The output is everything consumed by the call,
+    /// from the end of the previous match up to the end of this one.
+    ///
+    /// # Panics
+    ///
+    /// It panics with a diff if the output doesn't match the snapshot,
+    /// see [`Snapshot::assert`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{session::Snapshot, spawn, Expect};
+    ///
+    /// let mut p = spawn("cat").unwrap();
+    /// p.send_line("Hello World").unwrap();
+    /// p.expect_snapshot("World", &Snapshot::new("tests/snapshots/cat.snap")).unwrap();
+    /// ```
+    #[cfg(feature = "snapshot")]
+    #[track_caller]
+    pub fn expect_snapshot<N>(&mut self, needle: N, snapshot: &Snapshot) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let found = self.expect(needle)?;
+        snapshot.assert(found.as_bytes())?;
+
+        Ok(found)
+    }
+
+    /// Reads a line and asserts that it's equal to the `expected` one.
+    ///
+    /// A line ending (`\n` or `\r\n`) is not compared.
+    /// It's meant to be used in tests instead of checking a matched output by hand.
+    ///
+    /// # Panics
+    ///
+    /// Like [`assert_eq!`] it panics if the line differs,
+    /// with a diff where control characters are escaped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, Expect};
+    ///
+    /// let mut p = spawn("cat").unwrap();
+    /// p.send_line("Hello World").unwrap();
+    /// p.expect_eq_line("Hello World").unwrap();
+    /// ```
+    #[track_caller]
+    pub fn expect_eq_line(&mut self, expected: &str) -> Result<(), Error> {
+        self.expect_eq_lines(&[expected])
+    }
+
+    /// Reads lines and asserts that they are equal to the `expected` ones.
+    ///
+    /// It reads as many lines as expected or until EOF.
+    /// See [`Session::expect_eq_line`].
+    ///
+    /// # Panics
+    ///
+    /// It panics with a diff of the lines if they differ.
+    #[track_caller]
+    pub fn expect_eq_lines<L>(&mut self, expected: &[L]) -> Result<(), Error>
+    where
+        L: AsRef<str>,
+    {
+        let mut lines = Vec::with_capacity(expected.len());
+        while lines.len() < expected.len() {
+            let needles: [&dyn Needle; 2] = [&"\n", &Eof];
+            let found = self.expect(Any(needles))?;
+            let line = found.as_bytes();
+            if !line.ends_with(b"\n") {
+                if !line.is_empty() {
+                    lines.push(String::from_utf8_lossy(line).into_owned());
+                }
+
+                break;
+            }
+
+            lines.push(String::from_utf8_lossy(trim_line_ending(line)).into_owned());
+        }
+
+        assert_lines_eq(expected, &lines);
+
+        Ok(())
+    }
+}
+
+impl<P, S> Session<P, S>
+where
+    S: Write,
+{
+    /// Send a line to the stream ending it with `\r\n`
+    /// regardless of [`Session::set_line_ending`].
+    pub fn send_line_crlf<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        self.send_line_with(buf, LineEnding::CrLf)
+    }
+
+    /// Send a line to the stream ending it with `\n`
+    /// regardless of [`Session::set_line_ending`].
+    pub fn send_line_lf<B>(&mut self, buf: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        self.send_line_with(buf, LineEnding::Lf)
+    }
+
+    fn send_line_with<B>(&mut self, buf: B, line_ending: LineEnding) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        self.begin_send()?;
+        self.stream
+            .write_all(buf.as_ref())
+            .map_err(Error::io("write"))?;
+        self.stream
+            .write_all(line_ending.as_bytes())
+            .map_err(Error::io("write"))?;
+
+        Ok(())
+    }
+
+    /// Accounts a send call in a block run by [`Session::within`].
+    fn begin_send(&mut self) -> Result<(), Error> {
+        if let Some(within) = &mut self.within {
+            let _ = within.begin("send", self.clock.now())?;
+        }
+
+        Ok(())
+    }
+
+    /// Paste a text, which may contain many lines, into a process.
+    ///
+    /// If bracketed paste is on (see [`Session::set_bracketed_paste`])
+    /// the text is wrapped into bracketed paste sequences,
+    /// so a shell or a REPL doesn't treat it as typed input (autocompletion, auto-indent).
+    /// Otherwise the text is written by small chunks with a short delay between them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::spawn;
+    ///
+    /// let mut p = spawn("python3").unwrap();
+    /// p.paste("def f():\n    return 1\n\n").unwrap();
+    /// ```
+    pub fn paste<B>(&mut self, text: B) -> Result<(), Error>
+    where
+        B: AsRef<[u8]>,
+    {
+        self.begin_send()?;
+
+        let text = text.as_ref();
+        if self.bracketed_paste {
+            self.stream
+                .write_all(BRACKETED_PASTE_START)
+                .map_err(Error::io("write"))?;
+            self.stream.write_all(text).map_err(Error::io("write"))?;
+            self.stream
+                .write_all(BRACKETED_PASTE_END)
+                .map_err(Error::io("write"))?;
+            self.stream.flush().map_err(Error::io("write"))?;
+
+            return Ok(());
+        }
+
+        for (i, chunk) in text.chunks(PASTE_CHUNK_SIZE).enumerate() {
+            if i > 0 {
+                std::thread::sleep(PASTE_CHUNK_DELAY);
+            }
 
-        Ok(session)
-    }
-}
+            self.stream.write_all(chunk).map_err(Error::io("write"))?;
+            self.stream.flush().map_err(Error::io("write"))?;
+        }
 
-impl<P, S> Session<P, S> {
-    /// Set the pty session's expect timeout.
-    pub fn set_expect_timeout(&mut self, expect_timeout: Option<Duration>) {
-        self.expect_timeout = expect_timeout;
+        Ok(())
     }
 
-    /// Set a expect algorithm to be either gready or lazy.
+    /// Send a text character by character as if it was typed by a human.
     ///
-    /// Default algorithm is gready.
+    /// See [`TypingOptions`].
     ///
-    /// See [Session::expect].
-    pub fn set_expect_lazy(&mut self, lazy: bool) {
-        self.expect_lazy = lazy;
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use expectrl::{session::TypingOptions, spawn};
+    ///
+    /// let mut p = spawn("passwd").unwrap();
+    /// let options = TypingOptions::new(Duration::from_millis(50)).jitter(Duration::from_millis(30));
+    /// p.send_typed("secret\n", &options).unwrap();
+    /// ```
+    pub fn send_typed<T>(&mut self, text: T, options: &TypingOptions) -> Result<(), Error>
+    where
+        T: AsRef<str>,
+    {
+        self.begin_send()?;
+        self.type_text(text.as_ref(), options)
     }
 
-    /// Get a reference to original stream.
-    pub fn get_stream(&self) -> &S {
-        self.stream.as_ref()
-    }
+    /// Writes a text character by character with delays of [`TypingOptions`] between them.
+    fn type_text(&mut self, text: &str, options: &TypingOptions) -> Result<(), Error> {
+        let mut delays = options.delays();
+        let mut buf = [0; 4];
+        for (i, c) in text.chars().enumerate() {
+            if i > 0 {
+                if let Some(delay) = delays.next() {
+                    std::thread::sleep(delay);
+                }
+            }
 
-    /// Get a mut reference to original stream.
-    pub fn get_stream_mut(&mut self) -> &mut S {
-        self.stream.as_mut()
-    }
+            self.stream
+                .write_all(c.encode_utf8(&mut buf).as_bytes())
+                .map_err(Error::io("write"))?;
+            self.stream.flush().map_err(Error::io("write"))?;
+        }
 
-    /// Get a reference to a process running program.
-    pub fn get_process(&self) -> &P {
-        &self.proc
+        Ok(())
     }
 
-    /// Get a mut reference to a process running program.
-    pub fn get_process_mut(&mut self) -> &mut P {
-        &mut self.proc
+    /// Send a line character by character with a `delay` between keystrokes.
+    ///
+    /// The line ending is sent as the last keystroke, see [`Session::set_line_ending`].
+    pub fn send_line_slow<T>(&mut self, text: T, delay: Duration) -> Result<(), Error>
+    where
+        T: AsRef<str>,
+    {
+        self.begin_send()?;
+
+        let options = TypingOptions::new(delay);
+        self.type_text(text.as_ref(), &options)?;
+        std::thread::sleep(delay);
+        self.stream
+            .write_all(self.line_ending.as_bytes())
+            .map_err(Error::io("write"))?;
+        self.stream.flush().map_err(Error::io("write"))?;
+
+        Ok(())
     }
 }
 
-impl<P, S> Expect for Session<P, S>
+impl<P, S> Session<P, S>
 where
-    S: Write + Read + NonBlocking,
+    S: Read + Write + NonBlocking,
 {
-    fn expect<N>(&mut self, needle: N) -> Result<Captures, Error>
+    /// Expect waits until a pattern is matched, like [`Expect::expect`],
+    /// and calls `inspect` with each chunk of data received while waiting.
+    ///
+    /// It can be used to show a progress of a long running command.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::spawn;
+    ///
+    /// let mut p = spawn("cargo build").unwrap();
+    /// p.set_expect_timeout(None);
+    /// p.expect_with_inspect("Finished", |chunk| print!("{}", String::from_utf8_lossy(chunk)))
+    ///     .unwrap();
+    /// ```
+    pub fn expect_with_inspect<N, F>(&mut self, needle: N, inspect: F) -> Result<Captures, Error>
     where
         N: Needle,
+        F: FnMut(&[u8]),
     {
-        match self.expect_lazy {
-            true => self.expect_lazy(needle),
-            false => self.expect_gready(needle),
+        match self.find(needle, inspect) {
+            Ok(found) => Ok(self.take_match(found)),
+            Err(err) => Err(err.with_session_name(self.name.as_deref())),
         }
     }
 
-    fn check<N>(&mut self, needle: N) -> Result<Captures, Error>
+    /// Expect waits until a pattern is matched, like [`Expect::expect`],
+    /// but the returned captures borrow the session buffer instead of copying it.
+    ///
+    /// It saves an allocation per match,
+    /// which matters in tight loops processing a lot of output.
+    /// The matched bytes are dropped from the buffer once the session is used again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::spawn;
+    ///
+    /// let mut p = spawn("seq 1000").unwrap();
+    /// let mut sum = 0;
+    /// for _ in 0..1000 {
+    ///     let m = p.expect_ref("\n").unwrap();
+    ///     sum += m.before_str_lossy().trim().parse::<u64>().unwrap();
+    /// }
+    /// assert_eq!(sum, 500500);
+    /// ```
+    pub fn expect_ref<N>(&mut self, needle: N) -> Result<CapturesRef<'_>, Error>
     where
         N: Needle,
     {
-        let eof = self.stream.read_available()?;
-        let buf = self.stream.get_available();
-
-        let found = needle.check(buf, eof)?;
-        if !found.is_empty() {
-            let end_index = Captures::right_most_index(&found);
-            let involved_bytes = buf[..end_index].to_vec();
-            self.stream.consume_available(end_index);
-            return Ok(Captures::new(involved_bytes, found));
+        match self.find(needle, |_| ()) {
+            Ok(found) => Ok(self.lend_match(found)),
+            Err(err) => Err(err.with_session_name(self.name.as_deref())),
         }
+    }
 
-        if eof {
-            return Err(Error::Eof);
+    /// Expect waits until a pattern is matched, like [`Expect::expect`],
+    /// and writes the match into `captures`.
+    ///
+    /// The storage of `captures` is reused,
+    /// so there's no allocation per call once it's big enough for a match.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, Captures};
+    ///
+    /// let mut p = spawn("seq 1000").unwrap();
+    /// p.reserve_buffer(64 * 1024);
+    ///
+    /// let mut line = Captures::with_capacity(64);
+    /// for _ in 0..1000 {
+    ///     p.expect_into("\n", &mut line).unwrap();
+    /// }
+    /// assert_eq!(line.before(), b"1000\r");
+    /// ```
+    pub fn expect_into<N>(&mut self, needle: N, captures: &mut Captures) -> Result<(), Error>
+    where
+        N: Needle,
+    {
+        match self.find(needle, |_| ()) {
+            Ok(found) => {
+                self.take_match_into(&found, captures);
+                Ok(())
+            }
+            Err(err) => Err(err.with_session_name(self.name.as_deref())),
         }
-
-        Ok(Captures::new(Vec::new(), Vec::new()))
     }
 
-    fn is_matched<N>(&mut self, needle: N) -> Result<bool, Error>
+    /// Try to match a pattern without waiting for it.
+    ///
+    /// It makes at most one non-blocking read and one match attempt,
+    /// so unlike [`Expect::expect`] it returns `Ok(None)` right away if there's no match yet.
+    /// It can be used to drive a session from a custom event loop.
+    ///
+    /// [`Error::Eof`] is returned if the stream has reached EOF and there's no match.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::spawn;
+    ///
+    /// let mut p = spawn("echo Hello World").unwrap();
+    /// loop {
+    ///     if let Some(m) = p.try_expect("World").unwrap() {
+    ///         break;
+    ///     }
+    ///
+    ///     // do something else
+    /// }
+    /// ```
+    pub fn try_expect<N>(&mut self, needle: N) -> Result<Option<Captures>, Error>
     where
         N: Needle,
     {
-        let eof = self.stream.read_available()?;
+        let mut buf = vec![0; self.stream.chunk_size];
+        let eof = self
+            .stream
+            .read_available_once(&mut buf)
+            .map_err(Error::io("read"))?
+            == Some(0);
+        let _ = self.limit_buffer()?;
         let buf = self.stream.get_available();
 
+        if let Some((pattern, captures)) = self.abort_patterns.check(buf, 0, eof)? {
+            return Err(self.abort(pattern, captures));
+        }
+
         let found = needle.check(buf, eof)?;
         if !found.is_empty() {
-            return Ok(true);
+            let end_index = Captures::right_most_index(&found);
+            let involved_bytes = buf[..end_index].to_vec();
+            let timestamp = self.stream.timestamp(end_index);
+            let offset = self.stream.consumed();
+            self.consume_match(end_index);
+            let captures = Captures::new(involved_bytes, found)
+                .with_offset(offset)
+                .with_timestamp(timestamp);
+            return Ok(Some(captures));
         }
 
         if eof {
             return Err(Error::Eof);
         }
 
-        Ok(false)
+        Ok(None)
     }
 
-    fn send<B>(&mut self, buf: B) -> Result<(), Error>
+    /// Waits until a pattern is matched in a way set by [Session::set_expect_lazy].
+    ///
+    /// The match is left at the start of the buffer.
+    fn find<N, F>(&mut self, needle: N, inspect: F) -> Result<Vec<Match>, Error>
     where
-        B: AsRef<[u8]>,
+        N: Needle,
+        F: FnMut(&[u8]),
     {
-        self.stream.write_all(buf.as_ref())?;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("expect", session = self.name.as_deref()).entered();
+
+        let start = self.clock.now();
+        let timeout = self.expect_timeout;
+        let limited = match &mut self.within {
+            Some(within) => {
+                let left = within.begin("expect", start)?;
+                let (bound, limited) = Within::bound(timeout, left);
+                self.expect_timeout = bound;
+                limited
+            }
+            None => false,
+        };
 
-        Ok(())
+        let result = match self.expect_lazy {
+            true => self.find_lazy(needle, inspect),
+            false => self.find_gready(needle, inspect),
+        };
+
+        self.expect_timeout = timeout;
+
+        let elapsed = self.clock.now().saturating_duration_since(start);
+        self.metrics.record_expect(&result, elapsed);
+
+        let result = match (result, &self.within) {
+            (Err(Error::ExpectTimeout { .. }), Some(within)) if limited => Err(within.error()),
+            (result, _) => result,
+        };
+
+        #[cfg(feature = "tracing")]
+        match &result {
+            Ok(found) => tracing::debug!(bytes = Captures::right_most_index(found), "matched"),
+            Err(err) => tracing::debug!(error = %err, "not matched"),
+        }
+
+        result
     }
 
-    fn send_line<B>(&mut self, buf: B) -> Result<(), Error>
-    where
-        B: AsRef<[u8]>,
-    {
-        #[cfg(windows)]
-        const LINE_ENDING: &[u8] = b"\r\n";
-        #[cfg(not(windows))]
-        const LINE_ENDING: &[u8] = b"\n";
+    /// Consumes a match from the buffer and returns a copy of it.
+    fn take_match(&mut self, found: Vec<Match>) -> Captures {
+        let end_index = Captures::right_most_index(&found);
+        let involved_bytes = self.stream.get_available()[..end_index].to_vec();
+        let timestamp = self.stream.timestamp(end_index);
+        let offset = self.stream.consumed();
+        self.consume_match(end_index);
+
+        Captures::new(involved_bytes, found)
+            .with_offset(offset)
+            .with_timestamp(timestamp)
+    }
 
-        self.stream.write_all(buf.as_ref())?;
-        self.write_all(LINE_ENDING)?;
+    /// Consumes a match from the buffer and copies it into `captures`.
+    fn take_match_into(&mut self, found: &[Match], captures: &mut Captures) {
+        let end_index = Captures::right_most_index(found);
+        let involved_bytes = &self.stream.get_available()[..end_index];
+        let timestamp = self.stream.timestamp(end_index);
+        let offset = self.stream.consumed();
+        captures.replace(involved_bytes, found, offset, timestamp);
+        self.consume_match(end_index);
+    }
 
-        Ok(())
+    /// Consumes a match from the buffer and lends it.
+    ///
+    /// The bytes are kept in the buffer till a next use of the stream.
+    fn lend_match(&mut self, found: Vec<Match>) -> CapturesRef<'_> {
+        let end_index = Captures::right_most_index(&found);
+        let timestamp = self.stream.timestamp(end_index);
+        let offset = self.stream.consumed();
+        let n = match self.consume_mode {
+            ConsumeMode::UpToMatch => end_index,
+            ConsumeMode::WholeBuffer => self.stream.get_available().len(),
+        };
+
+        let involved_bytes = &self.stream.lend(n)[..end_index];
+
+        CapturesRef::new(involved_bytes, found)
+            .with_offset(offset)
+            .with_timestamp(timestamp)
     }
-}
 
-impl<P, S> Session<P, S>
-where
-    S: Read + NonBlocking,
-{
-    /// Expect which fills as much as possible to the buffer.
+    /// Find which fills as much as possible to the buffer.
     ///
     /// See [Session::expect].
-    fn expect_gready<N>(&mut self, needle: N) -> Result<Captures, Error>
+    fn find_gready<N, F>(&mut self, needle: N, mut inspect: F) -> Result<Vec<Match>, Error>
     where
         N: Needle,
+        F: FnMut(&[u8]),
     {
-        let start = time::Instant::now();
+        let start = self.clock.now();
+        let mut checked: usize = 0;
+        self.reset_timers();
         loop {
-            let eof = self.stream.read_available()?;
+            let length = self.stream.get_available().len();
+            let eof = self.stream.read_available().map_err(Error::io("read"))?;
+            let dropped = self.limit_buffer()?;
+            self.handle_pager()?;
             let data = self.stream.get_available();
+            checked = checked.saturating_sub(dropped).min(data.len());
+
+            let received = data
+                .get(length.saturating_sub(dropped)..)
+                .unwrap_or_default();
+            let idle = received.is_empty();
+            if !idle {
+                inspect(received);
+            }
 
-            let found = needle.check(data, eof)?;
-            if !found.is_empty() {
-                let end_index = Captures::right_most_index(&found);
-                let involved_bytes = data[..end_index].to_vec();
-                self.stream.consume_available(end_index);
+            if let Some((pattern, captures)) = self.abort_patterns.check(data, checked, eof)? {
+                return Err(self.abort(pattern, captures));
+            }
 
-                return Ok(Captures::new(involved_bytes, found));
+            let found = needle.check_from(data, checked, eof)?;
+            if !found.is_empty() {
+                return Ok(found);
             }
 
+            checked = data.len();
+
             if eof {
                 return Err(Error::Eof);
             }
 
             if let Some(timeout) = self.expect_timeout {
-                if start.elapsed() > timeout {
-                    return Err(Error::ExpectTimeout);
+                if self.clock.now().saturating_duration_since(start) > timeout {
+                    let buffer = self.stream.get_available();
+                    return Err(Error::expect_timeout(needle.describe(), buffer));
                 }
             }
+
+            self.check_timers()?;
+
+            if idle {
+                self.wait_output(start, needle.recheck_in())?;
+            }
         }
     }
 
-    /// Expect which reads byte by byte.
+    /// Find which reads byte by byte.
     ///
     /// See [Session::expect].
-    fn expect_lazy<N>(&mut self, needle: N) -> Result<Captures, Error>
+    fn find_lazy<N, F>(&mut self, needle: N, mut inspect: F) -> Result<Vec<Match>, Error>
     where
         N: Needle,
+        F: FnMut(&[u8]),
     {
         let mut checking_data_length = 0;
+        let mut checked: usize = 0;
         let mut eof = false;
-        let start = time::Instant::now();
+        let start = self.clock.now();
+        self.reset_timers();
         loop {
+            let mut idle = false;
             let mut available = self.stream.get_available();
             if checking_data_length == available.len() {
                 // We read by byte to make things as lazy as possible.
@@ -236,8 +1451,29 @@ where
                 // We could read all data available via `read_available` to reduce IO operations,
                 // but in such case we would need to keep a EOF indicator internally in stream,
                 // which is OK if EOF happens onces, but I am not sure if this is a case.
-                eof = self.stream.read_available_once(&mut [0; 1])? == Some(0);
+                let length = available.len();
+                let read = self
+                    .stream
+                    .read_available_once(&mut [0; 1])
+                    .map_err(Error::io("read"))?;
+                eof = read == Some(0);
+                idle = read.is_none();
+
+                let dropped = self.limit_buffer()?;
+                self.handle_pager()?;
+
                 available = self.stream.get_available();
+                checking_data_length = checking_data_length
+                    .saturating_sub(dropped)
+                    .min(available.len());
+                checked = checked.saturating_sub(dropped).min(available.len());
+
+                let received = available
+                    .get(length.saturating_sub(dropped)..)
+                    .unwrap_or_default();
+                if !received.is_empty() {
+                    inspect(received);
+                }
             }
 
             // We intentinally not increase the counter
@@ -249,24 +1485,152 @@ where
 
             let data = &available[..checking_data_length];
 
-            let found = needle.check(data, eof)?;
+            if let Some((pattern, captures)) = self.abort_patterns.check(data, checked, eof)? {
+                return Err(self.abort(pattern, captures));
+            }
+
+            let found = needle.check_from(data, checked, eof)?;
             if !found.is_empty() {
-                let end_index = Captures::right_most_index(&found);
-                let involved_bytes = data[..end_index].to_vec();
-                self.stream.consume_available(end_index);
-                return Ok(Captures::new(involved_bytes, found));
+                return Ok(found);
             }
 
+            checked = data.len();
+
             if eof {
                 return Err(Error::Eof);
             }
 
             if let Some(timeout) = self.expect_timeout {
-                if start.elapsed() > timeout {
-                    return Err(Error::ExpectTimeout);
+                if self.clock.now().saturating_duration_since(start) > timeout {
+                    let buffer = self.stream.get_available();
+                    return Err(Error::expect_timeout(needle.describe(), buffer));
                 }
             }
+
+            self.check_timers()?;
+
+            if idle {
+                self.wait_output(start, needle.recheck_in())?;
+            }
+        }
+    }
+
+    /// Applies a buffer limit.
+    ///
+    /// Returns an amount of dropped bytes.
+    fn limit_buffer(&mut self) -> Result<usize, Error> {
+        let limit = match &mut self.buffer_limit {
+            Some(limit) => limit,
+            None => return Ok(0),
+        };
+
+        let dropped = limit.check(self.stream.get_available())?;
+        self.stream.consume_available(dropped);
+
+        Ok(dropped)
+    }
+
+    /// Answers a pager prompt at the end of the buffer if a pager handler is set.
+    ///
+    /// The prompt is removed from the buffer.
+    fn handle_pager(&mut self) -> Result<(), Error> {
+        let pager = match &self.pager {
+            Some(pager) => pager,
+            None => return Ok(()),
+        };
+
+        if let Some(start) = pager.check(self.stream.get_available()) {
+            self.stream.truncate_available(start);
+            self.stream
+                .write_all(pager.get_response())
+                .map_err(Error::io("write"))?;
+            self.stream.flush().map_err(Error::io("write"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Consumes a matched data according to a consume mode.
+    fn consume_match(&mut self, end_index: usize) {
+        let n = match self.consume_mode {
+            ConsumeMode::UpToMatch => end_index,
+            ConsumeMode::WholeBuffer => self.stream.get_available().len(),
+        };
+
+        self.stream.consume_available(n);
+    }
+
+    fn abort(&mut self, pattern: String, captures: Captures) -> Error {
+        let offset = self.stream.consumed();
+        let end_index = captures.as_bytes().len();
+        let timestamp = self.stream.timestamp(end_index);
+        self.stream.consume_available(end_index);
+
+        Error::Aborted {
+            pattern,
+            captures: captures.with_offset(offset).with_timestamp(timestamp),
+        }
+    }
+
+    fn reset_timers(&mut self) {
+        let now = self.clock.now();
+
+        if let Some(watchdog) = &mut self.watchdog {
+            watchdog.reset(self.stream.get_available().len(), now);
+        }
+
+        if let Some(keepalive) = &mut self.keepalive {
+            keepalive.reset(now);
+        }
+    }
+
+    fn check_timers(&mut self) -> Result<(), Error> {
+        let now = self.clock.now();
+
+        if let Some(watchdog) = &mut self.watchdog {
+            if let Some(buf) = watchdog.check(self.stream.get_available().len(), now)? {
+                self.stream.write_all(&buf).map_err(Error::io("write"))?;
+                self.stream.flush().map_err(Error::io("write"))?;
+            }
         }
+
+        if let Some(keepalive) = &mut self.keepalive {
+            if let Some(buf) = keepalive.check(now) {
+                self.stream.write_all(buf).map_err(Error::io("write"))?;
+                self.stream.flush().map_err(Error::io("write"))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Waits for an output of a process when there was nothing to read,
+    /// until an expect timeout, a watchdog, a keepalive or a needle (see [`Needle::recheck_in`])
+    /// needs to be checked.
+    ///
+    /// It falls back to a pause of a poll interval
+    /// if the stream can't tell when data arrives.
+    fn wait_output(&mut self, start: Instant, recheck: Option<Duration>) -> io::Result<()> {
+        let now = self.clock.now();
+        let deadline = self
+            .expect_timeout
+            .map(|timeout| timeout.saturating_sub(now.saturating_duration_since(start)));
+        let watchdog = self.watchdog.as_ref().map(|w| w.remaining(now));
+        let keepalive = self.keepalive.as_ref().map(|k| k.remaining(now));
+
+        let mut timeout = [deadline, watchdog, keepalive, recheck]
+            .into_iter()
+            .flatten()
+            .min();
+        if !self.clock.is_real_time() {
+            timeout = Some(timeout.map_or(self.poll_interval, |t| t.min(self.poll_interval)));
+        }
+
+        if !self.stream.wait_readable(timeout)? {
+            std::thread::sleep(self.poll_interval);
+        }
+
+        Ok(())
     }
 }
 
@@ -355,6 +1719,33 @@ where
     }
 }
 
+impl<P, S> WindowSize for Session<P, S>
+where
+    P: WindowSize,
+{
+    fn set_window_size(&mut self, cols: u16, rows: u16) -> io::Result<()> {
+        self.get_process_mut().set_window_size(cols, rows)
+    }
+}
+
+impl<P, S> Usage for Session<P, S>
+where
+    P: Usage,
+{
+    fn resource_usage(&self) -> io::Result<Option<ResourceUsage>> {
+        self.get_process().resource_usage()
+    }
+}
+
+impl<P, S> OnDrop for Session<P, S>
+where
+    P: OnDrop,
+{
+    fn set_drop_policy(&mut self, policy: DropPolicy) {
+        self.get_process_mut().set_drop_policy(policy)
+    }
+}
+
 impl<P, S> NonBlocking for Session<P, S>
 where
     S: NonBlocking,
@@ -362,6 +1753,19 @@ where
     fn set_blocking(&mut self, on: bool) -> io::Result<()> {
         S::set_blocking(self.get_stream_mut(), on)
     }
+
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> io::Result<bool> {
+        if self.stream.has_buffered() {
+            return Ok(true);
+        }
+
+        self.stream.as_mut().wait_readable(timeout)
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        self.get_stream().readiness_fd()
+    }
 }
 
 #[cfg(unix)]
@@ -394,9 +1798,55 @@ where
     }
 }
 
+#[cfg(unix)]
+impl<P, S> std::os::fd::AsFd for Session<P, S>
+where
+    S: std::os::fd::AsFd,
+{
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        self.get_stream().as_fd()
+    }
+}
+
+#[cfg(all(unix, feature = "mio"))]
+impl<P, S> mio::event::Source for Session<P, S>
+where
+    S: mio::event::Source,
+{
+    fn register(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        self.get_stream_mut().register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        self.get_stream_mut().reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio::Registry) -> io::Result<()> {
+        self.get_stream_mut().deregister(registry)
+    }
+}
+
 #[derive(Debug)]
 struct TryStream<S> {
     stream: ControlledReader<S>,
+    consumed: usize,
+    chunk_size: usize,
+    /// A buffer reused by reads from the inner stream.
+    read_buf: Vec<u8>,
+    bytes_written: u64,
+    /// An amount of consumed bytes which are still kept at the start of the buffer,
+    /// as they are borrowed by [CapturesRef].
+    lent: usize,
 }
 
 impl<S> TryStream<S> {
@@ -421,25 +1871,92 @@ where
     fn new(stream: S) -> io::Result<Self> {
         Ok(Self {
             stream: ControlledReader::new(stream),
+            consumed: 0,
+            chunk_size: READ_CHUNK_SIZE,
+            read_buf: Vec::new(),
+            bytes_written: 0,
+            lent: 0,
         })
     }
 
     fn flush_in_buffer(&mut self) {
+        self.release();
         self.stream.flush_in_buffer();
     }
 }
 
 impl<S> TryStream<S> {
     fn keep_in_buffer(&mut self, v: &[u8]) {
+        self.release();
         self.stream.keep_in_buffer(v);
     }
 
-    fn get_available(&mut self) -> &[u8] {
-        self.stream.get_available()
+    fn get_available(&self) -> &[u8] {
+        &self.stream.get_available()[self.lent..]
+    }
+
+    /// Consumes `n` bytes but keeps them in the buffer till a next use of the stream.
+    ///
+    /// Returns the consumed bytes.
+    fn lend(&mut self, n: usize) -> &[u8] {
+        self.release();
+        self.lent = n;
+        self.consumed += n;
+        &self.stream.get_available()[..n]
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.stream.inner.get_mut().buffer.reserve(additional);
+    }
+
+    /// Returns an amount of bytes read from the inner stream.
+    fn bytes_read(&self) -> u64 {
+        self.stream.inner.get_ref().bytes_read
+    }
+
+    fn reset_io_counters(&mut self) {
+        self.stream.inner.get_mut().bytes_read = 0;
+        self.bytes_written = 0;
+    }
+
+    /// Drops lent bytes from the buffer.
+    fn release(&mut self) {
+        if self.lent > 0 {
+            self.stream.consume_available(self.lent);
+            self.lent = 0;
+        }
+    }
+
+    /// Checks whether a read would return data without touching an inner stream.
+    fn has_buffered(&self) -> bool {
+        !self.stream.inner.buffer().is_empty() || !self.get_available().is_empty()
     }
 
     fn consume_available(&mut self, n: usize) {
-        self.stream.consume_available(n)
+        self.release();
+        self.stream.consume_available(n);
+        self.consumed += n;
+    }
+
+    /// Removes not consumed bytes after `n`.
+    fn truncate_available(&mut self, n: usize) {
+        self.release();
+        self.stream.truncate_available(n);
+    }
+
+    /// Returns an amount of bytes consumed from the stream.
+    fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Returns a time when a byte before `end` position of the available data was read.
+    fn timestamp(&self, end: usize) -> Option<Instant> {
+        self.stream.timestamp(self.lent + end)
+    }
+
+    /// Returns a time of the latest read from the stream.
+    fn last_output(&self) -> Option<Instant> {
+        self.stream.timestamps.last()
     }
 }
 
@@ -451,9 +1968,13 @@ where
     ///
     /// It raises io::ErrorKind::WouldBlock if there's nothing to read.
     fn try_read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.release();
         self.stream.get_mut().set_blocking(false)?;
 
         let result = self.stream.inner.read(buf);
+        if let Ok(n) = result {
+            self.consumed += n;
+        }
 
         // As file is DUPed changes in one descriptor affects all ones
         // so we need to make blocking file after we finished.
@@ -473,10 +1994,11 @@ where
     }
 
     fn read_available(&mut self) -> std::io::Result<bool> {
-        self.stream.flush_in_buffer();
+        self.flush_in_buffer();
 
-        let mut buf = [0; 248];
-        loop {
+        let mut buf = std::mem::take(&mut self.read_buf);
+        buf.resize(self.chunk_size, 0);
+        let result = loop {
             match self.try_read_inner(&mut buf) {
                 Ok(0) => break Ok(true),
                 Ok(n) => {
@@ -485,11 +2007,14 @@ where
                 Err(err) if err.kind() == io::ErrorKind::WouldBlock => break Ok(false),
                 Err(err) => break Err(err),
             }
-        }
+        };
+        self.read_buf = buf;
+
+        result
     }
 
     fn read_available_once(&mut self, buf: &mut [u8]) -> std::io::Result<Option<usize>> {
-        self.stream.flush_in_buffer();
+        self.flush_in_buffer();
 
         match self.try_read_inner(buf) {
             Ok(0) => Ok(Some(0)),
@@ -502,11 +2027,22 @@ where
         }
     }
 
+    /// Waits for data in an inner stream, it doesn't account data in the buffers.
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> io::Result<bool> {
+        self.stream.get_mut().wait_readable(timeout)
+    }
+
     // non-buffered && non-blocking read
     fn try_read_inner(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.stream.get_mut().set_blocking(false)?;
 
         let result = self.stream.get_mut().read(buf);
+        if let Ok(n) = result {
+            self.stream.inner.get_mut().bytes_read += n as u64;
+
+            #[cfg(feature = "tracing")]
+            crate::trace::read(&buf[..n]);
+        }
 
         // As file is DUPed changes in one descriptor affects all ones
         // so we need to make blocking file after we finished.
@@ -521,7 +2057,13 @@ where
     S: Write,
 {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.stream.inner.get_mut().inner.write(buf)
+        let n = self.stream.inner.get_mut().inner.write(buf)?;
+        self.bytes_written += n as u64;
+
+        #[cfg(feature = "tracing")]
+        crate::trace::write(&buf[..n]);
+
+        Ok(n)
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -529,7 +2071,13 @@ where
     }
 
     fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
-        self.stream.inner.get_mut().inner.write_vectored(bufs)
+        let n = self.stream.inner.get_mut().inner.write_vectored(bufs)?;
+        self.bytes_written += n as u64;
+
+        #[cfg(feature = "tracing")]
+        crate::trace::write_vectored(bufs, n);
+
+        Ok(n)
     }
 }
 
@@ -538,7 +2086,10 @@ where
     R: Read,
 {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.stream.inner.read(buf)
+        self.release();
+        let n = self.stream.inner.read(buf)?;
+        self.consumed += n;
+        Ok(n)
     }
 }
 
@@ -547,17 +2098,21 @@ where
     R: Read,
 {
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.release();
         self.stream.inner.fill_buf()
     }
 
     fn consume(&mut self, amt: usize) {
-        self.stream.inner.consume(amt)
+        self.release();
+        self.stream.inner.consume(amt);
+        self.consumed += amt;
     }
 }
 
 #[derive(Debug)]
 struct ControlledReader<R> {
     inner: BufReader<BufferedReader<R>>,
+    timestamps: Timestamps,
 }
 
 impl<R> ControlledReader<R>
@@ -567,6 +2122,7 @@ where
     fn new(reader: R) -> Self {
         Self {
             inner: BufReader::new(BufferedReader::new(reader)),
+            timestamps: Timestamps::new(),
         }
     }
 
@@ -584,25 +2140,41 @@ where
 impl<R> ControlledReader<R> {
     fn keep_in_buffer(&mut self, v: &[u8]) {
         self.inner.get_mut().buffer.extend(v);
+
+        let len = self.inner.get_ref().buffer.len();
+        self.timestamps.push(v.len(), len);
     }
 
     fn get_mut(&mut self) -> &mut R {
         &mut self.inner.get_mut().inner
     }
 
-    fn get_available(&mut self) -> &[u8] {
+    fn get_available(&self) -> &[u8] {
         &self.inner.get_ref().buffer
     }
 
     fn consume_available(&mut self, n: usize) {
         let _ = self.inner.get_mut().buffer.drain(..n);
     }
+
+    fn truncate_available(&mut self, n: usize) {
+        let len = self.inner.get_ref().buffer.len();
+        self.inner.get_mut().buffer.truncate(n);
+        self.timestamps.truncate(len.saturating_sub(n));
+    }
+
+    /// Returns a time when a byte before `end` position of the buffer was read.
+    fn timestamp(&self, end: usize) -> Option<Instant> {
+        let len = self.inner.get_ref().buffer.len();
+        self.timestamps.get(end, len)
+    }
 }
 
 #[derive(Debug)]
 struct BufferedReader<R> {
     inner: R,
     buffer: Vec<u8>,
+    bytes_read: u64,
 }
 
 impl<R> BufferedReader<R> {
@@ -610,6 +2182,7 @@ impl<R> BufferedReader<R> {
         Self {
             inner: reader,
             buffer: Vec::new(),
+            bytes_read: 0,
         }
     }
 }
@@ -620,7 +2193,13 @@ where
 {
     fn read(&mut self, mut buf: &mut [u8]) -> std::io::Result<usize> {
         if self.buffer.is_empty() {
-            self.inner.read(buf)
+            let n = self.inner.read(buf)?;
+            self.bytes_read += n as u64;
+
+            #[cfg(feature = "tracing")]
+            crate::trace::read(&buf[..n]);
+
+            Ok(n)
         } else {
             let n = buf.write(&self.buffer)?;
             let _ = self.buffer.drain(..n);
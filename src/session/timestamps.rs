@@ -0,0 +1,114 @@
+//! This module contains a bookkeeping of times when an output was read.
+
+use std::{collections::VecDeque, time::Instant};
+
+/// Timestamps keeps a time of each chunk appended to a session buffer.
+///
+/// Chunk positions are absolute, counted from the first byte ever appended,
+/// so bytes taken from a front of a buffer don't need to be tracked;
+/// a caller provides a current buffer length instead.
+#[derive(Debug, Default)]
+pub(crate) struct Timestamps {
+    /// An end position of each chunk and a time it was read.
+    chunks: VecDeque<(usize, Instant)>,
+    /// An amount of bytes ever appended.
+    appended: usize,
+    last: Option<Instant>,
+}
+
+impl Timestamps {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `n` bytes were appended to a buffer of `buffer_len` bytes (including the appended ones).
+    pub(crate) fn push(&mut self, n: usize, buffer_len: usize) {
+        if n == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        self.appended += n;
+        self.chunks.push_back((self.appended, now));
+        self.last = Some(now);
+
+        // Drop chunks which were already taken out of the buffer.
+        let start = self.appended.saturating_sub(buffer_len);
+        while matches!(self.chunks.front(), Some((end, _)) if *end <= start) {
+            let _ = self.chunks.pop_front();
+        }
+    }
+
+    /// Records that `n` bytes were removed from an end of a buffer.
+    pub(crate) fn truncate(&mut self, n: usize) {
+        self.appended = self.appended.saturating_sub(n);
+
+        while matches!(self.chunks.back(), Some((end, _)) if *end > self.appended) {
+            let (_, time) = self.chunks.pop_back().expect("checked");
+            let previous_end = self.chunks.back().map_or(0, |(end, _)| *end);
+            if previous_end < self.appended {
+                self.chunks.push_back((self.appended, time));
+                break;
+            }
+        }
+    }
+
+    /// Returns a time when a byte before `end` position of a buffer of `buffer_len` bytes was read.
+    ///
+    /// For an empty range (`end == 0`) it's a time of the latest read before the buffer start.
+    pub(crate) fn get(&self, end: usize, buffer_len: usize) -> Option<Instant> {
+        let start = self.appended.saturating_sub(buffer_len);
+        let position = start + end;
+
+        self.chunks
+            .iter()
+            .find(|(chunk_end, _)| *chunk_end >= position)
+            .map(|(_, time)| *time)
+            .or(self.last)
+    }
+
+    /// Returns a time of the latest read.
+    pub(crate) fn last(&self) -> Option<Instant> {
+        self.last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamps() {
+        let mut timestamps = Timestamps::new();
+        assert_eq!(timestamps.get(0, 0), None);
+        assert_eq!(timestamps.last(), None);
+
+        timestamps.push(3, 3);
+        let first = timestamps.last().unwrap();
+        timestamps.push(0, 3);
+        assert_eq!(timestamps.last(), Some(first));
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        timestamps.push(3, 6);
+        let second = timestamps.last().unwrap();
+        assert!(second > first);
+
+        assert_eq!(timestamps.get(1, 6), Some(first));
+        assert_eq!(timestamps.get(3, 6), Some(first));
+        assert_eq!(timestamps.get(4, 6), Some(second));
+        assert_eq!(timestamps.get(6, 6), Some(second));
+
+        // first 4 bytes were consumed
+        assert_eq!(timestamps.get(1, 2), Some(second));
+
+        // the last 2 bytes of a 6 bytes buffer were removed
+        timestamps.truncate(2);
+        assert_eq!(timestamps.get(4, 4), Some(second));
+        timestamps.truncate(1);
+        assert_eq!(timestamps.get(3, 3), Some(first));
+        assert_eq!(timestamps.last(), Some(second));
+
+        timestamps.push(1, 1);
+        assert_eq!(timestamps.chunks.len(), 1);
+    }
+}
@@ -0,0 +1,118 @@
+//! This module contains options of a typing simulation.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// TypingOptions configures how [`Session::send_typed`] sends input.
+///
+/// Input is sent character by character with a delay between keystrokes,
+/// like a human would type it.
+/// It's useful for programs which drop input arriving faster than they read it,
+/// e.g. some TUIs and password prompts.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use expectrl::session::TypingOptions;
+///
+/// let options = TypingOptions::new(Duration::from_millis(50))
+///     .jitter(Duration::from_millis(20));
+/// ```
+///
+/// [`Session::send_typed`]: crate::session::Session::send_typed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypingOptions {
+    delay: Duration,
+    jitter: Duration,
+}
+
+impl TypingOptions {
+    /// Creates options with a constant delay between keystrokes.
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            jitter: Duration::ZERO,
+        }
+    }
+
+    /// Set a jitter, a random duration up to which is added to each delay.
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Returns a delay between keystrokes.
+    pub fn get_delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Returns a jitter.
+    pub fn get_jitter(&self) -> Duration {
+        self.jitter
+    }
+
+    /// Returns an endless sequence of delays between keystrokes.
+    pub(crate) fn delays(&self) -> Delays {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or_default();
+
+        Delays {
+            delay: self.delay,
+            jitter: self.jitter.as_nanos() as u64,
+            // xorshift must not be seeded with 0
+            state: seed | 1,
+        }
+    }
+}
+
+/// Delays generates delays between keystrokes.
+///
+/// A jitter is taken from a xorshift generator,
+/// which is good enough for an imitation of typing.
+#[derive(Debug)]
+pub(crate) struct Delays {
+    delay: Duration,
+    jitter: u64,
+    state: u64,
+}
+
+impl Iterator for Delays {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.jitter == 0 {
+            return Some(self.delay);
+        }
+
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        let jitter = self.state % (self.jitter + 1);
+
+        Some(self.delay + Duration::from_nanos(jitter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delays() {
+        let options = TypingOptions::new(Duration::from_millis(10));
+        assert!(options
+            .delays()
+            .take(100)
+            .all(|delay| delay == Duration::from_millis(10)));
+
+        let options = options.jitter(Duration::from_millis(5));
+        let delays = options.delays().take(100).collect::<Vec<_>>();
+        assert!(delays.iter().all(
+            |delay| *delay >= Duration::from_millis(10) && *delay <= Duration::from_millis(15)
+        ));
+        assert!(delays.iter().any(|delay| *delay != delays[0]));
+    }
+}
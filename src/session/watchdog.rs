@@ -0,0 +1,122 @@
+//! This module contains a read side inactivity watchdog of a session.
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::Error;
+
+/// An action which a session takes when a watchdog is triggered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchdogAction {
+    /// Keep waiting for an output.
+    Continue,
+    /// Send bytes to a process, e.g. a newline to nudge it.
+    Send(Vec<u8>),
+    /// Abort a current `expect` call with an error.
+    Abort,
+}
+
+/// Watchdog calls a callback when there was no output for a given period.
+pub(crate) struct Watchdog {
+    period: Duration,
+    callback: Box<dyn FnMut(Duration) -> WatchdogAction + Send>,
+    last_activity: Instant,
+    last_length: usize,
+}
+
+impl Watchdog {
+    pub(crate) fn new<F>(period: Duration, callback: F, now: Instant) -> Self
+    where
+        F: FnMut(Duration) -> WatchdogAction + Send + 'static,
+    {
+        Self {
+            period,
+            callback: Box::new(callback),
+            last_activity: now,
+            last_length: 0,
+        }
+    }
+
+    /// Starts a new period.
+    pub(crate) fn reset(&mut self, length: usize, now: Instant) {
+        self.last_activity = now;
+        self.last_length = length;
+    }
+
+    /// Returns a time left till the watchdog is triggered.
+    pub(crate) fn remaining(&self, now: Instant) -> Duration {
+        self.period
+            .saturating_sub(now.saturating_duration_since(self.last_activity))
+    }
+
+    /// Checks if a buffer has grown since the last check,
+    /// and calls a callback if it hasn't for the period.
+    ///
+    /// Returns bytes which must be sent to a process.
+    pub(crate) fn check(&mut self, length: usize, now: Instant) -> Result<Option<Vec<u8>>, Error> {
+        if length != self.last_length {
+            self.reset(length, now);
+            return Ok(None);
+        }
+
+        let idle = now.saturating_duration_since(self.last_activity);
+        if idle < self.period {
+            return Ok(None);
+        }
+
+        self.reset(length, now);
+
+        match (self.callback)(idle) {
+            WatchdogAction::Continue => Ok(None),
+            WatchdogAction::Send(buf) => Ok(Some(buf)),
+            WatchdogAction::Abort => Err(Error::Other {
+                message: "watchdog aborted an operation".to_string(),
+                err: format!("no output for {:?}", idle),
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for Watchdog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watchdog")
+            .field("period", &self.period)
+            .field("last_activity", &self.last_activity)
+            .field("last_length", &self.last_length)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchdog() {
+        let now = Instant::now();
+        let mut watchdog = Watchdog::new(
+            Duration::from_millis(100),
+            |_| WatchdogAction::Send(b"\n".to_vec()),
+            now,
+        );
+
+        assert_eq!(watchdog.check(0, now).unwrap(), None);
+        let now = now + Duration::from_millis(150);
+        assert_eq!(watchdog.check(0, now).unwrap(), Some(b"\n".to_vec()));
+        assert_eq!(watchdog.check(0, now).unwrap(), None);
+
+        let now = now + Duration::from_millis(150);
+        assert_eq!(watchdog.check(5, now).unwrap(), None);
+        assert_eq!(watchdog.check(5, now).unwrap(), None);
+    }
+
+    #[test]
+    fn test_watchdog_abort() {
+        let now = Instant::now();
+        let mut watchdog = Watchdog::new(Duration::from_millis(0), |_| WatchdogAction::Abort, now);
+
+        assert!(matches!(watchdog.check(0, now), Err(Error::Other { .. })));
+    }
+}
@@ -0,0 +1,114 @@
+//! This module contains a time limit of a block of steps, see `Session::within`.
+
+use std::time::{Duration, Instant};
+
+use crate::Error;
+
+/// Within tracks steps of a block which share one time limit.
+#[derive(Debug, Clone)]
+pub(crate) struct Within {
+    deadline: Instant,
+    limit: Duration,
+    /// An amount of started steps.
+    steps: usize,
+    /// A kind of a last started step.
+    action: &'static str,
+}
+
+impl Within {
+    pub(crate) fn new(now: Instant, limit: Duration) -> Self {
+        Self {
+            deadline: now + limit,
+            limit,
+            steps: 0,
+            action: "",
+        }
+    }
+
+    /// Makes a nested block end no later than the outer one.
+    pub(crate) fn nest(mut self, outer: Option<&Within>) -> Self {
+        if let Some(outer) = outer {
+            self.deadline = self.deadline.min(outer.deadline);
+        }
+
+        self
+    }
+
+    /// Accounts steps of a finished nested block.
+    pub(crate) fn add_steps(&mut self, nested: &Within) {
+        self.steps += nested.steps;
+        self.action = nested.action;
+    }
+
+    /// Starts a next step and returns a time left.
+    ///
+    /// It returns an error if the time is already over.
+    pub(crate) fn begin(&mut self, action: &'static str, now: Instant) -> Result<Duration, Error> {
+        self.steps += 1;
+        self.action = action;
+
+        let left = self.left(now);
+        if left.is_zero() {
+            return Err(self.error());
+        }
+
+        Ok(left)
+    }
+
+    /// Returns a timeout of an expect call bounded by a time left,
+    /// and whether it was the time left which was picked.
+    pub(crate) fn bound(timeout: Option<Duration>, left: Duration) -> (Option<Duration>, bool) {
+        match timeout {
+            Some(timeout) if timeout <= left => (Some(timeout), false),
+            _ => (Some(left), true),
+        }
+    }
+
+    /// Returns a time left till the end of the block.
+    pub(crate) fn left(&self, now: Instant) -> Duration {
+        self.deadline.saturating_duration_since(now)
+    }
+
+    /// Returns an error which points to a step in flight.
+    pub(crate) fn error(&self) -> Error {
+        Error::Within {
+            step: self.steps.saturating_sub(1),
+            action: self.action.to_owned(),
+            limit: self.limit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_within() {
+        let now = Instant::now();
+        let mut within = Within::new(now, Duration::from_secs(1));
+
+        let left = within.begin("expect", now).unwrap();
+        assert_eq!(left, Duration::from_secs(1));
+        assert_eq!(
+            Within::bound(Some(Duration::from_secs(10)), left),
+            (Some(left), true)
+        );
+        assert_eq!(
+            Within::bound(Some(Duration::from_millis(10)), left),
+            (Some(Duration::from_millis(10)), false)
+        );
+        assert_eq!(Within::bound(None, left), (Some(left), true));
+
+        let nested = Within::new(now, Duration::from_secs(10)).nest(Some(&within));
+        assert_eq!(nested.left(now), Duration::from_secs(1));
+
+        let err = within
+            .begin("send", now + Duration::from_secs(1))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Within { step: 1, ref action, .. } if action == "send"
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+//! This module contains a [RotatingFile]
+//! which can be used to keep a full output of a session on disk.
+
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// RotatingFile is a file writer which moves a file aside once it becomes too big or too old.
+///
+/// Rotated files get an index suffix, `capture.log.1` is the most recent one.
+/// Only [`RotatingFile::max_files`] of them are kept.
+///
+/// It's meant to be used with [`session::capture`]
+/// to persist everything a child writes during long running tests,
+/// independently of the buffer used by `expect` calls.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use expectrl::{spawn, session::capture, stream::capture::RotatingFile};
+///
+/// let file = RotatingFile::create("/tmp/capture.log")
+///     .unwrap()
+///     .max_size(10 * 1024 * 1024)
+///     .max_age(Duration::from_secs(60 * 60));
+///
+/// let p = capture(spawn("cat").unwrap(), file).unwrap();
+/// println!("the output is saved to {}", p.capture_path().display());
+/// ```
+///
+/// [`session::capture`]: crate::session::capture
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    created: Instant,
+    max_size: Option<u64>,
+    max_age: Option<Duration>,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    /// A default number of rotated files which are kept.
+    pub const MAX_FILES: usize = 5;
+
+    /// Creates a file, an existing one is truncated.
+    ///
+    /// By default the file is never rotated.
+    pub fn create(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = File::create(&path)?;
+
+        Ok(Self {
+            path,
+            file,
+            size: 0,
+            created: Instant::now(),
+            max_size: None,
+            max_age: None,
+            max_files: Self::MAX_FILES,
+        })
+    }
+
+    /// Rotate the file once it's bigger than `size` bytes.
+    pub fn max_size(mut self, size: u64) -> Self {
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Rotate the file once it's older than `age`.
+    pub fn max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    /// Set a number of rotated files which are kept.
+    ///
+    /// If it's 0 the file is truncated on rotation.
+    pub fn max_files(mut self, count: usize) -> Self {
+        self.max_files = count;
+        self
+    }
+
+    /// Returns a path of a current file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a path of a rotated file, 1 is the most recent one.
+    pub fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    /// Moves a current file aside and starts a new one.
+    pub fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files > 0 {
+            for index in (1..self.max_files).rev() {
+                let path = self.rotated_path(index);
+                if path.exists() {
+                    fs::rename(path, self.rotated_path(index + 1))?;
+                }
+            }
+
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        self.created = Instant::now();
+
+        Ok(())
+    }
+
+    fn is_rotation_needed(&self) -> bool {
+        if self.size == 0 {
+            return false;
+        }
+
+        let is_big = self.max_size.is_some_and(|size| self.size >= size);
+        let is_old = self
+            .max_age
+            .is_some_and(|age| self.created.elapsed() >= age);
+
+        is_big || is_old
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.is_rotation_needed() {
+            self.rotate()?;
+        }
+
+        let n = self.file.write(buf)?;
+        self.size += n as u64;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_file() {
+        let dir = std::env::temp_dir().join(format!("expectrl-capture-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut file = RotatingFile::create(dir.join("capture.log"))
+            .unwrap()
+            .max_size(4)
+            .max_files(2);
+
+        for chunk in ["1111", "2222", "3333", "44"] {
+            file.write_all(chunk.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        assert_eq!(fs::read(file.path()).unwrap(), b"44");
+        assert_eq!(fs::read(file.rotated_path(1)).unwrap(), b"3333");
+        assert_eq!(fs::read(file.rotated_path(2)).unwrap(), b"2222");
+        assert!(!file.rotated_path(3).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotating_file_by_age() {
+        let dir = std::env::temp_dir().join(format!("expectrl-capture-age-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut file = RotatingFile::create(dir.join("capture.log"))
+            .unwrap()
+            .max_age(Duration::from_millis(50));
+
+        file.write_all(b"1").unwrap();
+        file.write_all(b"2").unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        file.write_all(b"3").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(fs::read(file.path()).unwrap(), b"3");
+        assert_eq!(fs::read(file.rotated_path(1)).unwrap(), b"12");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
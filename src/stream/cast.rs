@@ -0,0 +1,307 @@
+//! This module contains a [CastStream]
+//! which records a stream in an [asciinema] v2 cast format.
+//!
+//! [asciinema]: https://docs.asciinema.org/manual/asciicast/v2/
+
+use std::{
+    fmt::Write as _,
+    io::{Read, Result, Write},
+    ops::{Deref, DerefMut},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::process::NonBlocking;
+
+/// CastStream a IO stream wrapper,
+/// which writes everything read from a stream into a writer as an [asciinema] v2 cast.
+///
+/// Each read becomes an output event timed from the creation of the stream,
+/// so a recording can be replayed by `asciinema play` the way it was seen.
+/// Writes are recorded as input events if it's turned on by [`CastStream::record_input`].
+///
+/// A header is written right away.
+/// Errors of the writer are ignored so they don't affect a session.
+///
+/// [asciinema]: https://docs.asciinema.org/manual/asciicast/v2/
+#[derive(Debug)]
+pub struct CastStream<S, W> {
+    stream: S,
+    writer: W,
+    start: Instant,
+    record_input: bool,
+    /// A tail of an output which is not a complete UTF-8 sequence yet.
+    pending: Vec<u8>,
+}
+
+impl<S, W: Write> CastStream<S, W> {
+    /// Creates a new instance of the stream for a terminal of a given size.
+    pub fn new(stream: S, mut writer: W, cols: u16, rows: u16) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+
+        let _ = writeln!(
+            writer,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}}}",
+            cols, rows, timestamp
+        );
+        let _ = writer.flush();
+
+        Self {
+            stream,
+            writer,
+            start: Instant::now(),
+            record_input: false,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Turns on or off recording of written bytes as input events.
+    pub fn record_input(mut self, on: bool) -> Self {
+        self.record_input = on;
+        self
+    }
+
+    fn output(&mut self, buf: &[u8]) {
+        self.pending.extend_from_slice(buf);
+        let text = take_utf8(&mut self.pending);
+        self.event("o", &text);
+    }
+
+    fn input(&mut self, buf: &[u8]) {
+        if self.record_input {
+            self.event("i", &String::from_utf8_lossy(buf));
+        }
+    }
+
+    fn event(&mut self, kind: &str, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        let time = self.start.elapsed().as_secs_f64();
+        let _ = writeln!(
+            self.writer,
+            "[{:.6}, \"{}\", {}]",
+            time,
+            kind,
+            json_string(text)
+        );
+        let _ = self.writer.flush();
+    }
+}
+
+impl<S, W> CastStream<S, W> {
+    /// Returns an inner stream and writer.
+    pub fn into_inner(self) -> (S, W) {
+        (self.stream, self.writer)
+    }
+
+    /// Returns a reference to a writer.
+    pub fn get_writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mut reference to a writer.
+    pub fn get_writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+impl<S: Write, W: Write> Write for CastStream<S, W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.stream.write(buf)?;
+        self.input(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl<S: Read, W: Write> Read for CastStream<S, W> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.stream.read(buf)?;
+        self.output(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<S, W> NonBlocking for CastStream<S, W>
+where
+    S: NonBlocking,
+{
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        self.stream.set_blocking(on)
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        self.stream.readiness_fd()
+    }
+}
+
+impl<S, W> Deref for CastStream<S, W> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl<S, W> DerefMut for CastStream<S, W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncWrite + Unpin, W: Write + Unpin> AsyncWrite for CastStream<S, W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        let result = Pin::new(&mut self.stream).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.input(&buf[..*n]);
+        }
+
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncRead + Unpin, W: Write + Unpin> AsyncRead for CastStream<S, W> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let result = Pin::new(&mut self.stream).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.output(&buf[..*n]);
+        }
+
+        result
+    }
+}
+
+/// Takes a text out of the buffer, leaving an incomplete UTF-8 sequence at its end.
+///
+/// Invalid sequences are replaced with [`char::REPLACEMENT_CHARACTER`].
+fn take_utf8(buf: &mut Vec<u8>) -> String {
+    let mut text = String::new();
+    let mut rest = &buf[..];
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                text.push_str(valid);
+                rest = &[];
+                break;
+            }
+            Err(err) => {
+                let (valid, invalid) = rest.split_at(err.valid_up_to());
+                text.push_str(&String::from_utf8_lossy(valid));
+                match err.error_len() {
+                    Some(n) => {
+                        text.push(char::REPLACEMENT_CHARACTER);
+                        rest = &invalid[n..];
+                    }
+                    None => {
+                        rest = invalid;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let taken = buf.len() - rest.len();
+    let _ = buf.drain(..taken);
+
+    text
+}
+
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn test_cast() {
+        let output = "Hello \u{1b}[1m\"World\"\u{1b}[0m\r\n—".as_bytes().to_vec();
+        let stream = Cursor::new(output);
+        let mut stream = CastStream::new(stream, Vec::new(), 80, 24).record_input(true);
+
+        let mut buf = [0; 24];
+        stream.read_exact(&mut buf).unwrap();
+        let mut rest = Vec::new();
+        let _ = stream.read_to_end(&mut rest).unwrap();
+        stream.write_all(b"q").unwrap();
+
+        let (_, cast) = stream.into_inner();
+        let cast = String::from_utf8(cast).unwrap();
+        let lines = cast.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(
+            lines[0].starts_with("{\"version\": 2, \"width\": 80, \"height\": 24, \"timestamp\": ")
+        );
+
+        // a split multibyte character is written as a whole in the next event.
+        assert!(lines[1].ends_with(", \"o\", \"Hello \\u001b[1m\\\"World\\\"\\u001b[0m\\r\\n\"]"));
+        assert!(lines[2].ends_with(", \"o\", \"—\"]"));
+        assert!(lines[3].ends_with(", \"i\", \"q\"]"));
+    }
+
+    #[test]
+    fn test_take_utf8() {
+        let mut buf = b"a\xffb\xe2\x80".to_vec();
+        assert_eq!(take_utf8(&mut buf), "a\u{fffd}b");
+        assert_eq!(buf, b"\xe2\x80");
+
+        buf.push(b'\x94');
+        assert_eq!(take_utf8(&mut buf), "—");
+        assert!(buf.is_empty());
+    }
+}
@@ -0,0 +1,379 @@
+//! This module contains a [ChaosStream]
+//! which injects failures into a stream to test how a code copes with them.
+
+use std::{
+    io::{self, Read, Result, Write},
+    ops::{Deref, DerefMut},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::process::NonBlocking;
+
+/// Chaos is a schedule of failures injected by a [`ChaosStream`].
+///
+/// Failures are chosen by a pseudo random generator,
+/// so the same seed gives the same sequence of failures for the same sequence of calls.
+///
+/// # Example
+///
+/// ```
+/// use expectrl::stream::chaos::Chaos;
+///
+/// let chaos = Chaos::new(42)
+///     .short_reads(0.5)
+///     .would_block(0.2, 5)
+///     .partial_writes(0.3)
+///     .eof_after(1024);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chaos {
+    seed: u64,
+    short_reads: f64,
+    would_block: f64,
+    would_block_storm: usize,
+    partial_writes: f64,
+    eof_after: Option<usize>,
+}
+
+impl Chaos {
+    /// Creates a schedule which doesn't inject anything yet.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            short_reads: 0.0,
+            would_block: 0.0,
+            would_block_storm: 1,
+            partial_writes: 0.0,
+            eof_after: None,
+        }
+    }
+
+    /// Set a probability of a read returning fewer bytes than it could.
+    pub fn short_reads(mut self, probability: f64) -> Self {
+        self.short_reads = probability;
+        self
+    }
+
+    /// Set a probability of a storm of [`io::ErrorKind::WouldBlock`] errors,
+    /// a storm is 1 to `max_storm` read calls long.
+    ///
+    /// The errors are injected only while a stream is in a non-blocking mode,
+    /// an async stream returns [`Poll::Pending`](std::task::Poll::Pending) and wakes itself instead.
+    pub fn would_block(mut self, probability: f64, max_storm: usize) -> Self {
+        self.would_block = probability;
+        self.would_block_storm = max_storm.max(1);
+        self
+    }
+
+    /// Set a probability of a write taking only a part of a buffer.
+    pub fn partial_writes(mut self, probability: f64) -> Self {
+        self.partial_writes = probability;
+        self
+    }
+
+    /// Make a stream report EOF after a given amount of bytes was read.
+    pub fn eof_after(mut self, bytes: usize) -> Self {
+        self.eof_after = Some(bytes);
+        self
+    }
+}
+
+/// ChaosStream is a IO stream wrapper which injects short reads, [`io::ErrorKind::WouldBlock`] storms,
+/// partial writes and an early EOF according to a [`Chaos`] schedule.
+#[derive(Debug)]
+pub struct ChaosStream<S> {
+    stream: S,
+    chaos: Chaos,
+    rng: Rng,
+    blocking: bool,
+    would_block_left: usize,
+    read: usize,
+}
+
+impl<S> ChaosStream<S> {
+    /// Creates a new instance of the stream.
+    pub fn new(stream: S, chaos: Chaos) -> Self {
+        Self {
+            stream,
+            rng: Rng::new(chaos.seed),
+            chaos,
+            blocking: true,
+            would_block_left: 0,
+            read: 0,
+        }
+    }
+
+    /// Returns a schedule of failures.
+    pub fn get_chaos(&self) -> &Chaos {
+        &self.chaos
+    }
+
+    /// Returns an inner stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Checks whether a read must fail with [`io::ErrorKind::WouldBlock`].
+    fn would_block(&mut self) -> bool {
+        if self.would_block_left == 0 && self.rng.chance(self.chaos.would_block) {
+            self.would_block_left = self.rng.range(self.chaos.would_block_storm);
+        }
+
+        if self.would_block_left > 0 {
+            self.would_block_left -= 1;
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns a length of a buffer to read into, it's 0 if EOF must be reported.
+    fn read_len(&mut self, len: usize) -> usize {
+        let len = match self.chaos.eof_after {
+            Some(limit) => len.min(limit.saturating_sub(self.read)),
+            None => len,
+        };
+
+        if len > 1 && self.rng.chance(self.chaos.short_reads) {
+            self.rng.range(len)
+        } else {
+            len
+        }
+    }
+
+    fn write_len(&mut self, len: usize) -> usize {
+        if len > 1 && self.rng.chance(self.chaos.partial_writes) {
+            self.rng.range(len - 1)
+        } else {
+            len
+        }
+    }
+
+    fn is_eof(&self) -> bool {
+        matches!(self.chaos.eof_after, Some(limit) if self.read >= limit)
+    }
+}
+
+impl<S: Write> Write for ChaosStream<S> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.write_len(buf.len());
+        self.stream.write(&buf[..n])
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl<S: Read> Read for ChaosStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if self.is_eof() {
+            return Ok(0);
+        }
+
+        if !self.blocking && self.would_block() {
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "an injected would block error",
+            ));
+        }
+
+        let n = self.read_len(buf.len());
+        let n = self.stream.read(&mut buf[..n])?;
+        self.read += n;
+
+        Ok(n)
+    }
+}
+
+impl<S> NonBlocking for ChaosStream<S>
+where
+    S: NonBlocking,
+{
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        self.stream.set_blocking(on)?;
+        self.blocking = on;
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        self.stream.readiness_fd()
+    }
+}
+
+impl<S> Deref for ChaosStream<S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl<S> DerefMut for ChaosStream<S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncWrite + Unpin> AsyncWrite for ChaosStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        let n = self.write_len(buf.len());
+        Pin::new(&mut self.stream).poll_write(cx, &buf[..n])
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncRead + Unpin> AsyncRead for ChaosStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        if self.is_eof() {
+            return Poll::Ready(Ok(0));
+        }
+
+        if self.would_block() {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        let n = self.read_len(buf.len());
+        let result = Pin::new(&mut self.stream).poll_read(cx, &mut buf[..n]);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.read += *n;
+        }
+
+        result
+    }
+}
+
+/// A small splitmix64 generator, it's enough to pick failures.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns true with a given probability.
+    fn chance(&mut self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            return false;
+        }
+
+        let value = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        value < probability
+    }
+
+    /// Returns a number in `1..=max`.
+    fn range(&mut self, max: usize) -> usize {
+        (self.next_u64() % max as u64) as usize + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    struct NonBlockingCursor(Cursor<Vec<u8>>);
+
+    impl Read for NonBlockingCursor {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl NonBlocking for NonBlockingCursor {
+        fn set_blocking(&mut self, _: bool) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn read_all(stream: &mut ChaosStream<NonBlockingCursor>) -> (Vec<u8>, usize) {
+        let mut data = Vec::new();
+        let mut would_block = 0;
+        let mut buf = [0; 16];
+        loop {
+            match stream.read(&mut buf) {
+                Ok(0) => return (data, would_block),
+                Ok(n) => data.extend(&buf[..n]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => would_block += 1,
+                Err(err) => panic!("{}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_chaos_read() {
+        let input = (0..200).collect::<Vec<u8>>();
+        let chaos = Chaos::new(7).short_reads(0.5).would_block(0.3, 4);
+
+        let mut stream = ChaosStream::new(NonBlockingCursor(Cursor::new(input.clone())), chaos);
+        stream.set_blocking(false).unwrap();
+        let (data, would_block) = read_all(&mut stream);
+        assert_eq!(data, input);
+        assert!(would_block > 0);
+
+        // the same seed gives the same schedule
+        let chaos = stream.get_chaos().clone();
+        let mut stream = ChaosStream::new(NonBlockingCursor(Cursor::new(input.clone())), chaos);
+        stream.set_blocking(false).unwrap();
+        assert_eq!(read_all(&mut stream), (input, would_block));
+    }
+
+    #[test]
+    fn test_chaos_eof() {
+        let chaos = Chaos::new(1).would_block(1.0, 1).eof_after(10);
+        let mut stream = ChaosStream::new(NonBlockingCursor(Cursor::new(vec![1; 100])), chaos);
+
+        // blocking reads never get would block errors
+        let (data, would_block) = read_all(&mut stream);
+        assert_eq!(data, vec![1; 10]);
+        assert_eq!(would_block, 0);
+    }
+
+    #[test]
+    fn test_chaos_partial_write() {
+        let chaos = Chaos::new(3).partial_writes(1.0);
+        let mut stream = ChaosStream::new(Vec::new(), chaos);
+
+        let n = stream.write(b"Hello World").unwrap();
+        assert!(n > 0 && n < 11);
+
+        stream.write_all(b"Hello World").unwrap();
+        assert_eq!(&stream.into_inner()[n..], b"Hello World");
+    }
+}
@@ -0,0 +1,272 @@
+//! This module contains a [DynStream]
+//! which hides a type of a stream, so sessions with differently wrapped streams have the same type.
+
+use std::{fmt, io::Write};
+
+#[cfg(not(feature = "async"))]
+use std::io::{Read, Result};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    io::Result,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(not(feature = "async"))]
+use crate::process::NonBlocking;
+
+use super::log::{hide_secrets, log};
+
+/// IoStream is a stream which can be used by [`DynStream`].
+///
+/// It's implemented for any suitable stream.
+#[cfg(not(feature = "async"))]
+pub trait IoStream: Read + Write + NonBlocking + Send {}
+
+#[cfg(not(feature = "async"))]
+impl<S> IoStream for S where S: Read + Write + NonBlocking + Send {}
+
+/// IoStream is a stream which can be used by [`DynStream`].
+///
+/// It's implemented for any suitable stream.
+#[cfg(feature = "async")]
+pub trait IoStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+#[cfg(feature = "async")]
+impl<S> IoStream for S where S: AsyncRead + AsyncWrite + Unpin + Send {}
+
+/// DynStream a IO stream wrapper, which uses a dynamic dispatch.
+///
+/// Unlike [`LogStream`] a log can be turned on and off at runtime
+/// without changing a type of a session,
+/// which makes it possible to keep sessions in a collection.
+///
+/// [`LogStream`]: crate::stream::log::LogStream
+pub struct DynStream {
+    stream: Box<dyn IoStream>,
+    logger: Option<Box<dyn Write + Send>>,
+    name: Option<String>,
+}
+
+impl DynStream {
+    /// Creates a new instance of the stream.
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: IoStream + 'static,
+    {
+        Self {
+            stream: Box::new(stream),
+            logger: None,
+            name: None,
+        }
+    }
+
+    /// Turns a log on.
+    ///
+    /// Each write/read operation is written into the `logger` the same way [`LogStream`] does.
+    /// A previous logger is replaced.
+    ///
+    /// [`LogStream`]: crate::stream::log::LogStream
+    pub fn enable_log<W>(&mut self, logger: W)
+    where
+        W: Write + Send + 'static,
+    {
+        self.logger = Some(Box::new(logger));
+    }
+
+    /// Turns a log off and returns the logger which was used.
+    pub fn disable_log(&mut self) -> Option<Box<dyn Write + Send>> {
+        self.logger.take()
+    }
+
+    /// Checks whether a log is on.
+    pub fn is_log_enabled(&self) -> bool {
+        self.logger.is_some()
+    }
+
+    /// Set a name which prefixes each log line.
+    pub fn set_name<N>(&mut self, name: N)
+    where
+        N: Into<String>,
+    {
+        self.name = Some(name.into());
+    }
+
+    /// Get a reference to an inner stream.
+    pub fn get_ref(&self) -> &dyn IoStream {
+        self.stream.as_ref()
+    }
+
+    /// Get a mut reference to an inner stream.
+    pub fn get_mut(&mut self) -> &mut dyn IoStream {
+        self.stream.as_mut()
+    }
+
+    pub(crate) fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name;
+        self
+    }
+
+    fn log_write(&mut self, buf: &[u8]) {
+        if let Some(logger) = &mut self.logger {
+            log(logger, self.name.as_deref(), "write", &hide_secrets(buf));
+        }
+    }
+
+    fn log_read(&mut self, buf: &[u8]) {
+        if let Some(logger) = &mut self.logger {
+            log(logger, self.name.as_deref(), "read", &hide_secrets(buf));
+        }
+    }
+}
+
+impl fmt::Debug for DynStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynStream")
+            .field("log", &self.logger.is_some())
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl Write for DynStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.stream.write(buf)?;
+        self.log_write(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl Read for DynStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.stream.read(buf)?;
+        self.log_read(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl NonBlocking for DynStream {
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        self.stream.set_blocking(on)
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        self.stream.readiness_fd()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncWrite for DynStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        let result = Pin::new(&mut self.stream).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.log_write(&buf[..*n]);
+        }
+
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncRead for DynStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let result = Pin::new(&mut self.stream).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.log_read(&buf[..*n]);
+        }
+
+        result
+    }
+}
+
+#[cfg(all(test, not(feature = "async")))]
+mod tests {
+    use super::*;
+
+    use std::{
+        io::Cursor,
+        sync::{Arc, Mutex},
+    };
+
+    struct Buffer(Cursor<Vec<u8>>);
+
+    impl Read for Buffer {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl NonBlocking for Buffer {
+        fn set_blocking(&mut self, _: bool) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_toggle_log() {
+        let logger = Shared::default();
+
+        let mut stream = DynStream::new(Buffer(Cursor::new(Vec::new())));
+        assert!(!stream.is_log_enabled());
+        stream.write_all(b"ls").unwrap();
+
+        stream.enable_log(logger.clone());
+        assert!(stream.is_log_enabled());
+        stream.write_all(b"pwd").unwrap();
+
+        assert!(stream.disable_log().is_some());
+        stream.write_all(b"exit").unwrap();
+
+        assert_eq!(logger.0.lock().unwrap().as_slice(), b"write: \"pwd\"\n");
+    }
+}
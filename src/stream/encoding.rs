@@ -0,0 +1,302 @@
+//! This module contains a [EncodingStream]
+//! which transcodes a stream of a legacy charset into UTF-8 and back.
+
+use std::io::{self, Read, Result, Write};
+
+use encoding_rs::{CoderResult, Decoder, Encoding};
+
+#[cfg(feature = "async")]
+use futures_lite::{ready, AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::process::NonBlocking;
+
+/// A size of a chunk which is read from an inner stream at once.
+const READ_CHUNK_SIZE: usize = 1024;
+
+/// EncodingStream a IO stream wrapper,
+/// which decodes everything read from a stream into UTF-8
+/// and encodes everything written into the stream's charset.
+///
+/// It lets a session of a system which speaks Latin-1, Shift-JIS, GBK etc.
+/// be matched with regular Rust strings.
+///
+/// A multi-byte character split between reads is decoded once it's complete.
+/// Malformed sequences are replaced by `U+FFFD`.
+/// Characters which can't be represented in the charset are written as HTML numeric references,
+/// see [`Encoding::encode`].
+pub struct EncodingStream<S> {
+    stream: S,
+    encoding: &'static Encoding,
+    decoder: Decoder,
+    /// Decoded bytes which were not read yet.
+    decoded: Vec<u8>,
+    /// A tail of an incomplete UTF-8 character which was written.
+    incomplete: Vec<u8>,
+    /// Encoded bytes which were not written to the stream yet.
+    #[cfg(feature = "async")]
+    encoded: Vec<u8>,
+}
+
+impl<S> EncodingStream<S> {
+    /// Creates a new instance of the stream.
+    pub fn new(stream: S, encoding: &'static Encoding) -> Self {
+        Self {
+            stream,
+            encoding,
+            decoder: encoding.new_decoder_without_bom_handling(),
+            decoded: Vec::new(),
+            incomplete: Vec::new(),
+            #[cfg(feature = "async")]
+            encoded: Vec::new(),
+        }
+    }
+
+    /// Returns a charset of the stream.
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+
+    /// Returns a reference to an inner stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mut reference to an inner stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Returns an inner stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Decodes a chunk read from the stream.
+    ///
+    /// An empty chunk means EOF, so everything what's left in a decoder is flushed.
+    fn decode(&mut self, chunk: &[u8]) {
+        let last = chunk.is_empty();
+        let mut src = chunk;
+        loop {
+            let max = self
+                .decoder
+                .max_utf8_buffer_length(src.len())
+                .unwrap_or(src.len() * 3 + 16);
+            let start = self.decoded.len();
+            self.decoded.resize(start + max, 0);
+
+            let (result, read, written, _) =
+                self.decoder
+                    .decode_to_utf8(src, &mut self.decoded[start..], last);
+            self.decoded.truncate(start + written);
+            src = &src[read..];
+
+            if result == CoderResult::InputEmpty {
+                break;
+            }
+        }
+
+        if last {
+            self.decoder = self.encoding.new_decoder_without_bom_handling();
+        }
+    }
+
+    /// Returns decoded bytes which were not read yet.
+    fn read_decoded(&mut self, buf: &mut [u8]) -> usize {
+        let n = std::cmp::min(buf.len(), self.decoded.len());
+        buf[..n].copy_from_slice(&self.decoded[..n]);
+        let _ = self.decoded.drain(..n);
+        n
+    }
+
+    /// Encodes a UTF-8 buffer.
+    ///
+    /// An incomplete character at the end of the buffer is kept until a next write.
+    fn encode(&mut self, buf: &[u8]) -> Result<Vec<u8>> {
+        self.incomplete.extend_from_slice(buf);
+
+        let valid = match std::str::from_utf8(&self.incomplete) {
+            Ok(text) => text.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => {
+                self.incomplete.clear();
+                return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+            }
+        };
+
+        let tail = self.incomplete.split_off(valid);
+        let text = std::str::from_utf8(&self.incomplete).expect("it's validated");
+        let (bytes, _, _) = self.encoding.encode(text);
+        let bytes = bytes.into_owned();
+        self.incomplete = tail;
+
+        Ok(bytes)
+    }
+}
+
+impl<S> std::fmt::Debug for EncodingStream<S>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncodingStream")
+            .field("stream", &self.stream)
+            .field("encoding", &self.encoding.name())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: Write> Write for EncodingStream<S> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let bytes = self.encode(buf)?;
+        self.stream.write_all(&bytes)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl<S: Read> Read for EncodingStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return self.stream.read(buf);
+        }
+
+        let mut chunk = [0; READ_CHUNK_SIZE];
+        while self.decoded.is_empty() {
+            let n = self.stream.read(&mut chunk)?;
+            self.decode(&chunk[..n]);
+
+            if n == 0 {
+                break;
+            }
+        }
+
+        Ok(self.read_decoded(buf))
+    }
+}
+
+impl<S: NonBlocking> NonBlocking for EncodingStream<S> {
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        self.stream.set_blocking(on)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncWrite + Unpin> EncodingStream<S> {
+    /// Writes encoded bytes which are left from previous writes.
+    fn poll_write_encoded(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        while !self.encoded.is_empty() {
+            let n = ready!(Pin::new(&mut self.stream).poll_write(cx, &self.encoded))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+
+            let _ = self.encoded.drain(..n);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncWrite + Unpin> AsyncWrite for EncodingStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        ready!(self.poll_write_encoded(cx))?;
+
+        let bytes = self.encode(buf)?;
+        self.encoded = bytes;
+
+        // The bytes are kept if the stream is not ready, they are written by next calls.
+        if let Poll::Ready(Err(err)) = self.poll_write_encoded(cx) {
+            return Poll::Ready(Err(err));
+        }
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.poll_write_encoded(cx))?;
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.poll_write_encoded(cx))?;
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncRead + Unpin> AsyncRead for EncodingStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Pin::new(&mut self.stream).poll_read(cx, buf);
+        }
+
+        let mut chunk = [0; READ_CHUNK_SIZE];
+        while self.decoded.is_empty() {
+            let n = ready!(Pin::new(&mut self.stream).poll_read(cx, &mut chunk))?;
+            self.decode(&chunk[..n]);
+
+            if n == 0 {
+                break;
+            }
+        }
+
+        Poll::Ready(Ok(self.read_decoded(buf)))
+    }
+}
+
+#[cfg(all(test, not(feature = "async")))]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn test_decode() {
+        // "こんにちは" in Shift-JIS, read by 1 byte to split characters.
+        let input = b"\x82\xb1\x82\xf1\x82\xc9\x82\xbf\x82\xcd".to_vec();
+        let mut stream = EncodingStream::new(Cursor::new(input), encoding_rs::SHIFT_JIS);
+
+        let mut output = Vec::new();
+        let mut buf = [0; 1];
+        loop {
+            let n = stream.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+
+            output.extend_from_slice(&buf[..n]);
+        }
+
+        assert_eq!(String::from_utf8(output).unwrap(), "こんにちは");
+    }
+
+    #[test]
+    fn test_encode() {
+        let mut stream = EncodingStream::new(Cursor::new(Vec::new()), encoding_rs::WINDOWS_1252);
+
+        let text = "café".as_bytes();
+        // split "é" between writes
+        stream.write_all(&text[..4]).unwrap();
+        stream.write_all(&text[4..]).unwrap();
+
+        assert_eq!(stream.into_inner().into_inner(), b"caf\xe9");
+    }
+}
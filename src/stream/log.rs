@@ -2,8 +2,11 @@
 //! which can wrap other streams in order to log a read/write operations.
 
 use std::{
+    borrow::Cow,
+    fmt,
     io::{self, Read, Result, Write},
     ops::{Deref, DerefMut},
+    sync::Mutex,
 };
 
 #[cfg(feature = "async")]
@@ -14,30 +17,192 @@ use std::{
     task::{Context, Poll},
 };
 
-use crate::process::NonBlocking;
+use crate::{process::NonBlocking, Error};
+
+const REDACTED: &[u8] = b"******";
+
+/// Secrets which are being sent at the moment, they're hidden by all log streams.
+static SECRETS: Mutex<Vec<Vec<u8>>> = Mutex::new(Vec::new());
+
+/// SecretGuard hides a secret from all logs while it's alive.
+///
+/// It's used by helpers which send passwords on their own,
+/// as they can't know how a session is logged.
+#[derive(Debug)]
+pub(crate) struct SecretGuard {
+    secret: Vec<u8>,
+}
+
+impl SecretGuard {
+    pub(crate) fn new(secret: impl Into<Vec<u8>>) -> Self {
+        let secret = secret.into();
+        if let Ok(mut secrets) = SECRETS.lock() {
+            secrets.push(secret.clone());
+        }
+
+        Self { secret }
+    }
+}
+
+impl Drop for SecretGuard {
+    fn drop(&mut self) {
+        if let Ok(mut secrets) = SECRETS.lock() {
+            if let Some(pos) = secrets.iter().position(|s| s == &self.secret) {
+                let _ = secrets.swap_remove(pos);
+            }
+        }
+    }
+}
+
+/// Replaces secrets guarded by [`SecretGuard`] with `******`.
+pub(crate) fn hide_secrets(buf: &[u8]) -> Cow<'_, [u8]> {
+    let secrets = match SECRETS.lock() {
+        Ok(secrets) => secrets,
+        Err(_) => return Cow::Borrowed(buf),
+    };
+
+    let mut buf = Cow::Borrowed(buf);
+    for secret in secrets.iter().filter(|s| !s.is_empty()) {
+        if let Some(hidden) = replace_all(&buf, secret, REDACTED) {
+            buf = Cow::Owned(hidden);
+        }
+    }
+
+    buf
+}
+
+fn replace_all(buf: &[u8], from: &[u8], to: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut rest = buf;
+    while let Some(pos) = rest.windows(from.len()).position(|w| w == from) {
+        out.extend_from_slice(&rest[..pos]);
+        out.extend_from_slice(to);
+        rest = &rest[pos + from.len()..];
+    }
+
+    if rest.len() == buf.len() {
+        return None;
+    }
+
+    out.extend_from_slice(rest);
+    Some(out)
+}
 
 /// LogStream a IO stream wrapper,
 /// which logs each write/read operation.
+///
+/// Secrets can be hidden from a log by [`LogStream::redact`] and [`LogStream::set_redaction_filter`],
+/// they're applied only to logged bytes so a stream gets the real ones.
 #[derive(Debug)]
 pub struct LogStream<S, W> {
     stream: S,
     logger: W,
+    redaction: Redaction,
+    name: Option<String>,
 }
 
 impl<S, W> LogStream<S, W> {
     /// Creates a new instance of the stream.
     pub fn new(stream: S, logger: W) -> Self {
-        Self { stream, logger }
+        Self {
+            stream,
+            logger,
+            redaction: Redaction::default(),
+            name: None,
+        }
+    }
+
+    /// Set a name which prefixes each log line.
+    pub fn set_name<N>(&mut self, name: N)
+    where
+        N: Into<String>,
+    {
+        self.name = Some(name.into());
+    }
+
+    /// Get a reference to a logger.
+    pub fn get_logger(&self) -> &W {
+        &self.logger
+    }
+
+    pub(crate) fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Replace all matches of a regex with `******` in a log.
+    ///
+    /// Notice that a secret which is split across several read operations may not be matched.
+    pub fn redact(&mut self, regex: &str) -> std::result::Result<(), Error> {
+        let regex = regex::bytes::Regex::new(regex).map_err(|_| Error::RegexParsing)?;
+        self.redaction.regexes.push(regex);
+        Ok(())
+    }
+
+    /// Set a filter which is applied to bytes before they're logged.
+    ///
+    /// It's called after regexes set by [`LogStream::redact`] are applied.
+    pub fn set_redaction_filter<F>(&mut self, filter: F)
+    where
+        F: Fn(&[u8]) -> Vec<u8> + Send + Sync + 'static,
+    {
+        self.redaction.filter = Some(Box::new(filter));
+    }
+}
+
+impl<S> LogStream<S, LogWriters> {
+    /// Adds one more writer to the log.
+    pub fn add_logger<W>(&mut self, logger: W)
+    where
+        W: Write + Send + 'static,
+    {
+        self.logger.push(logger);
     }
 }
 
 impl<S, W: Write> LogStream<S, W> {
     fn log_write(&mut self, buf: &[u8]) {
-        log(&mut self.logger, "write", buf);
+        let buf = self.redaction.apply(buf);
+        log(&mut self.logger, self.name.as_deref(), "write", &buf);
     }
 
     fn log_read(&mut self, buf: &[u8]) {
-        log(&mut self.logger, "read", buf);
+        let buf = self.redaction.apply(buf);
+        log(&mut self.logger, self.name.as_deref(), "read", &buf);
+    }
+}
+
+type RedactionFilter = Box<dyn Fn(&[u8]) -> Vec<u8> + Send + Sync>;
+
+#[derive(Default)]
+struct Redaction {
+    regexes: Vec<regex::bytes::Regex>,
+    filter: Option<RedactionFilter>,
+}
+
+impl Redaction {
+    fn apply<'a>(&self, buf: &'a [u8]) -> Cow<'a, [u8]> {
+        let mut buf = hide_secrets(buf);
+        for regex in &self.regexes {
+            if let Cow::Owned(redacted) = regex.replace_all(&buf, REDACTED) {
+                buf = Cow::Owned(redacted);
+            }
+        }
+
+        if let Some(filter) = &self.filter {
+            buf = Cow::Owned(filter(&buf));
+        }
+
+        buf
+    }
+}
+
+impl fmt::Debug for Redaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Redaction")
+            .field("regexes", &self.regexes)
+            .field("filter", &self.filter.is_some())
+            .finish()
     }
 }
 
@@ -89,6 +254,11 @@ where
     fn set_blocking(&mut self, on: bool) -> Result<()> {
         self.stream.set_blocking(on)
     }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        self.stream.readiness_fd()
+    }
 }
 
 impl<S, W> Deref for LogStream<S, W> {
@@ -149,9 +319,183 @@ impl<S: AsyncRead + Unpin, W: Write + Unpin> AsyncRead for LogStream<S, W> {
     }
 }
 
-fn log(mut writer: impl Write, target: &str, data: &[u8]) {
+/// LogWriters is a writer which duplicates everything into a list of writers.
+///
+/// It lets a [`LogStream`] write a log into several sinks at once, e.g. a file and STDERR.
+///
+/// ```
+/// use expectrl::{session::log, spawn, stream::log::LogWriters};
+///
+/// let loggers = LogWriters::new()
+///     .with(std::io::stderr())
+///     .with(Vec::new());
+///
+/// let p = spawn("cat").unwrap();
+/// let p = log(p, loggers);
+/// ```
+#[derive(Default)]
+pub struct LogWriters {
+    writers: Vec<Box<dyn Write + Send>>,
+}
+
+impl LogWriters {
+    /// Creates an empty list of writers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a writer to the list.
+    pub fn with<W>(mut self, writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        self.push(writer);
+        self
+    }
+
+    /// Adds a writer to the list.
+    pub fn push<W>(&mut self, writer: W)
+    where
+        W: Write + Send + 'static,
+    {
+        self.writers.push(Box::new(writer));
+    }
+
+    /// Returns an amount of writers.
+    pub fn len(&self) -> usize {
+        self.writers.len()
+    }
+
+    /// Checks whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.writers.is_empty()
+    }
+}
+
+impl Write for LogWriters {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        // A failed writer must not stop others from getting the log,
+        // so the first error is returned only after all of them were tried.
+        let mut result = Ok(());
+        for writer in &mut self.writers {
+            if let Err(err) = writer.write_all(buf) {
+                result = result.and(Err(err));
+            }
+        }
+
+        result.map(|_| buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        for writer in &mut self.writers {
+            if let Err(err) = writer.flush() {
+                result = result.and(Err(err));
+            }
+        }
+
+        result
+    }
+}
+
+impl fmt::Debug for LogWriters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogWriters")
+            .field("writers", &self.writers.len())
+            .finish()
+    }
+}
+
+pub(crate) fn log(mut writer: impl Write, name: Option<&str>, target: &str, data: &[u8]) {
+    if let Some(name) = name {
+        let _ = write!(writer, "[{}] ", name);
+    }
+
     let _ = match std::str::from_utf8(data) {
         Ok(data) => writeln!(writer, "{}: {:?}", target, data),
         Err(..) => writeln!(writer, "{}:(bytes): {:?}", target, data),
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn test_redact() {
+        let mut stream = LogStream::new(Cursor::new(Vec::<u8>::new()), Vec::new());
+        stream.redact("pass\\w+").unwrap();
+        stream.set_redaction_filter(|buf| buf.to_ascii_uppercase());
+
+        stream.write_all(b"login: password123").unwrap();
+
+        assert_eq!(stream.stream.get_ref(), b"login: password123");
+        assert_eq!(stream.logger, b"write: \"LOGIN: ******\"\n");
+    }
+
+    #[test]
+    fn test_name() {
+        let mut stream = LogStream::new(Cursor::new(Vec::<u8>::new()), Vec::new());
+        stream.set_name("node-1");
+
+        stream.write_all(b"ls").unwrap();
+
+        assert_eq!(stream.logger, b"[node-1] write: \"ls\"\n");
+    }
+
+    #[test]
+    fn test_log_writers() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Shared(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Shared {
+            fn write(&mut self, buf: &[u8]) -> Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let (first, second) = (Shared::default(), Shared::default());
+        let loggers = LogWriters::new().with(first.clone());
+
+        let mut stream = LogStream::new(Cursor::new(Vec::<u8>::new()), loggers);
+        stream.write_all(b"ls").unwrap();
+        stream.add_logger(second.clone());
+        stream.write_all(b"pwd").unwrap();
+
+        assert_eq!(stream.get_logger().len(), 2);
+        assert_eq!(
+            first.0.lock().unwrap().as_slice(),
+            b"write: \"ls\"\nwrite: \"pwd\"\n"
+        );
+        assert_eq!(second.0.lock().unwrap().as_slice(), b"write: \"pwd\"\n");
+    }
+
+    #[test]
+    fn test_secret_guard() {
+        let mut stream = LogStream::new(Cursor::new(Vec::<u8>::new()), Vec::new());
+
+        let guard = SecretGuard::new("p4ssw0rd");
+        stream.write_all(b"p4ssw0rd\n").unwrap();
+        drop(guard);
+        stream.write_all(b"p4ssw0rd").unwrap();
+
+        assert_eq!(
+            stream.logger,
+            b"write: \"******\\n\"\nwrite: \"p4ssw0rd\"\n"
+        );
+    }
+
+    #[test]
+    fn test_redact_invalid_regex() {
+        let mut stream = LogStream::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        assert!(matches!(stream.redact("("), Err(Error::RegexParsing)));
+    }
+}
@@ -1,4 +1,16 @@
 //! Stream module contains a set of IO (write/read) wrappers.
 
+pub mod capture;
+pub mod cast;
+pub mod chaos;
+pub mod dynamic;
+#[cfg(feature = "encoding")]
+pub mod encoding;
 pub mod log;
+pub mod pretty;
 pub mod stdin;
+pub mod tee;
+pub mod telnet;
+pub mod throttle;
+#[cfg(all(target_os = "linux", feature = "uring", not(feature = "async")))]
+pub mod uring;
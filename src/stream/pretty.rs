@@ -0,0 +1,296 @@
+//! This module contains a [PrettyLogStream]
+//! which logs a stream in a human friendly way, like a terminal transcript.
+
+use std::{
+    io::{self, Read, Result, Write},
+    ops::{Deref, DerefMut},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::process::NonBlocking;
+
+use super::log::hide_secrets;
+
+const INPUT_PREFIX: &str = ">> ";
+const INPUT_COLOR: &str = "\x1b[2;36m";
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// PrettyLogStream a IO stream wrapper,
+/// which logs the stream line by line, like a terminal transcript.
+///
+/// An output of a process is written as it is,
+/// while a sent input is prefixed with `>>` and colored.
+/// Control characters are rendered in a caret notation (`^C`) and `ESC` as `<ESC>`.
+///
+/// Lines are written only when they're completed,
+/// or when a direction is changed, or when the stream is dropped.
+#[derive(Debug)]
+pub struct PrettyLogStream<S, W: Write> {
+    stream: S,
+    logger: W,
+    color: bool,
+    name: Option<String>,
+    line: Vec<u8>,
+    direction: Option<Direction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Read,
+    Write,
+}
+
+impl<S, W: Write> PrettyLogStream<S, W> {
+    /// Creates a new instance of the stream.
+    ///
+    /// Colors are on by default.
+    pub fn new(stream: S, logger: W) -> Self {
+        Self {
+            stream,
+            logger,
+            color: true,
+            name: None,
+            line: Vec::new(),
+            direction: None,
+        }
+    }
+
+    /// Turns colors on or off.
+    pub fn set_color(&mut self, on: bool) {
+        self.color = on;
+    }
+
+    /// Set a name which prefixes each log line.
+    pub fn set_name<N>(&mut self, name: N)
+    where
+        N: Into<String>,
+    {
+        self.name = Some(name.into());
+    }
+
+    /// Get a reference to a logger.
+    pub fn get_logger(&self) -> &W {
+        &self.logger
+    }
+
+    pub(crate) fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name;
+        self
+    }
+
+    fn log_write(&mut self, buf: &[u8]) {
+        self.log(Direction::Write, buf);
+    }
+
+    fn log_read(&mut self, buf: &[u8]) {
+        self.log(Direction::Read, buf);
+    }
+
+    fn log(&mut self, direction: Direction, buf: &[u8]) {
+        if self.direction != Some(direction) {
+            if !self.line.is_empty() {
+                self.write_line();
+            }
+
+            self.direction = Some(direction);
+        }
+
+        let buf = hide_secrets(buf);
+        for &b in buf.iter() {
+            match b {
+                b'\n' => self.write_line(),
+                b'\r' => (),
+                b'\t' => self.line.push(b),
+                0x1b => self.line.extend_from_slice(b"<ESC>"),
+                0x7f => self.line.extend_from_slice(b"^?"),
+                b if b < 0x20 => self.line.extend_from_slice(&[b'^', b + 0x40]),
+                b => self.line.push(b),
+            }
+        }
+    }
+
+    fn write_line(&mut self) {
+        let line = String::from_utf8_lossy(&self.line);
+        let name = self
+            .name
+            .as_ref()
+            .map(|name| format!("[{}] ", name))
+            .unwrap_or_default();
+
+        let _ = match (self.direction, self.color) {
+            (Some(Direction::Write), true) => writeln!(
+                self.logger,
+                "{}{}{}{}{}",
+                name, INPUT_COLOR, INPUT_PREFIX, line, RESET_COLOR
+            ),
+            (Some(Direction::Write), false) => {
+                writeln!(self.logger, "{}{}{}", name, INPUT_PREFIX, line)
+            }
+            _ => writeln!(self.logger, "{}{}", name, line),
+        };
+
+        self.line.clear();
+    }
+}
+
+impl<S, W: Write> Drop for PrettyLogStream<S, W> {
+    fn drop(&mut self) {
+        if !self.line.is_empty() {
+            self.write_line();
+        }
+
+        let _ = self.logger.flush();
+    }
+}
+
+impl<S: Write, W: Write> Write for PrettyLogStream<S, W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.stream.write(buf)?;
+        self.log_write(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
+        let n = self.stream.write_vectored(bufs)?;
+
+        let mut rest = n;
+        for buf in bufs {
+            let written = std::cmp::min(buf.len(), rest);
+            rest -= written;
+
+            self.log_write(&buf[..written]);
+
+            if rest == 0 {
+                break;
+            }
+        }
+
+        Ok(n)
+    }
+}
+
+impl<S: Read, W: Write> Read for PrettyLogStream<S, W> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.stream.read(buf)?;
+        self.log_read(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<S, W> NonBlocking for PrettyLogStream<S, W>
+where
+    S: NonBlocking,
+    W: Write,
+{
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        self.stream.set_blocking(on)
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        self.stream.readiness_fd()
+    }
+}
+
+impl<S, W: Write> Deref for PrettyLogStream<S, W> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl<S, W: Write> DerefMut for PrettyLogStream<S, W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncWrite + Unpin, W: Write + Unpin> AsyncWrite for PrettyLogStream<S, W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        let result = Pin::new(&mut self.stream).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.log_write(&buf[..*n]);
+        }
+
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncRead + Unpin, W: Write + Unpin> AsyncRead for PrettyLogStream<S, W> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let result = Pin::new(&mut self.stream).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.log_read(&buf[..*n]);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn test_transcript() {
+        let mut stream = PrettyLogStream::new(Cursor::new(Vec::<u8>::new()), Vec::new());
+        stream.set_color(false);
+
+        stream.log_write(b"ls\n");
+        stream.log_read(b"ls\r\nfile");
+        stream.log_read(b"\r\n$ ");
+        stream.log_write(&[0x03, 0x1b, b'[', b'A']);
+        stream.write_line();
+
+        assert_eq!(
+            String::from_utf8_lossy(stream.get_logger()),
+            ">> ls\nls\nfile\n$ \n>> ^C<ESC>[A\n",
+        );
+    }
+
+    #[test]
+    fn test_color_and_name() {
+        let mut stream = PrettyLogStream::new(Cursor::new(Vec::<u8>::new()), Vec::new());
+        stream.set_name("node-1");
+
+        stream.write_all(b"ls\n").unwrap();
+
+        assert_eq!(stream.stream.get_ref(), b"ls\n");
+        assert_eq!(
+            String::from_utf8_lossy(stream.get_logger()),
+            "[node-1] \x1b[2;36m>> ls\x1b[0m\n",
+        );
+    }
+}
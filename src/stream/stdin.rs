@@ -103,22 +103,45 @@ impl polling::Source for Stdin {
     }
 }
 
+#[cfg(all(unix, feature = "mio"))]
+impl mio::event::Source for Stdin {
+    fn register(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> std::io::Result<()> {
+        let fd = std::os::unix::io::AsRawFd::as_raw_fd(self);
+        mio::unix::SourceFd(&fd).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> std::io::Result<()> {
+        let fd = std::os::unix::io::AsRawFd::as_raw_fd(self);
+        mio::unix::SourceFd(&fd).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio::Registry) -> std::io::Result<()> {
+        let fd = std::os::unix::io::AsRawFd::as_raw_fd(self);
+        mio::unix::SourceFd(&fd).deregister(registry)
+    }
+}
+
 #[cfg(unix)]
 mod inner {
     use super::*;
 
     use std::os::unix::prelude::AsRawFd;
 
-    use nix::{
-        libc::STDIN_FILENO,
-        sys::termios::{self, Termios},
-        unistd::isatty,
-    };
-    use ptyprocess::set_raw;
+    use crate::terminal::RawModeGuard;
 
     #[derive(Debug)]
     pub(super) struct StdinInner {
-        orig_flags: Option<Termios>,
+        guard: Option<RawModeGuard>,
         #[cfg(feature = "async")]
         stdin: async_io::Async<std::io::Stdin>,
         #[cfg(not(feature = "async"))]
@@ -132,50 +155,25 @@ mod inner {
             let stdin = async_io::Async::new(stdin)?;
 
             #[cfg(target_os = "macos")]
-            let orig_flags = None;
+            let guard = None;
 
             #[cfg(not(target_os = "macos"))]
-            let orig_flags = Self::prepare()?;
-
-            Ok(Self { stdin, orig_flags })
-        }
-
-        pub(super) fn prepare() -> Result<Option<Termios>, Error> {
-            // flush buffers
-            // self.stdin.flush()?;
-
-            let mut o_pty_flags = None;
-
-            // verify: possible controlling fd can be stdout and stderr as well?
-            // https://stackoverflow.com/questions/35873843/when-setting-terminal-attributes-via-tcsetattrfd-can-fd-be-either-stdout
-            let isatty_terminal = isatty(STDIN_FILENO)
-                .map_err(|e| Error::unknown("failed to call isatty", e.to_string()))?;
-            if isatty_terminal {
-                // tcgetattr issues error if a provided fd is not a tty,
-                // but we can work with such input as it may be redirected.
-                o_pty_flags = termios::tcgetattr(STDIN_FILENO)
-                    .map(Some)
-                    .map_err(|e| Error::unknown("failed to call tcgetattr", e.to_string()))?;
-
-                set_raw(STDIN_FILENO)
-                    .map_err(|e| Error::unknown("failed to set a raw tty", e.to_string()))?;
-            }
+            let guard = Some(RawModeGuard::new()?);
 
-            Ok(o_pty_flags)
+            Ok(Self { stdin, guard })
         }
 
         pub(super) fn close(&mut self) -> Result<(), Error> {
-            if let Some(origin_stdin_flags) = &self.orig_flags {
-                termios::tcsetattr(STDIN_FILENO, termios::SetArg::TCSAFLUSH, origin_stdin_flags)
-                    .map_err(|e| Error::unknown("failed to call tcsetattr", e.to_string()))?;
+            match self.guard.take() {
+                Some(guard) => guard.restore(),
+                None => Ok(()),
             }
-
-            Ok(())
         }
 
         #[cfg(not(feature = "async"))]
         pub(crate) fn blocking(&mut self, on: bool) -> Result<(), Error> {
-            crate::process::unix::make_non_blocking(self.as_raw_fd(), on).map_err(Error::IO)
+            crate::process::unix::make_non_blocking(self.as_raw_fd(), on)
+                .map_err(Error::io("set blocking"))
         }
     }
 
@@ -210,8 +208,11 @@ mod inner {
 
     use conpty::console::Console;
 
+    use crate::terminal::RawModeGuard;
+
     pub(super) struct StdinInner {
         terminal: Console,
+        guard: Option<RawModeGuard>,
         #[cfg(not(feature = "async"))]
         is_blocking: bool,
         #[cfg(not(feature = "async"))]
@@ -226,6 +227,7 @@ mod inner {
             {
                 f.debug_struct("StdinInner")
                     .field("terminal", &self.terminal)
+                    .field("guard", &self.guard)
                     .field("is_blocking", &self.is_blocking)
                     .field("stdin", &self.stdin)
                     .field("stdin", &self.stdin)
@@ -235,6 +237,7 @@ mod inner {
             {
                 f.debug_struct("StdinInner")
                     .field("terminal", &self.terminal)
+                    .field("guard", &self.guard)
                     .field("stdin", &self.stdin)
                     .field("stdin", &self.stdin)
                     .finish()
@@ -248,8 +251,8 @@ mod inner {
         /// It changes terminal's STDIN state therefore, after
         /// it's used please call [Stdin::close].
         pub(super) fn new() -> Result<Self, Error> {
-            let console = conpty::console::Console::current().map_err(to_io_error)?;
-            console.set_raw().map_err(to_io_error)?;
+            let guard = RawModeGuard::new()?;
+            let terminal = Console::current().map_err(to_io_error)?;
 
             let stdin = io::stdin();
 
@@ -257,7 +260,8 @@ mod inner {
             let stdin = blocking::Unblock::new(stdin);
 
             Ok(Self {
-                terminal: console,
+                terminal,
+                guard: Some(guard),
                 #[cfg(not(feature = "async"))]
                 is_blocking: false,
                 stdin,
@@ -265,8 +269,10 @@ mod inner {
         }
 
         pub(super) fn close(&mut self) -> Result<(), Error> {
-            self.terminal.reset().map_err(to_io_error)?;
-            Ok(())
+            match self.guard.take() {
+                Some(guard) => guard.restore(),
+                None => Ok(()),
+            }
         }
 
         #[cfg(not(feature = "async"))]
@@ -309,6 +315,8 @@ mod inner {
         fn clone(&self) -> Self {
             Self {
                 terminal: self.terminal.clone(),
+                // only the original handle restores a console state
+                guard: None,
                 is_blocking: self.is_blocking.clone(),
                 stdin: std::io::stdin(),
             }
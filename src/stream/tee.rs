@@ -0,0 +1,174 @@
+//! This module contains a [TeeStream]
+//! which copies everything read from a stream into a writer.
+
+use std::{
+    io::{self, Read, Result, Write},
+    ops::{Deref, DerefMut},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::process::NonBlocking;
+
+/// TeeStream a IO stream wrapper,
+/// which copies all read bytes as they are into a writer.
+///
+/// Unlike [`LogStream`] it doesn't log written bytes and doesn't format the output,
+/// so the writer gets a child's output as it would be seen in a terminal.
+///
+/// [`LogStream`]: crate::stream::log::LogStream
+#[derive(Debug)]
+pub struct TeeStream<S, W> {
+    stream: S,
+    writer: W,
+}
+
+impl<S, W> TeeStream<S, W> {
+    /// Creates a new instance of the stream.
+    pub fn new(stream: S, writer: W) -> Self {
+        Self { stream, writer }
+    }
+
+    /// Returns an inner stream and writer.
+    pub fn into_inner(self) -> (S, W) {
+        (self.stream, self.writer)
+    }
+
+    /// Returns a reference to a writer.
+    pub fn get_writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mut reference to a writer.
+    pub fn get_writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+impl<S, W: Write> TeeStream<S, W> {
+    fn copy(&mut self, buf: &[u8]) {
+        // An error in a copy must not affect a session.
+        let _ = self.writer.write_all(buf);
+        let _ = self.writer.flush();
+    }
+}
+
+impl<S: Write, W> Write for TeeStream<S, W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
+        self.stream.write_vectored(bufs)
+    }
+}
+
+impl<S: Read, W: Write> Read for TeeStream<S, W> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.stream.read(buf)?;
+        self.copy(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<S, W> NonBlocking for TeeStream<S, W>
+where
+    S: NonBlocking,
+{
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        self.stream.set_blocking(on)
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        self.stream.readiness_fd()
+    }
+}
+
+impl<S, W> Deref for TeeStream<S, W> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl<S, W> DerefMut for TeeStream<S, W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncWrite + Unpin, W: Unpin> AsyncWrite for TeeStream<S, W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        Pin::new(&mut self.stream).poll_write_vectored(cx, bufs)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncRead + Unpin, W: Write + Unpin> AsyncRead for TeeStream<S, W> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let result = Pin::new(&mut self.stream).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.copy(&buf[..*n]);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn test_tee() {
+        let mut stream = TeeStream::new(Cursor::new(b"Hello World".to_vec()), Vec::new());
+
+        let mut buf = String::new();
+        let _ = stream.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "Hello World");
+
+        stream.write_all(b"!").unwrap();
+
+        let (_, copy) = stream.into_inner();
+        assert_eq!(copy, b"Hello World");
+    }
+}
@@ -0,0 +1,261 @@
+//! This module contains a [TelnetStream]
+//! which handles telnet option negotiation of an underlying stream.
+
+use std::io::{self, Read, Result, Write};
+
+use crate::process::NonBlocking;
+
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+
+const OPT_ECHO: u8 = 1;
+const OPT_SUPPRESS_GO_AHEAD: u8 = 3;
+
+/// TelnetStream a IO stream wrapper,
+/// which strips telnet commands from a read data and answers option negotiation.
+///
+/// It agrees to server's `ECHO` and `SUPPRESS-GO-AHEAD` options and refuses all the others.
+/// Written `0xFF` bytes are escaped.
+#[derive(Debug)]
+pub struct TelnetStream<S> {
+    stream: S,
+    state: State,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Data,
+    Iac,
+    Negotiation(u8),
+    Subnegotiation,
+    SubnegotiationIac,
+}
+
+impl<S> TelnetStream<S> {
+    /// Creates a new instance of the stream.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            state: State::Data,
+        }
+    }
+
+    /// Returns an inner stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Returns a reference to an inner stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mut reference to an inner stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    // Processes buffer in place.
+    // Returns a length of data left and a list of replies.
+    fn process(&mut self, buf: &mut [u8]) -> (usize, Vec<u8>) {
+        let mut replies = Vec::new();
+        let mut n = 0;
+        for i in 0..buf.len() {
+            let b = buf[i];
+            self.state = match self.state {
+                State::Data if b == IAC => State::Iac,
+                State::Data => {
+                    buf[n] = b;
+                    n += 1;
+                    State::Data
+                }
+                State::Iac => match b {
+                    IAC => {
+                        buf[n] = IAC;
+                        n += 1;
+                        State::Data
+                    }
+                    WILL | WONT | DO | DONT => State::Negotiation(b),
+                    SB => State::Subnegotiation,
+                    _ => State::Data,
+                },
+                State::Negotiation(cmd) => {
+                    match cmd {
+                        WILL if b == OPT_ECHO || b == OPT_SUPPRESS_GO_AHEAD => {
+                            replies.extend([IAC, DO, b])
+                        }
+                        WILL => replies.extend([IAC, DONT, b]),
+                        DO => replies.extend([IAC, WONT, b]),
+                        // Refusals are not answered to not get into a loop.
+                        _ => (),
+                    }
+
+                    State::Data
+                }
+                State::Subnegotiation if b == IAC => State::SubnegotiationIac,
+                State::Subnegotiation => State::Subnegotiation,
+                State::SubnegotiationIac if b == SE => State::Data,
+                State::SubnegotiationIac => State::Subnegotiation,
+            };
+        }
+
+        (n, replies)
+    }
+}
+
+impl<S: Read + Write> Read for TelnetStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return self.stream.read(buf);
+        }
+
+        loop {
+            let n = self.stream.read(buf)?;
+            if n == 0 {
+                return Ok(0);
+            }
+
+            let (n, replies) = self.process(&mut buf[..n]);
+            if !replies.is_empty() {
+                self.stream.write_all(&replies)?;
+                self.stream.flush()?;
+            }
+
+            // a chunk could consist of commands only,
+            // but returning 0 would be considered as EOF.
+            if n > 0 {
+                return Ok(n);
+            }
+        }
+    }
+}
+
+impl<S: Write> Write for TelnetStream<S> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if !buf.contains(&IAC) {
+            return self.stream.write(buf);
+        }
+
+        let mut escaped = Vec::with_capacity(buf.len() + 1);
+        for &b in buf {
+            escaped.push(b);
+            if b == IAC {
+                escaped.push(IAC);
+            }
+        }
+
+        self.stream.write_all(&escaped)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
+        match bufs.iter().find(|b| !b.is_empty()) {
+            Some(buf) => self.write(buf),
+            None => Ok(0),
+        }
+    }
+}
+
+impl<S> NonBlocking for TelnetStream<S>
+where
+    S: NonBlocking,
+{
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        self.stream.set_blocking(on)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[derive(Debug)]
+    struct Mock {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Mock {
+        fn new(input: &[u8]) -> Self {
+            Self {
+                input: Cursor::new(input.to_vec()),
+                output: Vec::new(),
+            }
+        }
+    }
+
+    impl Read for Mock {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Mock {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_read_strips_negotiation() {
+        let mut stream = TelnetStream::new(Mock::new(&[
+            IAC, DO, 24, b'l', b'o', IAC, WILL, OPT_ECHO, b'g', IAC, WILL, 31, b'i', b'n',
+        ]));
+
+        let mut buf = String::new();
+        let _ = stream.read_to_string(&mut buf).unwrap();
+
+        assert_eq!(buf, "login");
+        assert_eq!(
+            stream.get_ref().output,
+            [IAC, WONT, 24, IAC, DO, OPT_ECHO, IAC, DONT, 31]
+        );
+    }
+
+    #[test]
+    fn test_read_skips_subnegotiation() {
+        let mut stream = TelnetStream::new(Mock::new(&[
+            b'a', IAC, SB, 24, 1, IAC, IAC, IAC, SE, b'b', IAC, IAC,
+        ]));
+
+        let mut buf = Vec::new();
+        let _ = stream.read_to_end(&mut buf).unwrap();
+
+        assert_eq!(buf, [b'a', b'b', IAC]);
+        assert!(stream.get_ref().output.is_empty());
+    }
+
+    #[test]
+    fn test_read_commands_only_chunk_is_not_eof() {
+        let mut stream = TelnetStream::new(Mock::new(&[IAC, DO, 24, b'a']));
+
+        let mut buf = [0; 3];
+        assert_eq!(stream.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], b'a');
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_write_escapes_iac() {
+        let mut stream = TelnetStream::new(Mock::new(&[]));
+
+        assert_eq!(stream.write(&[b'a', IAC, b'b']).unwrap(), 3);
+        assert_eq!(stream.get_ref().output, [b'a', IAC, IAC, b'b']);
+    }
+}
@@ -0,0 +1,205 @@
+//! This module contains a [ThrottledStream]
+//! which limits a rate of writes to a stream.
+
+use std::{
+    io::{self, Read, Result, Write},
+    ops::{Deref, DerefMut},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::process::NonBlocking;
+
+/// ThrottledStream is a IO stream wrapper which writes no faster than a given rate.
+///
+/// Serial consoles and bootloaders tend to drop characters when an input comes too fast,
+/// the stream spreads writes in time so a caller doesn't need to sleep between them.
+///
+/// Bytes are written by chunks of a burst size (1 byte by default),
+/// so a single big write is split as well. Reads are not affected.
+#[derive(Debug)]
+pub struct ThrottledStream<S> {
+    stream: S,
+    bytes_per_sec: u32,
+    burst: usize,
+    next_write: Option<Instant>,
+    #[cfg(feature = "async")]
+    delay: Option<futures_timer::Delay>,
+}
+
+impl<S> ThrottledStream<S> {
+    /// Creates a new instance of the stream.
+    ///
+    /// A rate of 0 is treated as 1 byte per second.
+    pub fn new(stream: S, bytes_per_sec: u32) -> Self {
+        Self {
+            stream,
+            bytes_per_sec: bytes_per_sec.max(1),
+            burst: 1,
+            next_write: None,
+            #[cfg(feature = "async")]
+            delay: None,
+        }
+    }
+
+    /// Set an amount of bytes which may be written at once.
+    pub fn burst(mut self, bytes: usize) -> Self {
+        self.burst = bytes.max(1);
+        self
+    }
+
+    /// Returns a rate limit in bytes per second.
+    pub fn get_rate(&self) -> u32 {
+        self.bytes_per_sec
+    }
+
+    /// Returns an inner stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Returns a time left till a next write is allowed.
+    fn wait_time(&self) -> Option<Duration> {
+        let next_write = self.next_write?;
+        let wait = next_write.saturating_duration_since(Instant::now());
+        (!wait.is_zero()).then_some(wait)
+    }
+
+    /// Accounts written bytes.
+    fn written(&mut self, n: usize) {
+        let now = Instant::now();
+        let start = self.next_write.map_or(now, |next| next.max(now));
+        let cost = Duration::from_secs_f64(n as f64 / f64::from(self.bytes_per_sec));
+        self.next_write = Some(start + cost);
+    }
+}
+
+impl<S: Write> Write for ThrottledStream<S> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if let Some(wait) = self.wait_time() {
+            std::thread::sleep(wait);
+        }
+
+        let n = buf.len().min(self.burst);
+        let n = self.stream.write(&buf[..n])?;
+        // It must be written right away, otherwise the pacing is lost in a buffer.
+        self.stream.flush()?;
+        self.written(n);
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl<S: Read> Read for ThrottledStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.stream.read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> Result<usize> {
+        self.stream.read_vectored(bufs)
+    }
+}
+
+impl<S> NonBlocking for ThrottledStream<S>
+where
+    S: NonBlocking,
+{
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        self.stream.set_blocking(on)
+    }
+
+    #[cfg(unix)]
+    fn readiness_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        self.stream.readiness_fd()
+    }
+}
+
+impl<S> Deref for ThrottledStream<S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl<S> DerefMut for ThrottledStream<S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncWrite + Unpin> AsyncWrite for ThrottledStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        loop {
+            if let Some(delay) = &mut self.delay {
+                futures_lite::ready!(Pin::new(delay).poll(cx));
+                self.delay = None;
+            }
+
+            match self.wait_time() {
+                Some(wait) => self.delay = Some(futures_timer::Delay::new(wait)),
+                None => break,
+            }
+        }
+
+        let n = buf.len().min(self.burst);
+        let n = futures_lite::ready!(Pin::new(&mut self.stream).poll_write(cx, &buf[..n]))?;
+        self.written(n);
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncRead + Unpin> AsyncRead for ThrottledStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttled_write() {
+        let mut stream = ThrottledStream::new(Vec::new(), 100).burst(2);
+
+        let start = Instant::now();
+        assert_eq!(stream.write(b"Hello").unwrap(), 2);
+        stream.write_all(b"llo World").unwrap();
+
+        // 11 bytes by 2 are 6 writes, the first one isn't delayed.
+        assert!(start.elapsed() >= Duration::from_millis(90));
+        assert_eq!(stream.into_inner(), b"Hello World");
+    }
+}
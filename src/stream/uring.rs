@@ -0,0 +1,333 @@
+//! This module contains a [UringStream]
+//! which reads a stream through `io_uring` on Linux.
+
+use std::{
+    fmt,
+    io::{self, Read, Result, Write},
+    ops::{Deref, Range},
+    os::unix::io::{AsRawFd, RawFd},
+    time::Duration,
+};
+
+use io_uring::{opcode, types, IoUring};
+
+use crate::process::NonBlocking;
+
+/// A size of each of read buffers.
+const BUFFER_SIZE: usize = 64 * 1024;
+/// An amount of entries in the submission queue.
+pub(crate) const RING_ENTRIES: u32 = 4;
+/// A user data of a cancellation request.
+const CANCEL_USER_DATA: u64 = u64::MAX;
+/// A time a drop waits for an in flight read to be cancelled.
+const CANCEL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// UringStream is a IO stream wrapper which reads through `io_uring`.
+///
+/// A read is always kept in flight in the kernel,
+/// so an output of a process is received while a caller handles a previous chunk.
+/// It makes a difference for processes which produce a lot of output quickly,
+/// as the data is read in big chunks with fewer syscalls.
+///
+/// Writes go directly to the inner stream.
+///
+/// It requires Linux 5.7 or newer.
+pub struct UringStream<S> {
+    stream: S,
+    ring: IoUring,
+    buffers: [Vec<u8>; 2],
+    /// An index of a buffer a kernel reads into.
+    in_flight: Option<usize>,
+    /// An index of a buffer and a range of it which is read but not returned to a caller yet.
+    ready: Option<(usize, Range<usize>)>,
+    blocking: bool,
+    eof: bool,
+}
+
+impl<S> UringStream<S>
+where
+    S: AsRawFd,
+{
+    /// Creates a new instance of the stream.
+    ///
+    /// It fails if `io_uring` is not supported by a kernel or is forbidden,
+    /// e.g. by a seccomp filter of a container.
+    pub fn new(stream: S) -> Result<Self> {
+        let ring = IoUring::new(RING_ENTRIES)?;
+        Ok(Self::with_ring(stream, ring))
+    }
+
+    pub(crate) fn with_ring(stream: S, ring: IoUring) -> Self {
+        Self {
+            stream,
+            ring,
+            buffers: [vec![0; BUFFER_SIZE], vec![0; BUFFER_SIZE]],
+            in_flight: None,
+            ready: None,
+            blocking: true,
+            eof: false,
+        }
+    }
+
+    /// Submits a read into a free buffer unless one is already in flight.
+    fn submit_read(&mut self) -> Result<()> {
+        if self.in_flight.is_some() || self.eof {
+            return Ok(());
+        }
+
+        let index = match &self.ready {
+            Some((index, _)) => 1 - index,
+            None => 0,
+        };
+
+        let buffer = &mut self.buffers[index];
+        let entry = opcode::Read::new(
+            types::Fd(self.stream.as_raw_fd()),
+            buffer.as_mut_ptr(),
+            buffer.len() as u32,
+        )
+        // Use a current file position, which is required for pipes and terminals.
+        .offset(u64::MAX)
+        .build()
+        .user_data(index as u64);
+
+        // SAFETY: The buffer is kept alive and untouched until the read is completed,
+        // which is ensured by Drop.
+        unsafe {
+            self.ring
+                .submission()
+                .push(&entry)
+                .map_err(|_| io::Error::new(io::ErrorKind::Other, "io_uring queue is full"))?;
+        }
+
+        self.ring.submit()?;
+        self.in_flight = Some(index);
+
+        Ok(())
+    }
+
+    /// Takes a completion of an in flight read, if there's one.
+    ///
+    /// It blocks till the read is completed if `wait` is set.
+    fn complete(&mut self, wait: bool) -> Result<()> {
+        let entry = loop {
+            if let Some(entry) = self.ring.completion().next() {
+                break entry;
+            }
+
+            if !wait {
+                return Ok(());
+            }
+
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        };
+
+        if entry.user_data() == CANCEL_USER_DATA {
+            return Ok(());
+        }
+
+        let index = entry.user_data() as usize;
+        self.in_flight = None;
+
+        match entry.result() {
+            n if n > 0 => {
+                self.ready = Some((index, 0..n as usize));
+                // Read ahead into the other buffer while the data is handled.
+                self.submit_read()?;
+                Ok(())
+            }
+            // A pty returns EIO when a process closes its side.
+            n if n == 0 || n == -libc::EIO => {
+                self.eof = true;
+                Ok(())
+            }
+            // A read will be resubmitted by a caller.
+            n if n == -libc::EAGAIN || n == -libc::EINTR => Ok(()),
+            n => Err(io::Error::from_raw_os_error(-n)),
+        }
+    }
+
+    /// Returns an inner stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+}
+
+impl<S> Read for UringStream<S>
+where
+    S: AsRawFd,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        loop {
+            if let Some((index, range)) = &mut self.ready {
+                let n = buf.len().min(range.len());
+                buf[..n].copy_from_slice(&self.buffers[*index][range.start..range.start + n]);
+                range.start += n;
+                if range.is_empty() {
+                    self.ready = None;
+                }
+
+                return Ok(n);
+            }
+
+            if self.eof || buf.is_empty() {
+                return Ok(0);
+            }
+
+            self.submit_read()?;
+            self.complete(self.blocking)?;
+
+            if self.ready.is_none() && !self.eof && !self.blocking {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, ""));
+            }
+        }
+    }
+}
+
+impl<S: Write> Write for UringStream<S> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
+        self.stream.write_vectored(bufs)
+    }
+}
+
+impl<S> NonBlocking for UringStream<S> {
+    fn set_blocking(&mut self, on: bool) -> Result<()> {
+        // The inner stream is never read directly,
+        // so only a read from the ring is affected.
+        self.blocking = on;
+        Ok(())
+    }
+
+    fn readiness_fd(&self) -> Option<RawFd> {
+        // A ring becomes readable once a completion is posted.
+        Some(self.ring.as_raw_fd())
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for UringStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UringStream")
+            .field("stream", &self.stream)
+            .field("in_flight", &self.in_flight)
+            .field("ready", &self.ready)
+            .field("blocking", &self.blocking)
+            .field("eof", &self.eof)
+            .finish()
+    }
+}
+
+impl<S> Deref for UringStream<S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl<S> Drop for UringStream<S> {
+    fn drop(&mut self) {
+        // A kernel may write into a buffer of an in flight read,
+        // so it must be completed before the buffers are freed.
+        let index = match self.in_flight {
+            Some(index) => index as u64,
+            None => return,
+        };
+
+        let cancel = opcode::AsyncCancel::new(index)
+            .build()
+            .user_data(CANCEL_USER_DATA);
+        // SAFETY: The entry doesn't refer to any memory.
+        let _ = unsafe { self.ring.submission().push(&cancel) };
+
+        let timespec = types::Timespec::from(CANCEL_TIMEOUT);
+        let args = types::SubmitArgs::new().timespec(&timespec);
+        let mut completed = false;
+        while !completed {
+            match self.ring.submitter().submit_with_args(1, &args) {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+
+            let mut received = false;
+            for entry in self.ring.completion() {
+                received = true;
+                completed |= entry.user_data() == index;
+            }
+
+            if !received {
+                break;
+            }
+        }
+
+        if !completed {
+            // A blocked read of a terminal may be not cancellable,
+            // so the buffers are leaked rather than freed under the kernel.
+            for buffer in &mut self.buffers {
+                std::mem::forget(std::mem::take(buffer));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn test_uring_read() {
+        let (mut tx, rx) = UnixStream::pair().unwrap();
+        let mut stream = match UringStream::new(rx) {
+            Ok(stream) => stream,
+            // A sandbox may forbid io_uring.
+            Err(_) => return,
+        };
+
+        stream.set_blocking(false).unwrap();
+        let mut buf = [0; 16];
+        assert_eq!(
+            stream.read(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        tx.write_all(b"Hello World").unwrap();
+        assert!(stream.wait_readable(Some(Duration::from_secs(5))).unwrap());
+
+        stream.set_blocking(true).unwrap();
+        let mut buf = [0; 5];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"Hello");
+
+        let data = vec![b'a'; 3 * BUFFER_SIZE];
+        let writer = std::thread::spawn(move || {
+            tx.write_all(&data).unwrap();
+        });
+
+        let mut received = Vec::new();
+        while received.len() < 6 + 3 * BUFFER_SIZE {
+            let mut buf = [0; 4096];
+            let n = stream.read(&mut buf).unwrap();
+            received.extend_from_slice(&buf[..n]);
+        }
+
+        writer.join().unwrap();
+        assert_eq!(&received[..6], b" World");
+        assert!(received[6..].iter().all(|&b| b == b'a'));
+
+        let mut buf = [0; 16];
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    }
+}
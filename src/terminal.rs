@@ -0,0 +1,324 @@
+//! This module contains a terminal raw mode management.
+//!
+//! [`RawModeGuard`] switches a terminal into a raw mode and restores its original state when it's dropped,
+//! including a drop caused by a panic.
+//! It lets programs which handle STDIN on their own to share a terminal with `expectrl`
+//! (e.g. [`Stdin`] uses the same guard) without fighting over termios settings.
+//!
+//! Guards can be nested, each one restores a state which was set before it.
+//!
+//! [`size`] returns a size of a host terminal,
+//! it's used to propagate resizes to a process in [`InteractSession::propagate_window_size`].
+//!
+#![cfg_attr(unix, doc = "```no_run")]
+#![cfg_attr(not(unix), doc = "```ignore")]
+//! use expectrl::terminal::RawModeGuard;
+//!
+//! let guard = RawModeGuard::new().unwrap();
+//! assert!(guard.is_raw());
+//!
+//! // read keystrokes one by one
+//!
+//! guard.restore().unwrap();
+//! ```
+//!
+//! [`Stdin`]: crate::stream::stdin::Stdin
+//! [`InteractSession::propagate_window_size`]: crate::interact::InteractSession::propagate_window_size
+
+use crate::Error;
+
+#[cfg(unix)]
+pub use unix::{size, RawModeGuard};
+
+#[cfg(windows)]
+pub use windows::{size, RawModeGuard};
+
+#[cfg(unix)]
+pub(crate) use unix::ResizeWatcher;
+
+#[cfg(windows)]
+pub(crate) use windows::ResizeWatcher;
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        io,
+        os::unix::io::RawFd,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use nix::{
+        libc::{self, STDIN_FILENO, STDOUT_FILENO},
+        sys::{
+            signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal},
+            termios::{self, Termios},
+        },
+        unistd::isatty,
+    };
+
+    use super::Error;
+
+    /// RawModeGuard keeps a terminal in a raw mode while it's alive.
+    ///
+    /// The original state is restored by [`RawModeGuard::restore`] or on drop.
+    #[derive(Debug)]
+    pub struct RawModeGuard {
+        fd: RawFd,
+        origin: Option<Termios>,
+    }
+
+    impl RawModeGuard {
+        /// Switches STDIN into a raw mode.
+        ///
+        /// If STDIN is not a terminal (e.g. it's redirected) nothing is changed.
+        pub fn new() -> Result<Self, Error> {
+            Self::with_fd(STDIN_FILENO)
+        }
+
+        /// Switches a terminal referenced by a file descriptor into a raw mode.
+        ///
+        /// If the descriptor is not a terminal nothing is changed.
+        pub fn with_fd(fd: RawFd) -> Result<Self, Error> {
+            let is_tty =
+                isatty(fd).map_err(|e| Error::unknown("failed to call isatty", e.to_string()))?;
+            if !is_tty {
+                // tcgetattr issues error if a provided fd is not a tty,
+                // but we can work with such input as it may be redirected.
+                return Ok(Self { fd, origin: None });
+            }
+
+            let origin = termios::tcgetattr(fd)
+                .map_err(|e| Error::unknown("failed to call tcgetattr", e.to_string()))?;
+
+            ptyprocess::set_raw(fd)
+                .map_err(|e| Error::unknown("failed to set a raw tty", e.to_string()))?;
+
+            Ok(Self {
+                fd,
+                origin: Some(origin),
+            })
+        }
+
+        /// Checks whether the guard has changed a terminal mode.
+        pub fn is_raw(&self) -> bool {
+            self.origin.is_some()
+        }
+
+        /// Restores the original terminal state.
+        pub fn restore(mut self) -> Result<(), Error> {
+            self.reset()
+        }
+
+        fn reset(&mut self) -> Result<(), Error> {
+            if let Some(origin) = self.origin.take() {
+                termios::tcsetattr(self.fd, termios::SetArg::TCSAFLUSH, &origin)
+                    .map_err(|e| Error::unknown("failed to call tcsetattr", e.to_string()))?;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Drop for RawModeGuard {
+        fn drop(&mut self) {
+            let _ = self.reset();
+        }
+    }
+
+    /// Returns a size of a host terminal in columns and rows.
+    ///
+    /// STDOUT is checked first and then STDIN, so it works when one of them is redirected.
+    pub fn size() -> Result<(u16, u16), Error> {
+        let mut last_err = None;
+        for fd in [STDOUT_FILENO, STDIN_FILENO] {
+            let mut ws = libc::winsize {
+                ws_row: 0,
+                ws_col: 0,
+                ws_xpixel: 0,
+                ws_ypixel: 0,
+            };
+
+            // SAFETY: TIOCGWINSZ only fills in the provided winsize structure.
+            let ret = unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut ws) };
+            if ret == 0 {
+                return Ok((ws.ws_col, ws.ws_row));
+            }
+
+            last_err = Some(io::Error::last_os_error());
+        }
+
+        Err(Error::Io {
+            op: "get terminal size",
+            source: last_err.unwrap_or_else(|| io::Error::other("no terminal found")),
+        })
+    }
+
+    static RESIZED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn on_sigwinch(_: libc::c_int) {
+        RESIZED.store(true, Ordering::SeqCst);
+    }
+
+    /// ResizeWatcher catches `SIGWINCH` while it's alive.
+    ///
+    /// A previous signal handler is restored on drop.
+    #[derive(Debug)]
+    pub(crate) struct ResizeWatcher {
+        previous: SigAction,
+    }
+
+    impl ResizeWatcher {
+        pub(crate) fn new() -> Result<Self, Error> {
+            let action = SigAction::new(
+                SigHandler::Handler(on_sigwinch),
+                SaFlags::SA_RESTART,
+                SigSet::empty(),
+            );
+
+            // SAFETY: the handler only touches an atomic, which is async-signal-safe.
+            let previous = unsafe { signal::sigaction(Signal::SIGWINCH, &action) }
+                .map_err(|e| Error::unknown("failed to set a SIGWINCH handler", e.to_string()))?;
+
+            // A size is propagated on start as well,
+            // the terminal could have been resized before.
+            RESIZED.store(true, Ordering::SeqCst);
+
+            Ok(Self { previous })
+        }
+
+        /// Checks whether a terminal was resized since the last [`ResizeWatcher::check`].
+        #[cfg(feature = "async")]
+        pub(crate) fn is_resized(&self) -> bool {
+            RESIZED.load(Ordering::SeqCst)
+        }
+
+        /// Returns a new size if a terminal was resized since the last call.
+        pub(crate) fn check(&mut self) -> Option<(u16, u16)> {
+            if RESIZED.swap(false, Ordering::SeqCst) {
+                size().ok()
+            } else {
+                None
+            }
+        }
+    }
+
+    impl Drop for ResizeWatcher {
+        fn drop(&mut self) {
+            // SAFETY: we restore a handler which was set before us.
+            let _ = unsafe { signal::sigaction(Signal::SIGWINCH, &self.previous) };
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::io;
+
+    use conpty::console::Console;
+    use windows_sys::Win32::System::Console::{
+        GetConsoleScreenBufferInfo, GetStdHandle, CONSOLE_SCREEN_BUFFER_INFO, STD_OUTPUT_HANDLE,
+    };
+
+    use super::Error;
+
+    /// RawModeGuard keeps a console in a raw mode while it's alive.
+    ///
+    /// The original state is restored by [`RawModeGuard::restore`] or on drop.
+    #[derive(Debug)]
+    pub struct RawModeGuard {
+        console: Option<Console>,
+    }
+
+    impl RawModeGuard {
+        /// Switches a current console into a raw mode.
+        pub fn new() -> Result<Self, Error> {
+            let console = Console::current().map_err(to_io_error)?;
+            console.set_raw().map_err(to_io_error)?;
+
+            Ok(Self {
+                console: Some(console),
+            })
+        }
+
+        /// Checks whether the guard has changed a console mode.
+        pub fn is_raw(&self) -> bool {
+            self.console.is_some()
+        }
+
+        /// Restores the original console state.
+        pub fn restore(mut self) -> Result<(), Error> {
+            self.reset()
+        }
+
+        fn reset(&mut self) -> Result<(), Error> {
+            if let Some(console) = self.console.take() {
+                console.reset().map_err(to_io_error)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Drop for RawModeGuard {
+        fn drop(&mut self) {
+            let _ = self.reset();
+        }
+    }
+
+    /// Returns a size of a host console in columns and rows.
+    pub fn size() -> Result<(u16, u16), Error> {
+        // SAFETY: the call only fills in the provided structure.
+        let info = unsafe {
+            let mut info: CONSOLE_SCREEN_BUFFER_INFO = std::mem::zeroed();
+            let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+            if GetConsoleScreenBufferInfo(handle, &mut info) == 0 {
+                return Err(Error::io("get terminal size")(io::Error::last_os_error()));
+            }
+
+            info
+        };
+
+        let cols = info.srWindow.Right - info.srWindow.Left + 1;
+        let rows = info.srWindow.Bottom - info.srWindow.Top + 1;
+
+        Ok((
+            u16::try_from(cols).unwrap_or_default(),
+            u16::try_from(rows).unwrap_or_default(),
+        ))
+    }
+
+    /// ResizeWatcher tracks a console size.
+    ///
+    /// There's no signal on windows so a size is compared on each check.
+    #[derive(Debug)]
+    pub(crate) struct ResizeWatcher {
+        last: Option<(u16, u16)>,
+    }
+
+    impl ResizeWatcher {
+        pub(crate) fn new() -> Result<Self, Error> {
+            Ok(Self { last: None })
+        }
+
+        /// Checks whether a console was resized since the last [`ResizeWatcher::check`].
+        #[cfg(feature = "async")]
+        pub(crate) fn is_resized(&self) -> bool {
+            size().map_or(false, |size| self.last != Some(size))
+        }
+
+        /// Returns a new size if a console was resized since the last call.
+        pub(crate) fn check(&mut self) -> Option<(u16, u16)> {
+            let size = size().ok()?;
+            if self.last == Some(size) {
+                return None;
+            }
+
+            self.last = Some(size);
+            Some(size)
+        }
+    }
+
+    fn to_io_error(err: impl std::error::Error) -> io::Error {
+        io::Error::other(err.to_string())
+    }
+}
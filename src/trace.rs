@@ -0,0 +1,56 @@
+//! This module contains helpers for an optional `tracing` instrumentation.
+
+/// A maximum amount of bytes shown in a preview of data.
+const PREVIEW_LENGTH: usize = 32;
+
+/// Emits an event of data read from a process.
+pub(crate) fn read(buf: &[u8]) {
+    tracing::trace!(bytes = buf.len(), data = %preview(buf), "read");
+}
+
+/// Emits an event of data written to a process.
+pub(crate) fn write(buf: &[u8]) {
+    tracing::trace!(bytes = buf.len(), data = %preview(buf), "write");
+}
+
+/// Emits events of data written to a process by a vectored write of `n` bytes.
+pub(crate) fn write_vectored(bufs: &[std::io::IoSlice<'_>], n: usize) {
+    let mut left = n;
+    for buf in bufs {
+        if left == 0 {
+            break;
+        }
+
+        let written = left.min(buf.len());
+        write(&buf[..written]);
+        left -= written;
+    }
+}
+
+/// Returns an escaped preview of data truncated to [`PREVIEW_LENGTH`] bytes.
+fn preview(buf: &[u8]) -> String {
+    let mut preview = buf
+        .iter()
+        .take(PREVIEW_LENGTH)
+        .flat_map(|&b| std::ascii::escape_default(b))
+        .map(char::from)
+        .collect::<String>();
+
+    if buf.len() > PREVIEW_LENGTH {
+        preview.push_str("...");
+    }
+
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview(b"Hello\r\n"), "Hello\\r\\n");
+        assert_eq!(preview(&[0x1b, b'[', b'A']), "\\x1b[A");
+        assert_eq!(preview(&[b'a'; 40]), format!("{}...", "a".repeat(32)));
+    }
+}
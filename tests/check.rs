@@ -388,3 +388,52 @@ fn check_macro_with_different_needles() {
         .unwrap();
     });
 }
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_macro_timeout_of_named_session() {
+    let mut session = spawn("cat").unwrap();
+    session.set_name("cat");
+    session.set_expect_timeout(Some(Duration::from_millis(300)));
+
+    let mut timed_out = false;
+    expectrl::expect!(
+        &mut session,
+        _ = "Something which is not inside" => {
+            panic!("Unexpected result");
+        },
+        timeout => {
+            timed_out = true;
+        },
+    )
+    .unwrap();
+
+    assert!(timed_out);
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_macro_timeout_of_named_session() {
+    let mut session = spawn("cat").unwrap();
+    session.set_name("cat");
+    session.set_expect_timeout(Some(Duration::from_millis(300)));
+
+    let mut timed_out = false;
+    futures_lite::future::block_on(async {
+        expectrl::expect!(
+            &mut session,
+            _ = "Something which is not inside" => {
+                panic!("Unexpected result");
+            },
+            timeout => {
+                timed_out = true;
+            },
+        )
+        .await
+        .unwrap();
+    });
+
+    assert!(timed_out);
+}
@@ -0,0 +1,33 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+
+use expectrl::{
+    session::{reset_default_options, set_default_options, SessionOptions},
+    spawn, LineEnding,
+};
+
+// The options are process-wide so the test is kept in its own binary.
+#[test]
+fn default_options() {
+    set_default_options(
+        SessionOptions::new()
+            .expect_timeout(Some(Duration::from_millis(300)))
+            .line_ending(LineEnding::Lf),
+    );
+
+    let session = spawn("cat").unwrap();
+    assert_eq!(
+        session.get_expect_timeout(),
+        Some(Duration::from_millis(300))
+    );
+    assert_eq!(session.get_line_ending(), LineEnding::Lf);
+
+    reset_default_options();
+
+    let session = spawn("cat").unwrap();
+    assert_eq!(
+        session.get_expect_timeout(),
+        Some(Duration::from_millis(10000))
+    );
+}
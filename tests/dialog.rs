@@ -0,0 +1,133 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+
+use expectrl::{
+    dialog::{Login, LoginOutcome, Sudo, SudoOutcome},
+    session::OsSession,
+    spawn,
+};
+
+const LOGIN_SCRIPT: &str = r#"
+printf "login: "; read u
+printf "Password: "; read p
+if [ "$u:$p" = "user:secret" ]; then printf "$ "; else echo "Login incorrect"; fi
+sleep 5
+"#;
+
+const SUDO_SCRIPT: &str = r#"
+read cmd
+printf "[sudo] password for user: "; read p
+if [ "$p" = "secret" ]; then echo "$cmd"; printf "$ "; else echo "Sorry, try again."; fi
+sleep 5
+"#;
+
+fn spawn_login() -> OsSession {
+    spawn_script(LOGIN_SCRIPT)
+}
+
+fn spawn_script(script: &str) -> OsSession {
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", script]);
+    OsSession::spawn(cmd).unwrap()
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn login() {
+    let mut session = spawn_login();
+    let outcome = Login::new("user", "secret").run(&mut session).unwrap();
+    assert_eq!(outcome, LoginOutcome::Shell);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn login() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn_login();
+        let outcome = Login::new("user", "secret")
+            .run(&mut session)
+            .await
+            .unwrap();
+        assert_eq!(outcome, LoginOutcome::Shell);
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn login_auth_failure() {
+    let mut session = spawn_login();
+    let outcome = Login::new("user", "wrong").run(&mut session).unwrap();
+    assert_eq!(outcome, LoginOutcome::AuthFailure);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn login_auth_failure() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn_login();
+        let outcome = Login::new("user", "wrong").run(&mut session).await.unwrap();
+        assert_eq!(outcome, LoginOutcome::AuthFailure);
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn login_timeout() {
+    let mut session = spawn("sleep 5").unwrap();
+    session.set_expect_timeout(Some(Duration::from_millis(300)));
+
+    let outcome = Login::new("user", "secret").run(&mut session).unwrap();
+    assert_eq!(outcome, LoginOutcome::Timeout);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn login_timeout() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("sleep 5").unwrap();
+        session.set_expect_timeout(Some(Duration::from_millis(300)));
+
+        let outcome = Login::new("user", "secret")
+            .run(&mut session)
+            .await
+            .unwrap();
+        assert_eq!(outcome, LoginOutcome::Timeout);
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn sudo() {
+    let mut session = spawn_script(SUDO_SCRIPT);
+    let outcome = Sudo::new("ls", "secret").run(&mut session).unwrap();
+    assert_eq!(outcome, SudoOutcome::Prompt);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn sudo() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn_script(SUDO_SCRIPT);
+        let outcome = Sudo::new("ls", "secret").run(&mut session).await.unwrap();
+        assert_eq!(outcome, SudoOutcome::Prompt);
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn sudo_auth_failure() {
+    let mut session = spawn_script(SUDO_SCRIPT);
+    let outcome = Sudo::new("ls", "wrong").run(&mut session).unwrap();
+    assert_eq!(outcome, SudoOutcome::AuthFailure);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn sudo_auth_failure() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn_script(SUDO_SCRIPT);
+        let outcome = Sudo::new("ls", "wrong").run(&mut session).await.unwrap();
+        assert_eq!(outcome, SudoOutcome::AuthFailure);
+    })
+}
@@ -201,17 +201,15 @@ fn expect_eof() {
 
 #[cfg(windows)]
 #[test]
-#[ignore = "https://stackoverflow.com/questions/68985384/does-a-conpty-reading-pipe-get-notified-on-process-termination"]
 fn expect_eof() {
-    let mut session = spawn("echo 'Hello World'").unwrap();
-
-    // give shell some time
-    std::thread::sleep(Duration::from_millis(300));
+    let mut session = spawn("cmd /C echo Hello World").unwrap();
 
+    // ConPTY output is full of control sequences so only a text is checked.
     #[cfg(not(feature = "async"))]
     {
         let m = session.expect(Eof).unwrap();
-        assert_eq!(m.get(0).unwrap(), b"'Hello World'\r\n");
+        let output = String::from_utf8_lossy(m.get(0).unwrap());
+        assert!(output.contains("Hello World"), "{:?}", output);
         assert_eq!(m.before(), b"");
     }
 
@@ -219,7 +217,8 @@ fn expect_eof() {
     {
         futures_lite::future::block_on(async {
             let m = session.expect(Eof).await.unwrap();
-            assert_eq!(m.get(0).unwrap(), b"'Hello World'\r\n");
+            let output = String::from_utf8_lossy(m.get(0).unwrap());
+            assert!(output.contains("Hello World"), "{:?}", output);
             assert_eq!(m.before(), b"");
         })
     }
@@ -294,7 +293,7 @@ fn expect_eof_timeout() {
     let mut p = spawn("sleep 3").expect("cannot run sleep 3");
     p.set_expect_timeout(Some(Duration::from_millis(100)));
     match p.expect(Eof) {
-        Err(expectrl::Error::ExpectTimeout) => {}
+        Err(expectrl::Error::ExpectTimeout { .. }) => {}
         r => panic!("reached a timeout {r:?}"),
     }
 }
@@ -307,7 +306,7 @@ fn expect_eof_timeout() {
         let mut p = spawn("sleep 3").expect("cannot run sleep 3");
         p.set_expect_timeout(Some(Duration::from_millis(100)));
         match p.expect(Eof).await {
-            Err(expectrl::Error::ExpectTimeout) => {}
+            Err(expectrl::Error::ExpectTimeout { .. }) => {}
             r => panic!("reached a timeout {r:?}"),
         }
     })
@@ -322,7 +321,7 @@ fn expect_eof_timeout() {
     #[cfg(not(feature = "async"))]
     {
         match p.expect(Eof) {
-            Err(expectrl::Error::ExpectTimeout) => {}
+            Err(expectrl::Error::ExpectTimeout { .. }) => {}
             r => panic!("should raise TimeOut {:?}", r),
         }
     }
@@ -331,9 +330,182 @@ fn expect_eof_timeout() {
     {
         futures_lite::future::block_on(async {
             match p.expect(Eof).await {
-                Err(expectrl::Error::ExpectTimeout) => {}
+                Err(expectrl::Error::ExpectTimeout { .. }) => {}
                 r => panic!("should raise TimeOut {:?}", r),
             }
         })
     }
 }
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_awaitable() {
+    use expectrl::{AsyncNeedle, Error, Match};
+
+    struct Delayed(&'static str);
+
+    impl AsyncNeedle for Delayed {
+        async fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+            futures_timer::Delay::new(std::time::Duration::from_millis(10)).await;
+            expectrl::Needle::check(self.0, buf, eof)
+        }
+    }
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.send_line("Hello World").await.unwrap();
+
+        let m = session.expect_awaitable(Delayed("World")).await.unwrap();
+        assert_eq!(m.before(), b"Hello ");
+
+        // a plain needle is an async one too
+        session.send_line("123").await.unwrap();
+        session.expect_awaitable("123").await.unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_capture() {
+    let mut session = spawn("cat").unwrap();
+    session.send_line("pid=1234").unwrap();
+
+    let pid = session
+        .expect_capture(Regex(r"pid=(?P<pid>\d+)"), "pid")
+        .unwrap();
+    assert_eq!(pid, "1234");
+
+    assert!(session
+        .expect_capture(Regex(r"pid=(?P<pid>\d+)"), "id")
+        .is_err());
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_capture() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.send_line("pid=1234").await.unwrap();
+
+        let pid = session
+            .expect_capture(Regex(r"pid=(?P<pid>\d+)"), "pid")
+            .await
+            .unwrap();
+        assert_eq!(pid, "1234");
+
+        assert!(session
+            .expect_capture(Regex(r"pid=(?P<pid>\d+)"), "id")
+            .await
+            .is_err());
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_literals_and_lists() {
+    let mut session = spawn("cat").unwrap();
+    session.send_line("Hello World").unwrap();
+
+    let m = session.expect(['x', 'o']).unwrap();
+    assert_eq!(m.before(), b"Hell");
+
+    let world = String::from("World");
+    session.expect(&world).unwrap();
+
+    let needles: Vec<Box<dyn expectrl::Needle>> = vec![Box::new("Bye"), Box::new(NBytes(2))];
+    let m = session.expect(&needles).unwrap();
+    assert_eq!(m.get(0).unwrap(), b"\r\n");
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_literals_and_lists() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.send_line("Hello World").await.unwrap();
+
+        let m = session.expect(['x', 'o']).await.unwrap();
+        assert_eq!(m.before(), b"Hell");
+
+        let world = String::from("World");
+        session.expect(&world).await.unwrap();
+
+        let needles: Vec<Box<dyn expectrl::Needle>> = vec![Box::new("Bye"), Box::new(NBytes(2))];
+        let m = session.expect(&needles).await.unwrap();
+        assert_eq!(m.get(0).unwrap(), b"\r\n");
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_through_wrappers() {
+    use std::{rc::Rc, sync::Arc};
+
+    use expectrl::Needle;
+
+    let mut session = Box::new(spawn("cat").unwrap());
+    session.send_line("Hello World").unwrap();
+
+    let needle: Rc<dyn Needle> = Rc::new("Hello");
+    let m = session.expect(needle).unwrap();
+    assert_eq!(m.get(0).unwrap(), b"Hello");
+
+    let needle: Arc<dyn Needle + Send + Sync> = Arc::new(Regex("W.rld"));
+    let m = session.expect(needle).unwrap();
+    assert_eq!(m.get(0).unwrap(), b"World");
+
+    let needle: Box<dyn Needle> = Box::new(NBytes(2));
+    let m = session.expect(needle).unwrap();
+    assert_eq!(m.get(0).unwrap(), b"\r\n");
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_through_wrappers() {
+    use std::{rc::Rc, sync::Arc};
+
+    use expectrl::Needle;
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.send_line("Hello World").await.unwrap();
+
+        let needle: Rc<dyn Needle> = Rc::new("Hello");
+        let m = session.expect(needle).await.unwrap();
+        assert_eq!(m.get(0).unwrap(), b"Hello");
+
+        let needle: Arc<dyn Needle + Send + Sync> = Arc::new(Regex("W.rld"));
+        let m = session.expect(needle).await.unwrap();
+        assert_eq!(m.get(0).unwrap(), b"World");
+
+        let needle: Box<dyn Needle> = Box::new(NBytes(2));
+        let m = session.expect(needle).await.unwrap();
+        assert_eq!(m.get(0).unwrap(), b"\r\n");
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_greedy_regex_after_quiet_period() {
+    use std::time::Instant;
+
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "printf 12345; sleep 5"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+    session.set_expect_timeout(Some(Duration::from_secs(3)));
+
+    // the match touches the end of the buffer, so it's returned once the output stops for a quiet period.
+    let start = Instant::now();
+    let m = session.expect(Regex("\\d+").greedy()).unwrap();
+    assert_eq!(m.get(0).unwrap(), b"12345");
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
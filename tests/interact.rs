@@ -10,7 +10,10 @@ use std::io::sink;
 
 #[cfg(not(feature = "async"))]
 use expectrl::{
-    interact::actions::lookup::Lookup, process::unix::WaitStatus, spawn, stream::stdin::Stdin,
+    interact::{actions::lookup::Lookup, StopReason},
+    process::unix::WaitStatus,
+    spawn,
+    stream::stdin::Stdin,
     Expect, NBytes,
 };
 
@@ -191,6 +194,97 @@ fn interact_filters() {
     assert_eq!(buffer, "19\r\nYES\r\n19\r\nYES\r\n");
 }
 
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_propagate_window_size() {
+    let reader = ReaderWithDelayEof::new("Hello World\n", Duration::from_secs(1));
+    let mut writer = io::Cursor::new(vec![0; 2048]);
+
+    let mut session = spawn("cat").unwrap();
+    session
+        .interact(reader, &mut writer)
+        .propagate_window_size()
+        .spawn()
+        .unwrap();
+
+    let buffer = String::from_utf8_lossy(writer.get_ref());
+    assert!(buffer.contains("Hello World"), "{buffer:?}");
+
+    // A test runner is not a terminal so the size is left untouched.
+    if expectrl::terminal::size().is_err() {
+        assert_eq!(session.get_process().get_window_size().unwrap(), (80, 24));
+    }
+}
+
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_wait_on_input_fd() {
+    use std::os::unix::net::UnixStream;
+
+    let (input, mut writer) = UnixStream::pair().unwrap();
+    input.set_nonblocking(true).unwrap();
+
+    let handle = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(300));
+        writer.write_all(b"Hello World\n").unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        writer.write_all(&[29]).unwrap();
+    });
+
+    let mut output = Cursor::new(Vec::new());
+    let mut session = spawn("cat").unwrap();
+    let summary = session
+        .interact(input, &mut output)
+        .wait_on_input_fd()
+        .spawn()
+        .unwrap();
+    handle.join().unwrap();
+
+    assert_eq!(summary.reason(), StopReason::Escape);
+    let buffer = String::from_utf8_lossy(output.get_ref());
+    assert!(buffer.contains("Hello World"), "{buffer:?}");
+}
+
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_idle_timeout() {
+    let reader = ReaderWithDelayEof::new("", Duration::from_secs(10));
+
+    let mut session = spawn("cat").unwrap();
+    let summary = session
+        .interact(reader, sink())
+        .set_idle_timeout(Duration::from_millis(500))
+        .spawn()
+        .unwrap();
+
+    assert_eq!(summary.reason(), StopReason::IdleTimeout);
+    assert!(summary.duration() < Duration::from_secs(10));
+}
+
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_idle_timeout_action() {
+    let reader = ReaderWithDelayEof::new("", Duration::from_secs(10));
+
+    let mut session = spawn("cat").unwrap();
+    let mut interact = session.interact(reader, sink()).with_state(0);
+    let summary = interact
+        .set_idle_timeout(Duration::from_millis(300))
+        .set_idle_timeout_action(|ctx| {
+            *ctx.state += 1;
+            Ok(*ctx.state == 3)
+        })
+        .spawn()
+        .unwrap();
+
+    assert_eq!(summary.reason(), StopReason::IdleTimeout);
+    assert_eq!(interact.into_state(), 3);
+}
+
 #[cfg(all(unix, not(any(feature = "async", feature = "polling"))))]
 #[test]
 fn interact_context() {
@@ -229,11 +323,12 @@ fn interact_context() {
             Ok(false)
         });
 
-    let is_alive = isession.spawn().unwrap();
+    let summary = isession.spawn().unwrap();
 
     let state = isession.into_state();
 
-    assert!(is_alive);
+    assert!(summary.is_alive());
+    assert!(summary.bytes_out() > 0);
 
     assert_eq!(state.0, 4);
     assert!(state.1 > 0, "{:?}", state.1);
@@ -283,11 +378,13 @@ fn interact_on_output_not_matched() {
             Ok(false)
         });
 
-    let is_alive = isession.spawn().unwrap();
+    let summary = isession.spawn().unwrap();
 
     let state = isession.into_state();
 
-    assert!(is_alive);
+    assert!(summary.is_alive());
+    assert_eq!(summary.reason(), StopReason::Escape);
+    assert_eq!(summary.bytes_in(), 9);
 
     assert_eq!(state.0, 2);
     assert_eq!(state.1, 0);
@@ -384,6 +481,52 @@ fn interact_output_callback() {
     assert!(matches!(state, 1 | 0), "{state:?}");
 }
 
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_cancel() {
+    let reader = ReaderWithDelayEof::new("", Duration::from_secs(10));
+
+    let mut session = spawn("cat").unwrap();
+    let mut interact = session.interact(reader, sink());
+
+    let handle = interact.handle();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(300));
+        handle.cancel();
+    });
+
+    let summary = interact.spawn().unwrap();
+    canceller.join().unwrap();
+
+    assert_eq!(summary.reason(), StopReason::Cancelled);
+    assert!(summary.duration() < Duration::from_secs(10));
+    assert!(!interact.handle().is_cancelled());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn interact_cancel() {
+    use expectrl::interact::StopReason;
+
+    let reader = ReaderWithDelayEof::new("", Duration::from_secs(10));
+    let writer = AsyncWriter(std::io::sink());
+
+    let mut session = expectrl::spawn("cat").unwrap();
+    let mut interact = session.interact(reader, writer);
+
+    let handle = interact.handle();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(300));
+        handle.cancel();
+    });
+
+    let summary = futures_lite::future::block_on(interact.spawn()).unwrap();
+    canceller.join().unwrap();
+
+    assert_eq!(summary.reason(), StopReason::Cancelled);
+    assert!(summary.duration() < Duration::from_secs(10));
+}
+
 struct ListReaderWithDelayedEof {
     lines: Vec<String>,
     eof_timeout: Duration,
@@ -160,6 +160,155 @@ fn log_read_line() {
     }
 }
 
+#[test]
+#[cfg(unix)]
+fn tee() {
+    let writer = StubWriter::default();
+    let mut session = session::tee(spawn("cat").unwrap(), writer.clone()).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.send_line("Hello World").unwrap();
+        let _ = session.expect("Hello World\r\n").unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.send_line("Hello World").await.unwrap();
+        let _ = session.expect("Hello World\r\n").await.unwrap();
+    });
+
+    let bytes = writer.inner.lock().unwrap();
+    let text = String::from_utf8_lossy(bytes.get_ref());
+    assert_eq!(text, "Hello World\r\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn cast() {
+    let writer = StubWriter::default();
+    let mut session = session::cast(spawn("cat").unwrap(), writer.clone(), 100, 30).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.send_line("Hello World").unwrap();
+        let _ = session.expect("Hello World\r\n").unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.send_line("Hello World").await.unwrap();
+        let _ = session.expect("Hello World\r\n").await.unwrap();
+    });
+
+    let bytes = writer.inner.lock().unwrap();
+    let text = String::from_utf8_lossy(bytes.get_ref());
+    let mut lines = text.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("{\"version\": 2, \"width\": 100, \"height\": 30,"));
+
+    let output = lines
+        .map(|line| line.split("\"o\", ").nth(1).unwrap())
+        .map(|text| text.trim_end_matches(']').trim_matches('"'))
+        .collect::<String>();
+    assert_eq!(output, "Hello World\\r\\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn log_pretty() {
+    let writer = StubWriter::default();
+    let mut session = session::log_pretty(spawn("cat").unwrap(), writer.clone()).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.send_line("Hello World").unwrap();
+        let _ = session.expect("Hello World\r\n").unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.send_line("Hello World").await.unwrap();
+        let _ = session.expect("Hello World\r\n").await.unwrap();
+    });
+
+    drop(session);
+
+    let bytes = writer.inner.lock().unwrap();
+    let text = String::from_utf8_lossy(bytes.get_ref());
+    assert_eq!(text, "\x1b[2;36m>> Hello World\x1b[0m\nHello World\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn dyn_stream_log_toggle() {
+    let writer = StubWriter::default();
+    let tee = session::tee(spawn("cat").unwrap(), io::sink()).unwrap();
+    let mut sessions = vec![
+        spawn("cat").unwrap().into_dyn().unwrap(),
+        tee.into_dyn().unwrap(),
+    ];
+
+    for session in &mut sessions {
+        session.enable_log(writer.clone());
+    }
+
+    #[cfg(not(feature = "async"))]
+    for session in &mut sessions {
+        session.send_line("Hello").unwrap();
+        let _ = session.expect("Hello\r\n").unwrap();
+        assert!(session.disable_log().is_some());
+        session.send_line("World").unwrap();
+        let _ = session.expect("World\r\n").unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        for session in &mut sessions {
+            session.send_line("Hello").await.unwrap();
+            let _ = session.expect("Hello\r\n").await.unwrap();
+            assert!(session.disable_log().is_some());
+            session.send_line("World").await.unwrap();
+            let _ = session.expect("World\r\n").await.unwrap();
+        }
+    });
+
+    let bytes = writer.inner.lock().unwrap();
+    let text = String::from_utf8_lossy(bytes.get_ref());
+    assert_eq!(text.matches("write: \"Hello").count(), 2, "{text:?}");
+    assert!(!text.contains("World"), "{text:?}");
+}
+
+#[test]
+#[cfg(unix)]
+fn capture() {
+    use expectrl::stream::capture::RotatingFile;
+
+    let path = std::env::temp_dir().join(format!("expectrl-test-capture-{}", std::process::id()));
+    let file = RotatingFile::create(&path).unwrap();
+    let mut session = session::capture(spawn("cat").unwrap(), file).unwrap();
+    assert_eq!(session.capture_path(), path);
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.send_line("Hello World").unwrap();
+        let _ = session.expect("Hello World\r\n").unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.send_line("Hello World").await.unwrap();
+        let _ = session.expect("Hello World\r\n").await.unwrap();
+    });
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(text, "Hello World\r\n");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
 #[derive(Debug, Clone, Default)]
 struct StubWriter {
     inner: Arc<Mutex<Cursor<Vec<u8>>>>,
@@ -0,0 +1,29 @@
+#![cfg(all(unix, feature = "mio", not(feature = "async")))]
+
+use std::time::Duration;
+
+use expectrl::{spawn, Expect};
+use mio::{Events, Interest, Poll, Token};
+
+#[test]
+fn mio_source() {
+    let mut session = spawn("cat").unwrap();
+
+    let mut poll = Poll::new().unwrap();
+    poll.registry()
+        .register(&mut session, Token(0), Interest::READABLE)
+        .unwrap();
+
+    session.send_line("Hello World").unwrap();
+
+    let mut events = Events::with_capacity(16);
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    assert!(events
+        .iter()
+        .any(|event| event.token() == Token(0) && event.is_readable()));
+
+    session.expect("Hello World").unwrap();
+
+    poll.registry().deregister(&mut session).unwrap();
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use expectrl::{
+    clock::ManualClock,
+    process::{mock::MockProcess, Process},
+    Error, Session,
+};
+
+#[cfg(not(feature = "async"))]
+use expectrl::Expect;
+
+#[cfg(feature = "async")]
+use expectrl::AsyncExpect;
+
+fn spawn_mock(process: MockProcess) -> Session<MockProcess, expectrl::process::mock::MockStream> {
+    let mut process = MockProcess::spawn_command(process).unwrap();
+    let stream = process.open_stream().unwrap();
+    Session::new(process, stream).unwrap()
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn mock_session() {
+    let clock = ManualClock::new();
+    let process = MockProcess::new()
+        .clock(clock.clone())
+        .echo(true)
+        .output("> ")
+        .on_input_after("date\n", Duration::from_secs(5), "Mon Jan 1\n> ")
+        .on_input("exit\n", "")
+        .exit(0);
+
+    let mut session = spawn_mock(process);
+    session.set_clock(clock.clone());
+    session.set_expect_timeout(Some(Duration::from_secs(1)));
+
+    session.expect("> ").unwrap();
+    session.send_line("date").unwrap();
+    session.expect("date").unwrap();
+
+    // the output is delayed for longer than the timeout
+    let handle = std::thread::spawn({
+        let clock = clock.clone();
+        move || {
+            std::thread::sleep(Duration::from_millis(100));
+            clock.advance(Duration::from_secs(2));
+        }
+    });
+    assert!(matches!(
+        session.expect("Mon"),
+        Err(Error::ExpectTimeout { .. })
+    ));
+    handle.join().unwrap();
+
+    clock.advance(Duration::from_secs(3));
+    session.expect("Mon Jan 1").unwrap();
+
+    session.send_line("exit").unwrap();
+    let status = session.wait_for_exit(None).unwrap();
+    assert!(status.success());
+    assert_eq!(session.get_process().get_written(), b"date\nexit\n");
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn mock_session() {
+    let clock = ManualClock::new();
+    let process = MockProcess::new()
+        .clock(clock.clone())
+        .echo(true)
+        .output("> ")
+        .on_input_after("date\n", Duration::from_secs(5), "Mon Jan 1\n> ")
+        .on_input("exit\n", "")
+        .exit(0);
+
+    let mut session = spawn_mock(process);
+    session.set_clock(clock.clone());
+    session.set_expect_timeout(Some(Duration::from_secs(1)));
+
+    futures_lite::future::block_on(async {
+        session.expect("> ").await.unwrap();
+        session.send_line("date").await.unwrap();
+        session.expect("date").await.unwrap();
+
+        // the output is delayed for longer than the timeout
+        let handle = std::thread::spawn({
+            let clock = clock.clone();
+            move || {
+                std::thread::sleep(Duration::from_millis(100));
+                clock.advance(Duration::from_secs(2));
+            }
+        });
+        assert!(matches!(
+            session.expect("Mon").await,
+            Err(Error::ExpectTimeout { .. })
+        ));
+        handle.join().unwrap();
+
+        session.set_expect_timeout(None);
+        let handle = std::thread::spawn({
+            let clock = clock.clone();
+            move || {
+                std::thread::sleep(Duration::from_millis(100));
+                clock.advance(Duration::from_secs(3));
+            }
+        });
+        session.expect("Mon Jan 1").await.unwrap();
+        handle.join().unwrap();
+
+        session.send_line("exit").await.unwrap();
+        let status = session.wait_for_exit(None).await.unwrap();
+        assert!(status.success());
+    });
+
+    assert_eq!(session.get_process().get_written(), b"date\nexit\n");
+}
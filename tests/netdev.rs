@@ -0,0 +1,94 @@
+#![cfg(unix)]
+
+use expectrl::{
+    dialog::LoginOutcome,
+    netdev::{Dialect, NetDevice},
+    session::OsSession,
+};
+
+const CISCO_SCRIPT: &str = r#"
+printf "Username: "; read u
+printf "Password: "; read p
+prompt='router>'
+while printf '%s' "$prompt"; read -r line; do
+  case "$line" in
+    enable)
+      printf 'Password: '; read -r pw
+      if [ "$pw" = "enable" ]; then prompt='router#'; else echo '% Access denied'; fi ;;
+    'terminal length 0') ;;
+    'configure terminal') prompt='router(config)#' ;;
+    end) prompt='router#' ;;
+    'interface '*) prompt='router(config-if)#' ;;
+    'description '*) ;;
+    *) echo "% Invalid input detected at '^' marker." ;;
+  esac
+done
+"#;
+
+fn spawn_router() -> NetDevice<OsSession> {
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", CISCO_SCRIPT]);
+    let session = OsSession::spawn(cmd).unwrap();
+    NetDevice::new(session, Dialect::Cisco)
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn cisco() {
+    let mut router = spawn_router();
+
+    let outcome = router.login("admin", "secret").unwrap();
+    assert_eq!(outcome, LoginOutcome::Shell);
+
+    router.enable("enable").unwrap();
+    router.disable_paging().unwrap();
+
+    let _ = router
+        .configure(["interface Gi0/1", "description uplink"])
+        .unwrap();
+
+    assert!(router.configure(["shutdown now"]).is_err());
+    assert!(router.execute("show bogus").is_err());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn cisco() {
+    futures_lite::future::block_on(async {
+        let mut router = spawn_router();
+
+        let outcome = router.login("admin", "secret").await.unwrap();
+        assert_eq!(outcome, LoginOutcome::Shell);
+
+        router.enable("enable").await.unwrap();
+        router.disable_paging().await.unwrap();
+
+        let _ = router
+            .configure(["interface Gi0/1", "description uplink"])
+            .await
+            .unwrap();
+
+        assert!(router.configure(["shutdown now"]).await.is_err());
+        assert!(router.execute("show bogus").await.is_err());
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn cisco_enable_failure() {
+    let mut router = spawn_router();
+    let _ = router.login("admin", "secret").unwrap();
+
+    assert!(router.enable("wrong").is_err());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn cisco_enable_failure() {
+    futures_lite::future::block_on(async {
+        let mut router = spawn_router();
+        let _ = router.login("admin", "secret").await.unwrap();
+
+        assert!(router.enable("wrong").await.is_err());
+    })
+}
@@ -0,0 +1,81 @@
+#![cfg(all(unix, feature = "pexpect-compat"))]
+
+use std::time::Duration;
+
+use expectrl::{pexpect::PexpectScript, Error};
+
+const SCRIPT: &str = r#"
+command: cat
+timeout: 5
+steps:
+  - sendline: Hello World
+  - expect: 'W\w+d'
+  - send: "Bye\n"
+  - expect_exact: ['Hi', 'Bye']
+  - sendcontrol: d
+  - expect_eof: null
+"#;
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn pexpect_run() {
+    let script = PexpectScript::from_yaml(SCRIPT).unwrap();
+    let mut session = script.spawn().unwrap();
+    script.run(&mut session).unwrap();
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn pexpect_run() {
+    futures_lite::future::block_on(async {
+        let script = PexpectScript::from_yaml(SCRIPT).unwrap();
+        let mut session = script.spawn().unwrap();
+        script.run(&mut session).await.unwrap();
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn pexpect_timeout() {
+    let script = PexpectScript::from_json(
+        r#"{"command": "cat", "steps": [{"sendline": "Hello"}, {"expect": "Bye", "timeout": 0.3}]}"#,
+    )
+    .unwrap();
+
+    let mut session = script.spawn().unwrap();
+    session.set_expect_timeout(Some(Duration::from_secs(10)));
+
+    match script.run(&mut session) {
+        Err(Error::Script { step, err }) => {
+            assert_eq!(step, [1]);
+            assert!(matches!(*err, Error::ExpectTimeout { .. }));
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    assert_eq!(session.get_expect_timeout(), Some(Duration::from_secs(10)));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn pexpect_timeout() {
+    futures_lite::future::block_on(async {
+        let script = PexpectScript::from_json(
+            r#"{"command": "cat", "steps": [{"sendline": "Hello"}, {"expect": "Bye", "timeout": 0.3}]}"#,
+        )
+        .unwrap();
+
+        let mut session = script.spawn().unwrap();
+        session.set_expect_timeout(Some(Duration::from_secs(10)));
+
+        match script.run(&mut session).await {
+            Err(Error::Script { step, err }) => {
+                assert_eq!(step, [1]);
+                assert!(matches!(*err, Error::ExpectTimeout { .. }));
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        assert_eq!(session.get_expect_timeout(), Some(Duration::from_secs(10)));
+    })
+}
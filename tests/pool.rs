@@ -0,0 +1,114 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+
+use expectrl::{spawn, Error, SessionPool};
+
+#[cfg(not(feature = "async"))]
+use expectrl::Expect;
+
+#[cfg(feature = "async")]
+use expectrl::AsyncExpect;
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn pool_expect_all() {
+    let mut pool = (0..3)
+        .map(|_| spawn("cat").unwrap())
+        .collect::<SessionPool<_>>();
+    assert_eq!(pool.len(), 3);
+
+    pool.broadcast_send_line("Hello World").unwrap();
+    let found = pool.expect_all("World").unwrap();
+    assert_eq!(found.len(), 3);
+    assert!(found.iter().all(|m| m.get(0) == Some(b"World".as_ref())));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn pool_expect_all() {
+    futures_lite::future::block_on(async {
+        let mut pool = (0..3)
+            .map(|_| spawn("cat").unwrap())
+            .collect::<SessionPool<_>>();
+        assert_eq!(pool.len(), 3);
+
+        pool.broadcast_send_line("Hello World").await.unwrap();
+        let found = pool.expect_all("World").await.unwrap();
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().all(|m| m.get(0) == Some(b"World".as_ref())));
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn pool_expect_any() {
+    let mut pool = SessionPool::new();
+    let _ = pool.push(spawn("cat").unwrap());
+    let index = pool.push(spawn("cat").unwrap());
+
+    pool.get_mut(index)
+        .unwrap()
+        .send_line("Hello World")
+        .unwrap();
+    let (i, _) = pool.expect_any("Hello").unwrap();
+    assert_eq!(i, index);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn pool_expect_any() {
+    futures_lite::future::block_on(async {
+        let mut pool = SessionPool::new();
+        let _ = pool.push(spawn("cat").unwrap());
+        let index = pool.push(spawn("cat").unwrap());
+
+        pool.get_mut(index)
+            .unwrap()
+            .send_line("Hello World")
+            .await
+            .unwrap();
+        let (i, _) = pool.expect_any("Hello").await.unwrap();
+        assert_eq!(i, index);
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn pool_expect_all_timeout() {
+    let mut pool = SessionPool::from(vec![spawn("cat").unwrap(), spawn("cat").unwrap()]);
+    pool.set_expect_timeout(Some(Duration::from_millis(300)));
+
+    pool.get_mut(0).unwrap().send_line("Hello World").unwrap();
+
+    match pool.expect_all("Hello") {
+        Err(Error::Pool { index, err }) => {
+            assert_eq!(index, 1);
+            assert!(matches!(*err, Error::ExpectTimeout { .. }));
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn pool_expect_all_timeout() {
+    futures_lite::future::block_on(async {
+        let mut pool = SessionPool::from(vec![spawn("cat").unwrap(), spawn("cat").unwrap()]);
+        pool.set_expect_timeout(Some(Duration::from_millis(300)));
+
+        pool.get_mut(0)
+            .unwrap()
+            .send_line("Hello World")
+            .await
+            .unwrap();
+
+        match pool.expect_all("Hello").await {
+            Err(Error::Pool { index, err }) => {
+                assert_eq!(index, 1);
+                assert!(matches!(*err, Error::ExpectTimeout { .. }));
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    })
+}
@@ -183,8 +183,7 @@ fn bash_pwd() {
         let mut p = spawn_bash().await.unwrap();
         p.execute("cd /tmp/").await.unwrap();
         p.send_line("pwd").await.unwrap();
-        let mut pwd = String::new();
-        p.read_line(&mut pwd).await.unwrap();
+        let pwd = p.expect_prompt().await.unwrap();
         assert!(pwd.contains("/tmp\r\n"));
     });
 }
@@ -211,8 +210,7 @@ fn bash_pwd() {
     let mut p = spawn_bash().unwrap();
     p.execute("cd /tmp/").unwrap();
     p.send_line("pwd").unwrap();
-    let mut pwd = String::new();
-    p.read_line(&mut pwd).unwrap();
+    let pwd = p.expect_prompt().unwrap();
     assert!(pwd.contains("/tmp\r\n"));
 }
 
@@ -229,3 +227,301 @@ fn bash_control_chars() {
     p.send(ControlCode::Substitute).unwrap(); // suspend:SIGTSTPcon
     p.expect_prompt().unwrap();
 }
+
+#[cfg(not(feature = "async"))]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_execute_lines() {
+    let mut p = spawn_bash().unwrap();
+
+    let lines = p.execute_lines("printf 'Hello\\nWorld\\n'").unwrap();
+    assert_eq!(lines, ["Hello", "World"]);
+
+    p.get_session_mut().get_process_mut().exit(true).unwrap();
+}
+
+#[cfg(feature = "async")]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_execute_lines() {
+    futures_lite::future::block_on(async {
+        let mut p = spawn_bash().await.unwrap();
+
+        let lines = p.execute_lines("printf 'Hello\\nWorld\\n'").await.unwrap();
+        assert_eq!(lines, ["Hello", "World"]);
+
+        p.get_session_mut().get_process_mut().exit(true).unwrap();
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_with_options() {
+    use expectrl::repl::{spawn_bash_with, BashOptions};
+
+    let options = BashOptions::new()
+        .prompt("CUSTOM_PROMPT")
+        .rcfile("/dev/null")
+        .env("FOO", "foo")
+        .init_command("export BAR=bar");
+
+    let mut p = spawn_bash_with(options).unwrap();
+    assert_eq!(p.get_prompt(), "CUSTOM_PROMPT");
+
+    let lines = p.execute_lines("echo $FOO $BAR").unwrap();
+    assert_eq!(lines, ["foo bar"]);
+
+    p.get_session_mut().get_process_mut().exit(true).unwrap();
+}
+
+#[cfg(feature = "async")]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_with_options() {
+    use expectrl::repl::{spawn_bash_with, BashOptions};
+
+    futures_lite::future::block_on(async {
+        let options = BashOptions::new()
+            .prompt("CUSTOM_PROMPT")
+            .rcfile("/dev/null")
+            .env("FOO", "foo")
+            .init_command("export BAR=bar");
+
+        let mut p = spawn_bash_with(options).await.unwrap();
+        assert_eq!(p.get_prompt(), "CUSTOM_PROMPT");
+
+        let lines = p.execute_lines("echo $FOO $BAR").await.unwrap();
+        assert_eq!(lines, ["foo bar"]);
+
+        p.get_session_mut().get_process_mut().exit(true).unwrap();
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+#[ignore = "requires ipython to be installed"]
+fn ipython() {
+    let mut p = expectrl::repl::spawn_ipython().unwrap();
+
+    let lines = p.execute_lines("print('Hello World')").unwrap();
+    assert_eq!(lines, ["Hello World"]);
+
+    p.exit().unwrap();
+}
+
+#[cfg(feature = "async")]
+#[test]
+#[ignore = "requires ipython to be installed"]
+fn ipython() {
+    futures_lite::future::block_on(async {
+        let mut p = expectrl::repl::spawn_ipython().await.unwrap();
+
+        let lines = p.execute_lines("print('Hello World')").await.unwrap();
+        assert_eq!(lines, ["Hello World"]);
+
+        p.exit().await.unwrap();
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+#[ignore = "requires node to be installed"]
+fn node() {
+    let mut p = expectrl::repl::spawn_node().unwrap();
+
+    let lines = p.execute_lines("console.log('Hello World')").unwrap();
+    assert_eq!(lines, ["Hello World", "undefined"]);
+
+    p.exit().unwrap();
+}
+
+#[cfg(feature = "async")]
+#[test]
+#[ignore = "requires node to be installed"]
+fn node() {
+    futures_lite::future::block_on(async {
+        let mut p = expectrl::repl::spawn_node().await.unwrap();
+
+        let lines = p.execute_lines("console.log('Hello World')").await.unwrap();
+        assert_eq!(lines, ["Hello World", "undefined"]);
+
+        p.exit().await.unwrap();
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+#[ignore = "requires gdb to be installed"]
+fn gdb() {
+    use expectrl::repl::{spawn_gdb_with, GdbOptions};
+
+    for mi in [false, true] {
+        let options = GdbOptions::new().mi(mi).args(["-c", "exit 3"]);
+        let mut p = spawn_gdb_with("/bin/sh", options).unwrap();
+
+        let err = p.run_until_break().unwrap_err();
+        assert!(
+            matches!(err, expectrl::Error::UnexpectedExit { status, .. } if status.code() == Some(3)),
+            "{err:?}"
+        );
+
+        p.exit().unwrap();
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+#[ignore = "requires gdb to be installed"]
+fn gdb() {
+    use expectrl::repl::{spawn_gdb_with, GdbOptions};
+
+    futures_lite::future::block_on(async {
+        let options = GdbOptions::new().args(["-c", "exit 3"]);
+        let mut p = spawn_gdb_with("/bin/sh", options).await.unwrap();
+
+        let err = p.run_until_break().await.unwrap_err();
+        assert!(
+            matches!(err, expectrl::Error::UnexpectedExit { status, .. } if status.code() == Some(3)),
+            "{err:?}"
+        );
+
+        p.exit().await.unwrap();
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_execute_multiline() {
+    let mut p = spawn_bash().unwrap();
+
+    let lines = p.execute_lines("cat <<EOF\nHello\nWorld\nEOF").unwrap();
+    assert_eq!(lines.last().unwrap(), "World");
+
+    let lines = p.execute_lines("echo after").unwrap();
+    assert_eq!(lines, ["after"]);
+
+    p.get_session_mut().get_process_mut().exit(true).unwrap();
+}
+
+#[cfg(feature = "async")]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_execute_multiline() {
+    futures_lite::future::block_on(async {
+        let mut p = spawn_bash().await.unwrap();
+
+        let lines = p
+            .execute_lines("cat <<EOF\nHello\nWorld\nEOF")
+            .await
+            .unwrap();
+        assert_eq!(lines.last().unwrap(), "World");
+
+        let lines = p.execute_lines("echo after").await.unwrap();
+        assert_eq!(lines, ["after"]);
+
+        p.get_session_mut().get_process_mut().exit(true).unwrap();
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn python_execute_multiline() {
+    let mut p = spawn_python().unwrap();
+
+    let _ = p.execute("def f():\n    return 'Hello World'\n").unwrap();
+
+    let out = p.execute("print(f())").unwrap();
+    let out = String::from_utf8_lossy(&out);
+    assert!(out.contains("Hello World"), "{out:?}");
+
+    p.exit().unwrap();
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn python_execute_multiline() {
+    futures_lite::future::block_on(async {
+        let mut p = spawn_python().await.unwrap();
+
+        let _ = p
+            .execute("def f():\n    return 'Hello World'\n")
+            .await
+            .unwrap();
+
+        let out = p.execute("print(f())").await.unwrap();
+        let out = String::from_utf8_lossy(&out);
+        assert!(out.contains("Hello World"), "{out:?}");
+
+        p.exit().await.unwrap();
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_sync() {
+    let mut p = spawn_bash().unwrap();
+
+    p.send_line("echo stale").unwrap();
+    p.sync().unwrap();
+
+    p.send_line("echo fresh").unwrap();
+    let found = p.expect("fresh").unwrap();
+    assert!(!found.before_str_lossy().contains("stale"));
+}
+
+#[cfg(feature = "async")]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_sync() {
+    futures_lite::future::block_on(async {
+        let mut p = spawn_bash().await.unwrap();
+
+        p.send_line("echo stale").await.unwrap();
+        p.sync().await.unwrap();
+
+        p.send_line("echo fresh").await.unwrap();
+        let found = p.expect("fresh").await.unwrap();
+        assert!(!found.before_str_lossy().contains("stale"));
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_execute_checked() {
+    let mut p = spawn_bash().unwrap();
+
+    let (out, code) = p.execute_checked("echo Hello; false").unwrap();
+    assert!(String::from_utf8_lossy(&out).contains("Hello"));
+    assert_eq!(code, 1);
+
+    let out = p.execute_success("echo Hello").unwrap();
+    assert!(String::from_utf8_lossy(&out).contains("Hello"));
+
+    let err = p
+        .execute_success("exit_with_3() { return 3; }; exit_with_3")
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        expectrl::Error::UnexpectedExit { status, .. } if status.code() == Some(3)
+    ));
+}
+
+#[cfg(feature = "async")]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_execute_checked() {
+    futures_lite::future::block_on(async {
+        let mut p = spawn_bash().await.unwrap();
+
+        let (out, code) = p.execute_checked("echo Hello; false").await.unwrap();
+        assert!(String::from_utf8_lossy(&out).contains("Hello"));
+        assert_eq!(code, 1);
+
+        let out = p.execute_success("echo Hello").await.unwrap();
+        assert!(String::from_utf8_lossy(&out).contains("Hello"));
+    })
+}
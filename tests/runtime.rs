@@ -0,0 +1,49 @@
+#![cfg(unix)]
+
+#[cfg(any(
+    feature = "runtime-async-std",
+    feature = "runtime-smol",
+    feature = "runtime-tokio"
+))]
+use expectrl::{AsyncExpect, Session};
+#[cfg(any(
+    feature = "runtime-async-std",
+    feature = "runtime-smol",
+    feature = "runtime-tokio"
+))]
+use std::process::Command;
+
+#[cfg(feature = "runtime-async-std")]
+#[test]
+fn async_std_runtime() {
+    async_std::task::block_on(async {
+        let mut session = Session::spawn_async_std(Command::new("cat")).unwrap();
+        session.send_line("Hello World").await.unwrap();
+        session.expect("Hello World").await.unwrap();
+    })
+}
+
+#[cfg(feature = "runtime-smol")]
+#[test]
+fn smol_runtime() {
+    smol::block_on(async {
+        let mut session = Session::spawn_smol(Command::new("cat")).unwrap();
+        session.send_line("Hello World").await.unwrap();
+        session.expect("Hello World").await.unwrap();
+    })
+}
+
+#[cfg(feature = "runtime-tokio")]
+#[test]
+fn tokio_runtime() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        let mut session = Session::spawn_tokio(Command::new("cat")).unwrap();
+        session.send_line("Hello World").await.unwrap();
+        session.expect("Hello World").await.unwrap();
+    })
+}
@@ -0,0 +1,64 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+
+use expectrl::{
+    scenario::{Action, Scenario},
+    spawn,
+};
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn scenario_report() {
+    let mut session = spawn("cat").unwrap();
+    session.set_expect_timeout(Some(Duration::from_millis(300)));
+
+    let mut scenario = Scenario::new("echo", session);
+    scenario.send_line("Hello World").unwrap();
+    scenario.expect("World").unwrap();
+    assert!(scenario.is_ok());
+
+    scenario.expect("Bye").unwrap_err();
+    assert!(!scenario.is_ok());
+
+    let steps = scenario.steps();
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[0].action(), Action::SendLine);
+    assert_eq!(steps[0].argument(), "Hello World");
+    assert_eq!(steps[1].action(), Action::Expect);
+    assert_eq!(steps[1].argument(), "\"World\"");
+    assert_eq!(steps[1].output(), b"Hello World");
+    assert!(steps[2].error().is_some());
+
+    let report = scenario.to_markdown();
+    assert!(report.contains("| 3 | expect | `\"Bye\"` | failed |"));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn scenario_report() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.set_expect_timeout(Some(Duration::from_millis(300)));
+
+        let mut scenario = Scenario::new("echo", session);
+        scenario.send_line("Hello World").await.unwrap();
+        scenario.expect("World").await.unwrap();
+        assert!(scenario.is_ok());
+
+        scenario.expect("Bye").await.unwrap_err();
+        assert!(!scenario.is_ok());
+
+        let steps = scenario.steps();
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[0].action(), Action::SendLine);
+        assert_eq!(steps[0].argument(), "Hello World");
+        assert_eq!(steps[1].action(), Action::Expect);
+        assert_eq!(steps[1].argument(), "\"World\"");
+        assert_eq!(steps[1].output(), b"Hello World");
+        assert!(steps[2].error().is_some());
+
+        let report = scenario.to_markdown();
+        assert!(report.contains("| 3 | expect | `\"Bye\"` | failed |"));
+    })
+}
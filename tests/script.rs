@@ -0,0 +1,86 @@
+#![cfg(unix)]
+
+use expectrl::{
+    script::{Script, Step},
+    spawn, Error,
+};
+
+#[cfg(not(feature = "async"))]
+use expectrl::Expect;
+
+#[cfg(feature = "async")]
+use expectrl::AsyncExpect;
+
+fn script() -> Script {
+    Script::new()
+        .send_line("Hello World")
+        .expect("Hello World")
+        .repeat(vec![
+            Step::SendLine(b"next".to_vec()),
+            Step::Branch(vec![
+                (Box::new("next"), vec![Step::SendLine(b"stop".to_vec())]),
+                (Box::new("never"), vec![]),
+            ]),
+            Step::Expect(Box::new("stop")),
+            Step::Break,
+        ])
+        .send_line("Bye")
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn script_run() {
+    let mut session = spawn("cat").unwrap();
+    script().run(&mut session).unwrap();
+    session.expect("Bye").unwrap();
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn script_run() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        script().run(&mut session).await.unwrap();
+        session.expect("Bye").await.unwrap();
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn script_error() {
+    let mut session = spawn("cat").unwrap();
+    session.set_expect_timeout(Some(std::time::Duration::from_millis(300)));
+
+    let script = Script::new()
+        .send_line("Hello World")
+        .repeat(vec![Step::Expect(Box::new("Goodbye"))]);
+
+    match script.run(&mut session) {
+        Err(Error::Script { step, err }) => {
+            assert_eq!(step, [1, 0]);
+            assert!(matches!(*err, Error::ExpectTimeout { .. }));
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn script_error() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.set_expect_timeout(Some(std::time::Duration::from_millis(300)));
+
+        let script = Script::new()
+            .send_line("Hello World")
+            .repeat(vec![Step::Expect(Box::new("Goodbye"))]);
+
+        match script.run(&mut session).await {
+            Err(Error::Script { step, err }) => {
+                assert_eq!(step, [1, 0]);
+                assert!(matches!(*err, Error::ExpectTimeout { .. }));
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    })
+}
@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+use expectrl::{select, select_timeout, spawn, Error};
+use std::time::Duration;
+
+#[cfg(not(feature = "async"))]
+use expectrl::Expect;
+
+#[cfg(feature = "async")]
+use expectrl::AsyncExpect;
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn select_reports_matched_session() {
+    let mut sessions = [spawn("cat").unwrap(), spawn("cat").unwrap()];
+    sessions[1].send_line("Hello World").unwrap();
+
+    let (index, found) = select(&mut sessions, "World").unwrap();
+    assert_eq!(index, 1);
+    assert_eq!(found.before(), b"Hello ");
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn select_reports_matched_session() {
+    futures_lite::future::block_on(async {
+        let mut sessions = [spawn("cat").unwrap(), spawn("cat").unwrap()];
+        sessions[1].send_line("Hello World").await.unwrap();
+
+        let (index, found) = select(&mut sessions, "World").await.unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(found.before(), b"Hello ");
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn select_timeout_expires() {
+    let mut sessions = [spawn("cat").unwrap(), spawn("cat").unwrap()];
+
+    let err = select_timeout(&mut sessions, "Hello", Some(Duration::from_millis(300))).unwrap_err();
+    assert!(matches!(err, Error::ExpectTimeout { .. }));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn select_timeout_expires() {
+    futures_lite::future::block_on(async {
+        let mut sessions = [spawn("cat").unwrap(), spawn("cat").unwrap()];
+
+        let err = select_timeout(&mut sessions, "Hello", Some(Duration::from_millis(300)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ExpectTimeout { .. }));
+    })
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn select_skips_closed_sessions() {
+    let mut sessions = [spawn("echo Hello").unwrap(), spawn("cat").unwrap()];
+    sessions[0].expect("Hello").unwrap();
+    sessions[1].send_line("World").unwrap();
+
+    let (index, _) = select(&mut sessions, "World").unwrap();
+    assert_eq!(index, 1);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn select_skips_closed_sessions() {
+    futures_lite::future::block_on(async {
+        let mut sessions = [spawn("echo Hello").unwrap(), spawn("cat").unwrap()];
+        sessions[0].expect("Hello").await.unwrap();
+        sessions[1].send_line("World").await.unwrap();
+
+        let (index, _) = select(&mut sessions, "World").await.unwrap();
+        assert_eq!(index, 1);
+    })
+}
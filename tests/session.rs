@@ -155,66 +155,1854 @@ fn send_line() {
     })
 }
 
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn send_line_with_line_ending() {
+    let mut session = spawn("cat").unwrap();
+    session.set_line_ending(expectrl::LineEnding::Cr);
+    session.send_line("Hello World").unwrap();
+
+    // a terminal translates CR into NL on input.
+    let m = session.expect('\n').unwrap();
+    let buf = String::from_utf8_lossy(m.before());
+    assert_eq!(buf, "Hello World\r");
+
+    session.send_line_lf("Hello").unwrap();
+
+    let m = session.expect('\n').unwrap();
+    let buf = String::from_utf8_lossy(m.before());
+    assert_eq!(buf, "Hello\r");
+
+    session.get_process_mut().exit(true).unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn send_line_with_line_ending() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.set_line_ending(expectrl::LineEnding::Cr);
+        session.send_line("Hello World").await.unwrap();
+
+        // a terminal translates CR into NL on input.
+        let m = session.expect('\n').await.unwrap();
+        let buf = String::from_utf8_lossy(m.before());
+        assert_eq!(buf, "Hello World\r");
+
+        session.send_line_lf("Hello").await.unwrap();
+
+        let m = session.expect('\n').await.unwrap();
+        let buf = String::from_utf8_lossy(m.before());
+        assert_eq!(buf, "Hello\r");
+
+        session.get_process_mut().exit(true).unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn detach_and_attach() {
+    let mut session = spawn("cat").unwrap();
+    session.set_line_ending(expectrl::LineEnding::Cr);
+    session.send_line("Hello World").unwrap();
+    session.expect("Hello").unwrap();
+
+    let detached = session.detach();
+
+    let mut session = std::thread::spawn(move || detached.attach().unwrap())
+        .join()
+        .unwrap();
+
+    assert_eq!(session.get_line_ending(), expectrl::LineEnding::Cr);
+
+    let m = session.expect('\n').unwrap();
+    let buf = String::from_utf8_lossy(m.before());
+    assert_eq!(buf, " World\r");
+
+    session.get_process_mut().exit(true).unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn detach_and_attach() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.set_line_ending(expectrl::LineEnding::Cr);
+        session.send_line("Hello World").await.unwrap();
+        session.expect("Hello").await.unwrap();
+
+        let detached = session.detach();
+
+        let mut session = std::thread::spawn(move || detached.attach().unwrap())
+            .join()
+            .unwrap();
+
+        assert_eq!(session.get_line_ending(), expectrl::LineEnding::Cr);
+
+        let m = session.expect('\n').await.unwrap();
+        let buf = String::from_utf8_lossy(m.before());
+        assert_eq!(buf, " World\r");
+
+        session.get_process_mut().exit(true).unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn detached_state() {
+    use expectrl::session::DetachedSession;
+
+    let mut session = spawn("cat").unwrap();
+    session.set_name("cat");
+    session.send_line("Hello World").unwrap();
+    session.expect("Hello").unwrap();
+
+    let pid = session.get_process().pid().as_raw() as u32;
+    let detached = session.detach();
+
+    let state = detached.state();
+    assert_eq!(state.get_pid(), pid);
+    assert_eq!(state.get_name(), Some("cat"));
+    assert!(b" World\r\n".starts_with(state.get_buffer()));
+
+    let (process, stream, _) = detached.into_parts();
+    let mut session = DetachedSession::from_state(process, stream, state)
+        .attach()
+        .unwrap();
+
+    let m = session.expect('\n').unwrap();
+    assert_eq!(m.before(), b" World\r");
+
+    session.get_process_mut().exit(true).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn spawn_with_pre_exec() {
+    let mut session = unsafe {
+        expectrl::Session::spawn_with_pre_exec(std::process::Command::new("pwd"), || {
+            nix::unistd::chdir("/")?;
+            Ok(())
+        })
+    }
+    .unwrap();
+
+    #[cfg(not(feature = "async"))]
+    let m = session.expect("\r\n").unwrap();
+    #[cfg(feature = "async")]
+    let m = futures_lite::future::block_on(session.expect("\r\n")).unwrap();
+
+    assert_eq!(m.before(), b"/");
+}
+
+#[cfg(unix)]
+#[test]
+fn spawn_sandboxed() {
+    use expectrl::process::unix::Sandbox;
+
+    let mut cmd = std::process::Command::new("pwd");
+    let _ = cmd.current_dir("/tmp");
+
+    let sandbox = Sandbox::new().current_dir("/");
+    let mut session = expectrl::Session::spawn_sandboxed(cmd, &sandbox).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    let m = session.expect("\r\n").unwrap();
+    #[cfg(feature = "async")]
+    let m = futures_lite::future::block_on(session.expect("\r\n")).unwrap();
+
+    assert_eq!(m.before(), b"/");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[ignore = "requires unprivileged user namespaces to be enabled"]
+fn spawn_sandboxed_user_namespace() {
+    use expectrl::process::unix::Sandbox;
+
+    let sandbox = Sandbox::new().map_root_user(true);
+    let mut session =
+        expectrl::Session::spawn_sandboxed(std::process::Command::new("id"), &sandbox).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    let m = session.expect("\r\n").unwrap();
+    #[cfg(feature = "async")]
+    let m = futures_lite::future::block_on(session.expect("\r\n")).unwrap();
+
+    assert!(m.before_str_lossy().starts_with("uid=0("));
+}
+
+#[cfg(unix)]
+#[test]
+fn signal_group() {
+    use expectrl::process::unix::{Signal, WaitStatus};
+
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "sleep 100; echo done"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+    session.signal_group(Signal::SIGKILL).unwrap();
+
+    let status = session.get_process().wait().unwrap();
+    assert_eq!(
+        status,
+        WaitStatus::Signaled(session.get_process().pid(), Signal::SIGKILL, false)
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn kill_tree() {
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "setsid sleep 100 & echo $!; wait"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    let m = session.expect(expectrl::Regex("\\d+")).unwrap();
+    #[cfg(feature = "async")]
+    let m = futures_lite::future::block_on(session.expect(expectrl::Regex("\\d+"))).unwrap();
+
+    let daemon = String::from_utf8_lossy(m.get(0).unwrap()).to_string();
+
+    session.kill_tree().unwrap();
+
+    // killed daemon is reparented so it may stay as a zombie for a while.
+    let is_running = || {
+        std::fs::read_to_string(format!("/proc/{}/stat", daemon))
+            .map(|stat| !stat.contains(") Z "))
+            .unwrap_or(false)
+    };
+
+    let start = std::time::Instant::now();
+    while is_running() && start.elapsed() < std::time::Duration::from_secs(5) {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    assert!(!is_running());
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn watchdog() {
+    use expectrl::session::WatchdogAction;
+    use std::time::Duration;
+
+    let mut session = spawn("cat").unwrap();
+    session.set_watchdog(Duration::from_millis(300), |_| {
+        WatchdogAction::Send(b"Hello World\n".to_vec())
+    });
+    session.expect("Hello World").unwrap();
+
+    session.set_watchdog(Duration::from_millis(300), |_| WatchdogAction::Abort);
+    let err = session.expect("Something else").unwrap_err();
+    assert!(matches!(err, expectrl::Error::Other { .. }));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn watchdog() {
+    use expectrl::session::WatchdogAction;
+    use std::time::Duration;
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.set_watchdog(Duration::from_millis(300), |_| {
+            WatchdogAction::Send(b"Hello World\n".to_vec())
+        });
+        session.expect("Hello World").await.unwrap();
+
+        session.set_watchdog(Duration::from_millis(300), |_| WatchdogAction::Abort);
+        let err = session.expect("Something else").await.unwrap_err();
+        assert!(matches!(err, expectrl::Error::Other { .. }));
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_until_deadline() {
+    use std::time::{Duration, Instant};
+
+    let mut session = spawn("cat").unwrap();
+    let deadline = Instant::now() + Duration::from_millis(500);
+
+    session.send_line("Hello World").unwrap();
+
+    let mut line = String::new();
+    let n = session.read_line_until(&mut line, deadline).unwrap();
+    assert_eq!(line, "Hello World\r\n");
+    assert_eq!(n, line.len());
+
+    let err = session
+        .expect_until("Something else", deadline)
+        .unwrap_err();
+    assert!(matches!(err, expectrl::Error::ExpectTimeout { .. }));
+    assert!(Instant::now() >= deadline);
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_until_deadline() {
+    use std::time::{Duration, Instant};
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        let deadline = Instant::now() + Duration::from_millis(500);
+
+        session.send_line("Hello World").await.unwrap();
+
+        let mut line = String::new();
+        let n = session.read_line_until(&mut line, deadline).await.unwrap();
+        assert_eq!(line, "Hello World\r\n");
+        assert_eq!(n, line.len());
+
+        let err = session
+            .expect_until("Something else", deadline)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, expectrl::Error::ExpectTimeout { .. }));
+        assert!(Instant::now() >= deadline);
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_offset() {
+    let mut session = spawn("cat").unwrap();
+    session.send_line("Hello World").unwrap();
+
+    let m = session.expect("World").unwrap();
+    assert_eq!(m.offset(), 0);
+    assert_eq!(m.match_range(0), Some(6..11));
+    assert_eq!(m.as_str_lossy(), "Hello World");
+    assert_eq!(m.before_str_lossy(), "Hello ");
+
+    let m = session.expect("\n").unwrap();
+    assert_eq!(m.offset(), 11);
+    assert_eq!(m.match_range(0), Some(12..13));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_offset() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.send_line("Hello World").await.unwrap();
+
+        let m = session.expect("World").await.unwrap();
+        assert_eq!(m.offset(), 0);
+        assert_eq!(m.match_range(0), Some(6..11));
+        assert_eq!(m.as_str_lossy(), "Hello World");
+        assert_eq!(m.before_str_lossy(), "Hello ");
+
+        let m = session.expect("\n").await.unwrap();
+        assert_eq!(m.offset(), 11);
+        assert_eq!(m.match_range(0), Some(12..13));
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn abort_pattern() {
+    use expectrl::Regex;
+
+    let mut session = spawn("cat").unwrap();
+    session.add_abort_pattern(Regex("panic|Segmentation fault"));
+    session.send_line("Segmentation fault").unwrap();
+
+    // "Done" never comes so the expect would wait till a timeout without the pattern.
+    let err = session.expect("Done").unwrap_err();
+    match err {
+        expectrl::Error::Aborted { pattern, captures } => {
+            assert_eq!(pattern, "Regex(\"panic|Segmentation fault\")");
+            assert_eq!(captures.as_bytes(), b"Segmentation fault");
+        }
+        err => panic!("unexpected error {:?}", err),
+    }
+
+    session.clear_abort_patterns();
+    session.send_line("Segmentation fault").unwrap();
+    session.send_line("Done").unwrap();
+    let m = session.expect("Done").unwrap();
+    assert!(m.before_str_lossy().contains("Segmentation fault"));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn abort_pattern() {
+    use expectrl::Regex;
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.add_abort_pattern(Regex("panic|Segmentation fault"));
+        session.send_line("Segmentation fault").await.unwrap();
+
+        // "Done" never comes so the expect would wait till a timeout without the pattern.
+        let err = session.expect("Done").await.unwrap_err();
+        match err {
+            expectrl::Error::Aborted { pattern, captures } => {
+                assert_eq!(pattern, "Regex(\"panic|Segmentation fault\")");
+                assert_eq!(captures.as_bytes(), b"Segmentation fault");
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+
+        session.clear_abort_patterns();
+        session.send_line("Segmentation fault").await.unwrap();
+        session.send_line("Done").await.unwrap();
+        let m = session.expect("Done").await.unwrap();
+        assert!(m.before_str_lossy().contains("Segmentation fault"));
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn buffer_limit() {
+    use expectrl::session::OverflowPolicy;
+
+    let mut session = spawn("cat").unwrap();
+    session.set_buffer_limit(4, OverflowPolicy::Error);
+    session.send_line("Hello World").unwrap();
+
+    let err = session.expect("Something else").unwrap_err();
+    assert!(matches!(err, expectrl::Error::Other { .. }));
+
+    session.remove_buffer_limit();
+    session.expect("Hello World").unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn buffer_limit() {
+    use expectrl::session::OverflowPolicy;
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.set_buffer_limit(4, OverflowPolicy::Error);
+        session.send_line("Hello World").await.unwrap();
+
+        let err = session.expect("Something else").await.unwrap_err();
+        assert!(matches!(err, expectrl::Error::Other { .. }));
+
+        session.remove_buffer_limit();
+        session.expect("Hello World").await.unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_cancellable() {
+    use std::time::Duration;
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.send_line("Hello World").await.unwrap();
+
+        let err = session
+            .expect_cancellable(
+                "Something else",
+                futures_timer::Delay::new(Duration::from_millis(100)),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, expectrl::Error::Cancelled));
+
+        // the output is not lost.
+        session
+            .expect_cancellable("Hello World", futures_lite::future::pending())
+            .await
+            .unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn send_control_str() {
+    let mut session = spawn("cat").unwrap();
+    session.send_control_str("C-d").unwrap();
+    session.expect(expectrl::Eof).unwrap();
+
+    assert!(session.send_control_str("C-dd").is_err());
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn send_control_str() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.send_control_str("C-d").await.unwrap();
+        session.expect(expectrl::Eof).await.unwrap();
+
+        assert!(session.send_control_str("C-dd").await.is_err());
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn paste() {
+    let text = "Hello World\n".repeat(20) + "Bye\n";
+
+    let mut session = spawn("cat").unwrap();
+    session.paste(&text).unwrap();
+    session.expect("Bye").unwrap();
+
+    session.set_bracketed_paste(true);
+    session.paste("Hello Paste\n").unwrap();
+    session.expect("Hello Paste").unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn paste() {
+    futures_lite::future::block_on(async {
+        let text = "Hello World\n".repeat(20) + "Bye\n";
+
+        let mut session = spawn("cat").unwrap();
+        session.paste(&text).await.unwrap();
+        session.expect("Bye").await.unwrap();
+
+        session.set_bracketed_paste(true);
+        session.paste("Hello Paste\n").await.unwrap();
+        session.expect("Hello Paste").await.unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn send_typed() {
+    use expectrl::session::TypingOptions;
+    use std::time::{Duration, Instant};
+
+    let mut session = spawn("cat").unwrap();
+
+    let options = TypingOptions::new(Duration::from_millis(5)).jitter(Duration::from_millis(5));
+    session.send_typed("Hello World\n", &options).unwrap();
+    session.expect("Hello World").unwrap();
+
+    let now = Instant::now();
+    session
+        .send_line_slow("Bye", Duration::from_millis(20))
+        .unwrap();
+    assert!(now.elapsed() >= Duration::from_millis(60));
+    session.expect("Bye").unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn send_typed() {
+    use expectrl::session::TypingOptions;
+    use std::time::{Duration, Instant};
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+
+        let options = TypingOptions::new(Duration::from_millis(5)).jitter(Duration::from_millis(5));
+        session.send_typed("Hello World\n", &options).await.unwrap();
+        session.expect("Hello World").await.unwrap();
+
+        let now = Instant::now();
+        session
+            .send_line_slow("Bye", Duration::from_millis(20))
+            .await
+            .unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(60));
+        session.expect("Bye").await.unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn try_expect() {
+    let mut session = spawn("cat").unwrap();
+    assert!(session.try_expect("Hello World").unwrap().is_none());
+
+    session.send_line("Hello World").unwrap();
+
+    let start = std::time::Instant::now();
+    let found = loop {
+        if let Some(found) = session.try_expect("World").unwrap() {
+            break found;
+        }
+
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+
+    assert_eq!(found.before(), b"Hello ");
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn try_expect() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        assert!(session.try_expect("Hello World").await.unwrap().is_none());
+
+        session.send_line("Hello World").await.unwrap();
+
+        let start = std::time::Instant::now();
+        let found = loop {
+            if let Some(found) = session.try_expect("World").await.unwrap() {
+                break found;
+            }
+
+            assert!(start.elapsed() < std::time::Duration::from_secs(5));
+            futures_timer::Delay::new(std::time::Duration::from_millis(10)).await;
+        };
+
+        assert_eq!(found.before(), b"Hello ");
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_with_inspect() {
+    let mut session = spawn("cat").unwrap();
+    session.send_line("Hello World").unwrap();
+
+    let mut received = Vec::new();
+    let found = session
+        .expect_with_inspect("World", |chunk| received.extend_from_slice(chunk))
+        .unwrap();
+
+    assert_eq!(found.get(0).unwrap(), b"World");
+    assert!(received.starts_with(b"Hello World"));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_with_inspect() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.send_line("Hello World").await.unwrap();
+
+        let mut received = Vec::new();
+        let found = session
+            .expect_with_inspect("World", |chunk| received.extend_from_slice(chunk))
+            .await
+            .unwrap();
+
+        assert_eq!(found.get(0).unwrap(), b"World");
+        assert!(received.starts_with(b"Hello World"));
+    })
+}
+
+#[cfg(unix)]
+#[test]
+fn raw_fd() {
+    use std::os::fd::{AsFd, AsRawFd};
+
+    let session = spawn("cat").unwrap();
+
+    let fd = session.as_raw_fd();
+    assert_eq!(session.as_fd().as_raw_fd(), fd);
+    assert!(nix::unistd::isatty(fd).unwrap());
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn consume_mode() {
+    use expectrl::session::ConsumeMode;
+    use std::{thread, time::Duration};
+
+    let mut session = spawn("cat").unwrap();
+
+    session.send_line("Hello World").unwrap();
+    thread::sleep(Duration::from_millis(300));
+    let _ = session.expect("Hello").unwrap();
+    assert_eq!(session.peek_buffer(), b" World\r\n");
+
+    session.set_consume_mode(ConsumeMode::WholeBuffer);
+    session.send_line("Hello World").unwrap();
+    thread::sleep(Duration::from_millis(300));
+    let _ = session.expect("Hello").unwrap();
+    assert_eq!(session.peek_buffer(), b"");
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn consume_mode() {
+    use expectrl::session::ConsumeMode;
+    use std::{thread, time::Duration};
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+
+        session.send_line("Hello World").await.unwrap();
+        thread::sleep(Duration::from_millis(300));
+        let _ = session.expect("Hello").await.unwrap();
+        assert_eq!(session.peek_buffer(), b" World\r\n");
+
+        session.set_consume_mode(ConsumeMode::WholeBuffer);
+        session.send_line("Hello World").await.unwrap();
+        thread::sleep(Duration::from_millis(300));
+        let _ = session.expect("Hello").await.unwrap();
+        assert_eq!(session.peek_buffer(), b"");
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn session_name() {
+    use std::time::Duration;
+
+    let mut session = spawn("cat").unwrap();
+    session.set_name("node-1");
+    session.set_expect_timeout(Some(Duration::from_millis(300)));
+    assert_eq!(session.get_name(), Some("node-1"));
+
+    let mut session = expectrl::session::log(session, Vec::new()).unwrap();
+    assert_eq!(session.get_name(), Some("node-1"));
+
+    session.send_line("Hello World").unwrap();
+    let log = String::from_utf8_lossy(session.get_stream().get_logger()).to_string();
+    assert!(log.starts_with("[node-1] write: "));
+
+    let err = session.expect("Something else").unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.starts_with(
+            "Reached a timeout for expect type of command, waiting for \"Something else\"; "
+        ),
+        "{}",
+        message
+    );
+    assert!(message.contains("Hello World"), "{}", message);
+    assert!(message.ends_with(" on session \"node-1\""), "{}", message);
+    assert!(
+        matches!(err, expectrl::Error::Session { name, err } if name == "node-1" && matches!(*err, expectrl::Error::ExpectTimeout { .. }))
+    );
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn session_name() {
+    use std::time::Duration;
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.set_name("node-1");
+        session.set_expect_timeout(Some(Duration::from_millis(300)));
+        assert_eq!(session.get_name(), Some("node-1"));
+
+        let mut session = expectrl::session::log(session, Vec::new()).unwrap();
+        assert_eq!(session.get_name(), Some("node-1"));
+
+        session.send_line("Hello World").await.unwrap();
+        let log = String::from_utf8_lossy(session.get_stream().get_logger()).to_string();
+        assert!(log.starts_with("[node-1] write: "));
+
+        let err = session.expect("Something else").await.unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.starts_with(
+                "Reached a timeout for expect type of command, waiting for \"Something else\"; "
+            ),
+            "{}",
+            message
+        );
+        assert!(message.contains("Hello World"), "{}", message);
+        assert!(message.ends_with(" on session \"node-1\""), "{}", message);
+        assert!(
+            matches!(err, expectrl::Error::Session { name, err } if name == "node-1" && matches!(*err, expectrl::Error::ExpectTimeout { .. }))
+        );
+    })
+}
+
+#[cfg(windows)]
+#[test]
+fn send_line() {
+    let mut session = spawn("python ./tests/actions/cat/main.py").unwrap();
+    #[cfg(not(feature = "async"))]
+    {
+        session.send_line("Hello World").unwrap();
+        let m = session.expect('\n').unwrap();
+        let buf = String::from_utf8_lossy(m.before());
+        assert!(buf.contains("Hello World"), "{:?}", buf);
+        session.get_process_mut().exit(0).unwrap();
+    }
+    #[cfg(feature = "async")]
+    {
+        use futures_lite::{AsyncBufReadExt, StreamExt};
+
+        futures_lite::future::block_on(async {
+            session.send_line("Hello World").await.unwrap();
+            let m = session.expect('\n').await.unwrap();
+            let buf = String::from_utf8_lossy(m.before());
+            assert!(buf.contains("Hello World"), "{:?}", buf);
+            session.get_process_mut().exit(0).unwrap();
+        })
+    }
+}
+
+#[cfg(unix)]
+fn spawn_sh(script: &str) -> OsSession {
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", script]);
+    OsSession::spawn(cmd).unwrap()
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_exit() {
+    let mut session = spawn_sh("echo Hello World; exit 3");
+    let status = session.expect_exit(3).unwrap();
+    assert_eq!(status, expectrl::process::ExitStatus::Exited(3));
+
+    let mut session = spawn_sh("echo Hello World; exit 1");
+    let err = session.expect_exit(0).unwrap_err();
+    assert!(matches!(
+        err,
+        expectrl::Error::UnexpectedExit { status, buffer }
+            if status.code() == Some(1) && buffer.starts_with(b"Hello World")
+    ));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_exit() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn_sh("echo Hello World; exit 3");
+        let status = session.expect_exit(3).await.unwrap();
+        assert_eq!(status, expectrl::process::ExitStatus::Exited(3));
+
+        let mut session = spawn_sh("echo Hello World; exit 1");
+        let err = session.expect_exit(0).await.unwrap_err();
+        assert!(matches!(
+            err,
+            expectrl::Error::UnexpectedExit { status, buffer }
+                if status.code() == Some(1) && buffer.starts_with(b"Hello World")
+        ));
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn wait_for_exit_timeout() {
+    let mut session = spawn_sh("echo Hello World; sleep 5");
+    let err = session
+        .wait_for_exit(Some(std::time::Duration::from_millis(300)))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        expectrl::Error::ProcessAlive { buffer } if buffer.starts_with(b"Hello World")
+    ));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn wait_for_exit_timeout() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn_sh("echo Hello World; sleep 5");
+        let err = session
+            .wait_for_exit(Some(std::time::Duration::from_millis(300)))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            expectrl::Error::ProcessAlive { buffer } if buffer.starts_with(b"Hello World")
+        ));
+    })
+}
+
+#[cfg(unix)]
+#[test]
+fn spawn_with_options() {
+    use expectrl::{session::SessionOptions, LineEnding};
+
+    let options = SessionOptions::new()
+        .expect_timeout(Some(std::time::Duration::from_millis(300)))
+        .line_ending(LineEnding::Lf)
+        .name("cat-1");
+
+    let session = OsSession::spawn_with(std::process::Command::new("cat"), &options).unwrap();
+    assert_eq!(
+        session.get_expect_timeout(),
+        Some(std::time::Duration::from_millis(300))
+    );
+    assert_eq!(session.get_line_ending(), LineEnding::Lf);
+    assert_eq!(session.get_name(), Some("cat-1"));
+}
+
 #[cfg(windows)]
 #[test]
-fn send_line() {
-    let mut session = spawn("python ./tests/actions/cat/main.py").unwrap();
+fn spawn_with_console_size() {
+    use expectrl::{
+        process::windows::{SpawnOptions, WinProcess},
+        process::Process,
+        Session,
+    };
+
+    let line = "x".repeat(150);
+
+    let mut cmd = std::process::Command::new("cmd");
+    let _ = cmd.args(["/C", "echo", &line]);
+
+    let options = SpawnOptions::new().console_size(200, 50);
+    let mut process = WinProcess::spawn_with_options(cmd, &options).unwrap();
+    let stream = process.open_stream().unwrap();
+    #[cfg(feature = "async")]
+    let stream = {
+        use expectrl::process::IntoAsyncStream;
+        stream.into_async_stream().unwrap()
+    };
+
+    let mut session = Session::new(process, stream).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    session.expect(line.as_str()).unwrap();
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(session.expect(line.as_str())).unwrap();
+}
+
+#[test]
+fn test_spawn_no_command() {
+    #[cfg(unix)]
+    assert!(spawn("").is_err());
+    #[cfg(windows)]
+    assert!(spawn("").is_ok());
+}
+
+#[test]
+#[ignore = "it's a compile time check"]
+fn test_session_as_writer() {
+    #[cfg(not(feature = "async"))]
+    {
+        let _: Box<dyn std::io::Write> = Box::new(spawn("ls").unwrap());
+        let _: Box<dyn std::io::Read> = Box::new(spawn("ls").unwrap());
+        let _: Box<dyn std::io::BufRead> = Box::new(spawn("ls").unwrap());
+
+        fn _io_copy(mut session: OsSession) {
+            let _ = std::io::copy(&mut std::io::empty(), &mut session).unwrap();
+        }
+    }
+    #[cfg(feature = "async")]
+    {
+        let _: Box<dyn futures_lite::AsyncWrite> =
+            Box::new(spawn("ls").unwrap()) as Box<dyn futures_lite::AsyncWrite>;
+        let _: Box<dyn futures_lite::AsyncRead> =
+            Box::new(spawn("ls").unwrap()) as Box<dyn futures_lite::AsyncRead>;
+        let _: Box<dyn futures_lite::AsyncBufRead> =
+            Box::new(spawn("ls").unwrap()) as Box<dyn futures_lite::AsyncBufRead>;
+
+        async fn _io_copy(mut session: OsSession) {
+            futures_lite::io::copy(futures_lite::io::empty(), &mut session)
+                .await
+                .unwrap();
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn window_size() {
+    use expectrl::process::WindowSize;
+
+    let mut session = spawn("cat").unwrap();
+    session.set_window_size(120, 40).unwrap();
+    assert_eq!(session.get_process().get_window_size().unwrap(), (120, 40));
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_with_retry() {
+    use expectrl::session::RetryPolicy;
+
+    let mut session = spawn_sh("read a; read b; echo READY");
+    session.set_expect_timeout(Some(std::time::Duration::from_millis(300)));
+    session.set_name("sh");
+
+    let err = session
+        .expect_with_retry("READY", &RetryPolicy::new(1))
+        .unwrap_err();
+    assert!(matches!(
+        err.root_error(),
+        expectrl::Error::ExpectTimeout { .. }
+    ));
+
+    let policy = RetryPolicy::new(3)
+        .backoff(std::time::Duration::from_millis(10))
+        .nudge("\n");
+    session.expect_with_retry("READY", &policy).unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_with_retry() {
+    use expectrl::session::RetryPolicy;
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn_sh("read a; read b; echo READY");
+        session.set_expect_timeout(Some(std::time::Duration::from_millis(300)));
+        session.set_name("sh");
+
+        let err = session
+            .expect_with_retry("READY", &RetryPolicy::new(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.root_error(),
+            expectrl::Error::ExpectTimeout { .. }
+        ));
+
+        let policy = RetryPolicy::new(3)
+            .backoff(std::time::Duration::from_millis(10))
+            .nudge("\n");
+        session.expect_with_retry("READY", &policy).await.unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn keepalive() {
+    let mut session = expectrl::spawn("cat").unwrap();
+    session.set_keepalive(std::time::Duration::from_millis(100), "ping\n");
+    session.expect("ping").unwrap();
+    session.expect("ping").unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn keepalive() {
+    futures_lite::future::block_on(async {
+        let mut session = expectrl::spawn("cat").unwrap();
+        session.set_keepalive(std::time::Duration::from_millis(100), "ping\n");
+        session.expect("ping").await.unwrap();
+        session.expect("ping").await.unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn flush_output() {
+    let mut session = expectrl::spawn("cat").unwrap();
+    session.send_line("stale").unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(300));
+
+    assert!(session.flush_output().unwrap() > 0);
+    assert!(session.peek_buffer().is_empty());
+    assert_eq!(session.discard_buffered(), 0);
+
+    session.send_line("fresh").unwrap();
+    let found = session.expect("fresh").unwrap();
+    assert!(!found.before_str_lossy().contains("stale"));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn flush_output() {
+    futures_lite::future::block_on(async {
+        let mut session = expectrl::spawn("cat").unwrap();
+        session.send_line("stale").await.unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+
+        assert!(session.flush_output().await.unwrap() > 0);
+        assert!(session.peek_buffer().is_empty());
+        assert_eq!(session.discard_buffered(), 0);
+
+        session.send_line("fresh").await.unwrap();
+        let found = session.expect("fresh").await.unwrap();
+        assert!(!found.before_str_lossy().contains("stale"));
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn pager() {
+    use expectrl::session::Pager;
+
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "printf 'page 1\\n--More--'; read x; echo page 2"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+    session.set_pager(Pager::new().response("\n"));
+
+    let found = session.expect("page 2").unwrap();
+    let before = found.before_str_lossy();
+    assert!(before.contains("page 1"));
+    assert!(!before.contains("--More--"));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn pager() {
+    use expectrl::session::Pager;
+
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "printf 'page 1\\n--More--'; read x; echo page 2"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+    session.set_pager(Pager::new().response("\n"));
+
+    futures_lite::future::block_on(async {
+        let found = session.expect("page 2").await.unwrap();
+        let before = found.before_str_lossy();
+        assert!(before.contains("page 1"));
+        assert!(!before.contains("--More--"));
+    })
+}
+
+#[cfg(unix)]
+#[test]
+fn resource_usage() {
+    use expectrl::process::Usage;
+
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "i=0; while [ $i -lt 20000 ]; do i=$((i+1)); done"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+    assert_eq!(session.resource_usage().unwrap(), None);
+
+    let timeout = Some(std::time::Duration::from_secs(10));
+    #[cfg(not(feature = "async"))]
+    let status = session.wait_for_exit(timeout).unwrap();
+    #[cfg(feature = "async")]
+    let status = futures_lite::future::block_on(session.wait_for_exit(timeout)).unwrap();
+    assert!(status.success());
+
+    let usage = session.resource_usage().unwrap().unwrap();
+    assert!(usage.cpu_time() > std::time::Duration::ZERO);
+    assert!(usage.max_rss() > 0);
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn output_timestamps() {
+    use std::time::Instant;
+
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "echo first; sleep 0.5; echo second"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+    assert!(session.time_since_last_output().is_none());
+
+    let start = Instant::now();
+    let first = session.expect("first").unwrap();
+    let second = session.expect("second").unwrap();
+
+    let first = first.timestamp().unwrap();
+    let second = second.timestamp().unwrap();
+    assert!(start <= first);
+    assert!(second.duration_since(first) >= std::time::Duration::from_millis(400));
+    assert!(second <= Instant::now());
+    assert!(session.time_since_last_output().is_some());
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn output_timestamps() {
+    use std::time::Instant;
+
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "echo first; sleep 0.5; echo second"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+    assert!(session.time_since_last_output().is_none());
+
+    futures_lite::future::block_on(async {
+        let start = Instant::now();
+        let first = session.expect("first").await.unwrap();
+        let second = session.expect("second").await.unwrap();
+
+        let first = first.timestamp().unwrap();
+        let second = second.timestamp().unwrap();
+        assert!(start <= first);
+        assert!(second.duration_since(first) >= std::time::Duration::from_millis(400));
+        assert!(second <= Instant::now());
+        assert!(session.time_since_last_output().is_some());
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_eq_lines() {
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "echo hello; printf 'wor\\tld\\n'; printf end"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+    session.expect_eq_line("hello").unwrap();
+    session.expect_eq_lines(&["wor\tld", "end"]).unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_eq_lines() {
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "echo hello; printf 'wor\\tld\\n'; printf end"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+    futures_lite::future::block_on(async {
+        session.expect_eq_line("hello").await.unwrap();
+        session.expect_eq_lines(&["wor\tld", "end"]).await.unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+#[should_panic(expected = "- \"hello world\"\n+ \"hello  world\"")]
+fn expect_eq_line_diff() {
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "echo 'hello  world'"]);
+
+    let mut session = expectrl::Session::spawn(cmd).unwrap();
+    session.expect_eq_line("hello world").unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn manual_clock_expect_timeout() {
+    use std::time::Duration;
+
+    use expectrl::clock::ManualClock;
+
+    let clock = ManualClock::new();
+
+    let mut session = spawn("cat").unwrap();
+    session.set_clock(clock.clone());
+    session.set_expect_timeout(Some(Duration::from_secs(3600)));
+
+    let handle = std::thread::spawn(move || {
+        for _ in 0..10 {
+            std::thread::sleep(Duration::from_millis(50));
+            clock.advance(Duration::from_secs(3600));
+        }
+    });
+
+    #[cfg(not(feature = "async"))]
+    let result = session.expect("never printed");
+    #[cfg(feature = "async")]
+    let result = futures_lite::future::block_on(session.expect("never printed"));
+
+    assert!(matches!(result, Err(expectrl::Error::ExpectTimeout { .. })));
+
+    handle.join().unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn chaos_stream() {
+    use expectrl::{session::chaos, stream::chaos::Chaos};
+
+    let session = spawn("cat").unwrap();
+    let chaos_schedule = Chaos::new(42)
+        .short_reads(0.5)
+        .would_block(0.5, 10)
+        .partial_writes(0.5);
+    let mut session = chaos(session, chaos_schedule).unwrap();
+
+    session.send_line("Hello World").unwrap();
+    session.expect("Hello World").unwrap();
+
+    let session = spawn("cat").unwrap();
+    let mut session = chaos(session, Chaos::new(42).eof_after(5)).unwrap();
+
+    session.send_line("Hello World").unwrap();
+    assert!(matches!(
+        session.expect("Hello World"),
+        Err(expectrl::Error::Eof)
+    ));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn chaos_stream() {
+    use expectrl::{session::chaos, stream::chaos::Chaos};
+
+    let session = spawn("cat").unwrap();
+    let chaos_schedule = Chaos::new(42)
+        .short_reads(0.5)
+        .would_block(0.5, 10)
+        .partial_writes(0.5);
+    let mut session = chaos(session, chaos_schedule).unwrap();
+
+    futures_lite::future::block_on(async {
+        session.send_line("Hello World").await.unwrap();
+        session.expect("Hello World").await.unwrap();
+    });
+
+    let session = spawn("cat").unwrap();
+    let mut session = chaos(session, Chaos::new(42).eof_after(5)).unwrap();
+
+    futures_lite::future::block_on(async {
+        session.send_line("Hello World").await.unwrap();
+        assert!(matches!(
+            session.expect("Hello World").await,
+            Err(expectrl::Error::Eof)
+        ));
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn throttled_writes() {
+    use std::time::{Duration, Instant};
+
+    use expectrl::session::throttle;
+
+    let session = spawn("cat").unwrap();
+    let mut session = throttle(session, 100).unwrap();
+
+    let start = Instant::now();
+
     #[cfg(not(feature = "async"))]
     {
         session.send_line("Hello World").unwrap();
-        let m = session.expect('\n').unwrap();
-        let buf = String::from_utf8_lossy(m.before());
-        assert!(buf.contains("Hello World"), "{:?}", buf);
-        session.get_process_mut().exit(0).unwrap();
+        session.expect("Hello World").unwrap();
     }
     #[cfg(feature = "async")]
-    {
-        use futures_lite::{AsyncBufReadExt, StreamExt};
+    futures_lite::future::block_on(async {
+        session.send_line("Hello World").await.unwrap();
+        session.expect("Hello World").await.unwrap();
+    });
 
-        futures_lite::future::block_on(async {
-            session.send_line("Hello World").await.unwrap();
-            let m = session.expect('\n').await.unwrap();
-            let buf = String::from_utf8_lossy(m.before());
-            assert!(buf.contains("Hello World"), "{:?}", buf);
-            session.get_process_mut().exit(0).unwrap();
+    // 12 bytes at 100 bytes per second
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn read_chunk_size_and_poll_interval() {
+    use std::time::Duration;
+
+    let mut session = spawn_sh("sleep 0.2; seq 1 2000");
+    assert_eq!(session.get_read_chunk_size(), 248);
+    assert_eq!(session.get_poll_interval(), Duration::from_millis(10));
+
+    session.set_read_chunk_size(4096);
+    session.set_poll_interval(Duration::from_millis(1));
+    assert_eq!(session.get_read_chunk_size(), 4096);
+    assert_eq!(session.get_poll_interval(), Duration::from_millis(1));
+
+    let m = session.expect("2000").unwrap();
+    assert!(m.before().starts_with(b"1\r\n2\r\n3\r\n"));
+    assert!(m.before().ends_with(b"1998\r\n1999\r\n"));
+    session.wait_for_exit(Some(Duration::from_secs(5))).unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn read_chunk_size_and_poll_interval() {
+    use std::time::Duration;
+
+    let mut session = spawn_sh("sleep 0.2; seq 1 2000");
+    assert_eq!(session.get_read_chunk_size(), 248);
+    assert_eq!(session.get_poll_interval(), Duration::from_millis(10));
+
+    session.set_read_chunk_size(4096);
+    session.set_poll_interval(Duration::from_millis(1));
+    assert_eq!(session.get_read_chunk_size(), 4096);
+    assert_eq!(session.get_poll_interval(), Duration::from_millis(1));
+
+    futures_lite::future::block_on(async {
+        let m = session.expect("2000").await.unwrap();
+        assert!(m.before().starts_with(b"1\r\n2\r\n3\r\n"));
+        assert!(m.before().ends_with(b"1998\r\n1999\r\n"));
+        session
+            .wait_for_exit(Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_blocks_until_output() {
+    use std::time::{Duration, Instant};
+
+    let mut session = spawn_sh("sleep 0.3; echo done; sleep 5");
+    // a long poll interval would delay the match if the session slept instead of waiting for the output.
+    session.set_poll_interval(Duration::from_secs(5));
+
+    let start = Instant::now();
+    session.expect("done").unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    session.set_expect_timeout(Some(Duration::from_millis(300)));
+    let start = Instant::now();
+    assert!(matches!(
+        session.expect("never"),
+        Err(expectrl::Error::ExpectTimeout { .. })
+    ));
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_ref() {
+    let mut session = spawn_sh("seq 1 100; echo done");
+
+    let mut sum = 0;
+    for i in 1..=100 {
+        let m = session.expect_ref("\n").unwrap();
+        let line = m.before_str_lossy();
+        let n: u32 = line.trim().parse().unwrap();
+        assert_eq!(n, i);
+        sum += n;
+    }
+    assert_eq!(sum, 5050);
+
+    let m = session.expect_ref("done").unwrap().to_owned();
+    assert_eq!(m.get(0), Some(b"done".as_ref()));
+
+    session.expect(expectrl::Eof).unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_ref() {
+    let mut session = spawn_sh("seq 1 100; echo done");
+
+    futures_lite::future::block_on(async {
+        let mut sum = 0;
+        for i in 1..=100 {
+            let m = session.expect_ref("\n").await.unwrap();
+            let line = m.before_str_lossy();
+            let n: u32 = line.trim().parse().unwrap();
+            assert_eq!(n, i);
+            sum += n;
+        }
+        assert_eq!(sum, 5050);
+
+        let m = session.expect_ref("done").await.unwrap().to_owned();
+        assert_eq!(m.get(0), Some(b"done".as_ref()));
+
+        session.expect(expectrl::Eof).await.unwrap();
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_into() {
+    let mut session = spawn_sh("seq 1 100; echo done");
+    session.reserve_buffer(4096);
+
+    let mut captures = expectrl::Captures::with_capacity(16);
+    for i in 1..=100 {
+        session.expect_into("\n", &mut captures).unwrap();
+        assert_eq!(captures.before_str_lossy().trim(), i.to_string());
+    }
+
+    session.expect_into("done", &mut captures).unwrap();
+    assert_eq!(captures.get(0), Some(b"done".as_ref()));
+    assert_eq!(captures.before(), b"");
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_into() {
+    let mut session = spawn_sh("seq 1 100; echo done");
+    session.reserve_buffer(4096);
+
+    futures_lite::future::block_on(async {
+        let mut captures = expectrl::Captures::with_capacity(16);
+        for i in 1..=100 {
+            session.expect_into("\n", &mut captures).await.unwrap();
+            assert_eq!(captures.before_str_lossy().trim(), i.to_string());
+        }
+
+        session.expect_into("done", &mut captures).await.unwrap();
+        assert_eq!(captures.get(0), Some(b"done".as_ref()));
+        assert_eq!(captures.before(), b"");
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn session_metrics() {
+    use std::time::Duration;
+
+    let mut session = spawn("cat").unwrap();
+    session.send_line("Hello World").unwrap();
+    session.expect("Hello World").unwrap();
+
+    session.set_expect_timeout(Some(Duration::from_millis(100)));
+    assert!(session.expect("Bye").is_err());
+
+    let metrics = session.metrics();
+    assert_eq!(metrics.expects(), 2);
+    assert_eq!(metrics.matches(), 1);
+    assert_eq!(metrics.timeouts(), 1);
+    assert_eq!(metrics.bytes_written(), 12);
+    assert!(metrics.bytes_read() >= 11);
+    assert!(metrics.average_match_latency().is_some());
+
+    session.reset_metrics();
+    assert_eq!(
+        session.metrics(),
+        expectrl::session::SessionMetrics::default()
+    );
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn session_metrics() {
+    use std::time::Duration;
+
+    let mut session = spawn("cat").unwrap();
+    futures_lite::future::block_on(async {
+        session.send_line("Hello World").await.unwrap();
+        session.expect("Hello World").await.unwrap();
+
+        session.set_expect_timeout(Some(Duration::from_millis(100)));
+        assert!(session.expect("Bye").await.is_err());
+    });
+
+    let metrics = session.metrics();
+    assert_eq!(metrics.expects(), 2);
+    assert_eq!(metrics.matches(), 1);
+    assert_eq!(metrics.timeouts(), 1);
+    assert_eq!(metrics.bytes_written(), 12);
+    assert!(metrics.bytes_read() >= 11);
+    assert!(metrics.average_match_latency().is_some());
+
+    session.reset_metrics();
+    assert_eq!(
+        session.metrics(),
+        expectrl::session::SessionMetrics::default()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn drop_policy() {
+    use expectrl::process::{DropPolicy, OnDrop};
+
+    let is_running = |pid: i32| {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .status()
+            .unwrap()
+            .success()
+    };
+
+    let session = spawn("sleep 100").unwrap();
+    let pid = session.get_process().pid().as_raw();
+    session.leak();
+    assert!(is_running(pid));
+
+    let _ = std::process::Command::new("kill")
+        .args(["-9", &pid.to_string()])
+        .status()
+        .unwrap();
+
+    let mut session = spawn("sleep 100").unwrap();
+    let pid = session.get_process().pid().as_raw();
+    session.set_drop_policy(DropPolicy::Kill);
+    drop(session);
+    assert!(!is_running(pid));
+}
+
+#[cfg(unix)]
+#[test]
+fn daemon() {
+    use expectrl::process::{
+        daemon::{Daemon, DaemonOptions},
+        Wait,
+    };
+    use std::time::{Duration, Instant};
+
+    let path = std::env::temp_dir().join(format!("expectrl-test-daemon-{}", std::process::id()));
+
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "echo started; echo failed >&2; sleep 100"]);
+
+    let options = DaemonOptions::new().output(&path);
+    let mut daemon = Daemon::spawn(cmd, &options).unwrap();
+    assert_eq!(daemon.stdout_path(), Some(path.as_path()));
+
+    let start = Instant::now();
+    let mut output = String::new();
+    while !output.contains("failed") && start.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(50));
+        output = std::fs::read_to_string(&path).unwrap();
+    }
+
+    assert_eq!(output, "started\nfailed\n");
+    assert_eq!(daemon.try_wait().unwrap(), None);
+
+    // a daemon is a leader of its own session.
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", daemon.pid()));
+    if let Ok(stat) = stat {
+        let mut fields = stat.rsplit(')').next().unwrap().split_whitespace();
+        let sid: u32 = fields.nth(3).unwrap().parse().unwrap();
+        assert_eq!(sid, daemon.pid());
+    }
+
+    daemon.kill().unwrap();
+    assert_eq!(
+        daemon.wait().unwrap(),
+        expectrl::process::ExitStatus::Signaled(9)
+    );
+    daemon.kill().unwrap();
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_stderr() {
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "echo out; echo err >&2; read line; echo $line"]);
+
+    let mut session = expectrl::Session::spawn_piped(cmd).unwrap();
+    session.expect_stderr("err").unwrap();
+    session.send_line("Hello World").unwrap();
+
+    let m = session.expect(expectrl::Eof).unwrap();
+    assert_eq!(m.get(0).unwrap(), b"out\nHello World\n");
+
+    let m = session.get_stderr_mut().expect(expectrl::Eof).unwrap();
+    assert_eq!(m.get(0).unwrap(), b"\n");
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn within() {
+    use std::time::Duration;
+
+    let mut session = spawn("cat").unwrap();
+    session
+        .within(Duration::from_secs(5), |s| {
+            s.send_line("Hello World")?;
+            s.expect("Hello World")
         })
+        .unwrap();
+
+    let err = session
+        .within(Duration::from_millis(300), |s| {
+            s.send_line("Hello")?;
+            s.expect("Hello")?;
+            s.expect("World")
+        })
+        .unwrap_err();
+
+    match err {
+        expectrl::Error::Within { step, action, .. } => {
+            assert_eq!(step, 2);
+            assert_eq!(action, "expect");
+        }
+        err => panic!("unexpected error {:?}", err),
     }
 }
 
+#[cfg(unix)]
+#[cfg(feature = "async")]
 #[test]
-fn test_spawn_no_command() {
-    #[cfg(unix)]
-    assert!(spawn("").is_err());
-    #[cfg(windows)]
-    assert!(spawn("").is_ok());
+fn within() {
+    use std::time::Duration;
+
+    let mut session = spawn("cat").unwrap();
+    futures_lite::future::block_on(async {
+        session
+            .within(Duration::from_secs(5), |s| {
+                Box::pin(async move {
+                    s.send_line("Hello World").await?;
+                    s.expect("Hello World").await
+                })
+            })
+            .await
+            .unwrap();
+
+        let err = session
+            .within(Duration::from_millis(300), |s| {
+                Box::pin(async move {
+                    s.send_line("Hello").await?;
+                    s.expect("Hello").await?;
+                    s.expect("World").await
+                })
+            })
+            .await
+            .unwrap_err();
+
+        match err {
+            expectrl::Error::Within { step, action, .. } => {
+                assert_eq!(step, 2);
+                assert_eq!(action, "expect");
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+    })
 }
 
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
 #[test]
-#[ignore = "it's a compile time check"]
-fn test_session_as_writer() {
-    #[cfg(not(feature = "async"))]
-    {
-        let _: Box<dyn std::io::Write> = Box::new(spawn("ls").unwrap());
-        let _: Box<dyn std::io::Read> = Box::new(spawn("ls").unwrap());
-        let _: Box<dyn std::io::BufRead> = Box::new(spawn("ls").unwrap());
+fn within_counts_slow_sends() {
+    use std::time::Duration;
 
-        fn _io_copy(mut session: OsSession) {
-            let _ = std::io::copy(&mut std::io::empty(), &mut session).unwrap();
+    let mut session = spawn("cat").unwrap();
+    let err = session
+        .within(Duration::from_millis(200), |s| {
+            s.send_line_slow("Hello", Duration::from_millis(50))?;
+            s.paste("World\n")
+        })
+        .unwrap_err();
+
+    match err {
+        expectrl::Error::Within { step, action, .. } => {
+            assert_eq!(step, 1);
+            assert_eq!(action, "send");
         }
+        err => panic!("unexpected error {:?}", err),
     }
-    #[cfg(feature = "async")]
-    {
-        let _: Box<dyn futures_lite::AsyncWrite> =
-            Box::new(spawn("ls").unwrap()) as Box<dyn futures_lite::AsyncWrite>;
-        let _: Box<dyn futures_lite::AsyncRead> =
-            Box::new(spawn("ls").unwrap()) as Box<dyn futures_lite::AsyncRead>;
-        let _: Box<dyn futures_lite::AsyncBufRead> =
-            Box::new(spawn("ls").unwrap()) as Box<dyn futures_lite::AsyncBufRead>;
+}
 
-        async fn _io_copy(mut session: OsSession) {
-            futures_lite::io::copy(futures_lite::io::empty(), &mut session)
-                .await
-                .unwrap();
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn within_counts_slow_sends() {
+    use std::time::Duration;
+
+    let mut session = spawn("cat").unwrap();
+    futures_lite::future::block_on(async {
+        let err = session
+            .within(Duration::from_millis(200), |s| {
+                Box::pin(async move {
+                    s.send_line_slow("Hello", Duration::from_millis(50)).await?;
+                    s.paste("World\n").await
+                })
+            })
+            .await
+            .unwrap_err();
+
+        match err {
+            expectrl::Error::Within { step, action, .. } => {
+                assert_eq!(step, 1);
+                assert_eq!(action, "send");
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn typed_errors() {
+    use std::{error::Error as _, time::Duration};
+
+    let mut session = spawn("cat").unwrap();
+    session.set_expect_timeout(Some(Duration::from_millis(300)));
+    session.send_line("Hello").unwrap();
+
+    match session.expect("World").unwrap_err() {
+        expectrl::Error::ExpectTimeout { needle, buffer } => {
+            assert_eq!(needle, "\"World\"");
+            assert!(buffer.ends_with(b"Hello\r\n"));
         }
+        err => panic!("unexpected error {:?}", err),
     }
+
+    session.check_alive().unwrap();
+    session
+        .send(expectrl::ControlCode::EndOfTransmission)
+        .unwrap();
+    let _ = session.wait_for_exit(Some(Duration::from_secs(5))).unwrap();
+    assert!(matches!(
+        session.check_alive(),
+        Err(expectrl::Error::ProcessExited { .. })
+    ));
+
+    let err = expectrl::Error::Session {
+        name: String::from("cat"),
+        err: Box::new(std::io::Error::from(std::io::ErrorKind::BrokenPipe).into()),
+    };
+    let source = err.source().unwrap().source().unwrap();
+    let source = source.downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(source.kind(), std::io::ErrorKind::BrokenPipe);
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn typed_errors() {
+    use std::time::Duration;
+
+    let mut session = spawn("cat").unwrap();
+    session.set_expect_timeout(Some(Duration::from_millis(300)));
+    futures_lite::future::block_on(async {
+        session.send_line("Hello").await.unwrap();
+
+        match session.expect("World").await.unwrap_err() {
+            expectrl::Error::ExpectTimeout { needle, buffer } => {
+                assert_eq!(needle, "\"World\"");
+                assert!(buffer.ends_with(b"Hello\r\n"));
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+
+        session.check_alive().unwrap();
+        session
+            .send(expectrl::ControlCode::EndOfTransmission)
+            .await
+            .unwrap();
+        let _ = session
+            .wait_for_exit(Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert!(matches!(
+            session.check_alive(),
+            Err(expectrl::Error::ProcessExited { .. })
+        ));
+    })
 }
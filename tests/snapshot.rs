@@ -0,0 +1,77 @@
+#![cfg(all(unix, feature = "snapshot"))]
+
+use expectrl::{
+    session::{OsSession, Snapshot},
+    Eof, Session,
+};
+use regex::Regex;
+
+fn snapshot_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!(
+        "expectrl-test-snapshot-{}-{}.snap",
+        name,
+        std::process::id()
+    ))
+}
+
+fn spawn_date() -> OsSession {
+    let mut cmd = std::process::Command::new("sh");
+    let _ = cmd.args(["-c", "echo hello; date +%s; echo world"]);
+    Session::spawn(cmd).unwrap()
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn snapshot() {
+    let path = snapshot_path("date");
+    let snapshot = Snapshot::new(&path).redact(Regex::new(r"(?m)^\d+$").unwrap(), "[TIMESTAMP]");
+
+    // the first run records a snapshot
+    let mut session = spawn_date();
+    let _ = session.expect_snapshot(Eof, &snapshot).unwrap();
+
+    let mut session = spawn_date();
+    let _ = session.expect_snapshot(Eof, &snapshot).unwrap();
+
+    let stored = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(stored, "hello\n[TIMESTAMP]\nworld\n");
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn snapshot() {
+    let path = snapshot_path("date");
+    let snapshot = Snapshot::new(&path).redact(Regex::new(r"(?m)^\d+$").unwrap(), "[TIMESTAMP]");
+
+    futures_lite::future::block_on(async {
+        // the first run records a snapshot
+        let mut session = spawn_date();
+        let _ = session.expect_snapshot(Eof, &snapshot).await.unwrap();
+
+        let mut session = spawn_date();
+        let _ = session.expect_snapshot(Eof, &snapshot).await.unwrap();
+    });
+
+    let stored = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(stored, "hello\n[TIMESTAMP]\nworld\n");
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "- \"hello\"\n+ \"bye\"")]
+fn snapshot_mismatch() {
+    let path = snapshot_path("mismatch");
+    std::fs::write(&path, "hello\n").unwrap();
+
+    let snapshot = Snapshot::new(&path);
+    let result =
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| snapshot.assert("bye\n")));
+    std::fs::remove_file(&path).unwrap();
+
+    if let Err(err) = result {
+        std::panic::resume_unwind(err);
+    }
+}
@@ -0,0 +1,64 @@
+#![cfg(unix)]
+
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use expectrl::{spawn, terminal::RawModeGuard};
+use nix::sys::termios::{tcgetattr, LocalFlags};
+
+#[test]
+fn raw_mode_guard() {
+    let session = spawn("cat").unwrap();
+    let fd = session.as_raw_fd();
+    assert!(is_canonical(fd));
+
+    let guard = RawModeGuard::with_fd(fd).unwrap();
+    assert!(guard.is_raw());
+    assert!(!is_canonical(fd));
+
+    guard.restore().unwrap();
+    assert!(is_canonical(fd));
+}
+
+#[test]
+fn raw_mode_guard_nested() {
+    let session = spawn("cat").unwrap();
+    let fd = session.as_raw_fd();
+
+    let guard = RawModeGuard::with_fd(fd).unwrap();
+    let nested = RawModeGuard::with_fd(fd).unwrap();
+    drop(nested);
+    assert!(!is_canonical(fd));
+
+    drop(guard);
+    assert!(is_canonical(fd));
+}
+
+#[test]
+fn raw_mode_guard_restored_on_panic() {
+    let session = spawn("cat").unwrap();
+    let fd = session.as_raw_fd();
+
+    let result = std::panic::catch_unwind(|| {
+        let _guard = RawModeGuard::with_fd(fd).unwrap();
+        panic!("a test panic");
+    });
+
+    assert!(result.is_err());
+    assert!(is_canonical(fd));
+}
+
+#[test]
+fn raw_mode_guard_not_a_tty() {
+    let (reader, _writer) = nix::unistd::pipe().unwrap();
+
+    let guard = RawModeGuard::with_fd(reader).unwrap();
+    assert!(!guard.is_raw());
+    guard.restore().unwrap();
+}
+
+fn is_canonical(fd: RawFd) -> bool {
+    tcgetattr(fd)
+        .unwrap()
+        .local_flags
+        .contains(LocalFlags::ICANON)
+}